@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+use angular_dependency_analyzer::{self as analyzer, AnalyzerConfig, Aggregate};
+use napi::{bindgen_prelude::AsyncTask, Env, Task};
+use napi_derive::napi;
+use serde_json::Value;
+
+// JavaScript から渡す解析オプション (すべて省略可)
+#[napi(object)]
+pub struct AnalyzeOptions {
+    // 並び順: "count" (既定) / "files" / "name" / "module"
+    pub sort: Option<String>,
+    // 並び順を逆にするか (既定は false)
+    pub reverse: Option<bool>,
+    // 解析結果のキャッシュを保存するディレクトリ (省略時はキャッシュしない)
+    pub cache_dir: Option<String>,
+    // files (ファイルごとの内訳) を含めるか (既定は true)
+    pub per_file: Option<bool>,
+}
+
+fn to_napi(err: impl std::fmt::Display) -> napi::Error {
+    napi::Error::from_reason(err.to_string())
+}
+
+fn config(options: Option<AnalyzeOptions>) -> napi::Result<AnalyzerConfig> {
+    let mut config = AnalyzerConfig::default();
+    if let Some(options) = options {
+        if let Some(sort) = options.sort {
+            config.sort = sort.parse().map_err(to_napi)?;
+        }
+        config.reverse = options.reverse.unwrap_or(false);
+        config.cache_dir = options.cache_dir.map(PathBuf::from);
+        config.keep_files = options.per_file.unwrap_or(true);
+    }
+    Ok(config)
+}
+
+// --format json と同じ形のレポート
+fn analyze(dir: &Path, config: &AnalyzerConfig) -> napi::Result<Value> {
+    let analysis = analyzer::analyze_dir(dir, config).map_err(to_napi)?;
+    serde_json::to_value(&analysis.report).map_err(to_napi)
+}
+
+// ディレクトリ以下を解析する (解析が終わるまでイベントループを止める)
+#[napi]
+pub fn analyze_dir_sync(dir: String, options: Option<AnalyzeOptions>) -> napi::Result<Value> {
+    analyze(Path::new(&dir), &config(options)?)
+}
+
+pub struct AnalyzeDirTask {
+    dir: PathBuf,
+    config: AnalyzerConfig,
+}
+
+impl Task for AnalyzeDirTask {
+    type Output = Value;
+    type JsValue = Value;
+
+    // libuv のスレッドプールで実行される
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        analyze(&self.dir, &self.config)
+    }
+
+    fn resolve(&mut self, _: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+// ディレクトリ以下をバックグラウンドで解析し、Promise でレポートを返す
+#[napi(ts_return_type = "Promise<Record<string, any>>")]
+pub fn analyze_dir(dir: String, options: Option<AnalyzeOptions>) -> napi::Result<AsyncTask<AnalyzeDirTask>> {
+    Ok(AsyncTask::new(AnalyzeDirTask {
+        dir: PathBuf::from(dir),
+        config: config(options)?,
+    }))
+}
+
+// ディスクを読まずにソース文字列 1 つを解析する (path は表示と .tsx 判定にのみ使う)
+#[napi]
+pub fn analyze_source(path: String, source: String, options: Option<AnalyzeOptions>) -> napi::Result<Value> {
+    let path = PathBuf::from(path);
+    let config = config(options)?;
+    let root = path.parent().unwrap_or(Path::new(""));
+    let mut aggregate = Aggregate::new(root, &config);
+    aggregate.add(analyzer::analyze_source(&path, source));
+    serde_json::to_value(&aggregate.finish().report).map_err(to_napi)
+}
@@ -0,0 +1,18 @@
+use std::{collections::BTreeMap, path::{Path, PathBuf}};
+use angular_dependency_analyzer::{self as analyzer, AnalyzerConfig};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+// files は Map<パス, ソース> (プレーンオブジェクトも可)。戻り値は --format json と同じ形のレポート
+#[wasm_bindgen(js_name = analyzeSources)]
+pub fn analyze_sources(files: JsValue) -> Result<JsValue, JsError> {
+    let files: BTreeMap<String, String> = serde_wasm_bindgen::from_value(files)?;
+    let analysis = analyzer::analyze_sources(
+        Path::new("."),
+        files.into_iter().map(|(path, src)| (PathBuf::from(path), src)),
+        &AnalyzerConfig::default(),
+    );
+    // Map ではなくプレーンオブジェクトとして返す (JSON.stringify でそのまま出力と同じ形になる)
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(analysis.report.serialize(&serializer)?)
+}
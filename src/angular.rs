@@ -0,0 +1,208 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use angular_dependency_analyzer::{
+    manifest,
+    output,
+    resolve::{self, PathAliases},
+    tsconfig,
+};
+use crate::{
+    cwd_for, open_output,
+    projects::{self, Members, Project},
+    Args,
+};
+
+// Angular CLI のワークスペースの定義
+const ANGULAR_JSON: &str = "angular.json";
+// ライブラリの公開 API を決める ng-packagr の設定
+const NG_PACKAGE_JSON: &str = "ng-package.json";
+// ng-package.json の lib.entryFile の既定値
+const DEFAULT_ENTRY_FILE: &str = "src/public-api.ts";
+
+// angular.json の projects の 1 つ
+struct AngularProject {
+    name: String,
+    // application / library
+    kind: Option<String>,
+    // root (正規化した絶対パス)
+    root: PathBuf,
+    // sourceRoot (無ければ root)
+    source_root: PathBuf,
+    // build ターゲットのビルダー
+    builder: Option<String>,
+    // build ターゲットの options.tsConfig
+    tsconfig: Option<PathBuf>,
+}
+
+// angular コマンド: angular.json の projects ごとに sourceRoot の下を解析し、プロジェクトをまたぐファイル間の依存を出力する
+pub fn run(args: &Args) -> Result<()> {
+    let Some(root) = find_root(&args.targets[0])? else {
+        bail!("angular.json が見つかりません");
+    };
+    let angular = load(&root)?;
+    if angular.is_empty() {
+        bail!("angular.json に projects がありません: {}", root.join(ANGULAR_JSON).display());
+    }
+
+    let cwd = cwd_for(&args.targets[0])?;
+    // ライブラリの名前のインポートは、paths が指すビルド結果 (dist) ではなくライブラリのソースに解決する
+    let mut aliases = args.path_aliases.clone();
+    for project in angular.iter().filter(|project| project.kind.as_deref() == Some(projects::LIBRARY)) {
+        add_library(&mut aliases, project);
+    }
+    let mut projects = Vec::new();
+    for project in &angular {
+        // build ターゲットの tsconfig があればその paths で解析する (ライブラリの解決は全体と同じにする)
+        let mut project_aliases = match &project.tsconfig {
+            Some(path) if path.is_file() => tsconfig::load(path)?.path_aliases(cwd.clone()),
+            _ => aliases.clone(),
+        };
+        for library in angular.iter().filter(|project| project.kind.as_deref() == Some(projects::LIBRARY)) {
+            add_library(&mut project_aliases, library);
+        }
+        projects.push(Project {
+            name: project.name.clone(),
+            config: root.join(ANGULAR_JSON),
+            members: Members::Dir(project.source_root.clone()),
+            references: Vec::new(),
+            aliases: project_aliases,
+            kind: project.kind.clone(),
+            build: project.builder.clone(),
+        });
+    }
+    let report = projects::analyze(args, projects, aliases, "angular.json", None, &|_, _, _| None)?;
+    let mut out = open_output(args)?;
+    output::projects::write(args.projects_format()?, &report, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+// start とその親ディレクトリから angular.json のあるディレクトリを探す
+fn find_root(start: &Path) -> Result<Option<PathBuf>> {
+    let start = fs::canonicalize(start)?;
+    Ok(start.ancestors().find(|dir| dir.join(ANGULAR_JSON).is_file()).map(Path::to_path_buf))
+}
+
+// angular.json の projects (名前の順)
+fn load(root: &Path) -> Result<Vec<AngularProject>> {
+    let path = root.join(ANGULAR_JSON);
+    let src = fs::read_to_string(&path).with_context(|| format!("angular.json を読み込めません: {}", path.display()))?;
+    let json: Value = serde_json::from_str(&tsconfig::strip_jsonc(&src))
+        .with_context(|| format!("angular.json の形式が正しくありません: {}", path.display()))?;
+    let Some(entries) = json.get("projects").and_then(Value::as_object) else {
+        return Ok(Vec::new());
+    };
+    let mut projects = Vec::new();
+    for (name, project) in entries {
+        let string = |pointer: &str| project.pointer(pointer).and_then(Value::as_str);
+        let project_root = resolve::normalize(&root.join(string("/root").unwrap_or("")));
+        // build ターゲットは architect か targets (新しい Angular CLI の別名) に書く
+        let build = project.get("architect").or_else(|| project.get("targets")).and_then(|targets| targets.get("build"));
+        let build_string = |pointer: &str| build.and_then(|build| build.pointer(pointer)).and_then(Value::as_str);
+        projects.push(AngularProject {
+            name: name.clone(),
+            kind: string("/projectType").map(str::to_string),
+            source_root: match string("/sourceRoot") {
+                Some(source_root) => resolve::normalize(&root.join(source_root)),
+                None => project_root.clone(),
+            },
+            root: project_root,
+            builder: build_string("/builder").or_else(|| build_string("/executor")).map(str::to_string),
+            tsconfig: build_string("/options/tsConfig").map(|path| resolve::normalize(&root.join(path))),
+        });
+    }
+    Ok(projects)
+}
+
+// ライブラリの名前 (root の package.json の name、無ければプロジェクト名) を ng-package.json の lib.entryFile に、
+// name/ 以下を root の下 (二次エントリポイント) に解決する。
+// entryFile が無ければ tsconfig の paths をそのまま使い、paths にも無ければ package.json の types / main に解決する
+fn add_library(aliases: &mut PathAliases, project: &AngularProject) {
+    let package = fs::read_to_string(project.root.join(manifest::FILE_NAME))
+        .ok()
+        .and_then(|src| serde_json::from_str::<Value>(&src).ok())
+        .unwrap_or_default();
+    let field = |key: &str| package.get(key).and_then(Value::as_str);
+    let name = field("name").map(str::to_string).unwrap_or_else(|| project.name.clone());
+    let entry = fs::read_to_string(project.root.join(NG_PACKAGE_JSON))
+        .ok()
+        .and_then(|src| serde_json::from_str::<Value>(&tsconfig::strip_jsonc(&src)).ok())
+        .and_then(|json| json.pointer("/lib/entryFile").and_then(Value::as_str).map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_ENTRY_FILE.to_string());
+    let entry = project.root.join(entry);
+    let wildcard = format!("{name}/*");
+    let configured = |aliases: &PathAliases, pattern: &str| aliases.paths.iter().any(|(p, _)| p == pattern);
+    if entry.is_file() {
+        aliases.paths.retain(|(pattern, _)| *pattern != name && *pattern != wildcard);
+        aliases.paths.push((name.clone(), vec![entry.to_string_lossy().to_string()]));
+    } else if !configured(aliases, &name)
+        && let Some(entry) = ["types", "typings", "main"]
+            .into_iter()
+            .filter_map(field)
+            .map(|path| project.root.join(path))
+            .find(|path| path.is_file())
+    {
+        aliases.paths.push((name.clone(), vec![entry.to_string_lossy().to_string()]));
+    }
+    if !configured(aliases, &wildcard) {
+        aliases.paths.push((wildcard, vec![format!("{}/*", project.root.to_string_lossy())]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(root: &Path) -> AngularProject {
+        AngularProject {
+            name: "ui".to_string(),
+            kind: Some(projects::LIBRARY.to_string()),
+            root: root.to_path_buf(),
+            source_root: root.join("src"),
+            builder: None,
+            tsconfig: None,
+        }
+    }
+
+    fn alias<'a>(aliases: &'a PathAliases, pattern: &str) -> Option<&'a [String]> {
+        aliases.paths.iter().find(|(p, _)| p == pattern).map(|(_, targets)| targets.as_slice())
+    }
+
+    #[test]
+    fn add_library_keeps_tsconfig_paths_without_entry_file() {
+        let dir = std::env::temp_dir().join(format!("angular-dep-library-test-{}", std::process::id()));
+        let root = dir.join("libs/ui");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(manifest::FILE_NAME), r#"{ "name": "@acme/ui", "main": "src/main.ts" }"#).unwrap();
+        fs::write(root.join("src/index.ts"), "").unwrap();
+        fs::write(root.join("src/main.ts"), "").unwrap();
+        let index = root.join("src/index.ts").to_string_lossy().to_string();
+        let main = root.join("src/main.ts").to_string_lossy().to_string();
+
+        // src/public-api.ts が無いので tsconfig の paths を残す
+        let mut aliases = PathAliases::default();
+        aliases.paths.push(("@acme/ui".to_string(), vec![index.clone()]));
+        add_library(&mut aliases, &library(&root));
+        assert_eq!(alias(&aliases, "@acme/ui"), Some(&[index.clone()][..]));
+        assert_eq!(alias(&aliases, "@acme/ui/*"), Some(&[format!("{}/*", root.to_string_lossy())][..]));
+
+        // paths にも無ければ package.json の main
+        let mut aliases = PathAliases::default();
+        add_library(&mut aliases, &library(&root));
+        assert_eq!(alias(&aliases, "@acme/ui"), Some(&[main][..]));
+
+        // entryFile があればそれを使う
+        fs::write(root.join(NG_PACKAGE_JSON), r#"{ "lib": { "entryFile": "src/index.ts" } }"#).unwrap();
+        let mut aliases = PathAliases::default();
+        aliases.paths.push(("@acme/ui".to_string(), vec!["dist/ui".to_string()]));
+        add_library(&mut aliases, &library(&root));
+        assert_eq!(alias(&aliases, "@acme/ui"), Some(&[index][..]));
+        assert_eq!(aliases.paths.len(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::Path,
+};
+use serde::{Deserialize, Serialize};
+use crate::{
+    graph::{DependencyGraph, Edge},
+    report::{DuplicateImport, Report, UnusedImport},
+    resolve,
+    ProjectAnalysis,
+};
+
+// ベースラインの形式のバージョン (互換性のない変更で上げる)
+pub const BASELINE_VERSION: u32 = 1;
+
+// --write-baseline で保存する解析結果のスナップショット。
+// --baseline で読み込み、増えた・減ったインポートと、新しく増えた指摘だけを報告する
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub baseline_version: u32,
+    pub usages: Vec<BaselineUsage>,
+    pub unused_imports: Vec<UnusedImport>,
+    pub duplicate_imports: Vec<DuplicateImport>,
+    // 循環しているファイルの組 (DependencyGraph::cycles。古いベースラインには無い)
+    #[serde(default)]
+    pub cycles: Vec<Vec<String>>,
+}
+
+// --write-cycles-baseline で保存する既知の循環依存。--cycles-baseline で読み込み、これに無い循環依存だけを報告して失敗にする
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CyclesBaseline {
+    pub baseline_version: u32,
+    // 循環しているファイルの組 (DependencyGraph::cycles。解析対象ディレクトリからの相対パス)
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl CyclesBaseline {
+    pub fn from_graph(graph: &DependencyGraph) -> Self {
+        Self {
+            baseline_version: BASELINE_VERSION,
+            cycles: graph.cycles(),
+        }
+    }
+
+    // files のすべてが既知の 1 つの組に含まれているか (一部を解消して小さくなった組も既知とみなす。
+    // 組が広がったり、別々の組がつながったりしたものは新しい循環依存)
+    pub fn is_known(&self, files: &[String]) -> bool {
+        self.cycles.iter().any(|known| files.iter().all(|file| known.contains(file)))
+    }
+
+    // cycles のうち既知でないもの
+    pub fn new_cycles(&self, cycles: Vec<Vec<String>>) -> Vec<Vec<String>> {
+        cycles.into_iter().filter(|cycle| !self.is_known(cycle)).collect()
+    }
+
+    // report の循環依存のうち既知のものを除く
+    pub fn retain_new(&self, report: &mut Report) {
+        let root = Path::new(&report.target);
+        report.cycles.retain(|cycle| {
+            let files: Vec<String> = cycle.files.iter().map(|file| resolve::relative_display(root, Path::new(file))).collect();
+            !self.is_known(&files)
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineUsage {
+    pub module: String,
+    pub name: String,
+    pub count: usize,
+    pub files: usize,
+}
+
+// (使用回数, ファイル数)
+type Counts = (usize, usize);
+
+// ベースラインと今回の解析結果の差分
+#[derive(Debug, Default, Serialize)]
+pub struct BaselineDiff {
+    // 新しくインポートされるようになったもの (before は 0)
+    pub added: Vec<UsageDelta>,
+    // インポートされなくなったもの (after は 0)
+    pub removed: Vec<UsageDelta>,
+    // 両方にあり、使用回数かファイル数が変わったもの
+    pub changed: Vec<UsageDelta>,
+    pub new_unused_imports: Vec<UnusedImport>,
+    // ベースラインにはあったが解消された未使用のインポート
+    pub fixed_unused_imports: Vec<UnusedImport>,
+    pub new_duplicate_imports: Vec<DuplicateImport>,
+}
+
+// diff コマンドの結果: 2 つのフォルダ (またはリビジョン) の解析結果の比較。
+// ファイルは各解析対象からの相対パスで比べる
+#[derive(Debug, Serialize)]
+pub struct TreeDiff {
+    pub before: String,
+    pub after: String,
+    #[serde(flatten)]
+    pub usages: BaselineDiff,
+    pub added_files: Vec<String>,
+    pub removed_files: Vec<String>,
+    // 新しく増えた・なくなったファイル間の依存 (重みと動的インポートかどうかは比べない)
+    pub added_edges: Vec<Edge>,
+    pub removed_edges: Vec<Edge>,
+}
+
+impl TreeDiff {
+    pub fn new(before: &ProjectAnalysis, after: &ProjectAnalysis) -> Self {
+        let baseline = |analysis: &ProjectAnalysis| {
+            Baseline::from_report(&analysis.report).relative_to(Path::new(&analysis.report.target))
+        };
+        let files = |analysis: &ProjectAnalysis| -> BTreeSet<String> {
+            analysis.graph.nodes.iter().map(|node| node.id.clone()).collect()
+        };
+        let edges = |analysis: &ProjectAnalysis| -> BTreeSet<(String, String)> {
+            analysis.graph.edges.iter().map(|edge| (edge.from.clone(), edge.to.clone())).collect()
+        };
+        let (files_before, files_after) = (files(before), files(after));
+        let (edges_before, edges_after) = (edges(before), edges(after));
+        let select = |graph: &DependencyGraph, keys: &BTreeSet<(String, String)>| -> Vec<Edge> {
+            graph
+                .edges
+                .iter()
+                .filter(|edge| !keys.contains(&(edge.from.clone(), edge.to.clone())))
+                .cloned()
+                .collect()
+        };
+        Self {
+            before: before.report.target.clone(),
+            after: after.report.target.clone(),
+            usages: baseline(before).compare(&baseline(after)),
+            added_files: files_after.difference(&files_before).cloned().collect(),
+            removed_files: files_before.difference(&files_after).cloned().collect(),
+            added_edges: select(&after.graph, &edges_before),
+            removed_edges: select(&before.graph, &edges_after),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageDelta {
+    pub module: String,
+    pub name: String,
+    pub before: usize,
+    pub after: usize,
+    // after - before
+    pub delta: i64,
+    pub files_before: usize,
+    pub files_after: usize,
+}
+
+impl UsageDelta {
+    // rxjs::map の形式
+    pub fn qualified_name(&self) -> String {
+        format!("{}::{}", self.module, self.name)
+    }
+}
+
+impl Baseline {
+    pub fn from_report(report: &Report) -> Self {
+        Self {
+            baseline_version: BASELINE_VERSION,
+            usages: report
+                .usages
+                .iter()
+                .map(|usage| BaselineUsage {
+                    module: usage.module.clone(),
+                    name: usage.name.clone(),
+                    count: usage.count,
+                    files: usage.files,
+                })
+                .collect(),
+            unused_imports: report.unused_imports.clone(),
+            duplicate_imports: report.duplicate_imports.clone(),
+            cycles: Vec::new(),
+        }
+    }
+
+    // 依存グラフの循環も含めたスナップショット (--write-baseline で保存する)
+    pub fn from_analysis(analysis: &ProjectAnalysis) -> Self {
+        Self {
+            cycles: analysis.graph.cycles(),
+            ..Self::from_report(&analysis.report)
+        }
+    }
+
+    // cycles のうちベースラインに無かったもの
+    pub fn new_cycles(&self, cycles: Vec<Vec<String>>) -> Vec<Vec<String>> {
+        cycles.into_iter().filter(|cycle| !self.cycles.contains(cycle)).collect()
+    }
+
+    // report との差分
+    pub fn diff(&self, report: &Report) -> BaselineDiff {
+        self.compare(&Baseline::from_report(report))
+    }
+
+    // after との差分。使用回数の差分は増えた順 (同じなら名前順) に並べる
+    pub fn compare(&self, after: &Baseline) -> BaselineDiff {
+        // (モジュール, インポート名) -> (ベースライン, 今回) の (使用回数, ファイル数)
+        let mut entries: BTreeMap<_, (Option<Counts>, Option<Counts>)> = BTreeMap::new();
+        for usage in &self.usages {
+            entries.entry((&usage.module, &usage.name)).or_default().0 = Some((usage.count, usage.files));
+        }
+        for usage in &after.usages {
+            entries.entry((&usage.module, &usage.name)).or_default().1 = Some((usage.count, usage.files));
+        }
+
+        let mut diff = BaselineDiff::default();
+        for ((module, name), (before, after)) in entries {
+            let (count_before, files_before) = before.unwrap_or_default();
+            let (count_after, files_after) = after.unwrap_or_default();
+            let delta = UsageDelta {
+                module: module.to_string(),
+                name: name.to_string(),
+                before: count_before,
+                after: count_after,
+                delta: count_after as i64 - count_before as i64,
+                files_before,
+                files_after,
+            };
+            match (before, after) {
+                (None, _) => diff.added.push(delta),
+                (_, None) => diff.removed.push(delta),
+                (before, after) if before != after => diff.changed.push(delta),
+                _ => {}
+            }
+        }
+        for list in [&mut diff.added, &mut diff.removed, &mut diff.changed] {
+            list.sort_by(|a, b| b.delta.cmp(&a.delta).then_with(|| a.qualified_name().cmp(&b.qualified_name())));
+        }
+
+        let known = self.known();
+        diff.new_unused_imports = after
+            .unused_imports
+            .iter()
+            .filter(|u| !known.unused.contains(&unused_key(u)))
+            .cloned()
+            .collect();
+        diff.new_duplicate_imports = after
+            .duplicate_imports
+            .iter()
+            .filter(|d| !known.duplicates.contains(&duplicate_key(d)))
+            .cloned()
+            .collect();
+        let unused: HashSet<_> = after.unused_imports.iter().map(unused_key).collect();
+        diff.fixed_unused_imports = self
+            .unused_imports
+            .iter()
+            .filter(|u| !unused.contains(&unused_key(u)))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    // report からベースラインにもあった指摘 (未使用・重複したインポート) を除き、新しく増えたものだけを残す。
+    // 行番号は比べないので、上の行の編集でずれても同じ指摘として扱う
+    pub fn retain_new(&self, report: &mut Report) {
+        let known = self.known();
+        report.unused_imports.retain(|u| !known.unused.contains(&unused_key(u)));
+        report.duplicate_imports.retain(|d| !known.duplicates.contains(&duplicate_key(d)));
+    }
+
+    // 指摘のパスを root からの相対パスにする (別のフォルダの解析結果と比べるため)
+    fn relative_to(mut self, root: &Path) -> Self {
+        for unused in &mut self.unused_imports {
+            unused.path = resolve::relative_display(root, Path::new(&unused.path));
+        }
+        for duplicate in &mut self.duplicate_imports {
+            duplicate.path = resolve::relative_display(root, Path::new(&duplicate.path));
+        }
+        self
+    }
+
+    fn known(&self) -> Known<'_> {
+        Known {
+            unused: self.unused_imports.iter().map(unused_key).collect(),
+            duplicates: self.duplicate_imports.iter().map(duplicate_key).collect(),
+        }
+    }
+}
+
+// ベースラインにある指摘 (行番号を除いたキー)
+struct Known<'a> {
+    unused: HashSet<(&'a str, &'a str, &'a str)>,
+    duplicates: HashSet<(&'a str, &'a str)>,
+}
+
+fn duplicate_key(duplicate: &DuplicateImport) -> (&str, &str) {
+    (&duplicate.path, &duplicate.module)
+}
+
+fn unused_key(unused: &UnusedImport) -> (&str, &str, &str) {
+    (&unused.path, &unused.module, &unused.name)
+}
@@ -0,0 +1,123 @@
+use std::{
+    fs,
+    hash::Hasher,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use twox_hash::XxHash64;
+use crate::FileOutcome;
+
+// 既定のキャッシュディレクトリ (解析対象ディレクトリ直下)
+pub const DEFAULT_DIR: &str = ".angular-dep-cache";
+
+// 解析結果の形式や集計方法を変えたら上げる (古いキャッシュを読まないようにする)
+const FORMAT_VERSION: u32 = 7;
+
+// ファイルごとの解析結果を、内容のハッシュとツールのバージョンをキーに保存する
+pub struct Cache {
+    dir: PathBuf,
+    // get で前回の結果を返した・返せなかった回数 (並列に解析するので atomic)
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    version: String,
+    content_hash: u64,
+    outcome: FileOutcome,
+}
+
+// 書き込み用 (解析結果をコピーせずに借りる)
+#[derive(Serialize)]
+struct EntryRef<'a> {
+    version: String,
+    content_hash: u64,
+    outcome: &'a FileOutcome,
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+pub fn content_hash(source: &str) -> u64 {
+    hash_bytes(source.as_bytes())
+}
+
+fn version() -> String {
+    format!("{}/{FORMAT_VERSION}", env!("CARGO_PKG_VERSION"))
+}
+
+impl Cache {
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        // 既定では解析対象のフォルダの中に作るので、git add -A でコミットされないようにする
+        let gitignore = dir.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(&gitignore, "*\n")?;
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        })
+    }
+
+    // これまでの get のヒット数とミス数
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+
+    // ソースファイルのパスごとに 1 エントリ
+    fn entry_path(&self, path: &Path) -> PathBuf {
+        let key = hash_bytes(path.to_string_lossy().as_bytes());
+        self.dir.join(format!("{key:016x}.json"))
+    }
+
+    // 内容が変わっていなければ前回の結果を返す。壊れたエントリは無視して解析し直す
+    pub fn get(&self, path: &Path, content_hash: u64) -> Option<FileOutcome> {
+        let outcome = self.lookup(path, content_hash);
+        let counter = if outcome.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        outcome
+    }
+
+    fn lookup(&self, path: &Path, content_hash: u64) -> Option<FileOutcome> {
+        let data = fs::read(self.entry_path(path)).ok()?;
+        let entry: Entry = serde_json::from_slice(&data).ok()?;
+        (entry.version == version() && entry.content_hash == content_hash).then_some(entry.outcome)
+    }
+
+    pub fn put(&self, path: &Path, content_hash: u64, outcome: &FileOutcome) -> Result<()> {
+        let entry = EntryRef {
+            version: version(),
+            content_hash,
+            outcome,
+        };
+        // 途中で中断されても壊れたエントリが残らないよう、一時ファイルに書いてから置き換える
+        let dest = self.entry_path(path);
+        let tmp = dest.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(&entry)?)?;
+        fs::rename(&tmp, &dest)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_writes_gitignore() {
+        let dir = std::env::temp_dir().join(format!("angular-dep-cache-test-{}", std::process::id()));
+        let cache = Cache::open(&dir).unwrap();
+        assert_eq!(fs::read_to_string(dir.join(".gitignore")).unwrap(), "*\n");
+        assert!(cache.get(Path::new("src/missing.ts"), 0).is_none());
+        assert_eq!(cache.stats(), (0, 1));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,890 @@
+use std::path::PathBuf;
+use anyhow::{anyhow, bail, Result};
+use angular_dependency_analyzer::{export::Export, graph::DirectoryGrouping, output::SortKey};
+use clap::{
+    error::{ContextKind, ErrorKind},
+    Arg, ArgAction, Args, CommandFactory, FromArgMatches, Parser, Subcommand,
+};
+
+use crate::{config::{self, FailOn}, git, projects::CycleScope, Command};
+
+// --help の表示 (clap の既定の見出しは英語なので、見出しは引数ごとに日本語で付ける)
+const HELP_TEMPLATE: &str = "{about}\n\n使い方: {usage}\n\n{all-args}{after-help}";
+// コマンドごとの --help では、コマンドの一覧に出す about の代わりに long_about (あれば) を表示する
+const SUBCOMMAND_HELP_TEMPLATE: &str = "{before-help}使い方: {usage}\n\n{all-args}{after-help}";
+
+// コマンドライン引数。コマンドごとに受け付けるオプションの検証と --help / --version は clap が行う。
+// 設定ファイルとの合成とオプションの組み合わせの検証は Args::parse で行う
+#[derive(Parser)]
+#[command(
+    name = "angular-dependency-analyzer",
+    version,
+    about = "Angular / TypeScript のインポートの使用状況と依存関係を解析する",
+    override_usage = "angular-dependency-analyzer [コマンド] [オプション] [フォルダ・ファイル...]",
+    subcommand_help_heading = "コマンド",
+    subcommand_value_name = "コマンド",
+    disable_help_flag = true,
+    disable_version_flag = true,
+    disable_help_subcommand = true,
+    args_conflicts_with_subcommands = true,
+    args_override_self = true,
+        mut_arg("format", |arg| arg.help(
+            "text / json / csv / markdown / html / sarif / ndjson / checkstyle / junit / github / gitlab"
+        ))
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<CommandArgs>,
+    // コマンドを省略したら analyze (同名のフォルダは ./graph のように指定する)
+    #[command(flatten)]
+    analyze: AnalyzeArgs,
+    #[arg(long, help = "JSON 出力のスキーマを表示する")]
+    schema: bool,
+}
+
+#[derive(Subcommand)]
+enum CommandArgs {
+    #[command(
+        about = "インポート名ごとの使用回数レポート (既定)",
+        override_usage = "angular-dependency-analyzer analyze [オプション] [フォルダ・ファイル...]",
+        mut_arg("format", |arg| arg.help(
+            "text / json / csv / markdown / html / sarif / ndjson / checkstyle / junit / github / gitlab"
+        ))
+    )]
+    Analyze(AnalyzeArgs),
+    #[command(
+        about = "変更を監視して解析し直す (analyze --watch と同じ)",
+        override_usage = "angular-dependency-analyzer watch [オプション] [フォルダ・ファイル...]",
+        mut_arg("format", |arg| arg.help(
+            "text / json / csv / markdown / html / sarif / ndjson / checkstyle / junit / github / gitlab"
+        ))
+    )]
+    Watch(AnalyzeArgs),
+    #[command(
+        about = "ファイル間の依存グラフ",
+        long_about = "ファイル間の依存グラフを出力する (解析対象内の相対インポートと、tsconfig の paths / baseUrl で解決できるインポート)",
+        override_usage = "angular-dependency-analyzer graph [オプション] [フォルダ・ファイル...]",
+        mut_arg("format", |arg| arg.help(
+            "dot (既定) / mermaid / graphml / gexf / csv (隣接行列) /\n\
+             dependency-cruiser (depcruise --output-type json と同じ JSON) /\n\
+             madge (madge --json と同じ隣接リスト)"
+        ))
+    )]
+    Graph(GraphArgs),
+    #[command(
+        about = "未使用のインポート一覧",
+        long_about = "未使用のインポートをファイルごとに一覧表示する",
+        override_usage = "angular-dependency-analyzer unused [オプション] [フォルダ・ファイル...]",
+        mut_arg("baseline", |arg| arg.help("ベースラインに無い未使用のインポートだけを表示する")),
+        mut_arg("max_unused_imports", |arg| arg.help("ベースラインに無い未使用のインポートが N 件を超えたら終了コード 2 で終了する")),
+        mut_arg("filter", |arg| arg.help("モジュール (パッケージ名) かインポート名がグロブに一致するものだけを表示する (複数指定可)"))
+    )]
+    Unused(UnusedArgs),
+    #[command(
+        about = "インポート名の使用箇所",
+        long_about = "インポート名の使用箇所を前後のソースとともに表示する",
+        override_usage = "angular-dependency-analyzer usages <インポート名 | モジュール::名前> [オプション] [フォルダ・ファイル...]"
+    )]
+    Usages(UsagesArgs),
+    #[command(
+        about = "ファイル・パッケージを直接・間接にインポートしているファイル",
+        long_about = "ファイルか npm パッケージを直接・間接にインポートしているファイルを、深さ (直接なら 1) ごとに表示する",
+        override_usage = "angular-dependency-analyzer rdeps <ファイル | パッケージ> [オプション] [フォルダ・ファイル...]"
+    )]
+    Rdeps(RdepsArgs),
+    #[command(
+        about = "変更したファイルの影響を (間接的にでも) 受けるファイル",
+        long_about = "変更したファイルを直接・間接にインポートしているファイルを、深さ (直接なら 1) とテストかどうかつきで表示する。\n\
+                      変更したファイルは --file か --changed で指定し、どちらも無ければ標準入力から 1 行に 1 パスで読む\n\
+                      (.html / .scss 等は同じ名前の .ts の変更とみなす)",
+        override_usage = "angular-dependency-analyzer impact [オプション] [フォルダ・ファイル...]",
+        mut_arg("changed", |arg| arg.help("git で変更されたファイル (ブランチを指定するとその分岐点からの変更)"))
+    )]
+    Impact(ImpactArgs),
+    #[command(
+        about = "ファイルから別のファイル・パッケージへの最短のインポートの連鎖",
+        long_about = "ファイルからインポートを辿って別のファイルかパッケージに着くまでの最短の連鎖を表示する\n\
+                      (なぜそのファイルがそのパッケージに依存しているのかを調べる)",
+        override_usage = "angular-dependency-analyzer path <ファイル> <ファイル | パッケージ> [オプション] [フォルダ・ファイル...]"
+    )]
+    Path(PathArgs),
+    #[command(
+        about = "インポートの多いファイルの組と、組と違うフォルダにあるファイル",
+        long_about = "ファイル間の依存グラフを Louvain 法でインポートの多いファイルの組にまとめ、組ごとに表示する。\n\
+                      組の主なフォルダと違うフォルダにあるファイルに印をつける (フォルダの分け方を見直す材料)",
+        override_usage = "angular-dependency-analyzer clusters [オプション] [フォルダ・ファイル...]"
+    )]
+    Clusters(ReportArgs),
+    #[command(
+        about = "多くのファイルをつないでいる (変更が広く波及する) ファイル",
+        long_about = "ファイルを媒介中心性 (ほかのファイル同士をつなぐインポートの連鎖を通っている度合い) の高い順に表示する。\n\
+                      fan-in / fan-out と、直接・間接にインポートしているファイル数 (変更が波及する範囲) も表示する",
+        override_usage = "angular-dependency-analyzer hubs [オプション] [フォルダ・ファイル...]",
+        mut_arg("top", |arg| arg.help("表示するファイル数 (既定は 20)"))
+    )]
+    Hubs(RankArgs),
+    #[command(
+        about = "エントリポイントからの最も長いインポートの連鎖",
+        long_about = "エントリポイント (main.ts・ルート定義・テスト等) ごとにインポートを辿る最も長い連鎖を求め、段数の最大・平均と\n\
+                      深い連鎖を表示する (循環している組は 1 段と数える)",
+        override_usage = "angular-dependency-analyzer depth [オプション] [フォルダ・ファイル...]"
+    )]
+    Depth(DepthArgs),
+    #[command(
+        about = "直接・間接に読み込むファイル・パッケージの多いファイル",
+        long_about = "各ファイルからインポートを辿って読み込まれるファイル・npm パッケージの数とサイズの合計を求め、\n\
+                      ファイル数の多い順に表示する (コンポーネントごとのバンドルへの影響の目安。import() で遅延読み込みするものは辿らない)",
+        override_usage = "angular-dependency-analyzer deps [オプション] [フォルダ・ファイル...]",
+        mut_arg("top", |arg| arg.help("表示するファイル数 (既定は 20)"))
+    )]
+    Deps(RankArgs),
+    #[command(
+        about = "未使用のインポート・循環依存などから求めた依存関係の健全性スコア (0〜100 / A〜F)",
+        long_about = "未使用のインポート・循環依存・パッケージの内部のインポート・インポートの多すぎるコンポーネント・依存ポリシーの違反の\n\
+                      それぞれについて、指摘のあるファイルの割合を重み付きで平均し、依存関係の健全性スコア (0〜100 と A〜F) を表示する\n\
+                      (重みは設定ファイルの [health] で変えられる。プロジェクトごとのスコアは projects / workspaces / nx / angular コマンドで表示する)",
+        override_usage = "angular-dependency-analyzer health [オプション] [フォルダ・ファイル...]",
+        mut_arg("policy", |arg| arg.help("依存ポリシー (既定はフォルダとその親の dependency-policy.yaml。違反を境界の違反に数える)"))
+    )]
+    Health(ReportArgs),
+    #[command(
+        about = "解析結果を保持したまま JSON-RPC で問い合わせに答える",
+        long_about = "解析結果をメモリに保持したまま、Unix ドメインソケットで JSON-RPC 2.0 の問い合わせに答える",
+        override_usage = "angular-dependency-analyzer daemon [オプション] [フォルダ]",
+        after_help = "メソッド: report / usages {\"symbol\"} / reverse_deps {\"path\"} / unused {\"path\"?} / shutdown"
+    )]
+    Daemon(DaemonArgs),
+    #[command(
+        about = "Language Server として起動する",
+        long_about = "標準入出力で Language Server Protocol を話す (ワークスペースのルートは initialize で受け取る)",
+        override_usage = "angular-dependency-analyzer lsp [オプション]"
+    )]
+    Lsp(LspArgs),
+    #[command(
+        about = "git の履歴を辿ってパッケージごとの使用回数の推移を表示する",
+        long_about = "git の履歴を辿り、各時点のファイルを (チェックアウトせずに) 解析してパッケージごとの使用回数の推移を表示する",
+        override_usage = "angular-dependency-analyzer history [オプション] [フォルダ]",
+        mut_arg("format", |arg| arg.help("text (既定) / json / csv"))
+    )]
+    History(HistoryArgs),
+    #[command(
+        about = "2 つのフォルダ・ブランチのインポートとファイル間の依存を比べる",
+        long_about = "2 つのフォルダ・ブランチを解析し、インポートの増減・使用回数の変化・ファイル間の依存の増減を表示する",
+        override_usage = "angular-dependency-analyzer diff [オプション] <比較元> <比較先>",
+        after_help = "比較元・比較先には、フォルダか git のリビジョン (main、origin/main:src/app のように <リビジョン>:<フォルダ> とも書ける) を指定する。\n\
+                      リビジョンはカレントディレクトリのリポジトリからチェックアウトせずに読む"
+    )]
+    Diff(DiffArgs),
+    #[command(
+        about = "tsconfig の references のプロジェクトごとに解析し、プロジェクト間の依存を表示する",
+        long_about = "tsconfig の references を辿り、プロジェクトごとにその tsconfig (files / include / exclude と paths) で解析して、\n\
+                      プロジェクトをまたぐファイル間の依存を表示する (references に無いプロジェクトへの依存には印を付ける)",
+        override_usage = "angular-dependency-analyzer projects [オプション] [フォルダ]",
+        mut_arg("tsconfig", |arg| arg.help("起点の tsconfig (既定はフォルダとその親の tsconfig.json)"))
+    )]
+    Projects(ProjectsArgs),
+    #[command(
+        about = "npm / yarn / pnpm のワークスペースのパッケージごとに解析し、パッケージ間の依存を表示する",
+        long_about = "package.json の workspaces か pnpm-workspace.yaml のパッケージごとに解析して、パッケージをまたぐファイル間の依存と、\n\
+                      パッケージ名と exports で公開している入口を使わずに他のパッケージの中を参照しているインポートを表示する",
+        override_usage = "angular-dependency-analyzer workspaces [オプション] [フォルダ]"
+    )]
+    Workspaces(ProjectsArgs),
+    #[command(
+        about = "Nx のワークスペースのプロジェクトごとに解析し、プロジェクト間の依存を表示する",
+        long_about = "nx.json のあるワークスペースの project.json ごとに解析して、プロジェクトをまたぐファイル間の依存とその行列と、\n\
+                      project.json の tags に対する depConstraints を破っているインポートを表示する (インポートは tsconfig.base.json の paths で解決する)",
+        override_usage = "angular-dependency-analyzer nx [オプション] [フォルダ]",
+        mut_arg("tsconfig", |arg| arg.help("paths を読む tsconfig (既定はワークスペースのルートの tsconfig.base.json)"))
+    )]
+    Nx(NxArgs),
+    #[command(
+        about = "angular.json のプロジェクトごとに解析し、プロジェクト間の依存を表示する",
+        long_about = "angular.json の projects ごとに sourceRoot の下を解析して、プロジェクトの種類 (application / library) とビルダー、\n\
+                      プロジェクトをまたぐファイル間の依存とその行列を表示する (ライブラリの名前のインポートは ng-package.json の入口に解決する)",
+        override_usage = "angular-dependency-analyzer angular [オプション] [フォルダ]",
+        mut_arg("tsconfig", |arg| arg.help("paths を読む tsconfig (既定はフォルダとその親の tsconfig.json。build の tsConfig があればそちらを使う)"))
+    )]
+    Angular(ProjectsArgs),
+}
+
+#[derive(Args)]
+struct AnalyzeArgs {
+    #[arg(value_name = "フォルダ・ファイル", help = "解析対象 (既定はカレントディレクトリ)")]
+    targets: Vec<PathBuf>,
+    #[command(flatten)]
+    output: OutputArgs,
+    // --html out.html は --format html --output out.html の省略形
+    #[arg(long, value_name = "ファイル", help = "--format html --output <ファイル> の省略形")]
+    html: Option<PathBuf>,
+    #[arg(long, value_name = "種類:パス", help = "sqlite:deps.db / parquet:usage.parquet にも書き出す (複数指定可)")]
+    export: Vec<Export>,
+    #[arg(long, help = "ファイルごとの内訳も表示する")]
+    per_file: bool,
+    #[arg(long, help = "npm パッケージ単位で集計する")]
+    by_package: bool,
+    #[arg(long, value_name = "並び順", help = "count (既定) / files / name / module")]
+    sort: Option<SortKey>,
+    #[arg(long, help = "並び順を逆にする")]
+    reverse: bool,
+    #[arg(long, value_name = "パターン", help = "モジュール (パッケージ名) かインポート名がグロブに一致するインポートだけを出力する (例: '@angular/*'。複数指定可)")]
+    filter: Vec<String>,
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_positive("--top", value), help = "上位 N 件のインポート名・パッケージだけを表示し、残りは「その他」の 1 行にまとめる")]
+    top: Option<usize>,
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_positive("--min-count", value), help = "使用回数が N 回未満のインポート名・パッケージを「その他」にまとめる\n(--top / --min-count はテキストと Markdown の表示でだけ指定できる)")]
+    min_count: Option<usize>,
+    #[command(flatten)]
+    watch: WatchArgs,
+    #[command(flatten)]
+    stdin: StdinArgs,
+    #[command(flatten)]
+    changed: ChangedArgs,
+    #[arg(long, help = "pre-commit フック: ステージされた内容で、コミットで増える指摘だけを表示する")]
+    hook: bool,
+    #[command(flatten)]
+    baseline: BaselineArgs,
+    #[arg(long, help = "package.json の依存とインポートを照合する (未宣言・devDependencies のみ・未使用のパッケージ)")]
+    check_deps: bool,
+    #[arg(long, value_name = "ファイル", help = "--check-deps で照合する package.json (既定はフォルダとその親の package.json)")]
+    package_json: Option<PathBuf>,
+    #[arg(long, help = "main.ts・ルート定義・テスト等のエントリポイントからインポートを辿って届かないファイルを報告する")]
+    orphans: bool,
+    #[arg(long, help = "どのファイルからもインポートされていないエクスポートを報告する (エントリポイントのエクスポートは除く)")]
+    unused_exports: bool,
+    #[arg(long, value_name = "グロブ", help = "--orphans / --unused-exports で起点に加えるファイル (例: 'src/workers/*.ts'。複数指定可)")]
+    entry: Vec<String>,
+    // analyze コマンドで指定できる範囲は all だけ (Args::parse で検証する)
+    #[arg(long, value_name = "範囲", num_args = 0..=1, require_equals = true, default_missing_value = "all", help = "ファイル間の循環依存を、インポートを辿って戻ってくる連鎖とともに報告する")]
+    cycles: Option<CycleScope>,
+    #[arg(long, value_name = "ファイル", help = "今ある循環依存を既知のものとして保存する")]
+    write_cycles_baseline: Option<PathBuf>,
+    #[arg(long, value_name = "ファイル", help = "既知の循環依存を除いて報告し、新しい循環依存があれば終了コード 6 で終了する")]
+    cycles_baseline: Option<PathBuf>,
+    #[arg(long, help = "ファイル・フォルダごとの fan-in (インポートされている数) と fan-out を fan-in の多い順に表示する\n(フォルダは不安定度・抽象度・主系列からの距離も)")]
+    coupling: bool,
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct GraphArgs {
+    #[arg(value_name = "フォルダ・ファイル", help = "解析対象 (既定はカレントディレクトリ)")]
+    targets: Vec<PathBuf>,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[arg(long, help = "madge 形式で循環依存の一覧を出す (madge --circular --json と同じ)")]
+    circular: bool,
+    #[arg(long, value_name = "dir[:<深さ>]", help = "ファイルをフォルダ (深さを指定したら解析対象からその階層まで) にまとめ、フォルダ間の依存にする")]
+    group_by: Option<DirectoryGrouping>,
+    #[command(flatten)]
+    watch: WatchArgs,
+    #[command(flatten)]
+    stdin: StdinArgs,
+    #[command(flatten)]
+    changed: ChangedArgs,
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct UnusedArgs {
+    #[arg(value_name = "フォルダ・ファイル", help = "解析対象 (既定はカレントディレクトリ)")]
+    targets: Vec<PathBuf>,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(flatten)]
+    baseline: BaselineArgs,
+    #[arg(long, value_name = "パターン")]
+    filter: Vec<String>,
+    #[command(flatten)]
+    watch: WatchArgs,
+    #[command(flatten)]
+    stdin: StdinArgs,
+    #[command(flatten)]
+    changed: ChangedArgs,
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct UsagesArgs {
+    #[arg(value_name = "インポート名 | モジュール::名前", help = "使用箇所を探すインポート名 (例: HttpClient)")]
+    symbol: String,
+    #[arg(value_name = "フォルダ・ファイル", help = "解析対象 (既定はカレントディレクトリ)")]
+    targets: Vec<PathBuf>,
+    #[arg(short = 'C', long, value_name = "行数", value_parser = |value: &str| parse_count("--context", value), help = "前後に表示する行数 (既定は 2)")]
+    context: Option<usize>,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(flatten)]
+    watch: WatchArgs,
+    #[command(flatten)]
+    stdin: StdinArgs,
+    #[command(flatten)]
+    changed: ChangedArgs,
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct RdepsArgs {
+    #[arg(value_name = "ファイル | パッケージ", help = "調べるファイルかパッケージ (例: src/app/core/api.service.ts / rxjs)")]
+    target: String,
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+#[derive(Args)]
+struct ImpactArgs {
+    #[arg(value_name = "フォルダ・ファイル", help = "解析対象 (既定はカレントディレクトリ)")]
+    targets: Vec<PathBuf>,
+    #[arg(long, value_name = "パス", help = "変更したファイル (複数指定可)")]
+    file: Vec<PathBuf>,
+    #[arg(long, value_name = "ブランチ", num_args = 0..=1, require_equals = true, default_missing_value = git::DEFAULT_BASE)]
+    changed: Option<String>,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct PathArgs {
+    #[arg(value_name = "ファイル", help = "連鎖の起点のファイル (例: src/app/login/login.component.ts)")]
+    from: String,
+    #[arg(value_name = "ファイル | パッケージ", help = "連鎖の終点のファイルかパッケージ (例: chart.js)")]
+    to: String,
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+// clusters / health コマンド (rdeps / path / hubs / depth / deps コマンドもこれに引数を加えたもの)
+#[derive(Args)]
+struct ReportArgs {
+    #[arg(value_name = "フォルダ・ファイル", help = "解析対象 (既定はカレントディレクトリ)")]
+    targets: Vec<PathBuf>,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(flatten)]
+    watch: WatchArgs,
+    #[command(flatten)]
+    thresholds: ThresholdArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+// hubs / deps コマンド
+#[derive(Args)]
+struct RankArgs {
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_positive("--top", value))]
+    top: Option<usize>,
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+#[derive(Args)]
+struct DepthArgs {
+    #[arg(long, value_name = "グロブ", help = "エントリポイントに加えるファイル (例: 'src/workers/*.ts'。複数指定可)")]
+    entry: Vec<String>,
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_positive("--top", value), help = "表示する連鎖の数 (既定は 10)")]
+    top: Option<usize>,
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+#[derive(Args)]
+struct DaemonArgs {
+    #[arg(value_name = "フォルダ", help = "解析対象 (既定はカレントディレクトリ)")]
+    target: Option<PathBuf>,
+    #[arg(long, value_name = "パス", help = "待ち受けるソケット (既定は <フォルダ>/.angular-dep.sock)")]
+    socket: Option<PathBuf>,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct LspArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    #[arg(value_name = "フォルダ", help = "解析対象 (既定はカレントディレクトリ)")]
+    target: Option<PathBuf>,
+    #[arg(long, value_name = "範囲", help = "辿るコミットの範囲 (git log の形式。既定は HEAD。第一親だけを辿る)")]
+    range: Option<String>,
+    #[arg(long, help = "コミットの代わりにタグを古い順に辿る")]
+    tags: bool,
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_positive("--limit", value), help = "辿る時点の数 (既定は 20。超えたら最初と最後を含めて等間隔に間引く)")]
+    limit: Option<usize>,
+    #[arg(long, value_name = "名前", help = "表示するパッケージ (例: --package rxjs --package lodash。複数指定可)")]
+    package: Vec<String>,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    #[arg(value_name = "比較元", help = "比べる元のフォルダかリビジョン")]
+    before: PathBuf,
+    #[arg(value_name = "比較先", help = "比べる先のフォルダかリビジョン")]
+    after: PathBuf,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+// projects / workspaces / angular コマンド (nx コマンドもこれに引数を加えたもの)
+#[derive(Args)]
+struct ProjectsArgs {
+    #[arg(value_name = "フォルダ", help = "解析対象 (既定はカレントディレクトリ)")]
+    targets: Vec<PathBuf>,
+    #[arg(long, value_name = "範囲", num_args = 0..=1, require_equals = true, default_missing_value = "all", help = "ファイル間の循環依存を連鎖で表示する (all (既定) / within: プロジェクト内だけ / across: プロジェクトをまたぐものだけ)")]
+    cycles: Option<CycleScope>,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct NxArgs {
+    #[arg(long, value_name = "ファイル", help = "nx graph --file=graph.json の出力と比べ、Nx の依存グラフに無い依存と、インポートの無い依存を表示する")]
+    nx_graph: Option<PathBuf>,
+    #[arg(long, value_name = "ファイル", help = "project.json の tags に対する depConstraints (ESLint の設定か depConstraints の配列の JSON。\n既定はワークスペースのルートの .eslintrc.json)。破っているインポートを表示する")]
+    nx_constraints: Option<PathBuf>,
+    #[command(flatten)]
+    projects: ProjectsArgs,
+}
+
+// 出力形式と出力先 (形式名はコマンドごとに解釈が異なるため文字列のまま受け取る)
+#[derive(Args, Default)]
+struct OutputArgs {
+    #[arg(long, value_name = "形式", help = "text (既定) / json")]
+    format: Option<String>,
+    #[arg(short, long, value_name = "ファイル", help = "出力先 (拡張子から形式を推定する)")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Default)]
+struct WatchArgs {
+    #[arg(short, long, help = "変更を監視して解析し直す")]
+    watch: bool,
+}
+
+#[derive(Args, Default)]
+struct StdinArgs {
+    #[arg(long, help = "標準入力のソースを 1 ファイルとして解析する (エディタの保存前の内容など)")]
+    stdin: bool,
+    #[arg(long, value_name = "パス", help = "--stdin のソースのファイルパス (表示・.tsx の判定・設定ファイルの検索に使う)")]
+    stdin_filepath: Option<PathBuf>,
+}
+
+#[derive(Args, Default)]
+struct ChangedArgs {
+    // --changed だけなら HEAD。--changed main は main を解析対象として扱う
+    #[arg(long, value_name = "ブランチ", num_args = 0..=1, require_equals = true, default_missing_value = git::DEFAULT_BASE, help = "git で変更されたファイルだけを解析する (ブランチを指定するとその分岐点からの変更)")]
+    changed: Option<String>,
+    #[arg(long, help = "--changed のファイルをインポートしているファイルも解析する")]
+    with_dependents: bool,
+}
+
+#[derive(Args, Default)]
+struct BaselineArgs {
+    #[arg(long, value_name = "ファイル", help = "解析結果をベースラインとして保存する")]
+    write_baseline: Option<PathBuf>,
+    #[arg(long, value_name = "ファイル", help = "ベースラインから増えた・減ったインポートと新しい指摘だけを報告する")]
+    baseline: Option<PathBuf>,
+}
+
+// 閾値。設定ファイルの [thresholds] の同じ名前のキーより優先する
+#[derive(Args, Default)]
+#[command(next_help_heading = "閾値 (設定ファイルの [thresholds] にも max-unused-imports のように -- を除いた名前で書ける)")]
+struct ThresholdArgs {
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_count("--max-unused-imports", value), help = "未使用のインポートが N 件を超えたら終了コード 2 で終了する")]
+    max_unused_imports: Option<usize>,
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_count("--max-duplicate-imports", value), help = "重複したインポートが N 件を超えたら終了コード 3 で終了する")]
+    max_duplicate_imports: Option<usize>,
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_count("--max-parse-failures", value), help = "パースエラーのあるファイル (エラー箇所を除いて解析したものを含む) が N 件を超えたら終了コード 4 で終了する")]
+    max_parse_failures: Option<usize>,
+    #[arg(long, help = "パースエラーのあるファイルが 1 つでもあれば終了コード 4 で終了する (--max-parse-failures 0 と同じ)")]
+    strict: bool,
+    #[arg(long, value_name = "パッケージ>=<N", value_parser = config::parse_package_limit, help = "パッケージの使用回数が N 回を超えたら終了コード 5 で終了する (複数指定可)")]
+    max_uses_of: Vec<(String, usize)>,
+    #[arg(long, value_name = "条件", help = "cycle (循環依存) / new-cycle (ベースラインに無い循環依存) があれば終了コード 6 で終了する")]
+    fail_on: Vec<FailOn>,
+}
+
+// 全コマンド共通: 設定ファイル・キャッシュ・走査するファイル・インポートの解決
+#[derive(Args)]
+#[command(next_help_heading = "共通のオプション")]
+struct CommonArgs {
+    #[arg(long, value_name = "ファイル", help = "依存ポリシー (既定はフォルダとその親の dependency-policy.yaml)。違反があれば終了コード 7 で終了する")]
+    policy: Option<PathBuf>,
+    #[arg(long, value_name = "ファイル", help = "paths / baseUrl でインポートを解決する tsconfig (既定はフォルダとその親の tsconfig.json)")]
+    tsconfig: Option<PathBuf>,
+    #[arg(long, help = "tsconfig の files / include / exclude でコンパイル対象になるファイルだけを解析する")]
+    tsconfig_files: bool,
+    #[arg(long, value_name = "名前>=<パス", value_parser = config::parse_alias, help = "webpack / Vite のエイリアスのように、名前とその下の指定子をパスに解決する (例: @legacy=./src/legacy。複数指定可)")]
+    alias: Vec<(String, PathBuf)>,
+    #[arg(long, help = "解析結果のキャッシュを使わない")]
+    no_cache: bool,
+    #[arg(long, value_name = "フォルダ", help = "キャッシュの保存先")]
+    cache_dir: Option<PathBuf>,
+    #[arg(long, value_name = "グロブ", help = "一致するファイルだけを解析する (例: '**/*.component.ts'。複数指定可)")]
+    include: Vec<String>,
+    #[arg(long, value_name = "グロブ", help = "一致するファイル・フォルダを走査しない (例: '**/*.spec.ts'。複数指定可)")]
+    exclude: Vec<String>,
+    #[arg(long, value_name = "ファイル", help = ".gitignore 形式の除外パターンを追加で読む (複数指定可)")]
+    ignore_file: Vec<PathBuf>,
+    #[arg(long, help = ".gitignore / .ignore に従わずに走査する")]
+    no_ignore: bool,
+    #[arg(long, value_name = "N", value_parser = |value: &str| parse_positive("--max-depth", value), help = "N 階層までしか走査しない (1 ならフォルダ直下のファイルだけ)")]
+    max_depth: Option<usize>,
+    #[arg(long, help = "シンボリックリンクを辿って走査する (ループは辿らず、同じファイルは 1 回だけ解析する)")]
+    follow_symlinks: bool,
+    #[arg(long, value_name = "サイズ", value_parser = config::parse_size, help = "これより大きいファイルは解析しない (例: 512K / 2M)")]
+    max_file_size: Option<u64>,
+    #[arg(long, help = "@generated 等の目印がある生成されたファイルも解析する")]
+    include_generated: bool,
+    #[arg(long, value_name = "ファイル", help = "設定ファイル (既定はフォルダとその親の .angular-analyzer.toml)")]
+    config: Option<PathBuf>,
+    #[arg(long, help = "設定ファイルを読まない")]
+    no_config: bool,
+}
+
+// コマンドによらない形にまとめたコマンドラインの値 (指定できないコマンドでは既定値のまま)
+#[derive(Default)]
+pub struct Options {
+    pub command: Command,
+    pub targets: Vec<PathBuf>,
+    // usages コマンドのインポート名 / rdeps コマンドのファイルかパッケージ / path コマンドの起点
+    pub symbol: Option<String>,
+    // path コマンドの終点
+    pub path_to: Option<String>,
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+    pub html: Option<PathBuf>,
+    pub export: Vec<Export>,
+    pub context: Option<usize>,
+    pub top: Option<usize>,
+    pub group_by: Option<DirectoryGrouping>,
+    pub circular: bool,
+    pub config: Option<PathBuf>,
+    pub no_config: bool,
+    pub no_cache: bool,
+    pub cache_dir: Option<PathBuf>,
+    pub filter: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub ignore_file: Vec<PathBuf>,
+    pub no_ignore: bool,
+    pub max_depth: Option<usize>,
+    pub follow_symlinks: bool,
+    pub max_file_size: Option<u64>,
+    pub include_generated: bool,
+    pub strict: bool,
+    pub file: Vec<PathBuf>,
+    pub socket: Option<PathBuf>,
+    pub stdin: bool,
+    pub stdin_filepath: Option<PathBuf>,
+    pub changed: Option<String>,
+    pub with_dependents: bool,
+    pub write_baseline: Option<PathBuf>,
+    pub write_cycles_baseline: Option<PathBuf>,
+    pub cycles_baseline: Option<PathBuf>,
+    pub baseline: Option<PathBuf>,
+    pub max_unused_imports: Option<usize>,
+    pub max_duplicate_imports: Option<usize>,
+    pub max_parse_failures: Option<usize>,
+    pub max_uses_of: Vec<(String, usize)>,
+    pub fail_on: Vec<FailOn>,
+    pub policy: Option<PathBuf>,
+    pub tsconfig: Option<PathBuf>,
+    pub tsconfig_files: bool,
+    pub alias: Vec<(String, PathBuf)>,
+    pub orphans: bool,
+    pub cycles: Option<CycleScope>,
+    pub coupling: bool,
+    pub unused_exports: bool,
+    pub entry: Vec<String>,
+    pub check_deps: bool,
+    pub package_json: Option<PathBuf>,
+    pub nx_graph: Option<PathBuf>,
+    pub nx_constraints: Option<PathBuf>,
+    pub hook: bool,
+    pub watch: bool,
+    pub schema: bool,
+    pub per_file: bool,
+    pub by_package: bool,
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+    pub min_count: Option<usize>,
+    pub range: Option<String>,
+    pub tags: bool,
+    pub limit: Option<usize>,
+    pub package: Vec<String>,
+}
+
+impl Options {
+    // コマンドライン引数を読む (--help / --version はここで表示して終了する)
+    pub fn from_env() -> Result<Self> {
+        let command = Cli::command()
+            .arg(
+                Arg::new("help")
+                    .short('h')
+                    .long("help")
+                    .global(true)
+                    .action(ArgAction::HelpShort)
+                    .help("使い方を表示する (コマンド名と組み合わせるとコマンドごとの使い方)"),
+            )
+            .arg(Arg::new("version").short('V').long("version").action(ArgAction::Version).help("バージョンを表示する"))
+            .help_template(HELP_TEMPLATE)
+            .term_width(0);
+        let command = headings(command).mut_subcommands(|subcommand| {
+            let description = subcommand.get_long_about().or(subcommand.get_about()).cloned().unwrap_or_default();
+            headings(subcommand.before_help(description).help_template(SUBCOMMAND_HELP_TEMPLATE).term_width(0))
+        });
+        let matches = match command.try_get_matches() {
+            Ok(matches) => matches,
+            Err(err) if matches!(err.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) => err.exit(),
+            Err(err) => return Err(error(err)),
+        };
+        let cli = Cli::from_arg_matches(&matches).map_err(error)?;
+        Ok(Self {
+            schema: cli.schema,
+            ..match cli.command {
+                None => cli.analyze.options(false),
+                Some(command) => command.options(),
+            }
+        })
+    }
+
+    // どのコマンドにもある出力・走査のオプションと、コマンドによってはあるオプションから
+    fn new(command: Command, common: CommonArgs, output: OutputArgs, stdin: StdinArgs, changed: ChangedArgs, thresholds: ThresholdArgs) -> Self {
+        Self {
+            command,
+            format: output.format,
+            output: output.output,
+            stdin: stdin.stdin,
+            stdin_filepath: stdin.stdin_filepath,
+            changed: changed.changed,
+            with_dependents: changed.with_dependents,
+            max_unused_imports: thresholds.max_unused_imports,
+            max_duplicate_imports: thresholds.max_duplicate_imports,
+            max_parse_failures: thresholds.max_parse_failures,
+            max_uses_of: thresholds.max_uses_of,
+            fail_on: thresholds.fail_on,
+            strict: thresholds.strict,
+            policy: common.policy,
+            tsconfig: common.tsconfig,
+            tsconfig_files: common.tsconfig_files,
+            alias: common.alias,
+            no_cache: common.no_cache,
+            cache_dir: common.cache_dir,
+            include: common.include,
+            exclude: common.exclude,
+            ignore_file: common.ignore_file,
+            no_ignore: common.no_ignore,
+            max_depth: common.max_depth,
+            follow_symlinks: common.follow_symlinks,
+            max_file_size: common.max_file_size,
+            include_generated: common.include_generated,
+            config: common.config,
+            no_config: common.no_config,
+            ..Self::default()
+        }
+    }
+}
+
+impl CommandArgs {
+    fn options(self) -> Options {
+        match self {
+            Self::Analyze(args) => args.options(false),
+            // watch は analyze --watch の別名
+            Self::Watch(args) => args.options(true),
+            Self::Graph(args) => Options {
+                targets: args.targets,
+                group_by: args.group_by,
+                circular: args.circular,
+                watch: args.watch.watch,
+                ..Options::new(Command::Graph, args.common, args.output, args.stdin, args.changed, args.thresholds)
+            },
+            Self::Unused(args) => Options {
+                targets: args.targets,
+                filter: args.filter,
+                baseline: args.baseline.baseline,
+                write_baseline: args.baseline.write_baseline,
+                watch: args.watch.watch,
+                ..Options::new(Command::Unused, args.common, args.output, args.stdin, args.changed, args.thresholds)
+            },
+            Self::Usages(args) => Options {
+                targets: args.targets,
+                symbol: Some(args.symbol),
+                context: args.context,
+                watch: args.watch.watch,
+                ..Options::new(Command::Usages, args.common, args.output, args.stdin, args.changed, args.thresholds)
+            },
+            Self::Rdeps(args) => Options { symbol: Some(args.target), ..args.report.options(Command::Rdeps) },
+            Self::Impact(args) => Options {
+                targets: args.targets,
+                file: args.file,
+                changed: args.changed,
+                ..Options::new(Command::Impact, args.common, args.output, StdinArgs::default(), ChangedArgs::default(), ThresholdArgs::default())
+            },
+            Self::Path(args) => Options {
+                symbol: Some(args.from),
+                path_to: Some(args.to),
+                ..args.report.options(Command::Path)
+            },
+            Self::Clusters(args) => args.options(Command::Clusters),
+            Self::Hubs(args) => Options { top: args.top, ..args.report.options(Command::Hubs) },
+            Self::Depth(args) => Options { top: args.top, entry: args.entry, ..args.report.options(Command::Depth) },
+            Self::Deps(args) => Options { top: args.top, ..args.report.options(Command::Deps) },
+            Self::Health(args) => args.options(Command::Health),
+            Self::Daemon(args) => Options {
+                targets: args.target.into_iter().collect(),
+                socket: args.socket,
+                ..Options::new(Command::Daemon, args.common, OutputArgs::default(), StdinArgs::default(), ChangedArgs::default(), ThresholdArgs::default())
+            },
+            Self::Lsp(args) => {
+                Options::new(Command::Lsp, args.common, OutputArgs::default(), StdinArgs::default(), ChangedArgs::default(), ThresholdArgs::default())
+            }
+            Self::History(args) => Options {
+                targets: args.target.into_iter().collect(),
+                range: args.range,
+                tags: args.tags,
+                limit: args.limit,
+                package: args.package,
+                ..Options::new(Command::History, args.common, args.output, StdinArgs::default(), ChangedArgs::default(), ThresholdArgs::default())
+            },
+            Self::Diff(args) => Options {
+                targets: vec![args.before, args.after],
+                ..Options::new(Command::Diff, args.common, args.output, StdinArgs::default(), ChangedArgs::default(), ThresholdArgs::default())
+            },
+            Self::Projects(args) => args.options(Command::Projects),
+            Self::Workspaces(args) => args.options(Command::Workspaces),
+            Self::Nx(args) => Options {
+                nx_graph: args.nx_graph,
+                nx_constraints: args.nx_constraints,
+                ..args.projects.options(Command::Nx)
+            },
+            Self::Angular(args) => args.options(Command::Angular),
+        }
+    }
+}
+
+impl AnalyzeArgs {
+    fn options(self, watch: bool) -> Options {
+        Options {
+            targets: self.targets,
+            html: self.html,
+            export: self.export,
+            per_file: self.per_file,
+            by_package: self.by_package,
+            sort: self.sort,
+            reverse: self.reverse,
+            filter: self.filter,
+            top: self.top,
+            min_count: self.min_count,
+            watch: watch || self.watch.watch,
+            hook: self.hook,
+            baseline: self.baseline.baseline,
+            write_baseline: self.baseline.write_baseline,
+            check_deps: self.check_deps,
+            package_json: self.package_json,
+            orphans: self.orphans,
+            unused_exports: self.unused_exports,
+            entry: self.entry,
+            cycles: self.cycles,
+            write_cycles_baseline: self.write_cycles_baseline,
+            cycles_baseline: self.cycles_baseline,
+            coupling: self.coupling,
+            ..Options::new(Command::Analyze, self.common, self.output, self.stdin, self.changed, self.thresholds)
+        }
+    }
+}
+
+impl ReportArgs {
+    fn options(self, command: Command) -> Options {
+        Options {
+            targets: self.targets,
+            watch: self.watch.watch,
+            ..Options::new(command, self.common, self.output, StdinArgs::default(), ChangedArgs::default(), self.thresholds)
+        }
+    }
+}
+
+impl ProjectsArgs {
+    fn options(self, command: Command) -> Options {
+        Options {
+            targets: self.targets,
+            cycles: self.cycles,
+            ..Options::new(command, self.common, self.output, StdinArgs::default(), ChangedArgs::default(), ThresholdArgs::default())
+        }
+    }
+}
+
+// 見出しの無い引数を日本語の見出しに入れる
+// (見出しを付けた位置引数は名前順に並ぶので、定義した順に表示されるように順番も付ける)
+fn headings(command: clap::Command) -> clap::Command {
+    let mut position = 0;
+    command.mut_args(|arg| match (arg.get_help_heading().is_some(), arg.is_positional()) {
+        (true, _) => arg,
+        (false, true) => {
+            position += 1;
+            arg.help_heading("引数").display_order(position)
+        }
+        (false, false) => arg.help_heading("オプション"),
+    })
+}
+
+// 件数・行数 (0 以上の整数)
+fn parse_count(option: &str, value: &str) -> Result<usize> {
+    match value.parse() {
+        Ok(n) => Ok(n),
+        Err(_) => bail!("{option} には 0 以上の整数を指定してください: {value}"),
+    }
+}
+
+// 表示する件数・深さなど (1 以上の整数)
+fn parse_positive(option: &str, value: &str) -> Result<usize> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => bail!("{option} には 1 以上の整数を指定してください: {value}"),
+    }
+}
+
+// clap のエラーをこれまでと同じ日本語のメッセージにする
+fn error(err: clap::Error) -> anyhow::Error {
+    let arg = err.get(ContextKind::InvalidArg).map(|arg| arg.to_string()).unwrap_or_default();
+    // "--format <FORMAT>" のような表示からオプション名だけを取り出す
+    let option = arg.split([' ', '=']).next().unwrap_or_default();
+    // 値の検証で返したエラー (メッセージにオプション名が入っている)
+    let source = std::error::Error::source(&err).map(ToString::to_string);
+    match (err.kind(), source) {
+        (ErrorKind::UnknownArgument, _) if !option.starts_with('-') => anyhow!("余分な引数です: {arg} (--help で使い方を表示します)"),
+        (ErrorKind::UnknownArgument, _) => anyhow!("{option} はこのコマンドでは使えません (--help で使い方を表示します)"),
+        (ErrorKind::ValueValidation, Some(source)) => anyhow!("{source}"),
+        (ErrorKind::InvalidValue, _) if !option.is_empty() => anyhow!("{option} には値が必要です"),
+        (ErrorKind::MissingRequiredArgument, _) => anyhow!("{arg} を指定してください (--help で使い方を表示します)"),
+        // その他は clap のメッセージの 1 行目 (使い方の表示は除く)
+        _ => {
+            let rendered = err.render().to_string();
+            let line = rendered.lines().next().unwrap_or_default();
+            anyhow!("{}", line.trim_start_matches("error: "))
+        }
+    }
+}
@@ -0,0 +1,650 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use angular_dependency_analyzer::{
+    baseline::{Baseline, CyclesBaseline},
+    glob::Glob,
+    health::HealthWeights,
+    manifest::{self, InstalledPackage, Manifest},
+    policy::{self, Policy},
+    report::{RuleLevel, RULES},
+    ProjectAnalysis,
+};
+
+// 解析対象ディレクトリから親へ辿って探す設定ファイル
+pub const FILE_NAME: &str = ".angular-analyzer.toml";
+
+// 設定ファイルの内容。コマンドラインで指定した値が優先される
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfigFile {
+    // node_modules 等に加えて走査しないパスのグロブ (dist のような名前だけならどの階層でも一致する)
+    pub exclude: Vec<String>,
+    // 空でなければ、いずれかに一致するファイルだけを解析する
+    pub include: Vec<String>,
+    // .gitignore 形式の除外パターンを書いたファイル (設定ファイルのディレクトリからの相対パス)
+    pub ignore_files: Vec<PathBuf>,
+    // 解析対象の拡張子 (指定が無ければ DEFAULT_EXTENSIONS)
+    pub extensions: Option<Vec<String>>,
+    // analyze コマンドの出力形式と出力先 (出力先は設定ファイルのディレクトリからの相対パス)
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+    pub sort: Option<String>,
+    pub reverse: bool,
+    pub per_file: bool,
+    pub by_package: bool,
+    pub follow_symlinks: bool,
+    pub max_depth: Option<usize>,
+    // これより大きいファイルは解析しない (バイト数か "512K" / "2M" のような文字列)
+    pub max_file_size: Option<FileSize>,
+    // @generated 等の目印があるファイルも解析する
+    pub include_generated: bool,
+    // --strict と同じ (パースに失敗したファイルが 1 つでもあれば失敗にする)
+    pub strict: bool,
+    // --baseline と同じ (設定ファイルのディレクトリからの相対パス)
+    pub baseline: Option<PathBuf>,
+    // --cycles-baseline と同じ (設定ファイルのディレクトリからの相対パス)
+    pub cycles_baseline: Option<PathBuf>,
+    // --policy と同じ (設定ファイルのディレクトリからの相対パス)
+    pub policy: Option<PathBuf>,
+    // --tsconfig と同じ (設定ファイルのディレクトリからの相対パス)
+    pub tsconfig: Option<PathBuf>,
+    // --alias と同じ (名前 -> 設定ファイルのディレクトリからの相対パス)
+    pub aliases: BTreeMap<String, PathBuf>,
+    // --tsconfig-files と同じ (tsconfig の files / include / exclude でコンパイル対象になるファイルだけを解析する)
+    pub tsconfig_files: bool,
+    // --check-deps と同じ (package.json の dependencies を照合する)
+    pub check_dependencies: bool,
+    // --package-json と同じ (設定ファイルのディレクトリからの相対パス)
+    pub package_json: Option<PathBuf>,
+    // package.json との照合で指摘しないパッケージのグロブ (例: "@angular/localize")
+    pub ignore_dependencies: Vec<String>,
+    // devDependencies をインポートしてよいテストのファイル (--exclude と同じ形式。既定は *.spec.* や test / e2e フォルダ等)
+    pub test_files: Vec<String>,
+    // --orphans と同じ (どのエントリポイントからも辿れないファイルを報告する)
+    pub check_orphans: bool,
+    // --unused-exports と同じ (どのファイルからもインポートされていないエクスポートを報告する)
+    pub check_unused_exports: bool,
+    // --cycles と同じ (ファイル間の循環依存を報告する)
+    pub check_cycles: bool,
+    // --coupling と同じ (ファイル・フォルダごとの fan-in / fan-out を表示する)
+    pub check_coupling: bool,
+    // --entry と同じ (既定のエントリポイントに加える。--exclude と同じ形式)
+    pub entry_points: Vec<String>,
+    pub thresholds: Thresholds,
+    // ルール ID ごとの重要度 (off / warning / error)
+    pub rules: BTreeMap<String, RuleLevel>,
+    // health コマンドなどの健全性スコアの指標ごとの重み
+    pub health: HealthWeights,
+}
+
+// max-file-size の値 (整数ならバイト数)
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum FileSize {
+    Bytes(u64),
+    Text(String),
+}
+
+impl FileSize {
+    pub fn bytes(&self) -> Result<u64> {
+        match self {
+            Self::Bytes(bytes) => Ok(*bytes),
+            Self::Text(text) => parse_size(text),
+        }
+    }
+}
+
+// 1048576 / 512K / 1.5M のようなサイズをバイト数にする (K / M / G は 1024 倍ずつ。末尾の B と大文字・小文字は問わない)
+pub fn parse_size(value: &str) -> Result<u64> {
+    let upper = value.trim().to_ascii_uppercase();
+    let number = upper.strip_suffix('B').unwrap_or(&upper);
+    let (number, unit) = match number.strip_suffix(['K', 'M', 'G']) {
+        Some(rest) => (rest, 1024u64.pow(" KMG".find(&number[rest.len()..]).unwrap_or(0) as u32)),
+        None => (number, 1),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if n > 0.0 && n.is_finite() => Ok((n * unit as f64) as u64),
+        _ => bail!("サイズには 1 以上の数か 512K / 2M のような値を指定してください: {value}"),
+    }
+}
+
+// 閾値を超えたときの終了コード (CI でどの閾値を超えたか区別できるよう項目ごとに分ける)
+pub const EXIT_UNUSED_IMPORTS: i32 = 2;
+pub const EXIT_DUPLICATE_IMPORTS: i32 = 3;
+pub const EXIT_PARSE_FAILURES: i32 = 4;
+pub const EXIT_PACKAGE_USES: i32 = 5;
+pub const EXIT_CYCLES: i32 = 6;
+pub const EXIT_POLICY: i32 = 7;
+
+// 閾値を超えたときのメッセージに並べるパースに失敗したファイルの数
+const PARSE_FAILURES_SHOWN: usize = 5;
+
+// 超えたら失敗として終了する件数の上限 (キーはコマンドラインの --max-unused-imports 等と同じ名前で、コマンドラインの指定が優先される)
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Thresholds {
+    pub max_unused_imports: Option<usize>,
+    pub max_duplicate_imports: Option<usize>,
+    pub max_parse_failures: Option<usize>,
+    // パッケージごとの使用回数の上限 (rxjs/operators は rxjs に数える)
+    pub max_uses_of: BTreeMap<String, usize>,
+    // 見つかったら失敗にするもの
+    pub fail_on: Vec<FailOn>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailOn {
+    // ファイル間の循環依存
+    Cycle,
+    // --baseline に無い循環依存 (ベースラインが無ければすべて)
+    NewCycle,
+}
+
+impl FromStr for FailOn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cycle" => Ok(Self::Cycle),
+            "new-cycle" => Ok(Self::NewCycle),
+            other => bail!("--fail-on に指定できるのは cycle / new-cycle です: {other}"),
+        }
+    }
+}
+
+// 超えた閾値。終了コードは超えた項目のうち最も小さいもの
+#[derive(Debug)]
+pub struct Exceeded {
+    pub code: i32,
+    pub message: String,
+}
+
+impl Thresholds {
+    // 上限を超えた項目があればまとめて返す
+    // cycles_baseline (--cycles-baseline) があれば、それに無い循環依存も失敗にする
+    pub fn check(
+        &self,
+        analysis: &ProjectAnalysis,
+        baseline: Option<&Baseline>,
+        cycles_baseline: Option<&CyclesBaseline>,
+    ) -> Option<Exceeded> {
+        let report = &analysis.report;
+        // パースエラーの箇所を除いて解析したファイルも、パースエラーのあるファイルとして数える
+        let failed_paths: Vec<&str> = report
+            .parse_failures
+            .iter()
+            .chain(report.partial_files.iter().map(|partial| &partial.failure))
+            .map(|failure| failure.path.as_str())
+            .collect();
+        let counts = [
+            (EXIT_UNUSED_IMPORTS, "未使用のインポート", self.max_unused_imports, report.unused_imports.len()),
+            (EXIT_DUPLICATE_IMPORTS, "重複したインポート", self.max_duplicate_imports, report.duplicate_imports.len()),
+            (EXIT_PARSE_FAILURES, "パースエラー", self.max_parse_failures, failed_paths.len()),
+        ];
+        let mut exceeded: Vec<(i32, String)> = counts
+            .iter()
+            .filter_map(|&(code, label, max, actual)| {
+                max.filter(|&max| actual > max).map(|max| (code, format!("{label} {actual} 件 (上限 {max} 件)")))
+            })
+            .collect();
+        // 統計から外れた・一部だけを解析したファイルが分かるよう、パースエラーのあるファイルを添える
+        if let Some((_, message)) = exceeded.iter_mut().find(|(code, _)| *code == EXIT_PARSE_FAILURES) {
+            let paths = &failed_paths[..failed_paths.len().min(PARSE_FAILURES_SHOWN)];
+            message.push_str(&format!(": {}", paths.join(", ")));
+            if failed_paths.len() > PARSE_FAILURES_SHOWN {
+                message.push_str(&format!(" ほか {} ファイル", failed_paths.len() - PARSE_FAILURES_SHOWN));
+            }
+        }
+        for (package, &max) in &self.max_uses_of {
+            let actual = report.packages.iter().find(|p| p.package == *package).map_or(0, |p| p.count);
+            if actual > max {
+                exceeded.push((EXIT_PACKAGE_USES, format!("{package} の使用回数 {actual} 回 (上限 {max} 回)")));
+            }
+        }
+        if !self.fail_on.is_empty() || cycles_baseline.is_some() {
+            let cycles = analysis.graph.cycles();
+            let cycles = match (self.fail_on.contains(&FailOn::Cycle), cycles_baseline, baseline) {
+                (false, Some(known), _) => known.new_cycles(cycles),
+                (false, None, Some(baseline)) => baseline.new_cycles(cycles),
+                _ => cycles,
+            };
+            if let Some(first) = cycles.first() {
+                let label = match cycles_baseline {
+                    Some(_) if !self.fail_on.contains(&FailOn::Cycle) => "ベースラインに無い循環依存",
+                    _ => "循環依存",
+                };
+                let chain = analysis.graph.cycle_chain(first);
+                exceeded.push((EXIT_CYCLES, format!("{label} {} 件 (例: {})", cycles.len(), chain.join(" → "))));
+            }
+        }
+        // 依存ポリシーの違反は閾値の指定が無くても失敗にする ([rules] で off にしたものは除く)
+        let violations = report
+            .policy_violations
+            .iter()
+            .filter(|v| report.rule_levels.get(v.rule.rule().id) != Some(&RuleLevel::Off))
+            .count();
+        if violations > 0 {
+            exceeded.push((EXIT_POLICY, format!("依存ポリシーの違反 {violations} 件")));
+        }
+        let code = exceeded.iter().map(|(code, _)| *code).min()?;
+        let messages: Vec<_> = exceeded.into_iter().map(|(_, message)| message).collect();
+        Some(Exceeded {
+            code,
+            message: format!("閾値を超えました: {}", messages.join(", ")),
+        })
+    }
+
+    // コマンドラインで指定されなかった項目に設定ファイルの値を使う
+    pub fn or(mut self, file: Thresholds) -> Self {
+        self.max_unused_imports = self.max_unused_imports.or(file.max_unused_imports);
+        self.max_duplicate_imports = self.max_duplicate_imports.or(file.max_duplicate_imports);
+        self.max_parse_failures = self.max_parse_failures.or(file.max_parse_failures);
+        for (package, max) in file.max_uses_of {
+            self.max_uses_of.entry(package).or_insert(max);
+        }
+        for fail_on in file.fail_on {
+            if !self.fail_on.contains(&fail_on) {
+                self.fail_on.push(fail_on);
+            }
+        }
+        self
+    }
+}
+
+// --max-uses-of lodash=50
+pub fn parse_package_limit(value: &str) -> Result<(String, usize)> {
+    match value.split_once('=') {
+        Some((package, max)) if !package.is_empty() => match max.parse() {
+            Ok(max) => Ok((package.to_string(), max)),
+            Err(_) => bail!("--max-uses-of の上限には 0 以上の整数を指定してください: {value}"),
+        },
+        _ => bail!("--max-uses-of は <パッケージ>=<上限> の形式で指定してください (例: lodash=50): {value}"),
+    }
+}
+
+// --alias @legacy=./src/legacy
+pub fn parse_alias(value: &str) -> Result<(String, PathBuf)> {
+    match value.split_once('=') {
+        Some((name, target)) if !name.is_empty() && !target.is_empty() => Ok((name.to_string(), target.into())),
+        _ => bail!("--alias は <名前>=<パス> の形式で指定してください (例: @legacy=./src/legacy): {value}"),
+    }
+}
+
+// start (解析対象ディレクトリ) とその親ディレクトリから設定ファイルを探す
+pub fn find(start: &Path) -> Option<PathBuf> {
+    find_file(start, FILE_NAME)
+}
+
+// 解析対象ディレクトリから親へ辿って dependency-policy.yaml を探す
+pub fn find_policy(start: &Path) -> Option<PathBuf> {
+    find_file(start, policy::FILE_NAME)
+}
+
+// 解析対象ディレクトリから親へ辿って package.json を探す
+pub fn find_manifest(start: &Path) -> Option<PathBuf> {
+    find_file(start, manifest::FILE_NAME)
+}
+
+fn find_file(start: &Path, name: &str) -> Option<PathBuf> {
+    let start = fs::canonicalize(start).ok()?;
+    start.ancestors().map(|dir| dir.join(name)).find(|path| path.is_file())
+}
+
+pub fn load_policy(path: &Path) -> Result<Policy> {
+    let src = fs::read_to_string(path)
+        .with_context(|| format!("依存ポリシーを読み込めません: {}", path.display()))?;
+    Policy::parse(&src).with_context(|| format!("依存ポリシーの形式が正しくありません: {}", path.display()))
+}
+
+// display は指摘に表示する package.json のパス。ignore は照合で指摘しないパッケージ、test_files は空でなければ
+// devDependencies をインポートしてよいテストのファイルの既定のパターンを置き換える
+pub fn load_manifest(path: &Path, display: &str, ignore: &[String], test_files: &[String]) -> Result<Manifest> {
+    let src = fs::read_to_string(path).with_context(|| format!("package.json を読み込めません: {}", path.display()))?;
+    let mut manifest = Manifest::parse(display, &src)
+        .with_context(|| format!("package.json の形式が正しくありません: {}", path.display()))?;
+    manifest.ignore = ignore.iter().map(|pattern| Glob::new(pattern)).collect();
+    if !test_files.is_empty() {
+        manifest.test_files = test_files.iter().map(|pattern| Glob::new(pattern)).collect();
+    }
+    // ロックファイルがあれば、範囲ではなく実際にインストールされるバージョンを使う
+    let dir = path.parent().unwrap_or(Path::new(""));
+    if let Some((name, lockfile)) = manifest::LOCKFILES.iter().map(|name| (name, dir.join(name))).find(|(_, path)| path.is_file()) {
+        let src = fs::read_to_string(&lockfile)
+            .with_context(|| format!("ロックファイルを読み込めません: {}", lockfile.display()))?;
+        manifest
+            .read_lockfile(name, &src)
+            .with_context(|| format!("ロックファイルの形式が正しくありません: {}", lockfile.display()))?;
+    }
+    // 宣言した依存と、それらのピア依存のインストールされているバージョン (読めないものはインストールされていないとみなす)
+    let mut pending: Vec<String> = [
+        &manifest.dependencies,
+        &manifest.dev_dependencies,
+        &manifest.peer_dependencies,
+        &manifest.optional_dependencies,
+    ]
+    .into_iter()
+    .flat_map(|deps| deps.keys().cloned())
+    .collect();
+    let mut visited = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        if !visited.insert(name.clone()) {
+            continue;
+        }
+        let installed = dir
+            .ancestors()
+            .map(|dir| dir.join("node_modules").join(&name).join(manifest::FILE_NAME))
+            .find_map(|path| fs::read_to_string(path).ok())
+            .and_then(|src| InstalledPackage::parse(&src).ok());
+        if let Some(installed) = installed {
+            pending.extend(installed.peer_dependencies.keys().cloned());
+            manifest.installed.insert(name, installed);
+        }
+    }
+    Ok(manifest)
+}
+
+pub fn load(path: &Path) -> Result<ConfigFile> {
+    let src = fs::read_to_string(path).with_context(|| format!("設定ファイルを読み込めません: {}", path.display()))?;
+    let value = parse_toml(&src).with_context(|| format!("設定ファイルの形式が正しくありません: {}", path.display()))?;
+    let mut config: ConfigFile =
+        serde_json::from_value(value).with_context(|| format!("設定ファイルの値が正しくありません: {}", path.display()))?;
+
+    for id in config.rules.keys() {
+        if !RULES.iter().any(|rule| rule.id == id) {
+            let ids: Vec<_> = RULES.iter().map(|rule| rule.id).collect();
+            bail!("{}: 不明なルールです: {id} ({})", path.display(), ids.join(" / "));
+        }
+    }
+    if config.max_depth == Some(0) {
+        bail!("{}: max-depth には 1 以上の整数を指定してください", path.display());
+    }
+    if let Some(size) = &config.max_file_size {
+        size.bytes().with_context(|| format!("{}: max-file-size が正しくありません", path.display()))?;
+    }
+    if let Some(extensions) = &mut config.extensions {
+        for ext in extensions.iter_mut() {
+            *ext = ext.trim_start_matches('.').to_string();
+        }
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    config.output = config.output.map(|output| dir.join(output));
+    config.baseline = config.baseline.map(|baseline| dir.join(baseline));
+    config.cycles_baseline = config.cycles_baseline.map(|baseline| dir.join(baseline));
+    config.policy = config.policy.map(|policy| dir.join(policy));
+    config.tsconfig = config.tsconfig.map(|tsconfig| dir.join(tsconfig));
+    config.package_json = config.package_json.map(|package_json| dir.join(package_json));
+    for target in config.aliases.values_mut() {
+        *target = dir.join(&*target);
+    }
+    config.ignore_files = config.ignore_files.iter().map(|file| dir.join(file)).collect();
+    Ok(config)
+}
+
+// TOML のうち設定ファイルで使う範囲 (テーブル見出し・文字列・整数・真偽値・配列・コメント) を読む
+fn parse_toml(src: &str) -> Result<Value> {
+    let mut root = Map::new();
+    let mut table: Vec<String> = Vec::new();
+    let mut lines = src.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let number = index + 1;
+        let mut line = scan(line).0.trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let Some(header) = header.strip_suffix(']') else {
+                bail!("{number} 行目: テーブル見出しが ] で閉じられていません");
+            };
+            if header.starts_with('[') {
+                bail!("{number} 行目: テーブルの配列 ([[...]]) には対応していません");
+            }
+            table = header.split('.').map(|key| unquote_key(key.trim())).collect();
+            entry_table(&mut root, &table).map_err(|err| anyhow::anyhow!("{number} 行目: {err}"))?;
+            continue;
+        }
+        // 複数行にわたる配列は閉じ括弧まで読み進める
+        while scan(&line).1 > 0 {
+            let Some((_, next)) = lines.next() else {
+                bail!("{number} 行目: 配列が ] で閉じられていません");
+            };
+            line.push(' ');
+            line.push_str(scan(next).0.trim());
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("{number} 行目: キー = 値 の形式ではありません");
+        };
+        let key = unquote_key(key.trim());
+        let mut cursor = Cursor { src: value.trim(), pos: 0 };
+        let value = cursor
+            .value()
+            .and_then(|value| cursor.end().map(|_| value))
+            .map_err(|err| anyhow::anyhow!("{number} 行目: {err}"))?;
+        let target = entry_table(&mut root, &table).map_err(|err| anyhow::anyhow!("{number} 行目: {err}"))?;
+        if target.insert(key.clone(), value).is_some() {
+            bail!("{number} 行目: キーが重複しています: {key}");
+        }
+    }
+    Ok(Value::Object(root))
+}
+
+// 文字列の外にある # 以降 (コメント) を除いた部分と、閉じていない [ の数
+fn scan(line: &str) -> (&str, i32) {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '#' => return (&line[..i], depth),
+                _ => {}
+            },
+        }
+    }
+    (line, depth)
+}
+
+fn unquote_key(key: &str) -> String {
+    key.trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+// [a.b] のテーブル (無ければ作る)
+fn entry_table<'a>(root: &'a mut Map<String, Value>, path: &[String]) -> Result<&'a mut Map<String, Value>> {
+    let mut table = root;
+    for key in path {
+        let value = table.entry(key.clone()).or_insert_with(|| Value::Object(Map::new()));
+        let Value::Object(next) = value else {
+            bail!("{key} はテーブルではありません");
+        };
+        table = next;
+    }
+    Ok(table)
+}
+
+struct Cursor<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn end(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        if !self.rest().is_empty() {
+            bail!("値の後に余分な文字があります: {}", self.rest());
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        match rest.chars().next() {
+            Some('"') => self.basic_string(),
+            Some('\'') => {
+                let Some(len) = rest[1..].find('\'') else {
+                    bail!("文字列が ' で閉じられていません");
+                };
+                self.pos += len + 2;
+                Ok(Value::String(rest[1..len + 1].to_string()))
+            }
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.rest().starts_with(']') {
+                        self.pos += 1;
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    if self.rest().starts_with(',') {
+                        self.pos += 1;
+                    } else if !self.rest().starts_with(']') {
+                        bail!("配列の要素は , で区切ってください");
+                    }
+                }
+            }
+            _ if rest.starts_with("true") => {
+                self.pos += 4;
+                Ok(Value::Bool(true))
+            }
+            _ if rest.starts_with("false") => {
+                self.pos += 5;
+                Ok(Value::Bool(false))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '+' || c == '_'))
+                    .unwrap_or(rest.len());
+                let number: i64 = rest[..len].replace('_', "").parse().context("整数として読めません")?;
+                self.pos += len;
+                Ok(Value::from(number))
+            }
+            _ => bail!("未対応の値です: {rest}"),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<Value> {
+        let mut out = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(Value::String(out));
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('\\') => out.push('\\'),
+                    Some('"') => out.push('"'),
+                    other => bail!("未対応のエスケープです: \\{}", other.map(String::from).unwrap_or_default()),
+                },
+                c => out.push(c),
+            }
+        }
+        bail!("文字列が \" で閉じられていません")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_toml_reads_tables_and_values() {
+        let src = "\
+# コメント
+format = \"json\"   # 行末のコメント
+max-depth = 1_000
+strict = true
+
+[thresholds]
+max-unused = 0
+
+[\"rules\".'x']
+level = 'warn'
+";
+        let value = parse_toml(src).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "format": "json",
+                "max-depth": 1000,
+                "strict": true,
+                "thresholds": { "max-unused": 0 },
+                "rules": { "x": { "level": "warn" } },
+            })
+        );
+    }
+
+    #[test]
+    fn parse_toml_reads_multi_line_arrays() {
+        let src = "exclude = [\n  \"**/*.spec.ts\", # テスト\n  '**/dist/**',\n]\nnested = [[1, 2], [3]]\n";
+        let value = parse_toml(src).unwrap();
+        assert_eq!(value["exclude"], json!(["**/*.spec.ts", "**/dist/**"]));
+        assert_eq!(value["nested"], json!([[1, 2], [3]]));
+
+        let err = parse_toml("exclude = [\n  'a',\n").unwrap_err();
+        assert!(err.to_string().contains("閉じられていません"), "{err}");
+    }
+
+    #[test]
+    fn parse_toml_keeps_hash_inside_strings() {
+        let value = parse_toml("a = \"#not-comment\" # comment\nb = '[#]'\nc = \"q\\\"#\"\n").unwrap();
+        assert_eq!(value, json!({ "a": "#not-comment", "b": "[#]", "c": "q\"#" }));
+    }
+
+    #[test]
+    fn parse_toml_rejects_duplicate_keys() {
+        let err = parse_toml("[a]\nx = 1\n\n[b]\nx = 1\n[a]\nx = 2\n").unwrap_err();
+        assert_eq!(err.to_string(), "7 行目: キーが重複しています: x");
+    }
+
+    #[test]
+    fn parse_toml_rejects_array_of_tables() {
+        let err = parse_toml("[[projects]]\nname = 'a'\n").unwrap_err();
+        assert!(err.to_string().starts_with("1 行目: "), "{err}");
+    }
+
+    #[test]
+    fn parse_toml_rejects_malformed_lines() {
+        assert!(parse_toml("[a\n").is_err());
+        assert!(parse_toml("a\n").is_err());
+        assert!(parse_toml("a = 1 2\n").is_err());
+        assert!(parse_toml("a = \"x\n").is_err());
+        assert!(parse_toml("a = 1\n[a]\n").is_err());
+    }
+
+    #[test]
+    fn parse_size_units() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("2KB").unwrap(), 2 * 1024);
+        assert!(parse_size("big").is_err());
+    }
+}
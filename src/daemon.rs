@@ -0,0 +1,207 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+};
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use angular_dependency_analyzer::{cache::Cache, output, resolve, ProjectAnalysis};
+use crate::{
+    watch::{Changes, Project},
+    Args,
+};
+
+// 既定のソケットファイル (解析対象ディレクトリ直下)
+pub const DEFAULT_SOCKET: &str = ".angular-dep.sock";
+
+// JSON-RPC 2.0 のエラーコード
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+struct Request {
+    // 通知 (id なし) には応答しない
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+// daemon コマンド: 解析結果をメモリに保持したまま、ソケットで JSON-RPC の問い合わせに答える。
+// 1 行に 1 つのリクエスト / レスポンスを書く (改行区切り)
+pub fn run(args: &Args, cache: Option<Cache>) -> Result<()> {
+    let targets = args.targets.clone();
+    let socket = args
+        .socket
+        .clone()
+        .unwrap_or_else(|| args.base_dir().join(DEFAULT_SOCKET));
+
+    let config = args.config()?;
+    let changes = Changes::watch(&targets, &config)?;
+    let mut project = Project::default();
+    project.refresh(&targets, &config, &HashSet::new(), cache.as_ref())?;
+    // 問い合わせに答える時点の解析結果。ファイルが変わるたびに作り直して差し替える
+    let snapshot = Arc::new(Mutex::new(Arc::new(snapshot(&project, args)?)));
+
+    // ファイルの変更を監視し、変わったファイルだけ解析し直す
+    {
+        let args = args.clone();
+        let snapshot = Arc::clone(&snapshot);
+        thread::spawn(move || {
+            loop {
+                let changed = match changes.wait() {
+                    Ok(changed) => changed,
+                    Err(err) => {
+                        eprintln!("⚠️ 変更の監視を停止しました: {err}");
+                        return;
+                    }
+                };
+                let analyzed = match project.refresh(&targets, &config, &changed, cache.as_ref()) {
+                    Ok(analyzed) => analyzed,
+                    Err(err) => {
+                        eprintln!("⚠️ 解析し直せません: {err}");
+                        continue;
+                    }
+                };
+                match self::snapshot(&project, &args) {
+                    Ok(fresh) => *snapshot.lock().unwrap() = Arc::new(fresh),
+                    Err(err) => eprintln!("⚠️ 解析結果を更新できません: {err}"),
+                }
+                eprintln!("--- {analyzed} ファイルを解析し直しました ---");
+            }
+        });
+    }
+
+    serve(&socket, &snapshot)
+}
+
+fn snapshot(project: &Project, args: &Args) -> Result<ProjectAnalysis> {
+    Ok(project.aggregate(args, &mut io::sink())?.finish())
+}
+
+#[cfg(unix)]
+fn serve(socket: &Path, snapshot: &Arc<Mutex<Arc<ProjectAnalysis>>>) -> Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    // 前回異常終了したときのソケットファイルが残っていれば消す
+    if socket.exists() {
+        fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    eprintln!("{} で問い合わせを待っています (Ctrl+C で終了)", socket.display());
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("⚠️ 接続を受け付けられません: {err}");
+                continue;
+            }
+        };
+        let snapshot = Arc::clone(snapshot);
+        let socket = socket.to_path_buf();
+        thread::spawn(move || {
+            let reader = match stream.try_clone() {
+                Ok(reader) => BufReader::new(reader),
+                Err(err) => {
+                    eprintln!("⚠️ 接続を受け付けられません: {err}");
+                    return;
+                }
+            };
+            let mut writer = stream;
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                // 応答中に解析し直しても、1 つのリクエストの間は同じ結果を見る
+                let current = Arc::clone(&snapshot.lock().unwrap());
+                let (response, shutdown) = handle(&line, &current);
+                if let Some(response) = response
+                    && writeln!(writer, "{response}").is_err()
+                {
+                    break;
+                }
+                if shutdown {
+                    let _ = fs::remove_file(&socket);
+                    std::process::exit(0);
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve(_: &Path, _: &Arc<Mutex<Arc<ProjectAnalysis>>>) -> Result<()> {
+    anyhow::bail!("daemon コマンドは Unix ドメインソケットが使える OS でのみ利用できます")
+}
+
+// 1 行分のリクエストを処理し、応答 (通知なら None) と終了要求かどうかを返す
+fn handle(line: &str, snapshot: &ProjectAnalysis) -> (Option<Value>, bool) {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => return (Some(error(Value::Null, PARSE_ERROR, &err.to_string())), false),
+    };
+    let shutdown = request.method == "shutdown";
+    let result = call(&request.method, &request.params, snapshot);
+    let response = request.id.map(|id| match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, &message),
+    });
+    (response, shutdown)
+}
+
+pub fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+// 文字列パラメータ (無ければ INVALID_PARAMS)
+fn string_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, (i64, String)> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| (INVALID_PARAMS, format!("パラメータ {name} (文字列) が必要です")))
+}
+
+fn call(method: &str, params: &Value, snapshot: &ProjectAnalysis) -> Result<Value, (i64, String)> {
+    let ProjectAnalysis { report, graph } = snapshot;
+    let root = Path::new(&report.target);
+    match method {
+        // JSON 出力と同じレポート全体
+        "report" => Ok(json!(report)),
+        // { "symbol": "HttpClient" } の使用箇所
+        "usages" => {
+            let symbol = string_param(params, "symbol")?;
+            Ok(json!(output::usages::find(report, symbol)))
+        }
+        // { "path": "src/app/user.service.ts" } を直接インポートしているファイル
+        "reverse_deps" => {
+            let path = string_param(params, "path")?;
+            let id = resolve::relative_display(root, Path::new(path));
+            Ok(json!(graph.dependents(&id)))
+        }
+        // 未使用のインポート。{ "path": ... } を指定するとそのファイルだけ
+        "unused" => {
+            let path = params
+                .get("path")
+                .and_then(Value::as_str)
+                .map(|p| resolve::relative_display(root, Path::new(p)));
+            let unused: Vec<_> = report
+                .unused_imports
+                .iter()
+                .filter(|u| path.as_ref().is_none_or(|p| resolve::relative_display(root, Path::new(&u.path)) == *p))
+                .collect();
+            Ok(json!(unused))
+        }
+        "shutdown" => Ok(Value::Null),
+        other => Err((METHOD_NOT_FOUND, format!("未対応のメソッドです: {other}"))),
+    }
+}
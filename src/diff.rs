@@ -0,0 +1,33 @@
+use std::path::Path;
+use anyhow::Result;
+use angular_dependency_analyzer::{self as analyzer, baseline::TreeDiff, cache, output, ProjectAnalysis};
+use crate::{git, open_output, Args};
+
+// diff コマンド: 2 つのフォルダ (またはブランチ・タグ等のリビジョン) を解析して比べる
+pub fn run(args: &Args) -> Result<()> {
+    let before = analyze(args, &args.targets[0])?;
+    let after = analyze(args, &args.targets[1])?;
+    let mut diff = TreeDiff::new(&before, &after);
+    // リビジョンはどちらも . として解析するので、指定されたとおりに表示する
+    diff.before = args.targets[0].display().to_string();
+    diff.after = args.targets[1].display().to_string();
+    let mut out = open_output(args)?;
+    output::baseline::write_tree_diff(args.diff_format()?, &diff, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+// 存在するパスならそのフォルダを、そうでなければ git のリビジョン (main、v1.0:src/app のように
+// <リビジョン>:<フォルダ> とも書ける) としてカレントディレクトリのリポジトリから読んで解析する
+fn analyze(args: &Args, side: &Path) -> Result<ProjectAnalysis> {
+    let mut config = args.config()?;
+    if side.exists() {
+        if !args.no_cache {
+            config.cache_dir = Some(args.cache_dir.clone().unwrap_or_else(|| side.join(cache::DEFAULT_DIR)));
+        }
+        return analyzer::analyze_dir(side, &config);
+    }
+    let side = side.to_string_lossy();
+    let (rev, dir) = side.split_once(':').unwrap_or((&side, "."));
+    git::analyze_revision(Path::new(if dir.is_empty() { "." } else { dir }), rev, &config)
+}
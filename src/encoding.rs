@@ -0,0 +1,116 @@
+// ソースファイルのバイト列を文字列にする。
+// UTF-8 (BOM 付きを含む)・UTF-16 (BOM の有無は問わない)・Shift_JIS (Windows の CP932) を読む。
+// どれとしても読めなければ None を返す (解析するファイルは読めなかったものとして飛ばす)
+
+use encoding_rs::SHIFT_JIS;
+
+const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: [u8; 2] = [0xFF, 0xFE];
+const UTF16_BE_BOM: [u8; 2] = [0xFE, 0xFF];
+
+// BOM の無い UTF-16 を見分けるために調べる先頭のバイト数
+const SNIFF_BYTES: usize = 1024;
+
+pub fn decode(bytes: &[u8]) -> Option<String> {
+    if let Some(rest) = bytes.strip_prefix(&UTF8_BOM) {
+        return Some(String::from_utf8_lossy(rest).into_owned());
+    }
+    if let Some(rest) = bytes.strip_prefix(&UTF16_LE_BOM) {
+        return Some(decode_utf16(rest, u16::from_le_bytes));
+    }
+    if let Some(rest) = bytes.strip_prefix(&UTF16_BE_BOM) {
+        return Some(decode_utf16(rest, u16::from_be_bytes));
+    }
+    match utf16_without_bom(bytes) {
+        Some(Endian::Little) => return Some(decode_utf16(bytes, u16::from_le_bytes)),
+        Some(Endian::Big) => return Some(decode_utf16(bytes, u16::from_be_bytes)),
+        None => {}
+    }
+    if let Ok(src) = std::str::from_utf8(bytes) {
+        return Some(src.to_string());
+    }
+    // Shift_JIS として読めないバイト列が 1 つでもあれば、別の文字コードとみなす
+    let (src, had_errors) = SHIFT_JIS.decode_without_bom_handling(bytes);
+    (!had_errors).then(|| src.into_owned())
+}
+
+// 表示や git の履歴の解析に使う。読めない文字は U+FFFD に置き換える
+pub fn decode_lossy(bytes: &[u8]) -> String {
+    decode(bytes).unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned())
+}
+
+enum Endian {
+    Little,
+    Big,
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]])).collect();
+    String::from_utf16_lossy(&units)
+}
+
+// ASCII 中心のソースを UTF-16 で保存すると、奇数番目 (LE) か偶数番目 (BE) のバイトの大半が 0 になる
+fn utf16_without_bom(bytes: &[u8]) -> Option<Endian> {
+    let head = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    if head.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = head.len() / 2;
+    let zeros_at = |offset: usize| head.iter().skip(offset).step_by(2).take(pairs).filter(|&&b| b == 0).count();
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    if odd * 2 > pairs && even == 0 {
+        Some(Endian::Little)
+    } else if even * 2 > pairs && odd == 0 {
+        Some(Endian::Big)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(src: &str, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        src.encode_utf16().flat_map(to_bytes).collect()
+    }
+
+    #[test]
+    fn strips_utf8_bom() {
+        assert_eq!(decode(b"\xEF\xBB\xBFimport 'a';").unwrap(), "import 'a';");
+        assert_eq!(decode("// 日本語".as_bytes()).unwrap(), "// 日本語");
+    }
+
+    #[test]
+    fn reads_utf16_with_bom() {
+        let src = "import { A } from 'a'; // 日本語";
+        let le = [&UTF16_LE_BOM[..], &utf16(src, u16::to_le_bytes)].concat();
+        let be = [&UTF16_BE_BOM[..], &utf16(src, u16::to_be_bytes)].concat();
+        assert_eq!(decode(&le).unwrap(), src);
+        assert_eq!(decode(&be).unwrap(), src);
+    }
+
+    #[test]
+    fn sniffs_utf16_without_bom() {
+        let src = "import { A } from 'a';\nexport const b = A;\n";
+        assert_eq!(decode(&utf16(src, u16::to_le_bytes)).unwrap(), src);
+        assert_eq!(decode(&utf16(src, u16::to_be_bytes)).unwrap(), src);
+        // 0 の混じる UTF-8 のバイト列は UTF-16 とみなさない
+        assert_eq!(decode(b"a\0b\0c").unwrap(), "a\0b\0c");
+    }
+
+    #[test]
+    fn reads_shift_jis() {
+        // "// 表示" と半角カナの "ｱ"。表 (0x95 0x5C) の 2 バイト目は \ と同じ値
+        let bytes = b"// \x95\x5C\x8E\xA6 \xB1\nimport 'a';";
+        assert_eq!(decode(bytes).unwrap(), "// 表示 ｱ\nimport 'a';");
+    }
+
+    #[test]
+    fn rejects_other_encodings() {
+        // Shift_JIS の 2 バイト目にならない 0xFF が続く
+        let bytes = b"// \x81\xFF\nimport 'a';";
+        assert_eq!(decode(bytes), None);
+        assert_eq!(decode_lossy(bytes), "// \u{FFFD}\u{FFFD}\nimport 'a';");
+    }
+}
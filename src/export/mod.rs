@@ -0,0 +1,38 @@
+mod parquet;
+mod sqlite;
+
+use std::{path::PathBuf, str::FromStr};
+use anyhow::{bail, Result};
+use crate::{graph::DependencyGraph, report::Report};
+
+// 標準の出力とは別に書き出すエクスポート先 (--export 種類:パス)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Export {
+    Sqlite(PathBuf),
+    Parquet(PathBuf),
+}
+
+impl FromStr for Export {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((kind, path)) = s.split_once(':') else {
+            bail!("--export は 種類:パス の形式で指定してください (例: sqlite:deps.db): {s}");
+        };
+        if path.is_empty() {
+            bail!("--export の出力先パスが空です: {s}");
+        }
+        match kind {
+            "sqlite" => Ok(Self::Sqlite(path.into())),
+            "parquet" => Ok(Self::Parquet(path.into())),
+            other => bail!("未対応のエクスポート形式です: {other} (sqlite / parquet)"),
+        }
+    }
+}
+
+pub fn write(export: &Export, report: &Report, graph: &DependencyGraph) -> Result<()> {
+    match export {
+        Export::Sqlite(path) => sqlite::write(path, report, graph),
+        Export::Parquet(path) => parquet::write(path, report),
+    }
+}
@@ -0,0 +1,50 @@
+use std::{fs::File, path::Path, sync::Arc};
+use anyhow::Result;
+use arrow_array::{ArrayRef, BooleanArray, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use crate::report::Report;
+
+// ファイルごとの使用回数テーブル (1 行 = 1 ファイル × 1 インポート)
+pub fn write(path: &Path, report: &Report) -> Result<()> {
+    let rows = report
+        .files
+        .iter()
+        .flat_map(|file| file.imports.iter().map(move |import| (file, import)));
+
+    let mut paths = Vec::new();
+    let mut names = Vec::new();
+    let mut modules = Vec::new();
+    let mut counts = Vec::new();
+    let mut type_only = Vec::new();
+    for (file, import) in rows {
+        paths.push(file.path.as_str());
+        names.push(import.name.as_str());
+        modules.push(import.module.as_str());
+        counts.push(import.count as u64);
+        type_only.push(import.type_only);
+    }
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("target", DataType::Utf8, false),
+        Field::new("path", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("module", DataType::Utf8, false),
+        Field::new("count", DataType::UInt64, false),
+        Field::new("type_only", DataType::Boolean, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![report.target.as_str(); paths.len()])),
+        Arc::new(StringArray::from(paths)),
+        Arc::new(StringArray::from(names)),
+        Arc::new(StringArray::from(modules)),
+        Arc::new(UInt64Array::from(counts)),
+        Arc::new(BooleanArray::from(type_only)),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use std::path::Path;
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection};
+use crate::{graph::DependencyGraph, report::Report};
+
+// テーブルの形を変えたら上げ、migrate に古い形からの変換を足す (PRAGMA user_version に記録する)
+const SCHEMA_VERSION: i64 = 3;
+
+// 実行ごとに runs へ 1 行追加し、他のテーブルは run_id で紐付ける。
+// 既存のデータベースに追記するので、複数回の実行結果を SQL で比較できる
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id             INTEGER PRIMARY KEY,
+    target         TEXT NOT NULL,
+    analyzed_at    TEXT NOT NULL DEFAULT (datetime('now')),
+    tool_version   TEXT NOT NULL,
+    files_analyzed INTEGER NOT NULL,
+    parse_failures INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS files (
+    id     INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    path   TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS imports (
+    id      INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files(id),
+    name    TEXT NOT NULL,
+    module    TEXT NOT NULL,
+    count     INTEGER NOT NULL,
+    type_only INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS usages (
+    run_id  INTEGER NOT NULL REFERENCES runs(id),
+    name    TEXT NOT NULL,
+    module  TEXT NOT NULL,
+    count   INTEGER NOT NULL,
+    files   INTEGER NOT NULL,
+    type_only_files INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS packages (
+    run_id  INTEGER NOT NULL REFERENCES runs(id),
+    package TEXT NOT NULL,
+    count   INTEGER NOT NULL,
+    files   INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS edges (
+    run_id    INTEGER NOT NULL REFERENCES runs(id),
+    from_path TEXT NOT NULL,
+    to_path   TEXT NOT NULL,
+    weight    INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS parse_failures (
+    run_id  INTEGER NOT NULL REFERENCES runs(id),
+    path    TEXT NOT NULL,
+    message TEXT NOT NULL,
+    line    INTEGER NOT NULL,
+    column  INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_files_run ON files(run_id);
+CREATE INDEX IF NOT EXISTS idx_imports_file ON imports(file_id);
+CREATE INDEX IF NOT EXISTS idx_usages_run ON usages(run_id);
+CREATE INDEX IF NOT EXISTS idx_packages_run ON packages(run_id);
+CREATE INDEX IF NOT EXISTS idx_edges_run ON edges(run_id);
+";
+
+pub fn write(path: &Path, report: &Report, graph: &DependencyGraph) -> Result<()> {
+    let mut conn = Connection::open(path)?;
+    migrate(&conn).with_context(|| format!("SQLite のデータベースを今の形にそろえられません: {}", path.display()))?;
+
+    // 途中で失敗しても中途半端な run が残らないよう 1 トランザクションで書く
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO runs (target, tool_version, files_analyzed, parse_failures) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            report.target,
+            env!("CARGO_PKG_VERSION"),
+            report.files_analyzed,
+            report.parse_failures.len()
+        ],
+    )?;
+    let run_id = tx.last_insert_rowid();
+
+    {
+        let mut insert_file = tx.prepare("INSERT INTO files (run_id, path) VALUES (?1, ?2)")?;
+        let mut insert_import =
+            tx.prepare("INSERT INTO imports (file_id, name, module, count, type_only) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for file in &report.files {
+            insert_file.execute(params![run_id, file.path])?;
+            let file_id = tx.last_insert_rowid();
+            for import in &file.imports {
+                insert_import.execute(params![file_id, import.name, import.module, import.count, import.type_only])?;
+            }
+        }
+
+        let mut insert_usage = tx.prepare(
+            "INSERT INTO usages (run_id, name, module, count, files, type_only_files) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for usage in &report.usages {
+            insert_usage.execute(params![
+                run_id,
+                usage.name,
+                usage.module,
+                usage.count,
+                usage.files,
+                usage.type_only_files
+            ])?;
+        }
+
+        let mut insert_package =
+            tx.prepare("INSERT INTO packages (run_id, package, count, files) VALUES (?1, ?2, ?3, ?4)")?;
+        for package in &report.packages {
+            insert_package.execute(params![run_id, package.package, package.count, package.files])?;
+        }
+
+        let mut insert_edge =
+            tx.prepare("INSERT INTO edges (run_id, from_path, to_path, weight) VALUES (?1, ?2, ?3, ?4)")?;
+        for edge in &graph.edges {
+            insert_edge.execute(params![run_id, edge.from, edge.to, edge.weight])?;
+        }
+
+        let mut insert_failure = tx.prepare(
+            "INSERT INTO parse_failures (run_id, path, message, line, column) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for failure in &report.parse_failures {
+            insert_failure.execute(params![run_id, failure.path, failure.message, failure.line, failure.column])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+// 既存のデータベースのテーブルを今の形にそろえる。user_version を記録する前に作られたものは列から形を判断する
+// (1: usages.modules にモジュールを ; でつないでいた / 2: 型のみのインポートの列が無い)
+fn migrate(conn: &Connection) -> Result<()> {
+    let mut version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        bail!(
+            "新しいバージョンのツールで作られたデータベースです (スキーマ {version} / 対応しているのは {SCHEMA_VERSION})。\
+             別のファイルに書き出してください"
+        );
+    }
+    if version == 0 && has_table(conn, "runs")? {
+        version = if has_column(conn, "usages", "modules")? {
+            1
+        } else if !has_column(conn, "usages", "type_only_files")? {
+            2
+        } else {
+            SCHEMA_VERSION
+        };
+    }
+    if version == 1 {
+        conn.execute_batch("ALTER TABLE usages RENAME COLUMN modules TO module;")?;
+    }
+    if (1..=2).contains(&version) {
+        conn.execute_batch(
+            "ALTER TABLE imports ADD COLUMN type_only INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE usages ADD COLUMN type_only_files INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+    conn.execute_batch(SCHEMA)?;
+    conn.execute_batch(&format!("PRAGMA user_version = {SCHEMA_VERSION};"))?;
+    Ok(())
+}
+
+fn has_table(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i64 =
+        conn.query_row("SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = ?1", [table], |row| row.get(0))?;
+    Ok(count > 0)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT count(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // synth-10 の形のデータベース
+    const SCHEMA_V1: &str = "
+        CREATE TABLE runs (id INTEGER PRIMARY KEY, target TEXT NOT NULL);
+        CREATE TABLE imports (id INTEGER PRIMARY KEY, file_id INTEGER NOT NULL, name TEXT NOT NULL,
+                              module TEXT NOT NULL, count INTEGER NOT NULL);
+        CREATE TABLE usages (run_id INTEGER NOT NULL, name TEXT NOT NULL, modules TEXT NOT NULL,
+                             count INTEGER NOT NULL, files INTEGER NOT NULL);
+        INSERT INTO usages VALUES (1, 'map', 'rxjs;lodash', 3, 2);
+    ";
+
+    fn version(conn: &Connection) -> i64 {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn creates_new_database_at_current_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        assert_eq!(version(&conn), SCHEMA_VERSION);
+        assert!(has_column(&conn, "usages", "type_only_files").unwrap());
+    }
+
+    #[test]
+    fn migrates_unversioned_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA_V1).unwrap();
+        migrate(&conn).unwrap();
+        assert_eq!(version(&conn), SCHEMA_VERSION);
+        assert!(has_column(&conn, "usages", "module").unwrap());
+        assert!(!has_column(&conn, "usages", "modules").unwrap());
+        assert!(has_column(&conn, "imports", "type_only").unwrap());
+        assert!(has_table(&conn, "packages").unwrap());
+        let (module, type_only_files): (String, i64) = conn
+            .query_row("SELECT module, type_only_files FROM usages", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((module.as_str(), type_only_files), ("rxjs;lodash", 0));
+        // 2 回目は何もしない
+        migrate(&conn).unwrap();
+    }
+
+    #[test]
+    fn rejects_newer_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA user_version = 99;").unwrap();
+        assert!(migrate(&conn).is_err());
+    }
+}
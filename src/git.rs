@@ -0,0 +1,214 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+use anyhow::{bail, Context, Result};
+use angular_dependency_analyzer::{
+    self as analyzer,
+    cache::Cache,
+    encoding,
+    resolve,
+    Aggregate,
+    AnalyzerConfig,
+    FileOutcome,
+    ProjectAnalysis,
+};
+
+// --changed で基準を省略したときは作業ツリーと HEAD の差分 (未コミットの変更) を見る
+pub const DEFAULT_BASE: &str = "HEAD";
+
+// dir を含むリポジトリで、base との分岐点から変更・追加されたファイル (実体のパス)。
+// コミット済みの変更に加えて、ステージ済み・未ステージの変更と未追跡のファイルも含める。削除されたファイルは除く
+pub fn changed_files(dir: &Path, base: &str) -> Result<HashSet<PathBuf>> {
+    let top = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim_end());
+    let merge_base = git(dir, &["merge-base", base, "HEAD"])
+        .with_context(|| format!("--changed の基準 {base} と HEAD の分岐点が見つかりません"))?;
+    let diff = git(dir, &["diff", "--name-only", "-z", "--diff-filter=d", merge_base.trim_end()])?;
+    let untracked = git(dir, &["ls-files", "--others", "--exclude-standard", "--full-name", "-z"])?;
+    Ok(diff
+        .split('\0')
+        .chain(untracked.split('\0'))
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| fs::canonicalize(top.join(name)).ok())
+        .collect())
+}
+
+// dir を含むリポジトリのルートと、ステージされたファイル (ルートからの相対パス)。削除されたファイルは除く
+pub fn staged_files(dir: &Path) -> Result<(PathBuf, Vec<String>)> {
+    let top = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim_end());
+    let names = git(dir, &["diff", "--cached", "--name-only", "-z", "--diff-filter=d"])?;
+    Ok((top, names.split('\0').filter(|name| !name.is_empty()).map(String::from).collect()))
+}
+
+// git show <object> の内容 (:path ならインデックス、HEAD:path なら最新のコミット)。無ければ None
+pub fn show(dir: &Path, object: &str) -> Option<String> {
+    git_output(dir, &["show", object]).ok().map(|bytes| encoding::decode_lossy(&bytes))
+}
+
+// 履歴をたどるときの 1 時点
+pub struct Revision {
+    // git の各コマンドに渡すオブジェクト名
+    pub id: String,
+    // コミット (タグ) の日付 (YYYY-MM-DD)
+    pub date: String,
+    // タグ名かコミットの件名
+    pub label: String,
+}
+
+// range (git log に渡す範囲。第一親だけを辿る) のコミットか、tags ならすべてのタグを古い順に
+pub fn revisions(dir: &Path, range: &str, tags: bool) -> Result<Vec<Revision>> {
+    let lines = if tags {
+        git(
+            dir,
+            &[
+                "for-each-ref",
+                "--sort=creatordate",
+                "--format=%(objectname)%09%(creatordate:short)%09%(refname:short)",
+                "refs/tags",
+            ],
+        )?
+    } else {
+        git(dir, &["log", "--first-parent", "--reverse", "--date=short", "--format=%H%x09%cd%x09%s", range])?
+    };
+    Ok(lines
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            Some(Revision {
+                id: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                label: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+// rev の時点で dir 以下にあったファイル (dir からの相対パス, blob の ID)
+pub fn tree_files(dir: &Path, rev: &str) -> Result<Vec<(PathBuf, String)>> {
+    let listing = git(dir, &["ls-tree", "-r", "-z", rev, "--", "."])?;
+    Ok(listing
+        .split('\0')
+        .filter_map(|entry| {
+            // <mode> SP <type> SP <object> TAB <path>
+            let (meta, path) = entry.split_once('\t')?;
+            let mut meta = meta.split(' ');
+            let (_, kind, id) = (meta.next()?, meta.next()?, meta.next()?);
+            (kind == "blob").then(|| (PathBuf::from(path), id.to_string()))
+        })
+        .collect())
+}
+
+// blob の内容をまとめて読む (git cat-file --batch を 1 回だけ起動する)
+pub fn read_blobs(dir: &Path, ids: &[String]) -> Result<Vec<String>> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("git を実行できません")?;
+    // 出力を読みながら書き込まないとパイプが詰まるので、要求は別スレッドで書く
+    let mut stdin = child.stdin.take().context("git cat-file の標準入力を開けません")?;
+    let request = ids.join("\n") + "\n";
+    let writer = thread::spawn(move || stdin.write_all(request.as_bytes()));
+
+    let mut reader = BufReader::new(child.stdout.take().context("git cat-file の標準出力を開けません")?);
+    let mut blobs = Vec::with_capacity(ids.len());
+    for id in ids {
+        // <object> SP <type> SP <size> LF <contents> LF
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let Some(size) = header.trim_end().rsplit(' ').next().and_then(|size| size.parse::<usize>().ok()) else {
+            bail!("git cat-file で {id} を読めません: {}", header.trim_end());
+        };
+        let mut contents = vec![0; size + 1];
+        reader.read_exact(&mut contents)?;
+        contents.pop();
+        blobs.push(encoding::decode_lossy(&contents));
+    }
+    writer.join().map_err(|_| anyhow::anyhow!("git cat-file への書き込みに失敗しました"))??;
+    child.wait()?;
+    Ok(blobs)
+}
+
+// rev の時点の dir 以下のソースファイルを、作業ツリーをチェックアウトせずに git から読んで解析する
+pub fn analyze_revision(dir: &Path, rev: &str, config: &AnalyzerConfig) -> Result<ProjectAnalysis> {
+    let files: Vec<_> = tree_files(dir, rev)?
+        .into_iter()
+        .map(|(path, id)| (dir.join(path), id))
+        .filter(|(path, _)| {
+            config.is_source_path(path) && !config.is_excluded(dir, path) && config.is_included(dir, path)
+        })
+        .collect();
+    let ids: Vec<_> = files.iter().map(|(_, id)| id.clone()).collect();
+    let sources = read_blobs(dir, &ids)?;
+    Ok(analyzer::analyze_sources(dir, files.into_iter().map(|(path, _)| path).zip(sources), config))
+}
+
+// git を dir で実行し、標準出力を返す
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    Ok(String::from_utf8_lossy(&git_output(dir, args)?).into_owned())
+}
+
+// git を dir で実行し、標準出力をバイト列のまま返す
+fn git_output(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("git を実行できません")?;
+    if !output.status.success() {
+        bail!(
+            "git {} に失敗しました: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+// targets 以下のソースファイルのうち、git で変更されたものだけを解析して結果を走査順に f へ渡す。
+// with_dependents なら、変更されたファイルを (間接的にでも) インポートしているファイルも含める
+pub fn for_each_changed_file(
+    targets: &[PathBuf],
+    changed: &HashSet<PathBuf>,
+    with_dependents: bool,
+    config: &AnalyzerConfig,
+    cache: Option<&Cache>,
+    mut f: impl FnMut(FileOutcome) -> Result<()>,
+) -> Result<()> {
+    let paths = analyzer::collect_source_files_in(targets, config)?;
+    let is_changed = |path: &Path| fs::canonicalize(path).is_ok_and(|path| changed.contains(&path));
+    if !with_dependents {
+        let paths: Vec<_> = paths.into_iter().filter(|path| is_changed(path)).collect();
+        return analyzer::for_each_path(&paths, config, cache, f);
+    }
+
+    // 被依存関係を辿るには依存グラフが要るので、全ファイルを解析してから絞り込む
+    let mut outcomes = Vec::with_capacity(paths.len());
+    let mut aggregate = Aggregate::with_targets(targets, config);
+    analyzer::for_each_path(&paths, config, cache, |outcome| {
+        aggregate.add(outcome.clone());
+        outcomes.push(outcome);
+        Ok(())
+    })?;
+    let ProjectAnalysis { report, graph } = aggregate.finish();
+    let root = Path::new(&report.target);
+    let id = |path: &Path| resolve::relative_display(root, path);
+
+    let changed: Vec<String> = paths.iter().filter(|path| is_changed(path)).map(|path| id(path)).collect();
+    let dependents = graph.transitive_dependents(&changed);
+    let selected: HashSet<String> = changed.into_iter().chain(dependents.into_iter().map(|(id, _)| id)).collect();
+    for outcome in outcomes {
+        if selected.contains(&id(outcome.path())) {
+            f(outcome)?;
+        }
+    }
+    Ok(())
+}
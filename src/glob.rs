@@ -0,0 +1,222 @@
+use std::path::Path;
+
+// .gitignore や --include / --exclude で使うグロブパターン。
+// / 区切りのパスに対して、* と ? は / 以外の文字、** は / をまたいで任意のディレクトリに一致する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    tokens: Vec<Token>,
+    // パターンが / を含む (matches_path で相対パス全体と比べる)
+    has_slash: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    // ?
+    Any,
+    // *
+    Star,
+    // パスの先頭か / の直後の **/ (0 個以上のディレクトリ)
+    Dirs,
+    // それ以外の ** (/ を含む任意の文字列)
+    DoubleStar,
+    // [abc] / [a-z] / [!a-z]
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' if i + 1 < chars.len() => {
+                    tokens.push(Token::Char(chars[i + 1]));
+                    i += 2;
+                }
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    let at_segment_start = i == 0 || chars[i - 1] == '/';
+                    if at_segment_start && chars.get(i + 2) == Some(&'/') {
+                        tokens.push(Token::Dirs);
+                        i += 3;
+                    } else {
+                        tokens.push(Token::DoubleStar);
+                        i += 2;
+                    }
+                }
+                '*' => {
+                    tokens.push(Token::Star);
+                    i += 1;
+                }
+                '?' => {
+                    tokens.push(Token::Any);
+                    i += 1;
+                }
+                '[' => match parse_class(&chars[i + 1..]) {
+                    Some((token, len)) => {
+                        tokens.push(token);
+                        i += len + 1;
+                    }
+                    // 閉じていない [ は文字として扱う
+                    None => {
+                        tokens.push(Token::Char('['));
+                        i += 1;
+                    }
+                },
+                c => {
+                    tokens.push(Token::Char(c));
+                    i += 1;
+                }
+            }
+        }
+        Self {
+            tokens,
+            has_slash: pattern.contains('/'),
+        }
+    }
+
+    // path (/ 区切り) 全体がパターンに一致するか
+    pub fn matches(&self, path: &str) -> bool {
+        let chars: Vec<char> = path.chars().collect();
+        matches_at(&self.tokens, &chars)
+    }
+
+    // 基準ディレクトリからの相対パスに一致するか。/ を含まないパターン (*.spec.ts, dist 等) は
+    // パスのどこかの要素 (ファイル名・ディレクトリ名) と一致すればよい
+    pub fn matches_path(&self, relative: &str) -> bool {
+        if self.has_slash {
+            self.matches(relative)
+        } else {
+            relative.split('/').any(|name| self.matches(name))
+        }
+    }
+}
+
+// パスの要素を / でつなぐ (Windows でもパターンと同じ区切りにする)
+pub fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// [ の後ろからクラスを読み、トークンと ] までの文字数を返す
+fn parse_class(chars: &[char]) -> Option<(Token, usize)> {
+    let mut i = 0;
+    let negated = matches!(chars.first(), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+    let mut ranges = Vec::new();
+    // 先頭の ] は文字として扱う
+    let start = i;
+    while i < chars.len() && (chars[i] != ']' || i == start) {
+        let c = chars[i];
+        if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|&end| end != ']') {
+            ranges.push((c, chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+    (i < chars.len()).then_some((Token::Class { negated, ranges }, i + 1))
+}
+
+fn matches_at(tokens: &[Token], path: &[char]) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return path.is_empty();
+    };
+    match token {
+        Token::Char(c) => path.first() == Some(c) && matches_at(rest, &path[1..]),
+        Token::Any => path.first().is_some_and(|&c| c != '/') && matches_at(rest, &path[1..]),
+        Token::Class { negated, ranges } => path.first().is_some_and(|&c| {
+            c != '/' && ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+        }) && matches_at(rest, &path[1..]),
+        Token::Star => {
+            let len = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+            (0..=len).any(|i| matches_at(rest, &path[i..]))
+        }
+        Token::DoubleStar => (0..=path.len()).any(|i| matches_at(rest, &path[i..])),
+        // 何も飛ばさないか、/ の直後から続ける
+        Token::Dirs => {
+            matches_at(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(i, &c)| c == '/' && matches_at(rest, &path[i + 1..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_star_slash_matches_zero_or_more_directories() {
+        let glob = Glob::new("**/*.spec.ts");
+        assert!(glob.matches("a.spec.ts"));
+        assert!(glob.matches("src/app/a.spec.ts"));
+        assert!(!glob.matches("src/app/a.ts"));
+
+        let glob = Glob::new("src/**/test/*.ts");
+        assert!(glob.matches("src/test/a.ts"));
+        assert!(glob.matches("src/a/b/test/a.ts"));
+        assert!(!glob.matches("src/a/test/b/a.ts"));
+    }
+
+    #[test]
+    fn star_and_question_mark_stop_at_slash() {
+        assert!(Glob::new("src/*.ts").matches("src/a.ts"));
+        assert!(!Glob::new("src/*.ts").matches("src/app/a.ts"));
+        assert!(Glob::new("a?c").matches("abc"));
+        assert!(!Glob::new("a?c").matches("a/c"));
+        assert!(Glob::new("src/**").matches("src/app/a.ts"));
+    }
+
+    #[test]
+    fn character_classes() {
+        let glob = Glob::new("[!a-z]*.ts");
+        assert!(glob.matches("App.ts"));
+        assert!(glob.matches("1.ts"));
+        assert!(!glob.matches("app.ts"));
+
+        let glob = Glob::new("v[0-9x].ts");
+        assert!(glob.matches("v1.ts"));
+        assert!(glob.matches("vx.ts"));
+        assert!(!glob.matches("vy.ts"));
+
+        // 先頭の ] はクラスの文字
+        assert!(Glob::new("[]a]").matches("]"));
+    }
+
+    #[test]
+    fn unclosed_bracket_is_literal() {
+        let glob = Glob::new("a[b");
+        assert!(glob.matches("a[b"));
+        assert!(!glob.matches("ab"));
+    }
+
+    #[test]
+    fn escaped_characters_are_literal() {
+        assert!(Glob::new(r"a\*b").matches("a*b"));
+        assert!(!Glob::new(r"a\*b").matches("axb"));
+    }
+
+    #[test]
+    fn matches_path_without_slash_matches_any_component() {
+        let glob = Glob::new("dist");
+        assert!(glob.matches_path("dist"));
+        assert!(glob.matches_path("packages/app/dist/main.js"));
+        assert!(!glob.matches_path("packages/distribution/main.js"));
+
+        assert!(Glob::new("*.spec.ts").matches_path("src/app/a.spec.ts"));
+
+        // / を含むパターンは相対パス全体と比べる
+        let glob = Glob::new("app/*.ts");
+        assert!(glob.matches_path("app/a.ts"));
+        assert!(!glob.matches_path("src/app/a.ts"));
+    }
+}
@@ -0,0 +1,992 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use crate::resolve::{self, PathAliases};
+
+// ファイル単位のインポート関係 (解析対象内のファイル同士のみ)
+#[derive(Debug, Default, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+// 解析対象ディレクトリからの相対パスを ID とするファイル
+#[derive(Debug, Clone, Serialize)]
+pub struct Node {
+    pub id: String,
+    // ファイルサイズ (バイト)
+    pub size: u64,
+}
+
+// from が to をインポートしている。weight は import / export 宣言の数
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub weight: usize,
+    // import() による遅延読み込みだけで繋がっている
+    pub dynamic: bool,
+}
+
+// ファイル内のモジュール参照 (import / export from / import())
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleRef {
+    pub specifier: String,
+    pub dynamic: bool,
+    // import type / export type のように型だけを参照している (コンパイル後に消える)
+    #[serde(default)]
+    pub type_only: bool,
+    // 参照しているエクスポート名。空ならモジュール全体 (namespace インポート・副作用のみ等)
+    pub names: Vec<String>,
+    // 宣言・import() の位置 (1 始まり)
+    pub line: usize,
+    pub column: usize,
+}
+
+// ファイルがエクスポートしている名前 (バレルファイルの解決用)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModuleExports {
+    // そのファイル自身で定義してエクスポートしている名前 (export default は "default")
+    pub local: HashSet<String>,
+    pub re_exports: Vec<ReExport>,
+    // local の名前を宣言している位置 (行・列は 1 始まり)
+    #[serde(default)]
+    pub locations: HashMap<String, (usize, usize)>,
+    // local のうち interface・abstract class (抽象度の計算用)
+    #[serde(default)]
+    pub abstract_names: HashSet<String>,
+}
+
+// export * from / export { X as Y } from による再エクスポート
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReExport {
+    pub specifier: String,
+    // (エクスポート名, 再エクスポート元での名前)。None なら export * from
+    // export * as ns from は ("ns", "*")
+    pub names: Option<Vec<(String, String)>>,
+}
+
+// 孤立ファイルの判定で既定で起点にするファイル (--entry / 設定ファイルの entry-points を加える)。
+// テストのファイル (manifest::TEST_FILES) も起点にする
+pub const DEFAULT_ENTRY_POINTS: &[&str] = &[
+    "main.ts", "main.server.ts", "server.ts", "polyfills.ts", "*.routes.ts", "*-routing.module.ts", "public-api.ts",
+    "public_api.ts", "environment*.ts", "*.config.ts", "*.config.js", "*.conf.js",
+];
+
+// 再エクスポートを辿るときの上限 (循環や極端に深いチェーン対策)
+const MAX_RE_EXPORT_DEPTH: usize = 32;
+
+// path からエクスポートされている name を、実際に定義しているファイルまで辿る。
+// 解析対象外 (npm パッケージ等) に行き着いた場合や見つからない場合は None
+fn defining_file(
+    path: &Path,
+    name: &str,
+    source_files: &HashSet<PathBuf>,
+    exports: &HashMap<PathBuf, ModuleExports>,
+    aliases: &PathAliases,
+    depth: usize,
+) -> Option<PathBuf> {
+    if depth > MAX_RE_EXPORT_DEPTH {
+        return None;
+    }
+    let module = exports.get(path)?;
+    if module.local.contains(name) {
+        return Some(path.to_path_buf());
+    }
+    for re_export in &module.re_exports {
+        let Some(names) = &re_export.names else {
+            continue;
+        };
+        if let Some((_, orig)) = names.iter().find(|(exported, _)| exported == name) {
+            let target = resolve::resolve(path, &re_export.specifier, source_files, aliases)?;
+            if orig == "*" {
+                return Some(target);
+            }
+            return defining_file(&target, orig, source_files, exports, aliases, depth + 1);
+        }
+    }
+    // export * from は default を再エクスポートしない
+    if name == "default" {
+        return None;
+    }
+    module
+        .re_exports
+        .iter()
+        .filter(|r| r.names.is_none())
+        .filter_map(|r| resolve::resolve(path, &r.specifier, source_files, aliases))
+        .find_map(|target| defining_file(&target, name, source_files, exports, aliases, depth + 1))
+}
+
+// from のインポート 1 件が依存するファイル。名前ごとに定義元を辿り、辿れなかった名前はインポート先そのものとする。
+// 解析対象内に解決できなければ空
+fn import_targets(
+    from: &Path,
+    source: &ModuleRef,
+    source_files: &HashSet<PathBuf>,
+    file_exports: &HashMap<PathBuf, ModuleExports>,
+    aliases: &PathAliases,
+) -> BTreeSet<PathBuf> {
+    let Some(to) = resolve::resolve(from, &source.specifier, source_files, aliases) else {
+        return BTreeSet::new();
+    };
+    if source.names.is_empty() {
+        return BTreeSet::from([to]);
+    }
+    source
+        .names
+        .iter()
+        .map(|name| defining_file(&to, name, source_files, file_exports, aliases, 0).unwrap_or_else(|| to.clone()))
+        .collect()
+}
+
+// from (正規化したパス) の sources のうち、to (正規化したパス) に依存している最初のインポートの位置
+pub fn import_location(
+    from: &Path,
+    to: &Path,
+    sources: &[ModuleRef],
+    source_files: &HashSet<PathBuf>,
+    file_exports: &HashMap<PathBuf, ModuleExports>,
+    aliases: &PathAliases,
+) -> Option<(usize, usize)> {
+    sources
+        .iter()
+        .find(|source| import_targets(from, source, source_files, file_exports, aliases).contains(to))
+        .map(|source| (source.line, source.column))
+}
+
+// is_entry (解析対象ディレクトリからの相対パスを受け取る) が真のファイルから、インポート・再エクスポート・import() を
+// 辿っても届かないファイル (file_sources のパスのまま、パスの順)。
+// バレルファイルも読み込まれるので、名前の定義元ではなくインポート先そのものを辿る
+pub fn unreachable_files(
+    root: &Path,
+    source_files: &HashSet<PathBuf>,
+    file_sources: &[(PathBuf, Vec<ModuleRef>)],
+    aliases: &PathAliases,
+    is_entry: impl Fn(&str) -> bool,
+) -> Vec<PathBuf> {
+    let imports: HashMap<PathBuf, Vec<PathBuf>> = file_sources
+        .iter()
+        .map(|(path, sources)| {
+            let from = resolve::normalize(path);
+            let targets = sources
+                .iter()
+                .filter_map(|source| resolve::resolve(&from, &source.specifier, source_files, aliases))
+                .collect();
+            (from, targets)
+        })
+        .collect();
+    let mut reached: HashSet<PathBuf> = source_files
+        .iter()
+        .filter(|path| is_entry(&resolve::relative_display(root, path)))
+        .cloned()
+        .collect();
+    let mut pending: Vec<PathBuf> = reached.iter().cloned().collect();
+    while let Some(path) = pending.pop() {
+        for target in imports.get(&path).into_iter().flatten() {
+            if reached.insert(target.clone()) {
+                pending.push(target.clone());
+            }
+        }
+    }
+    let mut unreachable: Vec<PathBuf> = file_sources
+        .iter()
+        .map(|(path, _)| path)
+        .filter(|path| !reached.contains(&resolve::normalize(path)))
+        .cloned()
+        .collect();
+    unreachable.sort();
+    unreachable
+}
+
+// どのファイルからもインポートされていない、そのファイルで定義しているエクスポート (パスの順、名前の順)。
+// 再エクスポートはインポートに数えず、名前ごとに定義元まで辿る。namespace インポート・import()・副作用のみのインポートは
+// そのファイル (と再エクスポート元) のすべてのエクスポートを使っているとみなす。
+// is_public (解析対象ディレクトリからの相対パスを受け取る) が真のファイルとその再エクスポートは外から使われるとみなす
+pub fn unused_exports(
+    root: &Path,
+    source_files: &HashSet<PathBuf>,
+    file_sources: &[(PathBuf, Vec<ModuleRef>)],
+    file_exports: &HashMap<PathBuf, ModuleExports>,
+    aliases: &PathAliases,
+    is_public: impl Fn(&str) -> bool,
+) -> Vec<(PathBuf, String)> {
+    let mut used: HashSet<(PathBuf, String)> = HashSet::new();
+    // すべてのエクスポートを使っているファイル
+    let mut all_used: HashSet<PathBuf> = HashSet::new();
+    let mut pending: Vec<PathBuf> = source_files
+        .iter()
+        .filter(|path| is_public(&resolve::relative_display(root, path)))
+        .cloned()
+        .collect();
+    for (path, sources) in file_sources {
+        let from = resolve::normalize(path);
+        let re_exported: HashSet<&str> = file_exports
+            .get(&from)
+            .map(|exports| exports.re_exports.iter().map(|r| r.specifier.as_str()).collect())
+            .unwrap_or_default();
+        for source in sources.iter().filter(|source| source.dynamic || !re_exported.contains(source.specifier.as_str())) {
+            let Some(target) = resolve::resolve(&from, &source.specifier, source_files, aliases) else {
+                continue;
+            };
+            if source.names.is_empty() {
+                pending.push(target);
+                continue;
+            }
+            for name in &source.names {
+                if let Some(definer) = defining_file(&target, name, source_files, file_exports, aliases, 0) {
+                    used.insert((definer, name.clone()));
+                }
+            }
+        }
+    }
+    // 全体を使っているファイルから再エクスポートを辿る
+    while let Some(path) = pending.pop() {
+        if !all_used.insert(path.clone()) {
+            continue;
+        }
+        let Some(exports) = file_exports.get(&path) else {
+            continue;
+        };
+        for re_export in &exports.re_exports {
+            let Some(target) = resolve::resolve(&path, &re_export.specifier, source_files, aliases) else {
+                continue;
+            };
+            match &re_export.names {
+                None => pending.push(target),
+                Some(names) => {
+                    for (_, orig) in names {
+                        if orig == "*" {
+                            pending.push(target.clone());
+                        } else if let Some(definer) = defining_file(&target, orig, source_files, file_exports, aliases, 0) {
+                            used.insert((definer, orig.clone()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut unused = Vec::new();
+    for (path, _) in file_sources {
+        let normalized = resolve::normalize(path);
+        if all_used.contains(&normalized) {
+            continue;
+        }
+        let Some(exports) = file_exports.get(&normalized) else {
+            continue;
+        };
+        let mut names: Vec<&String> = exports.local.iter().filter(|name| !used.contains(&(normalized.clone(), (*name).clone()))).collect();
+        names.sort();
+        unused.extend(names.into_iter().map(|name| (path.clone(), name.clone())));
+    }
+    unused.sort();
+    unused
+}
+
+impl DependencyGraph {
+    // 相対インポートと aliases (tsconfig.json の paths / baseUrl) で解決できるインポートから
+    // ファイル間の依存グラフを組み立てる。バレルファイル (index.ts 等) 経由の名前付きインポートは、実際に定義しているファイルへの依存とする
+    pub fn build(
+        root: &Path,
+        source_files: &HashSet<PathBuf>,
+        file_sizes: &HashMap<PathBuf, u64>,
+        file_sources: &[(PathBuf, Vec<ModuleRef>)],
+        file_exports: &HashMap<PathBuf, ModuleExports>,
+        aliases: &PathAliases,
+    ) -> Self {
+        // (from, to) -> (宣言の数, すべて import() か)
+        let mut weights: BTreeMap<(String, String), (usize, bool)> = BTreeMap::new();
+        for (path, sources) in file_sources {
+            let from = resolve::normalize(path);
+            for source in sources {
+                let Some(to) = resolve::resolve(&from, &source.specifier, source_files, aliases) else {
+                    continue;
+                };
+                for target in import_targets(&from, source, source_files, file_exports, aliases) {
+                    // バレル経由で自分自身に戻ってきた場合は辺にしない
+                    if target == from && target != to {
+                        continue;
+                    }
+                    let key = (
+                        resolve::relative_display(root, &from),
+                        resolve::relative_display(root, &target),
+                    );
+                    let entry = weights.entry(key).or_insert((0, true));
+                    entry.0 += 1;
+                    entry.1 &= source.dynamic;
+                }
+            }
+        }
+
+        let mut nodes: Vec<_> = source_files
+            .iter()
+            .map(|p| Node {
+                id: resolve::relative_display(root, p),
+                size: file_sizes.get(p).copied().unwrap_or(0),
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self {
+            nodes,
+            edges: weights
+                .into_iter()
+                .map(|((from, to), (weight, dynamic))| Edge { from, to, weight, dynamic })
+                .collect(),
+        }
+    }
+
+    // id のファイルを直接インポートしているファイルへの辺
+    pub fn dependents(&self, id: &str) -> Vec<&Edge> {
+        self.edges.iter().filter(|edge| edge.to == id).collect()
+    }
+
+    // ids のファイルを直接・間接にインポートしているファイルと、その深さ (直接インポートしていれば 1)。
+    // ids 自身は含めない。深さの順、同じ深さはファイル名順に並べる
+    pub fn transitive_dependents(&self, ids: &[String]) -> Vec<(String, usize)> {
+        let mut importers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            importers.entry(&edge.to).or_default().push(&edge.from);
+        }
+        let mut seen: HashSet<&str> = ids.iter().map(String::as_str).collect();
+        let mut level: Vec<&str> = seen.iter().copied().collect();
+        let mut found = Vec::new();
+        let mut depth = 0;
+        while !level.is_empty() {
+            depth += 1;
+            let mut next: Vec<&str> = Vec::new();
+            for id in level {
+                for &from in importers.get(id).into_iter().flatten() {
+                    if seen.insert(from) {
+                        next.push(from);
+                    }
+                }
+            }
+            next.sort();
+            found.extend(next.iter().map(|id| (id.to_string(), depth)));
+            level = next;
+        }
+        found
+    }
+
+    // 循環しているファイルの組 (強連結成分のうち 2 ファイル以上か、自分自身をインポートしているもの)。
+    // 各組はファイル名順、組どうしは先頭のファイル名順に並べる
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let adjacency = self.adjacency();
+        let mut cycles: Vec<Vec<String>> = strongly_connected(&adjacency)
+            .into_iter()
+            .filter(|component| component.len() > 1 || adjacency[component[0]].contains(&component[0]))
+            .map(|component| {
+                let mut ids: Vec<_> = component.iter().map(|&i| self.nodes[i].id.clone()).collect();
+                ids.sort();
+                ids
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    // starts の各ファイルからインポートを辿る最も長い連鎖 (先頭は starts のファイル)。循環している組は 1 段と数え、
+    // 組に入ったファイルだけを載せる (組から次のファイルへは、組のどのファイルからのインポートでもよい)。
+    // 長さが同じなら、ノードの順で先のファイルに進む
+    pub fn longest_chains(&self, starts: &[String]) -> Vec<Vec<String>> {
+        let adjacency = self.adjacency();
+        let components = strongly_connected(&adjacency);
+        let mut component_of = vec![0; self.nodes.len()];
+        for (c, component) in components.iter().enumerate() {
+            for &node in component {
+                component_of[node] = c;
+            }
+        }
+        // 組ごとの、そこから辿れる最も長い連鎖の段数と次に進むファイル。強連結成分は辿った先の組から順に見つかるので、
+        // 見つかった順に決めれば先の組はもう決まっている
+        let mut height = vec![0; components.len()];
+        let mut next: Vec<Option<usize>> = vec![None; components.len()];
+        for (c, component) in components.iter().enumerate() {
+            let mut targets: Vec<usize> = component
+                .iter()
+                .flat_map(|&node| adjacency[node].iter().copied())
+                .filter(|&to| component_of[to] != c)
+                .collect();
+            targets.sort();
+            for to in targets {
+                if next[c].is_none() || height[component_of[to]] + 1 > height[c] {
+                    height[c] = height[component_of[to]] + 1;
+                    next[c] = Some(to);
+                }
+            }
+        }
+        let index: HashMap<&str, usize> = self.nodes.iter().enumerate().map(|(i, n)| (n.id.as_str(), i)).collect();
+        starts
+            .iter()
+            .filter_map(|start| index.get(start.as_str()))
+            .map(|&start| {
+                let mut chain = vec![self.nodes[start].id.clone()];
+                let mut current = component_of[start];
+                while let Some(to) = next[current] {
+                    chain.push(self.nodes[to].id.clone());
+                    current = component_of[to];
+                }
+                chain
+            })
+            .collect()
+    }
+
+    // ノードの番号ごとの、インポートしているノードの番号
+    fn adjacency(&self) -> Vec<Vec<usize>> {
+        let index: HashMap<&str, usize> = self.nodes.iter().enumerate().map(|(i, n)| (n.id.as_str(), i)).collect();
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+                adjacency[from].push(to);
+            }
+        }
+        adjacency
+    }
+
+    // from のファイルからインポートを辿って is_goal が真のファイルに着くまでの最短の連鎖 (from と着いたファイルを含む)。
+    // 着かなければ None
+    pub fn shortest_path(&self, from: &str, is_goal: impl Fn(&str) -> bool) -> Option<Vec<String>> {
+        // 幅優先探索で、各ファイルに最初に辿り着いたときの 1 つ前のファイル
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut seen: HashSet<&str> = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(id) = queue.pop_front() {
+            if is_goal(id) {
+                let mut chain = vec![id.to_string()];
+                let mut current = id;
+                while let Some(&before) = previous.get(current) {
+                    chain.push(before.to_string());
+                    current = before;
+                }
+                chain.reverse();
+                return Some(chain);
+            }
+            for edge in self.edges.iter().filter(|edge| edge.from == id) {
+                if seen.insert(&edge.to) {
+                    previous.insert(&edge.to, id);
+                    queue.push_back(&edge.to);
+                }
+            }
+        }
+        None
+    }
+
+    // 循環しているファイルの組 (cycles の 1 つ) の先頭のファイルから、組の中の辺を辿って先頭に戻ってくる最短の連鎖
+    // (先頭と末尾は同じファイル)
+    pub fn cycle_chain(&self, files: &[String]) -> Vec<String> {
+        let Some(start) = files.first() else {
+            return Vec::new();
+        };
+        let members: HashSet<&str> = files.iter().map(String::as_str).collect();
+        // 幅優先探索で、各ファイルに最初に辿り着いたときの 1 つ前のファイル
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([start.as_str()]);
+        while let Some(id) = queue.pop_front() {
+            for edge in self.edges.iter().filter(|edge| edge.from == id && members.contains(edge.to.as_str())) {
+                if edge.to == *start {
+                    let mut chain = vec![start.clone(), id.to_string()];
+                    let mut current = id;
+                    while let Some(&before) = previous.get(current) {
+                        chain.push(before.to_string());
+                        current = before;
+                    }
+                    chain.reverse();
+                    return chain;
+                }
+                if !previous.contains_key(edge.to.as_str()) {
+                    previous.insert(&edge.to, id);
+                    queue.push_back(&edge.to);
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    // 各ファイルをインポートしているファイル数 (被依存数)
+    pub fn fan_in(&self) -> HashMap<&str, usize> {
+        let mut counts: HashMap<&str, usize> = self.nodes.iter().map(|n| (n.id.as_str(), 0)).collect();
+        for edge in &self.edges {
+            *counts.entry(edge.to.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // 各ファイルがインポートしているファイル数 (依存数)
+    pub fn fan_out(&self) -> HashMap<&str, usize> {
+        let mut counts: HashMap<&str, usize> = self.nodes.iter().map(|n| (n.id.as_str(), 0)).collect();
+        for edge in &self.edges {
+            *counts.entry(edge.from.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // フォルダ (directory_of) ごとの (ファイル数, フォルダの外からインポートしているファイル数, フォルダの外で
+    // インポートしているファイル数)。フォルダの中同士のインポートは数えない
+    pub fn directory_coupling(&self) -> BTreeMap<String, (usize, usize, usize)> {
+        let mut files: BTreeMap<String, usize> = BTreeMap::new();
+        for node in &self.nodes {
+            *files.entry(directory_of(&node.id)).or_insert(0) += 1;
+        }
+        let mut importers: HashMap<String, HashSet<&str>> = HashMap::new();
+        let mut imported: HashMap<String, HashSet<&str>> = HashMap::new();
+        for edge in &self.edges {
+            let (from, to) = (directory_of(&edge.from), directory_of(&edge.to));
+            if from != to {
+                importers.entry(to).or_default().insert(&edge.from);
+                imported.entry(from).or_default().insert(&edge.to);
+            }
+        }
+        files
+            .into_iter()
+            .map(|(directory, count)| {
+                let fan_in = importers.get(&directory).map_or(0, HashSet::len);
+                let fan_out = imported.get(&directory).map_or(0, HashSet::len);
+                (directory, (count, fan_in, fan_out))
+            })
+            .collect()
+    }
+
+    // ファイルを grouping のフォルダにまとめたグラフ。ノードのサイズはファイルの合計、辺の重みはフォルダ間の
+    // import / export 宣言の数の合計で、すべて import() だけのときだけ dynamic にする。フォルダの中同士の辺は除く
+    pub fn group_by_directory(&self, grouping: DirectoryGrouping) -> DependencyGraph {
+        let mut sizes: BTreeMap<String, u64> = BTreeMap::new();
+        for node in &self.nodes {
+            *sizes.entry(grouping.key(&node.id)).or_insert(0) += node.size;
+        }
+        let mut edges: BTreeMap<(String, String), (usize, bool)> = BTreeMap::new();
+        for edge in &self.edges {
+            let (from, to) = (grouping.key(&edge.from), grouping.key(&edge.to));
+            if from != to {
+                let entry = edges.entry((from, to)).or_insert((0, true));
+                entry.0 += edge.weight;
+                entry.1 &= edge.dynamic;
+            }
+        }
+        DependencyGraph {
+            nodes: sizes.into_iter().map(|(id, size)| Node { id, size }).collect(),
+            edges: edges
+                .into_iter()
+                .map(|((from, to), (weight, dynamic))| Edge { from, to, weight, dynamic })
+                .collect(),
+        }
+    }
+
+    // 各ファイルの媒介中心性 (ほかのファイル同士をつなぐ最短のインポートの連鎖のうち、そのファイルを通るものの割合の
+    // 合計)。(n-1)(n-2) で割って 0〜1 にする。Brandes のアルゴリズムで、連鎖はインポートの向きに辿る
+    pub fn betweenness(&self) -> HashMap<&str, f64> {
+        let n = self.nodes.len();
+        let index: HashMap<&str, usize> = self.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+        let mut imports: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for edge in &self.edges {
+            if let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) {
+                imports[from].push(to);
+            }
+        }
+        let mut centrality = vec![0.0; n];
+        for start in 0..n {
+            // start からの最短の連鎖の数・長さ・1 つ手前のファイル
+            let mut paths = vec![0.0; n];
+            let mut distance: Vec<Option<usize>> = vec![None; n];
+            let mut previous: Vec<Vec<usize>> = vec![Vec::new(); n];
+            let mut order = Vec::new();
+            let mut queue = VecDeque::from([start]);
+            paths[start] = 1.0;
+            distance[start] = Some(0);
+            while let Some(node) = queue.pop_front() {
+                order.push(node);
+                let next = distance[node].map(|d| d + 1);
+                for &to in &imports[node] {
+                    if distance[to].is_none() {
+                        distance[to] = next;
+                        queue.push_back(to);
+                    }
+                    if distance[to] == next {
+                        paths[to] += paths[node];
+                        previous[to].push(node);
+                    }
+                }
+            }
+            let mut dependency = vec![0.0; n];
+            for &node in order.iter().rev() {
+                for &before in &previous[node] {
+                    dependency[before] += paths[before] / paths[node] * (1.0 + dependency[node]);
+                }
+                if node != start {
+                    centrality[node] += dependency[node];
+                }
+            }
+        }
+        let scale = match n {
+            0..=2 => 1.0,
+            n => ((n - 1) * (n - 2)) as f64,
+        };
+        self.nodes.iter().zip(centrality).map(|(node, value)| (node.id.as_str(), value / scale)).collect()
+    }
+
+    // インポートの向きと重みを無視した無向グラフを Louvain 法でまとめたファイルの組 (大きい順、組の中はパスの順) と、
+    // その分け方のモジュラリティ (-0.5〜1。大きいほど組の中のインポートが多く組の間が少ない)。
+    // 同じ入力なら同じ結果になるよう、ノード・隣接は常に順番に辿る
+    pub fn communities(&self) -> (Vec<Vec<String>>, f64) {
+        let index: HashMap<&str, usize> = self.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+        let mut adjacency: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); self.nodes.len()];
+        for edge in &self.edges {
+            let (Some(&from), Some(&to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) else {
+                continue;
+            };
+            if from != to {
+                *adjacency[from].entry(to).or_insert(0.0) += 1.0;
+                *adjacency[to].entry(from).or_insert(0.0) += 1.0;
+            }
+        }
+        let original = adjacency.clone();
+
+        // 元のノードが今どのノード (組をまとめたもの) に入っているか
+        let mut membership: Vec<usize> = (0..self.nodes.len()).collect();
+        while let Some(community) = louvain_level(&adjacency) {
+            // 組の番号を詰め直して、組を 1 つのノードにまとめたグラフを作る
+            let mut renumber: BTreeMap<usize, usize> = BTreeMap::new();
+            for &c in &community {
+                let next = renumber.len();
+                renumber.entry(c).or_insert(next);
+            }
+            let mut aggregated: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); renumber.len()];
+            for (node, neighbors) in adjacency.iter().enumerate() {
+                for (&neighbor, &weight) in neighbors {
+                    let (from, to) = (renumber[&community[node]], renumber[&community[neighbor]]);
+                    *aggregated[from].entry(to).or_insert(0.0) += weight;
+                }
+            }
+            for member in &mut membership {
+                *member = renumber[&community[*member]];
+            }
+            adjacency = aggregated;
+        }
+
+        let degrees: Vec<f64> = original.iter().map(|neighbors| neighbors.values().sum()).collect();
+        let total: f64 = degrees.iter().sum();
+        let mut inside = vec![0.0; adjacency.len()];
+        let mut degree = vec![0.0; adjacency.len()];
+        for (node, neighbors) in original.iter().enumerate() {
+            degree[membership[node]] += degrees[node];
+            for (&neighbor, &weight) in neighbors {
+                if membership[neighbor] == membership[node] {
+                    inside[membership[node]] += weight;
+                }
+            }
+        }
+        let modularity = match total {
+            0.0 => 0.0,
+            total => inside.iter().zip(&degree).map(|(inside, degree)| inside / total - (degree / total).powi(2)).sum(),
+        };
+
+        let mut groups: Vec<Vec<String>> = vec![Vec::new(); adjacency.len()];
+        for (node, &group) in membership.iter().enumerate() {
+            groups[group].push(self.nodes[node].id.clone());
+        }
+        for group in &mut groups {
+            group.sort();
+        }
+        groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        (groups, modularity)
+    }
+}
+
+// Tarjan の強連結成分分解。辿った先の成分ほど先に並ぶ (深いグラフでもスタックを溢れさせないよう再帰を使わない)
+fn strongly_connected(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut order = vec![usize::MAX; adjacency.len()];
+    let mut low = vec![0; adjacency.len()];
+    let mut on_stack = vec![false; adjacency.len()];
+    let mut stack = Vec::new();
+    let mut counter = 0;
+    let mut components = Vec::new();
+    for start in 0..adjacency.len() {
+        if order[start] != usize::MAX {
+            continue;
+        }
+        // (ノード, 次に見る隣接ノードの位置)
+        let mut frames = vec![(start, 0)];
+        order[start] = counter;
+        low[start] = counter;
+        counter += 1;
+        stack.push(start);
+        on_stack[start] = true;
+        while let Some(&mut (node, ref mut next)) = frames.last_mut() {
+            if let Some(&to) = adjacency[node].get(*next) {
+                *next += 1;
+                if order[to] == usize::MAX {
+                    order[to] = counter;
+                    low[to] = counter;
+                    counter += 1;
+                    stack.push(to);
+                    on_stack[to] = true;
+                    frames.push((to, 0));
+                } else if on_stack[to] {
+                    low[node] = low[node].min(order[to]);
+                }
+                continue;
+            }
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if low[node] == order[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+// Louvain 法の 1 段: 各ノードを、モジュラリティが最も増える隣の組へ移すことを、移すものが無くなるまで繰り返す。
+// adjacency は対称で、自己ループ (まとめた組の中の重み) は両向きの分を 1 つに足してある。
+// どのノードも移らなければ None、移ったら各ノードの組 (ノード番号のどれか)
+fn louvain_level(adjacency: &[BTreeMap<usize, f64>]) -> Option<Vec<usize>> {
+    let degrees: Vec<f64> = adjacency.iter().map(|neighbors| neighbors.values().sum()).collect();
+    let total: f64 = degrees.iter().sum();
+    if total == 0.0 {
+        return None;
+    }
+    let mut community: Vec<usize> = (0..adjacency.len()).collect();
+    // 組ごとの次数の合計
+    let mut community_degree = degrees.clone();
+    let mut moved = false;
+    loop {
+        let mut changed = false;
+        for node in 0..adjacency.len() {
+            let current = community[node];
+            let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+            for (&neighbor, &weight) in adjacency[node].iter().filter(|(neighbor, _)| **neighbor != node) {
+                *links.entry(community[neighbor]).or_insert(0.0) += weight;
+            }
+            community_degree[current] -= degrees[node];
+            let gain = |c: usize| links.get(&c).copied().unwrap_or(0.0) - community_degree[c] * degrees[node] / total;
+            let mut best = (current, gain(current));
+            for &c in links.keys() {
+                let candidate = gain(c);
+                // 誤差で行ったり来たりしないよう、はっきり増えるときだけ移す
+                if candidate > best.1 + 1e-12 {
+                    best = (c, candidate);
+                }
+            }
+            community_degree[best.0] += degrees[node];
+            if best.0 != current {
+                community[node] = best.0;
+                changed = true;
+                moved = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    moved.then_some(community)
+}
+
+// --group-by dir[:<深さ>]: ファイルをフォルダ (深さを指定したら解析対象ディレクトリからその階層まで) にまとめる
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectoryGrouping {
+    pub depth: Option<usize>,
+}
+
+impl DirectoryGrouping {
+    // ノード ID のファイルをまとめる先 (深さより浅いフォルダにあるファイルはそのフォルダ)
+    pub fn key(&self, id: &str) -> String {
+        let directory = directory_of(id);
+        match self.depth {
+            Some(depth) if directory != "." => directory.split('/').take(depth).collect::<Vec<_>>().join("/"),
+            _ => directory,
+        }
+    }
+}
+
+impl FromStr for DirectoryGrouping {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            None if s == "dir" => Ok(Self { depth: None }),
+            Some(("dir", depth)) => match depth.parse() {
+                Ok(depth) if depth > 0 => Ok(Self { depth: Some(depth) }),
+                _ => bail!("--group-by dir:<深さ> の深さには 1 以上の整数を指定してください: {depth}"),
+            },
+            _ => bail!("--group-by に指定できるのは dir か dir:<深さ> です: {s}"),
+        }
+    }
+}
+
+// ノード ID のファイルがあるフォルダ (解析対象ディレクトリからの相対パス。直下のファイルは ".")
+pub fn directory_of(id: &str) -> String {
+    match id.rsplit_once('/') {
+        Some((directory, _)) => directory.to_string(),
+        None => ".".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(nodes: &[&str], edges: &[(&str, &str)]) -> DependencyGraph {
+        DependencyGraph {
+            nodes: nodes.iter().map(|id| Node { id: id.to_string(), size: 0 }).collect(),
+            edges: edges
+                .iter()
+                .map(|(from, to)| Edge { from: from.to_string(), to: to.to_string(), weight: 1, dynamic: false })
+                .collect(),
+        }
+    }
+
+    fn module_ref(specifier: &str, names: &[&str]) -> ModuleRef {
+        ModuleRef {
+            specifier: specifier.to_string(),
+            dynamic: false,
+            type_only: false,
+            names: names.iter().map(|name| name.to_string()).collect(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn local_exports(names: &[&str]) -> ModuleExports {
+        ModuleExports {
+            local: names.iter().map(|name| name.to_string()).collect(),
+            ..ModuleExports::default()
+        }
+    }
+
+    fn betweenness(nodes: &[&str], edges: &[(&str, &str)]) -> HashMap<String, f64> {
+        let graph = graph(nodes, edges);
+        graph.betweenness().into_iter().map(|(id, value)| (id.to_string(), value)).collect()
+    }
+
+    #[track_caller]
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn betweenness_of_chain() {
+        let centrality = betweenness(&["a", "b", "c"], &[("a", "b"), ("b", "c")]);
+        // a -> c の連鎖だけが b を通る。(n-1)(n-2) = 2 で割る
+        assert_close(centrality["b"], 0.5);
+        assert_close(centrality["a"], 0.0);
+        assert_close(centrality["c"], 0.0);
+    }
+
+    #[test]
+    fn betweenness_splits_equal_shortest_paths() {
+        let centrality = betweenness(&["a", "b", "c", "d"], &[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")]);
+        assert_close(centrality["b"], 0.5 / 6.0);
+        assert_close(centrality["c"], 0.5 / 6.0);
+        assert_close(centrality["a"], 0.0);
+    }
+
+    #[test]
+    fn betweenness_follows_import_direction() {
+        let edges = [("a", "hub"), ("b", "hub"), ("hub", "x"), ("hub", "y")];
+        let centrality = betweenness(&["a", "b", "hub", "x", "y"], &edges);
+        // a / b から x / y への 4 本がすべて hub を通る
+        assert_close(centrality["hub"], 4.0 / 12.0);
+        // 逆向きには辿らないので x -> a 等は数えない
+        let centrality = betweenness(&["a", "b", "c"], &[("b", "a"), ("b", "c")]);
+        assert_close(centrality["b"], 0.0);
+    }
+
+    #[test]
+    fn betweenness_of_tiny_graphs() {
+        assert!(betweenness(&[], &[]).is_empty());
+        let centrality = betweenness(&["a", "b"], &[("a", "b"), ("b", "a")]);
+        assert_close(centrality["a"], 0.0);
+    }
+
+    #[test]
+    fn communities_split_two_triangles() {
+        let edges = [("a", "b"), ("b", "c"), ("c", "a"), ("d", "e"), ("e", "f"), ("f", "d"), ("c", "d")];
+        let (groups, modularity) = graph(&["a", "b", "c", "d", "e", "f"], &edges).communities();
+        assert_eq!(groups, [vec!["a", "b", "c"], vec!["d", "e", "f"]]);
+        // 組ごとに 6/14 - (7/14)^2
+        assert_close(modularity, 5.0 / 14.0);
+    }
+
+    #[test]
+    fn communities_ignore_direction_weight_and_self_imports() {
+        let mut graph = graph(&["a", "b", "c"], &[("a", "b"), ("b", "a"), ("c", "c")]);
+        graph.edges[0].weight = 10;
+        let (groups, modularity) = graph.communities();
+        // 孤立したファイルは 1 ファイルの組になる
+        assert_eq!(groups, [vec!["a", "b"], vec!["c"]]);
+        // 辺のある組が 1 つだけなら 4/4 - (4/4)^2
+        assert_close(modularity, 0.0);
+    }
+
+    #[test]
+    fn communities_without_edges() {
+        let (groups, modularity) = graph(&["b", "a"], &[]).communities();
+        assert_eq!(groups, [vec!["a"], vec!["b"]]);
+        assert_close(modularity, 0.0);
+        assert_eq!(graph(&[], &[]).communities(), (Vec::<Vec<String>>::new(), 0.0));
+    }
+
+    #[test]
+    fn communities_are_deterministic() {
+        let nodes: Vec<String> = (0..12).map(|i| format!("src/f{i:02}.ts")).collect();
+        let ids: Vec<&str> = nodes.iter().map(String::as_str).collect();
+        let edges: Vec<(&str, &str)> = (0..12).map(|i| (ids[i], ids[(i * 5 + 3) % 12])).collect();
+        let first = graph(&ids, &edges).communities();
+        for _ in 0..5 {
+            assert_eq!(graph(&ids, &edges).communities(), first);
+        }
+        assert_eq!(first.0.iter().map(Vec::len).sum::<usize>(), 12);
+    }
+
+    #[test]
+    fn named_imports_through_barrels_depend_on_defining_files() {
+        let files: HashSet<PathBuf> =
+            ["app.ts", "lib/index.ts", "lib/a.ts", "lib/b.ts", "lib/deep/index.ts", "lib/deep/c.ts"].map(PathBuf::from).into();
+        let mut barrel = local_exports(&["VERSION"]);
+        barrel.re_exports = vec![
+            ReExport { specifier: "./a".into(), names: None },
+            ReExport { specifier: "./b".into(), names: Some(vec![("Renamed".into(), "B".into())]) },
+            ReExport { specifier: "./deep".into(), names: None },
+        ];
+        let deep = ModuleExports {
+            re_exports: vec![ReExport { specifier: "./c".into(), names: None }],
+            ..ModuleExports::default()
+        };
+        let exports = HashMap::from([
+            (PathBuf::from("lib/index.ts"), barrel),
+            (PathBuf::from("lib/a.ts"), local_exports(&["A", "default"])),
+            (PathBuf::from("lib/b.ts"), local_exports(&["B"])),
+            (PathBuf::from("lib/deep/index.ts"), deep),
+            (PathBuf::from("lib/deep/c.ts"), local_exports(&["C"])),
+        ]);
+        let edges = |names: &[&str]| {
+            let sources = [(PathBuf::from("app.ts"), vec![module_ref("./lib", names)])];
+            let graph =
+                DependencyGraph::build(Path::new(""), &files, &HashMap::new(), &sources, &exports, &PathAliases::default());
+            graph.edges.into_iter().map(|edge| edge.to).collect::<Vec<_>>()
+        };
+        // export * from と export { B as Renamed } from を辿り、入れ子のバレルも定義元まで辿る
+        assert_eq!(edges(&["A", "Renamed", "C"]), ["lib/a.ts", "lib/b.ts", "lib/deep/c.ts"]);
+        // バレル自身で定義している名前・辿れない名前はバレルへの依存
+        assert_eq!(edges(&["VERSION"]), ["lib/index.ts"]);
+        assert_eq!(edges(&["Missing"]), ["lib/index.ts"]);
+        // export * from は default を再エクスポートしない
+        assert_eq!(edges(&["default"]), ["lib/index.ts"]);
+        // 名前の無いインポート (namespace・副作用のみ) はバレルへの依存
+        assert_eq!(edges(&[]), ["lib/index.ts"]);
+    }
+}
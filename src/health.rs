@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::{graph::DependencyGraph, report::Report, resolve};
+
+// これ以上のモジュールをインポートしているコンポーネント (*.component.ts) を大きすぎるコンポーネントとみなす
+pub const GOD_COMPONENT_IMPORTS: usize = 20;
+
+// npm パッケージの公開していない内部を指すサブパスの要素 ('rxjs/internal/...'・'@org/lib/src/...' 等)
+const PRIVATE_SEGMENTS: &[&str] = &["src", "lib", "dist", "internal", "private"];
+
+// 健全性スコアの指標ごとの重み (設定ファイルの [health]。0 ならその指標は数えない)
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HealthWeights {
+    pub unused_imports: u32,
+    pub cycles: u32,
+    pub deep_imports: u32,
+    pub god_components: u32,
+    pub boundary_violations: u32,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            unused_imports: 1,
+            cycles: 3,
+            deep_imports: 2,
+            god_components: 2,
+            boundary_violations: 3,
+        }
+    }
+}
+
+// 依存関係の健全性スコア (0〜100 と A〜F)
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    pub score: u32,
+    pub grade: char,
+    // 解析したファイル数 (各指標の割合の分母)
+    pub files: usize,
+    pub signals: Vec<HealthSignal>,
+}
+
+// スコアの指標 1 つ
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthSignal {
+    pub signal: Signal,
+    pub weight: u32,
+    // 指摘の数 (循環依存は組の数)
+    pub count: usize,
+    // 指摘のあるファイル数と、解析したファイルに占める割合 (0〜1)
+    pub files: usize,
+    pub ratio: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Signal {
+    // 未使用のインポート
+    UnusedImports,
+    // ファイル間の循環依存
+    Cycles,
+    // npm パッケージの内部 (公開していないサブパス) のインポート
+    DeepImports,
+    // インポートの多すぎるコンポーネント
+    GodComponents,
+    // 依存ポリシー・プロジェクトの境界の違反
+    BoundaryViolations,
+}
+
+impl Signal {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::UnusedImports => "未使用のインポート",
+            Self::Cycles => "循環依存",
+            Self::DeepImports => "パッケージの内部のインポート",
+            Self::GodComponents => "インポートの多すぎるコンポーネント",
+            Self::BoundaryViolations => "ポリシー・境界の違反",
+        }
+    }
+}
+
+// report と graph から健全性スコアを求める。boundary は境界の違反があったファイルのパス
+// (依存ポリシーの違反に加えるプロジェクトの境界の違反など。report と同じ表示のパス)。
+// 各指標で指摘のあるファイルの割合を重みで平均し、100 から引いたものをスコアにする
+pub fn score(report: &Report, graph: &DependencyGraph, boundary: &[&str], weights: &HealthWeights) -> Health {
+    let files = graph.nodes.len();
+
+    let unused: HashSet<&str> = report.unused_imports.iter().map(|unused| unused.path.as_str()).collect();
+
+    let cycles = graph.cycles();
+    let in_cycles: usize = cycles.iter().map(Vec::len).sum();
+
+    // ファイルごとのインポートしているモジュール (tsconfig の paths 等で解析対象内に解決される指定子を
+    // パッケージとして数えないよう、Report::packages にあるものだけを deep import とみなす)
+    let known: HashSet<&str> = report.packages.iter().map(|package| package.package.as_str()).collect();
+    let mut modules: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for file in &report.files {
+        modules.entry(&file.path).or_default().extend(file.imports.iter().map(|import| import.module.as_str()));
+    }
+    let others = report.side_effect_imports.iter().map(|import| (&import.path, &import.module));
+    let others = others.chain(report.dynamic_imports.iter().map(|import| (&import.path, &import.module)));
+    for (path, module) in others {
+        modules.entry(path).or_default().insert(module);
+    }
+    let deep: Vec<(&str, &str)> = modules
+        .iter()
+        .flat_map(|(&path, modules)| modules.iter().map(move |&module| (path, module)))
+        .filter(|(_, module)| resolve::package_name(module).is_some_and(|package| known.contains(package)))
+        .filter(|(_, module)| is_deep_import(module))
+        .collect();
+    let god: Vec<&str> = modules
+        .iter()
+        .filter(|(path, modules)| path.ends_with(".component.ts") && modules.len() >= GOD_COMPONENT_IMPORTS)
+        .map(|(&path, _)| path)
+        .collect();
+
+    let mut violations: Vec<&str> = report.policy_violations.iter().map(|violation| violation.path.as_str()).collect();
+    violations.extend(boundary);
+    let deep_files: HashSet<&str> = deep.iter().map(|(path, _)| *path).collect();
+    let violation_files: HashSet<&str> = violations.iter().copied().collect();
+
+    let signal = |signal, weight, count, affected: usize| HealthSignal {
+        signal,
+        weight,
+        count,
+        files: affected,
+        ratio: match files {
+            0 => 0.0,
+            n => (affected as f64 / n as f64).min(1.0),
+        },
+    };
+    let signals = vec![
+        signal(Signal::UnusedImports, weights.unused_imports, report.unused_imports.len(), unused.len()),
+        signal(Signal::Cycles, weights.cycles, cycles.len(), in_cycles),
+        signal(Signal::DeepImports, weights.deep_imports, deep.len(), deep_files.len()),
+        signal(Signal::GodComponents, weights.god_components, god.len(), god.len()),
+        signal(Signal::BoundaryViolations, weights.boundary_violations, violations.len(), violation_files.len()),
+    ];
+    let total: u32 = signals.iter().map(|signal| signal.weight).sum();
+    let penalty = match total {
+        0 => 0.0,
+        total => signals.iter().map(|signal| signal.weight as f64 * signal.ratio).sum::<f64>() / total as f64,
+    };
+    let score = (100.0 * (1.0 - penalty)).round() as u32;
+    Health {
+        score,
+        grade: grade(score),
+        files,
+        signals,
+    }
+}
+
+// 90 以上が A、以下 10 ずつ B / C / D、60 未満が F
+pub fn grade(score: u32) -> char {
+    match score {
+        90.. => 'A',
+        80..=89 => 'B',
+        70..=79 => 'C',
+        60..=69 => 'D',
+        _ => 'F',
+    }
+}
+
+// パッケージ名の後ろのサブパスに src / internal 等の要素か、esm2022 / fesm2022 のようなビルド成果物のフォルダがある
+fn is_deep_import(specifier: &str) -> bool {
+    let Some(package) = resolve::package_name(specifier) else {
+        return false;
+    };
+    specifier[package.len()..].split('/').any(|segment| {
+        PRIVATE_SEGMENTS.contains(&segment)
+            || segment.starts_with("esm")
+            || segment.starts_with("fesm")
+            || (segment.starts_with('_') && segment.len() > 1)
+    })
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use anyhow::{bail, Result};
+use rayon::prelude::*;
+use angular_dependency_analyzer::{
+    output::{self, history::HistoryPoint},
+    report::PackageUsage,
+};
+use crate::{git, open_output, Args};
+
+// history コマンドで、範囲を指定しなかったときに辿る時点の数の上限
+pub const DEFAULT_LIMIT: usize = 20;
+
+// history コマンドのオプション
+#[derive(Debug, Clone)]
+pub struct Options {
+    // git log に渡す範囲 (v1.0..HEAD 等。既定は HEAD までのすべてのコミット)
+    pub range: Option<String>,
+    // コミットの代わりにタグを辿る
+    pub tags: bool,
+    // 辿る時点の数の上限。超えたら最初と最後を含めて等間隔に間引く
+    pub limit: usize,
+    // 表示するパッケージ (空ならすべて)
+    pub packages: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            range: None,
+            tags: false,
+            limit: DEFAULT_LIMIT,
+            packages: Vec::new(),
+        }
+    }
+}
+
+// history コマンド: 作業ツリーをチェックアウトせずに各時点のファイルを git から読んで解析し、
+// パッケージごとの使用回数の推移を出力する
+pub fn run(args: &Args) -> Result<()> {
+    let options = &args.history_options;
+    let target = &args.targets[0];
+    if !target.is_dir() {
+        bail!("history コマンドの解析対象はフォルダを指定してください: {}", target.display());
+    }
+    let config = args.config()?;
+    let revisions = git::revisions(target, options.range.as_deref().unwrap_or("HEAD"), options.tags)?;
+    if revisions.is_empty() {
+        bail!("辿るコミット・タグがありません");
+    }
+    let revisions = sample(revisions, options.limit);
+
+    let points = revisions
+        .par_iter()
+        .map(|revision| {
+            let report = git::analyze_revision(target, &revision.id, &config)?.report;
+            Ok(HistoryPoint {
+                revision: revision.id.clone(),
+                date: revision.date.clone(),
+                label: revision.label.clone(),
+                files_analyzed: report.files_analyzed,
+                packages: select(report.packages, &options.packages),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut out = open_output(args)?;
+    output::history::write(args.history_format()?, &points, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+// 最初と最後を含めて、等間隔に limit 個まで間引く (limit が 1 なら最後だけ)
+fn sample<T>(items: Vec<T>, limit: usize) -> Vec<T> {
+    if items.len() <= limit {
+        return items;
+    }
+    if limit == 1 {
+        return items.into_iter().last().into_iter().collect();
+    }
+    let last = items.len() - 1;
+    let picked: Vec<usize> = (0..limit).map(|i| i * last / (limit - 1)).collect();
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| picked.contains(index))
+        .map(|(_, item)| item)
+        .collect()
+}
+
+// --package の指定があれば、そのパッケージだけを指定した順に (使われていない時点は 0 回として) 並べる
+fn select(packages: Vec<PackageUsage>, names: &[String]) -> Vec<PackageUsage> {
+    if names.is_empty() {
+        return packages;
+    }
+    let mut by_name: HashMap<_, _> = packages.into_iter().map(|p| (p.package.clone(), p)).collect();
+    names
+        .iter()
+        .map(|name| {
+            by_name.remove(name).unwrap_or_else(|| PackageUsage {
+                package: name.clone(),
+                count: 0,
+                files: 0,
+                symbols: Vec::new(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,70 @@
+use std::{collections::HashSet, fs, io::Write, path::PathBuf};
+use anyhow::{bail, Result};
+use angular_dependency_analyzer::{self as analyzer, baseline::Baseline, report::Report};
+use crate::{git, open_output, Args};
+
+// --hook: pre-commit フック向けに、ステージされた内容 (作業ツリーではなくインデックス) を
+// HEAD の内容と比べ、コミットで新しく増える指摘だけを 1 行ずつ表示する。指摘があれば失敗として終了する
+pub fn run(args: &Args) -> Result<()> {
+    let config = args.config()?;
+    let dir = args.base_dir();
+    let (top, names) = git::staged_files(dir)?;
+    let top = fs::canonicalize(top)?;
+
+    let mut count = 0;
+    let mut out = open_output(args)?;
+    for target in &args.targets {
+        let Ok(canonical) = fs::canonicalize(target) else {
+            continue;
+        };
+        // 解析対象の下にあるファイルだけを、解析対象のパスの形で扱う
+        let paths: Vec<(PathBuf, &str)> = names
+            .iter()
+            .filter_map(|name| {
+                let relative = top.join(name).strip_prefix(&canonical).ok()?.to_path_buf();
+                let path = match relative.as_os_str().is_empty() {
+                    true => target.clone(),
+                    false => target.join(relative),
+                };
+                Some((path, name.as_str()))
+            })
+            .collect();
+        if paths.is_empty() {
+            continue;
+        }
+        let staged = paths
+            .iter()
+            .filter_map(|(path, name)| Some((path.clone(), git::show(dir, &format!(":{name}"))?)));
+        let head = paths
+            .iter()
+            .filter_map(|(path, name)| Some((path.clone(), git::show(dir, &format!("HEAD:{name}"))?)));
+        let staged = analyzer::analyze_sources(target, staged, &config).report;
+        let head = analyzer::analyze_sources(target, head, &config).report;
+        for finding in new_findings(staged, &head) {
+            count += 1;
+            writeln!(
+                out,
+                "{}:{}:{}: {} [{}] {}",
+                finding.path,
+                finding.line,
+                finding.column,
+                finding.severity.as_str(),
+                finding.rule.id,
+                finding.message
+            )?;
+        }
+    }
+    out.flush()?;
+    if count > 0 {
+        bail!("コミットで新しい指摘が {count} 件増えます");
+    }
+    Ok(())
+}
+
+// staged の検出結果のうち、HEAD の同じファイルには無かったもの (行番号の違いは無視する)
+fn new_findings(mut staged: Report, head: &Report) -> Vec<analyzer::report::Finding> {
+    Baseline::from_report(head).retain_new(&mut staged);
+    let failures: HashSet<_> = head.parse_failures.iter().map(|f| f.path.as_str()).collect();
+    staged.parse_failures.retain(|f| !failures.contains(f.path.as_str()));
+    staged.findings()
+}
@@ -0,0 +1,235 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use anyhow::{Context, Result};
+use crate::glob::{slash_path, Glob};
+
+// 走査するディレクトリごとに読む無視ファイル (ripgrep と同じ)
+pub const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+// .gitignore 形式の無視ルール。走査中はディレクトリごとに with_dir で子孫向けのルールを作る
+#[derive(Debug, Clone, Default)]
+pub struct Ignore {
+    // 後ろのルールほど優先する (親ディレクトリ → 子ディレクトリの順に積む)
+    rules: Arc<Vec<Rule>>,
+    // 各ディレクトリの .gitignore / .ignore を読むか
+    read_dirs: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    // 無視ファイルのあるディレクトリ (走査で得るパスと同じ形)
+    base: PathBuf,
+    // 解析対象ディレクトリより上の無視ファイルでは、そこから解析対象ディレクトリまでの相対パス
+    prefix: String,
+    glob: Glob,
+    // / を含むパターンは base からの相対パス全体に、含まないものはファイル名に一致させる
+    anchored: bool,
+    // ! で始まるパターン (無視しない)
+    negated: bool,
+    // / で終わるパターン (ディレクトリにのみ一致)
+    dir_only: bool,
+}
+
+impl Ignore {
+    // root (解析対象ディレクトリ) の親からリポジトリのルートまでの .gitignore / .ignore と、
+    // 指定された無視ファイル (パターンは root からの相対パスとして扱う) を読む。
+    // read_dirs が false なら指定された無視ファイルだけを使う
+    pub fn new(root: &Path, ignore_files: &[PathBuf], read_dirs: bool) -> Result<Self> {
+        let mut rules = Vec::new();
+        if read_dirs && let Ok(canonical) = fs::canonicalize(root) {
+            // .git のあるディレクトリより上は見ない (リポジトリの外なら親は読まない)
+            let parents: Vec<_> = canonical.ancestors().skip(1).collect();
+            if !canonical.join(".git").exists()
+                && let Some(top) = parents.iter().position(|dir| dir.join(".git").exists())
+            {
+                for dir in parents[..=top].iter().rev() {
+                    let prefix = canonical.strip_prefix(dir).map(slash_path).unwrap_or_default();
+                    for name in IGNORE_FILE_NAMES {
+                        if let Ok(src) = fs::read_to_string(dir.join(name)) {
+                            rules.extend(parse(&src, root, &prefix));
+                        }
+                    }
+                }
+            }
+        }
+        for path in ignore_files {
+            let src = fs::read_to_string(path)
+                .with_context(|| format!("無視ファイルを読み込めません: {}", path.display()))?;
+            rules.extend(parse(&src, root, ""));
+        }
+        Ok(Self {
+            rules: Arc::new(rules),
+            read_dirs,
+        })
+    }
+
+    // dir にある .gitignore / .ignore のルールを加えたもの (dir 以下の判定に使う)
+    pub fn with_dir(&self, dir: &Path) -> Self {
+        if !self.read_dirs {
+            return self.clone();
+        }
+        let added: Vec<_> = IGNORE_FILE_NAMES
+            .iter()
+            .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+            .flat_map(|src| parse(&src, dir, ""))
+            .collect();
+        if added.is_empty() {
+            return self.clone();
+        }
+        let mut rules = Vec::with_capacity(self.rules.len() + added.len());
+        rules.extend(self.rules.iter().cloned());
+        rules.extend(added);
+        Self {
+            rules: Arc::new(rules),
+            read_dirs: self.read_dirs,
+        }
+    }
+
+    // 最後に一致したルールで決める (! のルールなら無視しない)
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    // 走査を経ずに root 以下の 1 つのパスを判定する (途中のディレクトリの無視ファイルも読む)。
+    // 無視されたディレクトリの中は、! のルールがあっても無視する (git と同じ)
+    pub fn is_ignored_under(&self, root: &Path, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+        let components: Vec<_> = relative.components().collect();
+        let mut ignore = self.with_dir(root);
+        let mut current = root.to_path_buf();
+        for (i, component) in components.iter().enumerate() {
+            current.push(component);
+            let last = i + 1 == components.len();
+            if ignore.is_ignored(&current, !last || is_dir) {
+                return true;
+            }
+            if !last {
+                ignore = ignore.with_dir(&current);
+            }
+        }
+        false
+    }
+}
+
+impl Rule {
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        let relative = slash_path(relative);
+        if relative.is_empty() {
+            return false;
+        }
+        if self.anchored {
+            match self.prefix.as_str() {
+                "" => self.glob.matches(&relative),
+                prefix => self.glob.matches(&format!("{prefix}/{relative}")),
+            }
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(&relative);
+            self.glob.matches(name)
+        }
+    }
+}
+
+// 無視ファイルの内容を読む (空行と # で始まる行は読み飛ばす)
+fn parse(src: &str, base: &Path, prefix: &str) -> Vec<Rule> {
+    src.lines()
+        .filter_map(|line| {
+            // 末尾の空白は \ でエスケープされていなければ除く
+            let mut line = line.trim_end_matches('\r');
+            while line.ends_with(' ') && !line.ends_with("\\ ") {
+                line = &line[..line.len() - 1];
+            }
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let pattern = line.strip_prefix('/').unwrap_or(line);
+            (!pattern.is_empty()).then(|| Rule {
+                base: base.to_path_buf(),
+                prefix: prefix.to_string(),
+                glob: Glob::new(pattern),
+                anchored,
+                negated,
+                dir_only,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignore(src: &str, prefix: &str) -> Ignore {
+        Ignore {
+            rules: Arc::new(parse(src, Path::new("root"), prefix)),
+            read_dirs: false,
+        }
+    }
+
+    #[test]
+    fn later_negation_unignores() {
+        let ignore = ignore("# 生成物\n*.js\n!keep.js\n\n", "");
+        assert!(ignore.is_ignored(Path::new("root/src/a.js"), false));
+        assert!(!ignore.is_ignored(Path::new("root/src/keep.js"), false));
+        assert!(!ignore.is_ignored(Path::new("root/src/a.ts"), false));
+
+        // 後ろのルールが優先する
+        let ignore = self::ignore("!keep.js\n*.js\n", "");
+        assert!(ignore.is_ignored(Path::new("root/keep.js"), false));
+    }
+
+    #[test]
+    fn anchored_patterns_match_from_base() {
+        let ignore = ignore("/dist\nsrc/generated/\nbuild\n", "");
+        assert!(ignore.is_ignored(Path::new("root/dist"), true));
+        assert!(!ignore.is_ignored(Path::new("root/app/dist"), true));
+        assert!(ignore.is_ignored(Path::new("root/src/generated"), true));
+        assert!(!ignore.is_ignored(Path::new("root/app/src/generated"), true));
+        // / を含まないパターンはどの階層の名前にも一致する
+        assert!(ignore.is_ignored(Path::new("root/app/build"), true));
+    }
+
+    #[test]
+    fn trailing_slash_matches_only_directories() {
+        let ignore = ignore("tmp/\n", "");
+        assert!(ignore.is_ignored(Path::new("root/a/tmp"), true));
+        assert!(!ignore.is_ignored(Path::new("root/a/tmp"), false));
+    }
+
+    #[test]
+    fn parent_ignore_file_applies_with_prefix() {
+        // 解析対象 (root) がリポジトリの packages/app のとき、上の .gitignore の packages/app/dist
+        let ignore = ignore("packages/app/dist\n", "packages/app");
+        assert!(ignore.is_ignored(Path::new("root/dist"), true));
+        assert!(!ignore.is_ignored(Path::new("root/src/dist"), true));
+    }
+
+    #[test]
+    fn paths_outside_base_are_not_ignored() {
+        let ignore = ignore("*.js\n", "");
+        assert!(!ignore.is_ignored(Path::new("other/a.js"), false));
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+use anyhow::Result;
+use angular_dependency_analyzer::{
+    self as analyzer,
+    glob::Glob,
+    manifest,
+    output::{
+        self,
+        impact::{AffectedFile, ImpactReport},
+    },
+    resolve, Aggregate, ProjectAnalysis,
+};
+use crate::{git, open_cache, open_output, Args};
+
+// テンプレート・スタイルの変更は同じ名前の .ts (コンポーネント) の変更とみなす
+const COMPONENT_EXTENSION: &str = "ts";
+
+// impact コマンド: 変更したファイル (--file、--changed、どちらも無ければ標準入力の 1 行 1 パス) を直接・間接に
+// インポートしているファイルを、全ファイルの依存グラフから求めて出力する
+pub fn run(args: &Args) -> Result<()> {
+    let changed: Vec<PathBuf> = match &args.changed {
+        Some(base) => git::changed_files(args.base_dir(), base)?.into_iter().collect(),
+        None if !args.impact_files.is_empty() => args.impact_files.clone(),
+        None => {
+            let mut paths = Vec::new();
+            for line in io::stdin().lock().lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    paths.push(PathBuf::from(line.trim()));
+                }
+            }
+            paths
+        }
+    };
+
+    let config = args.config()?;
+    let cache = open_cache(args);
+    let mut aggregate = Aggregate::with_targets(&args.targets, &config);
+    analyzer::for_each_file(&args.targets, &config, cache.as_ref(), |outcome| {
+        aggregate.add(outcome);
+        Ok(())
+    })?;
+    let ProjectAnalysis { report, graph } = aggregate.finish();
+
+    let target = Path::new(&report.target);
+    let root = fs::canonicalize(target)?;
+    let nodes: HashSet<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+    // 解析対象のファイルならグラフのノード ID
+    let node_id = |path: &Path| {
+        let path = fs::canonicalize(path).ok().filter(|path| path.starts_with(&root))?;
+        let id = resolve::relative_display(&root, &path);
+        nodes.contains(id.as_str()).then_some(id)
+    };
+    let mut ids = BTreeSet::new();
+    let mut ignored = Vec::new();
+    for path in &changed {
+        let id = node_id(path).or_else(|| match path.extension() {
+            Some(extension) if extension != COMPONENT_EXTENSION => node_id(&path.with_extension(COMPONENT_EXTENSION)),
+            _ => None,
+        });
+        match id {
+            Some(id) => {
+                ids.insert(id);
+            }
+            None => ignored.push(path.display().to_string()),
+        }
+    }
+    ignored.sort();
+
+    let ids: Vec<String> = ids.into_iter().collect();
+    let tests: Vec<Glob> = manifest::TEST_FILES.iter().map(|pattern| Glob::new(pattern)).collect();
+    let display = |id: &str| target.join(id).display().to_string();
+    let impact = ImpactReport {
+        schema_version: report.schema_version,
+        affected: graph
+            .transitive_dependents(&ids)
+            .into_iter()
+            .map(|(id, depth)| AffectedFile {
+                path: display(&id),
+                depth,
+                test: tests.iter().any(|glob| glob.matches_path(&id)),
+            })
+            .collect(),
+        changed: ids.iter().map(|id| display(id)).collect(),
+        ignored,
+        target: report.target,
+    };
+    let mut out = open_output(args)?;
+    output::impact::write(args.unused_format()?, &impact, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,1911 @@
+pub mod baseline;
+#[cfg(feature = "native")]
+pub mod cache;
+pub mod encoding;
+#[cfg(feature = "native")]
+pub mod export;
+pub mod glob;
+pub mod graph;
+pub mod health;
+#[cfg(feature = "native")]
+pub mod ignore;
+pub mod manifest;
+pub mod output;
+pub mod policy;
+pub mod report;
+pub mod resolve;
+mod scope;
+pub mod semver;
+pub mod tsconfig;
+
+use std::{cmp::Ordering, collections::{BTreeMap, BTreeSet, HashMap, HashSet}, path::{Path, PathBuf}};
+use glob::Glob;
+use graph::{DependencyGraph, ModuleExports, ModuleRef, ReExport};
+use manifest::Manifest;
+use policy::{ImportSite, Policy};
+use resolve::PathAliases;
+use tsconfig::ProjectFiles;
+use output::SortKey;
+use scope::Scopes;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
+use {anyhow::Result, cache::Cache, ignore::Ignore, jwalk::WalkDirGeneric, rayon::prelude::*, std::fs};
+use swc_common::{sync::Lrc, FileName, SourceFile, SourceMap, Span, Spanned};
+use swc_ecma_parser::{EsSyntax, Parser, StringInput, Syntax, TsSyntax};
+use swc_ecma_visit::{Visit, VisitWith};
+use swc_ecma_ast::{
+    ArrowExpr, BlockStmt, BreakStmt, CallExpr, Callee, CatchClause, ClassExpr, Constructor, ContinueStmt, Decl,
+    DefaultDecl, ExportAll, ExportDecl, ExportDefaultDecl, ExportDefaultExpr, ExportSpecifier, Expr, FnExpr, ForHead,
+    ForInStmt, ForOfStmt, ForStmt, Function, Ident, ImportDecl, ImportSpecifier, LabeledStmt, Lit, ModuleExportName, NamedExport,
+    ObjectPatProp, ParamOrTsParamProp, Pat, Program, PropName, TsEntityName, TsEnumMember, TsExprWithTypeArgs, TsGetterSignature,
+    TsImportEqualsDecl, TsMethodSignature, TsModuleName, TsModuleRef, TsParamPropParam, TsPropertySignature,
+    TsSetterSignature, TsType, TsTypeAnn, VarDeclOrExpr, VarDeclarator,
+};
+use report::{
+    Cycle, DependencyIssue, DirectoryCoupling, DuplicateImport, DynamicImport, FileCoupling, FileEntry, FileImport, Location,
+    OrphanFile, PackageUsage, ParseFailure, PartialFile, PolicyViolation, Report, RuleLevel, RunSummary,
+    SideEffectImport, SkipCount, SkipReason, SkippedFile, TargetSummary, UnusedExport, UnusedImport, UsageEntry,
+};
+
+// 既定で解析する拡張子 (ESM の .mts / .mjs、CommonJS の .cts / .cjs、JSX の .tsx / .jsx も含む)
+pub const DEFAULT_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+// 解析の設定
+#[derive(Debug, Clone)]
+pub struct AnalyzerConfig {
+    // 集計結果の並び順
+    pub sort: SortKey,
+    // 並び順を逆にする
+    pub reverse: bool,
+    // ファイルごとの内訳 (Report::files) を残すか
+    pub keep_files: bool,
+    // analyze_dir で解析結果をキャッシュするディレクトリ (None なら読み書きしない。native 機能でのみ使う)
+    pub cache_dir: Option<PathBuf>,
+    // node_modules 等に加えて走査しないパスのグロブ (解析対象ディレクトリからの相対パス。
+    // / を含まない dist や *.spec.ts はパスのどこかの要素と一致すれば除外する)
+    pub exclude: Vec<Glob>,
+    // 空でなければ、いずれかに一致するファイルだけを解析する (exclude と同じ形式)
+    pub include: Vec<Glob>,
+    // 解析対象の拡張子 (先頭の . は付けない)
+    pub extensions: Vec<String>,
+    // 走査する深さの上限 (1 なら解析対象ディレクトリ直下のファイルだけ。native 機能でのみ使う)
+    pub max_depth: Option<usize>,
+    // シンボリックリンクを辿って走査するか (native 機能でのみ使う。ループは辿らず、
+    // 複数の経路から辿れるファイルは最初に見つかったパスで 1 回だけ解析する)
+    pub follow_symlinks: bool,
+    // 各ディレクトリの .gitignore / .ignore に従って走査しないか (native 機能でのみ使う)
+    pub use_ignore_files: bool,
+    // .gitignore と同じ形式で追加の除外パターンを書いたファイル (パターンは解析対象ディレクトリからの相対パス)
+    pub ignore_files: Vec<PathBuf>,
+    // これより大きい (バイト数) ファイルは解析せずに Report::skipped_files に記録する
+    pub max_file_size: Option<u64>,
+    // 先頭のコメントに @generated 等の目印があるファイルを解析せずに飛ばすか
+    pub skip_generated: bool,
+    // ルールごとの重要度の変更 (ルール ID -> 重要度)
+    pub rules: BTreeMap<String, RuleLevel>,
+    // 依存ポリシー (違反を Report::policy_violations に記録する)
+    pub policy: Option<Policy>,
+    // tsconfig.json の paths / baseUrl。'@app/core' のような指定子を依存グラフで解決し、npm パッケージとして数えない
+    pub path_aliases: PathAliases,
+    // 指定があれば、tsconfig の files / include / exclude でコンパイル対象になるファイルだけを解析する
+    pub project_files: Option<ProjectFiles>,
+    // 指定があれば、package.json に無いパッケージ・本番のコードからの devDependencies のインポートと、
+    // どこからもインポートされていない dependencies・@angular/* のバージョンのずれ・満たされていないピア依存を
+    // Report::dependency_issues に記録する
+    pub manifest: Option<Manifest>,
+    // 孤立ファイル・未使用のエクスポートの判定で起点にする (外から使われる) ファイル (解析対象ディレクトリからの相対パス)
+    pub entry_points: Vec<Glob>,
+    // entry_points からインポートを辿って届かないファイルを Report::orphan_files に記録する
+    pub check_orphans: bool,
+    // どのファイルからもインポートされていないエクスポートを Report::unused_exports に記録する
+    pub check_unused_exports: bool,
+    // ファイル間の循環依存を Report::cycles に記録する
+    pub check_cycles: bool,
+    // ファイル・フォルダごとの fan-in / fan-out を Report::file_coupling / directory_coupling に記録する
+    pub check_coupling: bool,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            sort: SortKey::default(),
+            reverse: false,
+            keep_files: true,
+            cache_dir: None,
+            exclude: Vec::new(),
+            include: Vec::new(),
+            extensions: DEFAULT_EXTENSIONS.iter().map(|ext| ext.to_string()).collect(),
+            max_depth: None,
+            follow_symlinks: false,
+            use_ignore_files: true,
+            ignore_files: Vec::new(),
+            max_file_size: None,
+            skip_generated: true,
+            rules: BTreeMap::new(),
+            policy: None,
+            path_aliases: PathAliases::default(),
+            project_files: None,
+            manifest: None,
+            entry_points: Vec::new(),
+            check_orphans: false,
+            check_unused_exports: false,
+            check_cycles: false,
+            check_coupling: false,
+        }
+    }
+}
+
+impl AnalyzerConfig {
+    // root (解析対象ディレクトリ) 以下で走査しないパスか。
+    // dist/** のようなパターンでディレクトリごと飛ばせるよう、ディレクトリの中身としても判定する
+    pub fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        if is_excluded(path) {
+            return true;
+        }
+        let relative = relative_path(root, path);
+        let contents = format!("{relative}/");
+        self.exclude
+            .iter()
+            .any(|glob| glob.matches_path(&relative) || glob.matches_path(&contents))
+    }
+
+    // --include の指定があれば、いずれかに一致するファイルか (tsconfig のコンパイル対象に絞るときはその中のファイルか)
+    pub fn is_included(&self, root: &Path, path: &Path) -> bool {
+        let relative = relative_path(root, path);
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.matches_path(&relative)))
+            && self.project_files.as_ref().is_none_or(|files| files.contains(path))
+    }
+
+    // 解析対象の拡張子か (.d.ts / .d.mts / .d.cts は除く)
+    pub fn is_source_path(&self, path: &Path) -> bool {
+        let name = path.to_string_lossy();
+        ![".d.ts", ".d.mts", ".d.cts"].iter().any(|suffix| name.ends_with(suffix))
+            && path
+                .extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|ext| self.extensions.iter().any(|e| e == ext))
+    }
+}
+
+// root からの相対パス (root の外なら path のまま) を / 区切りで
+fn relative_path(root: &Path, path: &Path) -> String {
+    glob::slash_path(path.strip_prefix(root).unwrap_or(path))
+}
+
+// ディレクトリ全体の解析結果
+#[derive(Debug)]
+pub struct ProjectAnalysis {
+    pub report: Report,
+    pub graph: DependencyGraph,
+}
+
+// dir 以下のソースファイルをすべて解析して集計する
+#[cfg(feature = "native")]
+pub fn analyze_dir(dir: &Path, config: &AnalyzerConfig) -> Result<ProjectAnalysis> {
+    analyze_dirs(&[dir.to_path_buf()], config)
+}
+
+// 複数のフォルダ・ファイルをまとめて解析する (解析対象ごとの小計は Report::targets)
+#[cfg(feature = "native")]
+pub fn analyze_dirs(targets: &[PathBuf], config: &AnalyzerConfig) -> Result<ProjectAnalysis> {
+    let cache = config.cache_dir.as_deref().map(Cache::open).transpose()?;
+    let mut aggregate = Aggregate::with_targets(targets, config);
+    for_each_file(targets, config, cache.as_ref(), |outcome| {
+        aggregate.add(outcome);
+        Ok(())
+    })?;
+    Ok(aggregate.finish())
+}
+
+// targets 以下のソースファイルを並列に解析し、結果を走査順に f へ渡す
+#[cfg(feature = "native")]
+pub fn for_each_file(
+    targets: &[PathBuf],
+    config: &AnalyzerConfig,
+    cache: Option<&Cache>,
+    f: impl FnMut(FileOutcome) -> Result<()>,
+) -> Result<()> {
+    let paths = collect_source_files_in(targets, config)?;
+    for_each_path(&paths, config, cache, f)
+}
+
+// 走査済みのファイルを並列に解析し、結果を paths の順に f へ渡す
+#[cfg(feature = "native")]
+pub fn for_each_path(
+    paths: &[PathBuf],
+    config: &AnalyzerConfig,
+    cache: Option<&Cache>,
+    mut f: impl FnMut(FileOutcome) -> Result<()>,
+) -> Result<()> {
+    for chunk in paths.chunks(PARALLEL_CHUNK) {
+        // par_iter の collect は入力順を保つので、マージ結果は逐次実行と変わらない
+        let outcomes = chunk
+            .par_iter()
+            .map(|path| analyze_file(path, config, cache))
+            .collect::<Result<Vec<_>>>()?;
+        for outcome in outcomes {
+            f(outcome)?;
+        }
+    }
+    Ok(())
+}
+
+// ディスクを読まずに (パス, ソース) の組をまとめて解析する。
+// analyze_dir の走査と同じくパス順に集計するので、渡す順序によらず結果は同じになる
+pub fn analyze_sources(
+    root: &Path,
+    files: impl IntoIterator<Item = (PathBuf, String)>,
+    config: &AnalyzerConfig,
+) -> ProjectAnalysis {
+    let mut files: Vec<_> = files
+        .into_iter()
+        .filter(|(path, _)| {
+            !config.is_excluded(root, path) && config.is_source_path(path) && config.is_included(root, path)
+        })
+        .collect();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut aggregate = Aggregate::new(root, config);
+    for (path, src) in files {
+        let outcome = match skip_reason(&src, config) {
+            Some(reason) => skipped(&path, src.len() as u64, reason),
+            None => analyze_source(&path, src),
+        };
+        aggregate.add(outcome);
+    }
+    aggregate.finish()
+}
+
+// インポートで導入されたローカル名の情報
+struct ImportBinding {
+    module: String,
+    // インポート元でのエクスポート名 (import { A as B } の A)。集計はこの名前で行う
+    imported: String,
+    // 再エクスポートの解決に使う本来のエクスポート名 (default インポートは "default"、namespace は "*")
+    export_name: String,
+    span: Span,
+    // import type { X } / import { type X } (バンドルに含まれない)
+    type_only: bool,
+}
+
+struct Analyzer {
+    // ローカル名 -> インポート元
+    imports: HashMap<String, ImportBinding>,
+    // import 宣言の外で参照された回数 (import 文の識別子自体は数えない)
+    usage: HashMap<String, usize>,
+    // そのうち型注釈など型の位置での使用回数 (コンパイル後に消える)
+    type_usage: HashMap<String, usize>,
+    // 使用箇所 (出現順)
+    usage_spans: HashMap<String, Vec<Span>>,
+    // import / export 宣言・import() のモジュール指定子と位置 (出現順)
+    sources: Vec<(ModuleRef, Span)>,
+    // このファイルがエクスポートしている名前と再エクスポート
+    exports: ModuleExports,
+    // exports.local の名前を宣言している位置 (解析後に行・列に直す)
+    export_spans: Vec<(String, Span)>,
+    // import('...') の指定子と位置
+    dynamic_imports: Vec<(String, Span)>,
+    // import 宣言ごとのモジュール指定子と位置 (重複検出用)
+    import_decls: Vec<(String, Span)>,
+    // import 'zone.js' のような副作用のみのインポート
+    side_effect_imports: Vec<(String, Span)>,
+    // インポート名を隠しているローカル変数・引数
+    scopes: Scopes,
+    // 型の位置 (型注釈・implements 等) を辿っている
+    in_type: bool,
+}
+
+impl Analyzer {
+    fn new() -> Self {
+        Self {
+            imports: HashMap::new(),
+            usage: HashMap::new(),
+            type_usage: HashMap::new(),
+            usage_spans: HashMap::new(),
+            sources: Vec::new(),
+            exports: ModuleExports::default(),
+            export_spans: Vec::new(),
+            dynamic_imports: Vec::new(),
+            import_decls: Vec::new(),
+            side_effect_imports: Vec::new(),
+            scopes: Scopes::default(),
+            in_type: false,
+        }
+    }
+}
+
+impl Analyzer {
+    // 型の位置にあっても実行時に参照される名前 (DI トークンになるコンストラクタ引数の型)
+    fn visit_entity_as_value(&mut self, name: &TsEntityName) {
+        match name {
+            TsEntityName::Ident(id) => {
+                let prev = std::mem::replace(&mut self.in_type, false);
+                self.visit_ident(id);
+                self.in_type = prev;
+            }
+            TsEntityName::TsQualifiedName(q) => self.visit_entity_as_value(&q.left),
+        }
+    }
+
+    // 位置は解析後に行・列に直す
+    fn add_source(&mut self, specifier: &str, dynamic: bool, type_only: bool, names: Vec<String>, span: Span) {
+        let source = ModuleRef {
+            specifier: specifier.to_string(),
+            dynamic,
+            type_only,
+            names,
+            line: 0,
+            column: 0,
+        };
+        self.sources.push((source, span));
+    }
+
+    // default / namespace インポートはエクスポート名を持たないのでローカル名で集計する
+    fn bind(&mut self, local: &Ident, module: &str, export_name: &str, type_only: bool) {
+        let imported = match export_name {
+            "default" | "*" => local.sym.to_string(),
+            name => name.to_string(),
+        };
+        self.imports.insert(
+            local.sym.to_string(),
+            ImportBinding {
+                module: module.to_string(),
+                imported,
+                export_name: export_name.to_string(),
+                span: local.span,
+                type_only,
+            },
+        );
+    }
+
+    // const x = require('foo') / const { a, b: c } = require('foo') の左辺をインポートとして登録する
+    fn bind_require_pat(&mut self, pat: &Pat, module: &str) {
+        match pat {
+            Pat::Ident(id) => self.bind(&id.id, module, "*", false),
+            Pat::Object(obj) => {
+                for prop in &obj.props {
+                    match prop {
+                        // { a: b } は a のエイリアス b
+                        ObjectPatProp::KeyValue(kv) => match (&kv.key, &*kv.value) {
+                            (PropName::Ident(key), Pat::Ident(id)) => self.bind(&id.id, module, &key.sym, false),
+                            (PropName::Str(key), Pat::Ident(id)) => self.bind(&id.id, module, &key.value, false),
+                            (_, value) => self.bind_require_pat(value, module),
+                        },
+                        ObjectPatProp::Assign(assign) => {
+                            self.bind(&assign.key.id, module, &assign.key.id.sym, false)
+                        }
+                        ObjectPatProp::Rest(rest) => self.bind_require_pat(&rest.arg, module),
+                    }
+                }
+            }
+            Pat::Assign(assign) => self.bind_require_pat(&assign.left, module),
+            _ => {}
+        }
+    }
+}
+
+// 'foo' や `foo` (埋め込み式なしのテンプレートリテラル) なら指定子の文字列を返す
+fn static_specifier(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(Lit::Str(s)) => Some(s.value.to_string()),
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+            .quasis
+            .first()
+            .map(|q| q.cooked.as_ref().unwrap_or(&q.raw).to_string()),
+        Expr::Paren(paren) => static_specifier(&paren.expr),
+        _ => None,
+    }
+}
+
+// 引数パターンの型注釈
+fn pat_type_ann(pat: &Pat) -> Option<&TsTypeAnn> {
+    match pat {
+        Pat::Ident(id) => id.type_ann.as_deref(),
+        Pat::Array(array) => array.type_ann.as_deref(),
+        Pat::Object(obj) => obj.type_ann.as_deref(),
+        Pat::Rest(rest) => rest.type_ann.as_deref(),
+        Pat::Assign(assign) => pat_type_ann(&assign.left),
+        _ => None,
+    }
+}
+
+// require('foo') の呼び出しなら指定子を返す
+fn require_specifier(n: &CallExpr) -> Option<String> {
+    match &n.callee {
+        Callee::Expr(callee) if matches!(&**callee, Expr::Ident(id) if &*id.sym == "require") && n.args.len() == 1 => {
+            static_specifier(&n.args[0].expr)
+        }
+        _ => None,
+    }
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(id) => id.sym.to_string(),
+        ModuleExportName::Str(s) => s.value.to_string(),
+    }
+}
+
+fn for_head_names(head: &ForHead) -> HashSet<String> {
+    match head {
+        ForHead::VarDecl(var) => scope::lexical_decl_names(var),
+        _ => Default::default(),
+    }
+}
+
+impl Visit for Analyzer {
+    fn visit_import_decl(&mut self, n: &ImportDecl) {
+        // namespace インポートはモジュール全体への依存
+        let names = if n.specifiers.iter().any(|s| matches!(s, ImportSpecifier::Namespace(_))) {
+            Vec::new()
+        } else {
+            n.specifiers
+                .iter()
+                .map(|spec| match spec {
+                    ImportSpecifier::Named(named) => named
+                        .imported
+                        .as_ref()
+                        .map(export_name)
+                        .unwrap_or_else(|| named.local.sym.to_string()),
+                    _ => "default".to_string(),
+                })
+                .collect()
+        };
+        // import type と、すべてのインポート名に type を付けたものはコンパイル後に消える
+        let type_only = n.type_only
+            || (!n.specifiers.is_empty() && n.specifiers.iter().all(|spec| matches!(spec, ImportSpecifier::Named(named) if named.is_type_only)));
+        self.add_source(&n.src.value, false, type_only, names, n.span);
+        self.import_decls.push((n.src.value.to_string(), n.span));
+        if n.specifiers.is_empty() && !n.type_only {
+            self.side_effect_imports.push((n.src.value.to_string(), n.span));
+        }
+        for spec in &n.specifiers {
+            let (local, name) = match spec {
+                ImportSpecifier::Named(named) => (
+                    &named.local,
+                    named
+                        .imported
+                        .as_ref()
+                        .map(export_name)
+                        .unwrap_or_else(|| named.local.sym.to_string()),
+                ),
+                ImportSpecifier::Default(def) => (&def.local, "default".to_string()),
+                ImportSpecifier::Namespace(ns) => (&ns.local, "*".to_string()),
+            };
+            self.bind(local, &n.src.value, &name, n.type_only || spec.is_type_only());
+        }
+        // import 文の中の識別子は使用回数に数えないので子ノードは辿らない
+    }
+
+    // import x = require('foo') (TypeScript の CommonJS 互換構文)
+    fn visit_ts_import_equals_decl(&mut self, n: &TsImportEqualsDecl) {
+        if let TsModuleRef::TsExternalModuleRef(module_ref) = &n.module_ref {
+            let module = module_ref.expr.value.to_string();
+            self.add_source(&module, false, n.is_type_only, Vec::new(), n.span);
+            self.bind(&n.id, &module, "*", n.is_type_only);
+        } else {
+            n.visit_children_with(self);
+        }
+    }
+
+    // const x = require('foo') は左辺をインポート名として扱い、左辺自体は import 宣言と同様に使用回数に数えない
+    fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+        let required = n.init.as_deref().and_then(|init| match init {
+            Expr::Call(call) => require_specifier(call),
+            _ => None,
+        });
+        match required {
+            Some(module) => {
+                self.bind_require_pat(&n.name, &module);
+                // 関数内の require もインポートとして数える (ブロックスコープの宣言扱いを外す)
+                let mut names = Vec::new();
+                scope::pat_names(&n.name, &mut names);
+                names.iter().for_each(|name| self.scopes.unshadow(name));
+                n.init.visit_with(self);
+            }
+            None => n.visit_children_with(self),
+        }
+    }
+
+    // export class X / export const x 等、そのファイルで定義してエクスポートしている名前
+    fn visit_export_decl(&mut self, n: &ExportDecl) {
+        let mut names = Vec::new();
+        match &n.decl {
+            Decl::Class(c) if c.class.is_abstract => {
+                self.exports.abstract_names.insert(c.ident.sym.to_string());
+                names.push(c.ident.sym.to_string());
+            }
+            Decl::Class(c) => names.push(c.ident.sym.to_string()),
+            Decl::Fn(f) => names.push(f.ident.sym.to_string()),
+            Decl::Var(var) => var.decls.iter().for_each(|d| scope::pat_names(&d.name, &mut names)),
+            Decl::TsInterface(i) => {
+                self.exports.abstract_names.insert(i.id.sym.to_string());
+                names.push(i.id.sym.to_string());
+            }
+            Decl::TsTypeAlias(t) => names.push(t.id.sym.to_string()),
+            Decl::TsEnum(e) => names.push(e.id.sym.to_string()),
+            Decl::TsModule(m) => {
+                if let TsModuleName::Ident(id) = &m.id {
+                    names.push(id.sym.to_string());
+                }
+            }
+            Decl::Using(_) => {}
+        }
+        self.export_spans.extend(names.iter().map(|name| (name.clone(), n.span)));
+        self.exports.local.extend(names);
+        n.visit_children_with(self);
+    }
+
+    fn visit_export_default_decl(&mut self, n: &ExportDefaultDecl) {
+        let is_abstract = match &n.decl {
+            DefaultDecl::Class(c) => c.class.is_abstract,
+            DefaultDecl::TsInterfaceDecl(_) => true,
+            DefaultDecl::Fn(_) => false,
+        };
+        if is_abstract {
+            self.exports.abstract_names.insert("default".into());
+        }
+        self.exports.local.insert("default".into());
+        self.export_spans.push(("default".into(), n.span));
+        n.visit_children_with(self);
+    }
+
+    fn visit_export_default_expr(&mut self, n: &ExportDefaultExpr) {
+        self.exports.local.insert("default".into());
+        self.export_spans.push(("default".into(), n.span));
+        n.visit_children_with(self);
+    }
+
+    // export * from / export { X } from もファイル間の依存として扱う
+    fn visit_export_all(&mut self, n: &ExportAll) {
+        self.add_source(&n.src.value, false, n.type_only, Vec::new(), n.span);
+        self.exports.re_exports.push(ReExport {
+            specifier: n.src.value.to_string(),
+            names: None,
+        });
+        n.visit_children_with(self);
+    }
+
+    fn visit_named_export(&mut self, n: &NamedExport) {
+        // (エクスポート名, 元の名前)
+        let names: Vec<(String, String)> = n
+            .specifiers
+            .iter()
+            .map(|spec| match spec {
+                ExportSpecifier::Named(named) => {
+                    let orig = export_name(&named.orig);
+                    let exported = named.exported.as_ref().map(export_name).unwrap_or_else(|| orig.clone());
+                    (exported, orig)
+                }
+                ExportSpecifier::Namespace(ns) => (export_name(&ns.name), "*".to_string()),
+                ExportSpecifier::Default(def) => (def.exported.sym.to_string(), "default".to_string()),
+            })
+            .collect();
+        match &n.src {
+            Some(src) => {
+                let referenced = if names.iter().any(|(_, orig)| orig == "*") {
+                    Vec::new()
+                } else {
+                    names.iter().map(|(_, orig)| orig.clone()).collect()
+                };
+                let type_only = n.type_only
+                    || (!n.specifiers.is_empty()
+                        && n.specifiers.iter().all(|spec| matches!(spec, ExportSpecifier::Named(named) if named.is_type_only)));
+                self.add_source(&src.value, false, type_only, referenced, n.span);
+                self.exports.re_exports.push(ReExport {
+                    specifier: src.value.to_string(),
+                    names: Some(names),
+                });
+            }
+            // import { X } from './x'; export { X } も再エクスポートとして扱う
+            None => {
+                for (exported, orig) in names {
+                    match self.imports.get(&orig) {
+                        Some(binding) => self.exports.re_exports.push(ReExport {
+                            specifier: binding.module.clone(),
+                            names: Some(vec![(exported, binding.export_name.clone())]),
+                        }),
+                        None => {
+                            self.export_spans.push((exported.clone(), n.span));
+                            self.exports.local.insert(exported);
+                        }
+                    }
+                }
+            }
+        }
+        n.visit_children_with(self);
+    }
+
+    // import('...') による動的インポート。指定子が静的に決まるものだけを記録する
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        if let Callee::Import(_) = n.callee
+            && let Some(specifier) = n.args.first().and_then(|arg| static_specifier(&arg.expr))
+        {
+            self.add_source(&specifier, true, false, Vec::new(), n.span);
+            self.dynamic_imports.push((specifier, n.span));
+        } else if let Some(specifier) = require_specifier(n) {
+            // 変数に代入しない require('foo') もファイル間の依存として扱う
+            self.add_source(&specifier, false, false, Vec::new(), n.span);
+        }
+        n.visit_children_with(self);
+    }
+
+    // 関数・ブロック等に入るたびに、そこで宣言されるローカル名をスコープとして積む
+    fn visit_function(&mut self, n: &Function) {
+        self.scopes.push(scope::function_scope(n.params.iter().map(|p| &p.pat), n.body.as_ref()));
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+        self.scopes.push(scope::arrow_scope(n));
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    // Angular の DI はコンストラクタ引数の型を実行時に参照するので、
+    // constructor(private http: HttpClient) の HttpClient は値としての使用に数える
+    fn visit_constructor(&mut self, n: &Constructor) {
+        self.scopes.push(scope::constructor_scope(n));
+        for param in &n.params {
+            let (decorators, type_ann, rest) = match param {
+                ParamOrTsParamProp::Param(p) => (&p.decorators, pat_type_ann(&p.pat), Some(&p.pat)),
+                ParamOrTsParamProp::TsParamProp(p) => (
+                    &p.decorators,
+                    match &p.param {
+                        TsParamPropParam::Ident(id) => id.type_ann.as_deref(),
+                        TsParamPropParam::Assign(assign) => pat_type_ann(&assign.left),
+                    },
+                    None,
+                ),
+            };
+            decorators.visit_with(self);
+            match type_ann.map(|ann| &*ann.type_ann) {
+                Some(TsType::TsTypeRef(type_ref)) => {
+                    self.visit_entity_as_value(&type_ref.type_name);
+                    type_ref.type_params.visit_with(self);
+                }
+                Some(other) => other.visit_with(self),
+                None => {}
+            }
+            // デフォルト値など型注釈以外の部分
+            match (param, rest) {
+                (_, Some(Pat::Assign(assign))) => assign.right.visit_with(self),
+                (ParamOrTsParamProp::TsParamProp(p), _) => {
+                    if let TsParamPropParam::Assign(assign) = &p.param {
+                        assign.right.visit_with(self);
+                    }
+                }
+                _ => {}
+            }
+        }
+        n.body.visit_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_ts_type(&mut self, n: &TsType) {
+        let prev = std::mem::replace(&mut self.in_type, true);
+        n.visit_children_with(self);
+        self.in_type = prev;
+    }
+
+    // class X implements OnInit / interface X extends Y
+    fn visit_ts_expr_with_type_args(&mut self, n: &TsExprWithTypeArgs) {
+        let prev = std::mem::replace(&mut self.in_type, true);
+        n.visit_children_with(self);
+        self.in_type = prev;
+    }
+
+    fn visit_block_stmt(&mut self, n: &BlockStmt) {
+        self.scopes.push(scope::block_scope(&n.stmts));
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_catch_clause(&mut self, n: &CatchClause) {
+        let mut names = Vec::new();
+        if let Some(param) = &n.param {
+            scope::pat_names(param, &mut names);
+        }
+        self.scopes.push(names.into_iter().collect());
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_for_stmt(&mut self, n: &ForStmt) {
+        let names = match &n.init {
+            Some(VarDeclOrExpr::VarDecl(var)) => scope::lexical_decl_names(var),
+            _ => Default::default(),
+        };
+        self.scopes.push(names);
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+        self.scopes.push(for_head_names(&n.left));
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+        self.scopes.push(for_head_names(&n.left));
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    // const f = function map() {} / class X {} 式の名前はその内側だけで有効
+    fn visit_fn_expr(&mut self, n: &FnExpr) {
+        self.scopes.push(n.ident.iter().map(|id| id.sym.to_string()).collect());
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_class_expr(&mut self, n: &ClassExpr) {
+        self.scopes.push(n.ident.iter().map(|id| id.sym.to_string()).collect());
+        n.visit_children_with(self);
+        self.scopes.pop();
+    }
+
+    // ラベル名・enum のメンバー名・interface のプロパティ名はインポートへの参照ではない
+    // (メンバー式のプロパティやオブジェクトリテラルのキーは IdentName なので visit_ident に来ない)
+    fn visit_labeled_stmt(&mut self, n: &LabeledStmt) {
+        n.body.visit_with(self);
+    }
+
+    fn visit_break_stmt(&mut self, _: &BreakStmt) {}
+
+    fn visit_continue_stmt(&mut self, _: &ContinueStmt) {}
+
+    fn visit_ts_enum_member(&mut self, n: &TsEnumMember) {
+        n.init.visit_with(self);
+    }
+
+    fn visit_ts_property_signature(&mut self, n: &TsPropertySignature) {
+        if n.computed {
+            n.key.visit_with(self);
+        }
+        n.type_ann.visit_with(self);
+    }
+
+    fn visit_ts_method_signature(&mut self, n: &TsMethodSignature) {
+        if n.computed {
+            n.key.visit_with(self);
+        }
+        n.params.visit_with(self);
+        n.type_ann.visit_with(self);
+        n.type_params.visit_with(self);
+    }
+
+    fn visit_ts_getter_signature(&mut self, n: &TsGetterSignature) {
+        if n.computed {
+            n.key.visit_with(self);
+        }
+        n.type_ann.visit_with(self);
+    }
+
+    fn visit_ts_setter_signature(&mut self, n: &TsSetterSignature) {
+        if n.computed {
+            n.key.visit_with(self);
+        }
+        n.param.visit_with(self);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        let key = ident.sym.to_string();
+        if self.imports.contains_key(&key) && !self.scopes.is_shadowed(&key) {
+            if self.in_type {
+                *self.type_usage.entry(key.clone()).or_insert(0) += 1;
+            }
+            self.usage_spans.entry(key.clone()).or_default().push(ident.span);
+            *self.usage.entry(key).or_insert(0) += 1;
+        }
+    }
+}
+
+// インポート名ごとの集計値
+#[derive(Default)]
+struct SymbolStats {
+    count: usize,
+    type_count: usize,
+    files: usize,
+    type_only_files: usize,
+}
+
+// パッケージごとの集計値
+#[derive(Default)]
+struct PackageStats {
+    count: usize,
+    files: usize,
+    symbols: BTreeSet<String>,
+}
+
+// 並列に解析するファイル数の単位。チャンクごとに走査順でマージ・書き出しする
+#[cfg(feature = "native")]
+const PARALLEL_CHUNK: usize = 256;
+
+// 常に除外するディレクトリ (パスにこれらを含むものは辿らない)
+fn is_excluded(path: &Path) -> bool {
+    let p = path.to_string_lossy();
+    p.contains("node_modules") || p.contains(".vscode") || p.contains(".angular") || p.contains(".git")
+}
+
+// 走査中にディレクトリから子孫のディレクトリへ引き継ぐ状態
+#[cfg(feature = "native")]
+#[derive(Debug, Clone, Default)]
+struct WalkState {
+    ignore: Ignore,
+    // --follow-symlinks のとき、ルートから読んでいるディレクトリまでの実体のパス (リンクのループ検出用)
+    ancestors: Vec<PathBuf>,
+}
+
+// 再帰的に解析対象の拡張子のファイルだけを走査 (.d.ts と、.gitignore / .ignore で無視されるものは除外)。
+// ディレクトリの読み込みと除外判定は jwalk で並列に行い、結果はファイル名順の深さ優先で返す
+#[cfg(feature = "native")]
+pub fn collect_source_files(target: &Path, config: &AnalyzerConfig) -> Result<Vec<PathBuf>> {
+    // ファイルを直接指定したときは --include / --exclude や無視ファイルによらず解析する
+    if target.is_file() {
+        return Ok(config.is_source_path(target).then(|| target.to_path_buf()).into_iter().collect());
+    }
+    // 走査スレッドから参照するので複製して渡す
+    let walk_config = config.clone();
+    let walk_target = target.to_path_buf();
+    let state = WalkState {
+        ignore: Ignore::new(target, &config.ignore_files, config.use_ignore_files)?,
+        ancestors: Vec::new(),
+    };
+    let paths: Vec<PathBuf> = WalkDirGeneric::<(WalkState, ())>::new(target)
+        .sort(true)
+        .skip_hidden(false)
+        .follow_links(config.follow_symlinks)
+        .max_depth(config.max_depth.unwrap_or(usize::MAX))
+        .root_read_dir_state(state)
+        .process_read_dir(move |depth, dir, state, children| {
+            // 読んだディレクトリの無視ファイルは子孫のディレクトリにも引き継がれる (depth が None なのはルート自体)
+            if depth.is_some() {
+                state.ignore = state.ignore.with_dir(dir);
+                if walk_config.follow_symlinks && let Ok(real) = fs::canonicalize(dir) {
+                    state.ancestors.push(real);
+                }
+            }
+            children.retain(|entry| {
+                entry.as_ref().is_ok_and(|e| {
+                    let path = e.path();
+                    let is_dir = e.file_type().is_dir();
+                    // 実体が祖先のディレクトリと同じなら、リンクを辿ってループしている
+                    let is_loop = walk_config.follow_symlinks
+                        && is_dir
+                        && fs::canonicalize(&path).is_ok_and(|real| state.ancestors.contains(&real));
+                    !is_loop && !walk_config.is_excluded(&walk_target, &path) && !state.ignore.is_ignored(&path, is_dir)
+                })
+            });
+        })
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let path = e.path();
+            e.file_type().is_file() && config.is_source_path(&path) && config.is_included(target, &path)
+        })
+        .map(|e| e.path())
+        .collect();
+    if !config.follow_symlinks {
+        return Ok(paths);
+    }
+    // 別の経路から同じ実体に辿り着いたファイルは、走査順で最初のパスだけを残す
+    let mut seen = HashSet::new();
+    Ok(paths
+        .into_iter()
+        .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .collect())
+}
+
+// 複数の解析対象を指定順に走査する。重なっている解析対象に含まれるファイルは最初の 1 回だけ返す
+#[cfg(feature = "native")]
+pub fn collect_source_files_in(targets: &[PathBuf], config: &AnalyzerConfig) -> Result<Vec<PathBuf>> {
+    if let [target] = targets {
+        return collect_source_files(target, config);
+    }
+    let mut seen = HashSet::new();
+    let mut paths = Vec::new();
+    for target in targets {
+        for path in collect_source_files(target, config)? {
+            // ./src/a.ts と src/a.ts のように書き方が違っても同じファイルとみなす
+            let key = fs::canonicalize(&path).unwrap_or_else(|_| resolve::normalize(&path));
+            if seen.insert(key) {
+                paths.push(path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+// 1 ファイル分の解析結果。ファイルは並列に解析し、結果は走査順にマージして出力を決定的に保つ
+// (キャッシュにもこの形で保存する)
+#[derive(Clone, Serialize, Deserialize)]
+pub enum FileOutcome {
+    Parsed(Box<FileAnalysis>),
+    Failed {
+        path: PathBuf,
+        size: u64,
+        failure: ParseFailure,
+    },
+    // サイズの上限を超えた・生成された・文字コードを判別できないファイルとして解析しなかった (キャッシュには保存しない)
+    Skipped { path: PathBuf, skipped: SkippedFile },
+}
+
+impl FileOutcome {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Parsed(analysis) => &analysis.path,
+            Self::Failed { path, .. } | Self::Skipped { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FileAnalysis {
+    pub path: PathBuf,
+    pub size: u64,
+    pub sources: Vec<ModuleRef>,
+    pub exports: ModuleExports,
+    pub unused: Vec<UnusedImport>,
+    pub dynamic: Vec<DynamicImport>,
+    pub side_effects: Vec<SideEffectImport>,
+    pub duplicates: Vec<DuplicateImport>,
+    pub entry: FileEntry,
+    pub counts: Vec<SymbolCount>,
+    // パースエラーがあり、エラーの箇所を除いて解析した
+    pub partial: Option<PartialFile>,
+}
+
+// ファイル 1 つ分の、インポート名ごとの集計
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SymbolCount {
+    pub module: String,
+    pub name: String,
+    pub count: usize,
+    // 型の位置での使用回数
+    pub type_count: usize,
+    // すべて import type で取り込まれているか
+    pub type_only: bool,
+}
+
+// ファイルを読み込んで解析する。内容が前回と同じならキャッシュの結果を使う。
+// config.max_file_size を超えるファイルは読み込まずに、生成されたファイルはパースせずに飛ばす
+#[cfg(feature = "native")]
+pub fn analyze_file(path: &Path, config: &AnalyzerConfig, cache: Option<&Cache>) -> Result<FileOutcome> {
+    if let Some(limit) = config.max_file_size {
+        let size = fs::metadata(path)?.len();
+        if size > limit {
+            return Ok(skipped(path, size, SkipReason::TooLarge));
+        }
+    }
+    let bytes = fs::read(path)?;
+    let Some(src) = encoding::decode(&bytes) else {
+        return Ok(skipped(path, bytes.len() as u64, SkipReason::Undecodable));
+    };
+    if let Some(reason) = skip_reason(&src, config) {
+        return Ok(skipped(path, src.len() as u64, reason));
+    }
+    let Some(cache) = cache else {
+        return Ok(analyze_source(path, src));
+    };
+    let hash = cache::content_hash(&src);
+    if let Some(outcome) = cache.get(path, hash) {
+        return Ok(outcome);
+    }
+    let outcome = analyze_source(path, src);
+    if let Err(err) = cache.put(path, hash, &outcome) {
+        eprintln!("⚠️ キャッシュに書き込めません: {}: {err}", path.display());
+    }
+    Ok(outcome)
+}
+
+// 生成されたファイルの先頭のコメントに書かれる目印 (小文字で比べる)
+const GENERATED_MARKERS: [&str; 5] = ["@generated", "autogenerated", "auto-generated", "code generated by", "do not edit"];
+
+// 生成されたファイルの目印を探す先頭の行数
+const GENERATED_HEADER_LINES: usize = 20;
+
+// 解析せずに飛ばすファイルならその理由
+fn skip_reason(src: &str, config: &AnalyzerConfig) -> Option<SkipReason> {
+    if config.max_file_size.is_some_and(|limit| src.len() as u64 > limit) {
+        return Some(SkipReason::TooLarge);
+    }
+    (config.skip_generated && is_generated(src)).then_some(SkipReason::Generated)
+}
+
+// 先頭のコメント (最初のコードの行より前) に生成されたファイルの目印があるか
+fn is_generated(src: &str) -> bool {
+    src.trim_start_matches('\u{feff}')
+        .lines()
+        .take(GENERATED_HEADER_LINES)
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with("//") || line.starts_with("/*") || line.starts_with('*'))
+        .any(|line| {
+            let line = line.to_lowercase();
+            GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+        })
+}
+
+fn skipped(path: &Path, size: u64, reason: SkipReason) -> FileOutcome {
+    FileOutcome::Skipped {
+        path: path.to_path_buf(),
+        skipped: SkippedFile {
+            path: path.display().to_string(),
+            size,
+            reason,
+        },
+    }
+}
+
+// ソースをパースし、インポートと使用箇所を集計する
+pub fn analyze_source(path: &Path, src: String) -> FileOutcome {
+    // SourceMap はスレッド間で共有できないのでファイルごとに作る
+    let cm: Lrc<SourceMap> = Default::default();
+
+    // SourceFile化
+    let size = src.len() as u64;
+    let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), src);
+
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let syntax = syntax_for(ext);
+
+    // パースに失敗したら、エラーの箇所を除いて解析し直す。それでも駄目ならスキップして次へ
+    let (module, recovery) = match parse(&fm, syntax, ext) {
+        Ok(m) => (m, None),
+        Err(err) => {
+            let loc = cm.lookup_char_pos(err.span().lo);
+            let failure = ParseFailure {
+                path: path.display().to_string(),
+                message: err.kind().msg().to_string(),
+                kind: error_kind(err.kind()),
+                line: loc.line,
+                column: loc.col.0 + 1,
+                snippet: snippet(&fm.src, loc.line),
+            };
+            match recover(&cm, path, &fm.src, syntax, ext, loc.line) {
+                Some((module, recovery)) => (module, Some((recovery, failure))),
+                None => {
+                    return FileOutcome::Failed {
+                        path: path.to_path_buf(),
+                        size,
+                        failure,
+                    };
+                }
+            }
+        }
+    };
+
+    // AST をトラバースして imports と usage を収集
+    let mut analyzer = Analyzer::new();
+    module.visit_with(&mut analyzer);
+
+    // 一度も使われていなければ未使用 (一部だけ解析したファイルでは、除いた箇所で使われているかもしれないので判定しない)
+    let mut unused: Vec<_> = analyzer
+        .imports
+        .iter()
+        .filter(|(name, _)| recovery.is_none() && !analyzer.usage.contains_key(*name))
+        .map(|(name, binding)| {
+            let loc = cm.lookup_char_pos(binding.span.lo);
+            UnusedImport {
+                path: path.display().to_string(),
+                name: name.clone(),
+                module: binding.module.clone(),
+                line: loc.line,
+                column: loc.col.0 + 1,
+            }
+        })
+        .collect();
+    unused.sort_by_key(|u| (u.line, u.column));
+
+    let dynamic = analyzer
+        .dynamic_imports
+        .iter()
+        .map(|(module, span)| {
+            let loc = cm.lookup_char_pos(span.lo);
+            DynamicImport {
+                path: path.display().to_string(),
+                module: module.clone(),
+                line: loc.line,
+                column: loc.col.0 + 1,
+            }
+        })
+        .collect();
+
+    let side_effects = analyzer
+        .side_effect_imports
+        .iter()
+        .map(|(module, span)| SideEffectImport {
+            path: path.display().to_string(),
+            module: module.clone(),
+            line: cm.lookup_char_pos(span.lo).line,
+        })
+        .collect();
+
+    // 同じモジュールを別々の import 文で読み込んでいるもの
+    let mut decl_lines: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (module, span) in &analyzer.import_decls {
+        decl_lines
+            .entry(module.as_str())
+            .or_default()
+            .push(cm.lookup_char_pos(span.lo).line);
+    }
+    let duplicates = decl_lines
+        .into_iter()
+        .filter(|(_, lines)| lines.len() > 1)
+        .map(|(module, lines)| DuplicateImport {
+            path: path.display().to_string(),
+            module: module.to_string(),
+            lines,
+        })
+        .collect();
+
+    // ファイル単位の内訳 (HTML のドリルダウン等で使用)
+    let mut file_imports: Vec<_> = analyzer
+        .imports
+        .iter()
+        .map(|(local, binding)| FileImport {
+            name: binding.imported.clone(),
+            alias: (*local != binding.imported).then(|| local.clone()),
+            module: binding.module.clone(),
+            count: analyzer.usage.get(local).copied().unwrap_or(0),
+            type_count: analyzer.type_usage.get(local).copied().unwrap_or(0),
+            locations: analyzer
+                .usage_spans
+                .get(local)
+                .into_iter()
+                .flatten()
+                .map(|span| {
+                    let loc = cm.lookup_char_pos(span.lo);
+                    Location {
+                        line: loc.line,
+                        column: loc.col.0 + 1,
+                    }
+                })
+                .collect(),
+            type_only: binding.type_only,
+        })
+        .collect();
+    file_imports.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.alias.cmp(&b.alias))
+    });
+
+    // エイリアスは元のエクスポート名にまとめる (同じ名前を別名で 2 回インポートしても 1 ファイルと数える)
+    // 一度も使われていないインポートも 0 回として集計に含める
+    let mut counts: BTreeMap<(String, String), (usize, usize, bool)> = BTreeMap::new();
+    for (local, binding) in &analyzer.imports {
+        let entry = counts
+            .entry((binding.module.clone(), binding.imported.clone()))
+            .or_insert((0, 0, true));
+        entry.0 += analyzer.usage.get(local).copied().unwrap_or(0);
+        entry.1 += analyzer.type_usage.get(local).copied().unwrap_or(0);
+        entry.2 &= binding.type_only;
+    }
+
+    FileOutcome::Parsed(Box::new(FileAnalysis {
+        path: path.to_path_buf(),
+        size,
+        sources: analyzer
+            .sources
+            .into_iter()
+            .map(|(mut source, span)| {
+                let loc = cm.lookup_char_pos(span.lo);
+                source.line = loc.line;
+                source.column = loc.col.0 + 1;
+                source
+            })
+            .collect(),
+        exports: ModuleExports {
+            // 同じ名前 (関数のオーバーロード等) は最初の宣言の位置にする
+            locations: analyzer
+                .export_spans
+                .into_iter()
+                .rev()
+                .map(|(name, span)| {
+                    let loc = cm.lookup_char_pos(span.lo);
+                    (name, (loc.line, loc.col.0 + 1))
+                })
+                .collect(),
+            ..analyzer.exports
+        },
+        unused,
+        dynamic,
+        side_effects,
+        duplicates,
+        entry: FileEntry {
+            path: path.display().to_string(),
+            imports: file_imports,
+            partial: recovery.is_some(),
+        },
+        counts: counts
+            .into_iter()
+            .map(|((module, name), (count, type_count, type_only))| SymbolCount {
+                module,
+                name,
+                count,
+                type_count,
+                type_only,
+            })
+            .collect(),
+        partial: recovery.map(|(recovery, failure)| PartialFile {
+            failure,
+            imports_only: recovery == Recovery::ImportsOnly,
+        }),
+    }))
+}
+
+// パースエラーから立て直したときの、解析できた範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Recovery {
+    // エラーのあった行を除いた残り全体
+    Lines,
+    // import / export ... from 宣言だけ (使用回数は数えられない)
+    ImportsOnly,
+}
+
+// エラーの行を空白にして (他の行の位置は変えずに) パースし直す回数の上限
+const MAX_RECOVERY_ATTEMPTS: usize = 10;
+
+// エラーの行を空白にしてパースし直すのを繰り返し、それでも駄目なら import 宣言だけを取り出してパースする
+fn recover(
+    cm: &Lrc<SourceMap>,
+    path: &Path,
+    src: &str,
+    syntax: Syntax,
+    ext: &str,
+    first_error_line: usize,
+) -> Option<(Program, Recovery)> {
+    let mut lines: Vec<String> = src.split_inclusive('\n').map(str::to_string).collect();
+    let mut error_line = first_error_line;
+    for _ in 0..MAX_RECOVERY_ATTEMPTS {
+        let line = lines.get_mut(error_line.checked_sub(1)?)?;
+        if line.trim().is_empty() {
+            break;
+        }
+        *line = blank(line);
+        let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), lines.concat());
+        match parse(&fm, syntax, ext) {
+            Ok(module) => return Some((module, Recovery::Lines)),
+            Err(err) => error_line = cm.lookup_char_pos(err.span().lo).line,
+        }
+    }
+
+    let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), imports_only(src));
+    let module = parse(&fm, syntax, ext).ok()?;
+    Some((module, Recovery::ImportsOnly))
+}
+
+// 拡張子ごとの構文。TypeScript は .tsx だけ、JavaScript は .js / .jsx とも JSX を許す
+fn syntax_for(ext: &str) -> Syntax {
+    match ext {
+        "js" | "jsx" | "mjs" | "cjs" => Syntax::Es(EsSyntax {
+            jsx: matches!(ext, "js" | "jsx"),
+            decorators: true,
+            ..Default::default()
+        }),
+        _ => Syntax::Typescript(TsSyntax {
+            tsx: ext == "tsx",
+            decorators: true, // Angular の @Component 等を許可
+            ..Default::default()
+        }),
+    }
+}
+
+// .ts / .tsx / .mts / .mjs は ES モジュールとして、それ以外 (.js / .cjs / .cts 等) は import / export が
+// あればモジュール、無ければ CommonJS のスクリプトとしてパースする
+fn parse(fm: &SourceFile, syntax: Syntax, ext: &str) -> swc_ecma_parser::PResult<Program> {
+    let mut parser = Parser::new(syntax, StringInput::from(fm), None);
+    match ext {
+        "ts" | "tsx" | "mts" | "mjs" => parser.parse_module().map(Program::Module),
+        _ => parser.parse_program(),
+    }
+}
+
+// 改行以外を空白にする
+fn blank(line: &str) -> String {
+    line.chars().map(|c| if c == '\n' || c == '\r' { c } else { ' ' }).collect()
+}
+
+// import 宣言と export ... from 宣言 (複数行にわたるものも) の行だけを残し、他は空白にする
+fn imports_only(src: &str) -> String {
+    let mut kept = String::with_capacity(src.len());
+    let mut in_decl = false;
+    for line in src.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let starts_decl = (trimmed.starts_with("import") && !trimmed.starts_with("import("))
+            || trimmed.starts_with("export {")
+            || trimmed.starts_with("export *")
+            || trimmed.starts_with("export type {");
+        if !in_decl && starts_decl {
+            in_decl = true;
+        }
+        if !in_decl {
+            kept.push_str(&blank(line));
+            continue;
+        }
+        kept.push_str(line);
+        let has_specifier = trimmed.contains("from '") || trimmed.contains("from \"");
+        let side_effect = trimmed.starts_with("import '") || trimmed.starts_with("import \"");
+        if has_specifier || side_effect || trimmed.ends_with(';') {
+            in_decl = false;
+        }
+    }
+    kept
+}
+
+// SyntaxError のバリアント名 (Unexpected { got, expected } -> "Unexpected")
+fn error_kind(kind: &impl std::fmt::Debug) -> String {
+    let debug = format!("{kind:?}");
+    let end = debug.find([' ', '(', '{']).unwrap_or(debug.len());
+    debug[..end].to_string()
+}
+
+// エラー位置の行 (長い行は SNIPPET_MAX_CHARS 文字で切る)
+const SNIPPET_MAX_CHARS: usize = 200;
+
+fn snippet(src: &str, line: usize) -> String {
+    let text = src.lines().nth(line.saturating_sub(1)).unwrap_or("");
+    text.chars().take(SNIPPET_MAX_CHARS).collect()
+}
+
+// 解析対象に共通する親ディレクトリ。解析対象が 1 つなら指定どおりの形のまま
+// (ファイルを指定したときはそのファイルのあるディレクトリ)
+fn common_root(targets: &[PathBuf]) -> PathBuf {
+    let dirs: Vec<&Path> = targets
+        .iter()
+        .map(|target| match target.is_file() {
+            true => target.parent().unwrap_or(Path::new("")),
+            false => target.as_path(),
+        })
+        .collect();
+    if let [dir] = dirs[..] {
+        return dir.to_path_buf();
+    }
+    let mut dirs = dirs.into_iter().map(resolve::normalize);
+    let Some(mut root) = dirs.next() else {
+        return PathBuf::from(".");
+    };
+    for dir in dirs {
+        while !dir.starts_with(&root) {
+            if !root.pop() {
+                break;
+            }
+        }
+    }
+    if root.as_os_str().is_empty() {
+        root.push(".");
+    }
+    root
+}
+
+// 走査したファイルの解析結果を、走査順に積み上げた全体の集計
+pub struct Aggregate {
+    // 依存グラフとレポートの基準になるディレクトリ (解析対象が複数なら共通の親)
+    root: PathBuf,
+    // 解析対象 (正規化したパス) ごとの小計
+    targets: Vec<(PathBuf, TargetSummary)>,
+    sort: SortKey,
+    reverse: bool,
+    keep_files: bool,
+    rules: BTreeMap<String, RuleLevel>,
+    policy: Option<Policy>,
+    aliases: PathAliases,
+    manifest: Option<Manifest>,
+    entry_points: Vec<Glob>,
+    check_orphans: bool,
+    check_unused_exports: bool,
+    check_cycles: bool,
+    check_coupling: bool,
+    // インポートされている npm パッケージ (package.json との照合用)
+    imported_packages: HashSet<String>,
+    dependency_issues: Vec<DependencyIssue>,
+    // 予算を持つパッケージを最初にインポートしている箇所
+    budget_sites: HashMap<String, ImportSite>,
+    // 同名でもインポート元が違えば別物として (モジュール, インポート名) で集計する
+    global_counts: HashMap<(String, String), SymbolStats>,
+    package_counts: HashMap<String, PackageStats>,
+    files_analyzed: usize,
+    // import / export ... from / import() の数
+    import_declarations: usize,
+    parse_failures: Vec<ParseFailure>,
+    partial_files: Vec<PartialFile>,
+    skipped_files: Vec<SkippedFile>,
+    unused_imports: Vec<UnusedImport>,
+    duplicate_imports: Vec<DuplicateImport>,
+    dynamic_imports: Vec<DynamicImport>,
+    side_effect_imports: Vec<SideEffectImport>,
+    policy_violations: Vec<PolicyViolation>,
+    files: Vec<FileEntry>,
+    // 依存グラフ用: 走査した全ソースファイルと、ファイルごとのモジュール指定子
+    source_files: HashSet<PathBuf>,
+    file_sizes: HashMap<PathBuf, u64>,
+    file_sources: Vec<(PathBuf, Vec<ModuleRef>)>,
+    file_exports: HashMap<PathBuf, ModuleExports>,
+}
+
+impl Aggregate {
+    pub fn new(target: &Path, config: &AnalyzerConfig) -> Self {
+        Self::with_targets(&[target.to_path_buf()], config)
+    }
+
+    // 複数の解析対象の結果をまとめる。各ファイルは、それを含む最初の解析対象の小計に数える
+    pub fn with_targets(targets: &[PathBuf], config: &AnalyzerConfig) -> Self {
+        Self {
+            root: common_root(targets),
+            targets: targets
+                .iter()
+                .map(|target| {
+                    let summary = TargetSummary {
+                        target: target.display().to_string(),
+                        ..Default::default()
+                    };
+                    (resolve::normalize(target), summary)
+                })
+                .collect(),
+            sort: config.sort,
+            reverse: config.reverse,
+            rules: config.rules.clone(),
+            policy: config.policy.clone().filter(|policy| !policy.is_empty()),
+            aliases: config.path_aliases.clone(),
+            manifest: config.manifest.clone(),
+            entry_points: config.entry_points.clone(),
+            check_orphans: config.check_orphans,
+            check_unused_exports: config.check_unused_exports,
+            check_cycles: config.check_cycles,
+            check_coupling: config.check_coupling,
+            imported_packages: HashSet::new(),
+            dependency_issues: Vec::new(),
+            budget_sites: HashMap::new(),
+            keep_files: config.keep_files,
+            global_counts: HashMap::new(),
+            package_counts: HashMap::new(),
+            files_analyzed: 0,
+            import_declarations: 0,
+            parse_failures: Vec::new(),
+            partial_files: Vec::new(),
+            skipped_files: Vec::new(),
+            unused_imports: Vec::new(),
+            duplicate_imports: Vec::new(),
+            dynamic_imports: Vec::new(),
+            side_effect_imports: Vec::new(),
+            policy_violations: Vec::new(),
+            files: Vec::new(),
+            source_files: HashSet::new(),
+            file_sizes: HashMap::new(),
+            file_sources: Vec::new(),
+            file_exports: HashMap::new(),
+        }
+    }
+
+    // 1 ファイル分の結果をマージする
+    pub fn add(&mut self, outcome: FileOutcome) {
+        let path = resolve::normalize(outcome.path());
+        if let Some((_, summary)) = self.targets.iter_mut().find(|(target, _)| path.starts_with(target)) {
+            match &outcome {
+                FileOutcome::Parsed(analysis) => {
+                    summary.files_analyzed += 1;
+                    summary.partial_files += usize::from(analysis.partial.is_some());
+                    summary.usages += analysis.counts.iter().map(|c| c.count).sum::<usize>();
+                    summary.unused_imports += analysis.unused.len();
+                    summary.duplicate_imports += analysis.duplicates.len();
+                }
+                FileOutcome::Failed { .. } => summary.parse_failures += 1,
+                FileOutcome::Skipped { .. } => summary.skipped_files += 1,
+            }
+        }
+
+        // 解析できなかったファイルも、依存グラフではインポート先として解決できるようにする
+        let analysis = match outcome {
+            FileOutcome::Parsed(analysis) => analysis,
+            FileOutcome::Failed { path, size, failure, .. } => {
+                self.source_files.insert(resolve::normalize(&path));
+                self.file_sizes.insert(resolve::normalize(&path), size);
+                self.parse_failures.push(failure);
+                return;
+            }
+            FileOutcome::Skipped { path, skipped } => {
+                self.source_files.insert(resolve::normalize(&path));
+                self.file_sizes.insert(resolve::normalize(&path), skipped.size);
+                self.skipped_files.push(skipped);
+                return;
+            }
+        };
+        let FileAnalysis {
+            path,
+            size,
+            sources,
+            exports,
+            unused,
+            dynamic,
+            side_effects,
+            duplicates,
+            entry,
+            counts,
+            partial,
+        } = *analysis;
+
+        self.files_analyzed += 1;
+        self.import_declarations += sources.len();
+        self.partial_files.extend(partial);
+        if let Some(policy) = &self.policy {
+            let display = path.display().to_string();
+            // paths / baseUrl で解決するインポートはパッケージではない
+            let external: Vec<ModuleRef> =
+                sources.iter().filter(|s| !self.aliases.is_local(&s.specifier)).cloned().collect();
+            self.policy_violations.extend(policy.check_file(&display, &external));
+            policy.record_sites(&display, &external, &mut self.budget_sites);
+        }
+        if let Some(manifest) = &self.manifest {
+            let external: Vec<ModuleRef> =
+                sources.iter().filter(|s| !self.aliases.is_local(&s.specifier)).cloned().collect();
+            for source in &external {
+                if let Some(package) = resolve::package_name(&source.specifier) {
+                    self.imported_packages.insert(package.to_string());
+                }
+            }
+            let relative = resolve::relative_display(&self.root, &path);
+            self.dependency_issues.extend(manifest.check_imports(&path.display().to_string(), &relative, &external));
+        }
+        self.source_files.insert(resolve::normalize(&path));
+        self.file_sizes.insert(resolve::normalize(&path), size);
+        self.file_exports.insert(resolve::normalize(&path), exports);
+        self.file_sources.push((path, sources));
+        self.unused_imports.extend(unused);
+        self.dynamic_imports.extend(dynamic);
+        self.side_effect_imports.extend(side_effects);
+        self.duplicate_imports.extend(duplicates);
+
+        if self.keep_files {
+            self.files.push(entry);
+        }
+
+        // ファイルごとの結果をグローバル集計へマージ
+        let mut file_packages = HashSet::new();
+        for SymbolCount { module, name, count, type_count, type_only } in counts {
+            if let Some(package) = resolve::package_name(&module).filter(|_| !self.aliases.is_local(&module)) {
+                let stats = self.package_counts.entry(package.to_string()).or_default();
+                stats.count += count;
+                stats.symbols.insert(name.clone());
+                if file_packages.insert(package.to_string()) {
+                    stats.files += 1;
+                }
+            }
+            let stats = self.global_counts.entry((module, name)).or_default();
+            stats.count += count;
+            stats.type_count += type_count;
+            stats.files += 1;
+            if type_only {
+                stats.type_only_files += 1;
+            }
+        }
+    }
+
+    // 集計を並べ替えてレポートと依存グラフにする
+    pub fn finish(self) -> ProjectAnalysis {
+        let graph = DependencyGraph::build(
+            &self.root,
+            &self.source_files,
+            &self.file_sizes,
+            &self.file_sources,
+            &self.file_exports,
+            &self.aliases,
+        );
+
+        // 最終結果を --sort の指標で降順ソート (同数ならもう一方の指標、さらに名前順で出力を安定させる)
+        let mut usages: Vec<_> = self
+            .global_counts
+            .into_iter()
+            .map(|((module, name), stats)| UsageEntry {
+                name,
+                module,
+                count: stats.count,
+                type_count: stats.type_count,
+                files: stats.files,
+                type_only_files: stats.type_only_files,
+            })
+            .collect();
+        let (sort, reverse) = (self.sort, self.reverse);
+        // --reverse では同順位の並びも含めて逆にする
+        let directed = |order: Ordering| if reverse { order.reverse() } else { order };
+        usages.sort_by(|a, b| {
+            let primary = match sort {
+                SortKey::Count => b.count.cmp(&a.count).then(b.files.cmp(&a.files)),
+                SortKey::Files => b.files.cmp(&a.files).then(b.count.cmp(&a.count)),
+                SortKey::Name => a.name.cmp(&b.name),
+                SortKey::Module => Ordering::Equal,
+            };
+            directed(primary.then_with(|| a.module.cmp(&b.module)).then_with(|| a.name.cmp(&b.name)))
+        });
+
+        let mut packages: Vec<_> = self
+            .package_counts
+            .into_iter()
+            .map(|(package, stats)| PackageUsage {
+                package,
+                count: stats.count,
+                files: stats.files,
+                symbols: stats.symbols.into_iter().collect(),
+            })
+            .collect();
+        packages.sort_by(|a, b| {
+            let primary = match sort {
+                SortKey::Count => b.count.cmp(&a.count).then(b.files.cmp(&a.files)),
+                SortKey::Files => b.files.cmp(&a.files).then(b.count.cmp(&a.count)),
+                // パッケージ単位の集計ではインポート名もモジュールもパッケージ名で並べる
+                SortKey::Name | SortKey::Module => Ordering::Equal,
+            };
+            directed(primary.then_with(|| a.package.cmp(&b.package)))
+        });
+
+        let mut policy_violations = self.policy_violations;
+        if let Some(policy) = &self.policy {
+            policy_violations.extend(policy.check_budgets(&packages, &self.budget_sites));
+            policy_violations.extend(policy.check_resolved(
+                &self.root,
+                &self.source_files,
+                &self.file_sources,
+                &self.aliases,
+            ));
+        }
+        policy_violations.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+        // ファイルごとの指摘は位置の順に、package.json の指摘はその後ろに種類ごとにまとめる
+        let mut dependency_issues = self.dependency_issues;
+        dependency_issues.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+        if let Some(manifest) = &self.manifest {
+            dependency_issues.extend(manifest.unused_dependencies(&self.imported_packages));
+            dependency_issues.extend(manifest.angular_version_skew(&packages));
+            dependency_issues.extend(manifest.unmet_peers(&packages, &self.imported_packages));
+        }
+
+        let is_entry = |relative: &str| self.entry_points.iter().any(|glob| glob.matches_path(relative));
+        let orphan_files = match self.check_orphans {
+            true => graph::unreachable_files(&self.root, &self.source_files, &self.file_sources, &self.aliases, is_entry)
+                .into_iter()
+                .map(|path| OrphanFile {
+                    size: self.file_sizes.get(&resolve::normalize(&path)).copied().unwrap_or(0),
+                    path: path.display().to_string(),
+                })
+                .collect(),
+            false => Vec::new(),
+        };
+        let mut unused_exports = match self.check_unused_exports {
+            true => graph::unused_exports(
+                &self.root,
+                &self.source_files,
+                &self.file_sources,
+                &self.file_exports,
+                &self.aliases,
+                is_entry,
+            )
+            .into_iter()
+            .map(|(path, name)| {
+                let (line, column) = self
+                    .file_exports
+                    .get(&resolve::normalize(&path))
+                    .and_then(|exports| exports.locations.get(&name).copied())
+                    .unwrap_or((1, 1));
+                UnusedExport {
+                    path: path.display().to_string(),
+                    name,
+                    line,
+                    column,
+                }
+            })
+            .collect(),
+            false => Vec::new(),
+        };
+        unused_exports.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+        let cycles = match self.check_cycles {
+            true => {
+                let sources: HashMap<PathBuf, &[ModuleRef]> = self
+                    .file_sources
+                    .iter()
+                    .map(|(path, sources)| (resolve::normalize(path), sources.as_slice()))
+                    .collect();
+                let display = |id: &String| self.root.join(id).display().to_string();
+                graph
+                    .cycles()
+                    .into_iter()
+                    .map(|files| {
+                        let chain = graph.cycle_chain(&files);
+                        let location = match chain.as_slice() {
+                            [from, to, ..] => {
+                                let from = resolve::normalize(&self.root.join(from));
+                                let to = resolve::normalize(&self.root.join(to));
+                                sources.get(&from).and_then(|sources| {
+                                    let (files, exports) = (&self.source_files, &self.file_exports);
+                                    graph::import_location(&from, &to, sources, files, exports, &self.aliases)
+                                })
+                            }
+                            _ => None,
+                        };
+                        let (line, column) = location.unwrap_or((1, 1));
+                        Cycle {
+                            files: files.iter().map(display).collect(),
+                            chain: chain.iter().map(display).collect(),
+                            line,
+                            column,
+                        }
+                    })
+                    .collect()
+            }
+            false => Vec::new(),
+        };
+        // fan-in の多い順 (同じなら fan-out の多い順、パスの順)
+        let (mut file_coupling, mut directory_coupling) = match self.check_coupling {
+            true => {
+                let (fan_in, fan_out) = (graph.fan_in(), graph.fan_out());
+                // フォルダごとの (エクスポートの数, そのうち interface・abstract class の数)
+                let mut exports: HashMap<String, (usize, usize)> = HashMap::new();
+                for node in &graph.nodes {
+                    if let Some(file) = self.file_exports.get(&resolve::normalize(&self.root.join(&node.id))) {
+                        let counts = exports.entry(graph::directory_of(&node.id)).or_default();
+                        counts.0 += file.local.len();
+                        counts.1 += file.abstract_names.len();
+                    }
+                }
+                let files: Vec<FileCoupling> = graph
+                    .nodes
+                    .iter()
+                    .map(|node| FileCoupling {
+                        path: self.root.join(&node.id).display().to_string(),
+                        fan_in: fan_in[node.id.as_str()],
+                        fan_out: fan_out[node.id.as_str()],
+                    })
+                    .collect();
+                let directories: Vec<DirectoryCoupling> = graph
+                    .directory_coupling()
+                    .into_iter()
+                    .map(|(directory, (files, fan_in, fan_out))| {
+                        let (exports, abstract_exports) = exports.get(&directory).copied().unwrap_or_default();
+                        let instability = match fan_in + fan_out {
+                            0 => 0.0,
+                            total => fan_out as f64 / total as f64,
+                        };
+                        let abstractness = match exports {
+                            0 => 0.0,
+                            exports => abstract_exports as f64 / exports as f64,
+                        };
+                        DirectoryCoupling {
+                            path: match directory.as_str() {
+                                "." => self.root.display().to_string(),
+                                directory => self.root.join(directory).display().to_string(),
+                            },
+                            files,
+                            fan_in,
+                            fan_out,
+                            exports,
+                            abstract_exports,
+                            instability,
+                            abstractness,
+                            distance: (abstractness + instability - 1.0).abs(),
+                        }
+                    })
+                    .collect();
+                (files, directories)
+            }
+            false => (Vec::new(), Vec::new()),
+        };
+        file_coupling.sort_by(|a, b| (b.fan_in, b.fan_out, &a.path).cmp(&(a.fan_in, a.fan_out, &b.path)));
+        directory_coupling.sort_by(|a, b| (b.fan_in, b.fan_out, &a.path).cmp(&(a.fan_in, a.fan_out, &b.path)));
+
+        let files_skipped = [SkipReason::TooLarge, SkipReason::Generated, SkipReason::Undecodable]
+            .into_iter()
+            .map(|reason| SkipCount {
+                reason,
+                files: self.skipped_files.iter().filter(|skipped| skipped.reason == reason).count(),
+            })
+            .filter(|count| count.files > 0)
+            .collect();
+        let summary = RunSummary {
+            files_scanned: self.files_analyzed + self.parse_failures.len() + self.skipped_files.len(),
+            files_skipped,
+            import_declarations: self.import_declarations,
+            distinct_packages: packages.len(),
+            elapsed_ms: None,
+            cache: None,
+        };
+        let report = Report {
+            schema_version: report::SCHEMA_VERSION,
+            target: self.root.display().to_string(),
+            summary,
+            files_analyzed: self.files_analyzed,
+            parse_failures: self.parse_failures,
+            partial_files: self.partial_files,
+            skipped_files: self.skipped_files,
+            usages,
+            packages,
+            unused_imports: self.unused_imports,
+            duplicate_imports: self.duplicate_imports,
+            dynamic_imports: self.dynamic_imports,
+            side_effect_imports: self.side_effect_imports,
+            policy_violations,
+            dependency_issues,
+            orphan_files,
+            unused_exports,
+            cycles,
+            file_coupling,
+            directory_coupling,
+            files: self.files,
+            targets: self.targets.into_iter().map(|(_, summary)| summary).collect(),
+            rule_levels: self.rules,
+        };
+        ProjectAnalysis { report, graph }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(src: &str) -> FileAnalysis {
+        let FileOutcome::Parsed(analysis) = analyze_source(Path::new("a.ts"), src.to_string()) else {
+            panic!("パースに失敗しました");
+        };
+        *analysis
+    }
+
+    // インポート名ごとの (使用回数, そのうち型の位置での回数)
+    fn counts(src: &str) -> Vec<(String, usize, usize)> {
+        analyze(src).counts.into_iter().map(|count| (count.name, count.count, count.type_count)).collect()
+    }
+
+    #[test]
+    fn import_declaration_is_not_a_usage() {
+        let src = "import { map, filter as keep } from 'rxjs';\nimport Default, * as ns from 'lib';\nmap(x);\n";
+        assert_eq!(
+            counts(src),
+            [("Default".into(), 0, 0), ("ns".into(), 0, 0), ("filter".into(), 0, 0), ("map".into(), 1, 0)]
+        );
+        let unused: Vec<_> = analyze(src).unused.into_iter().map(|unused| unused.name).collect();
+        assert_eq!(unused, ["keep", "Default", "ns"]);
+    }
+
+    #[test]
+    fn property_names_keys_and_labels_are_not_usages() {
+        let src = "\
+import { Component } from '@angular/core';
+foo.Component;
+foo?.Component;
+const o = { Component: 1, Component() {}, get Component() { return 1; } };
+class A { Component = 1; Component() {} }
+Component: for (;;) { break Component; }
+enum E { Component }
+interface I { Component: string; Component(): void; }
+const { Component: renamed } = o;
+@Component({}) class B {}
+const p = { Component, key: Component };
+";
+        assert_eq!(counts(src), [("Component".into(), 3, 0)]);
+    }
+
+    #[test]
+    fn type_positions_are_counted_separately() {
+        let src = "\
+import { HttpClient, Observable } from 'lib';
+let client: HttpClient;
+function get(http: HttpClient): Observable<HttpClient[]> { return http.get(); }
+class Service implements Observable {}
+type Alias = typeof HttpClient;
+class Api { constructor(private http: HttpClient) {} }
+new HttpClient();
+";
+        // 型の中の typeof は型の位置 (import type にできる)。コンストラクタ引数の型は DI で実行時に参照されるので値の位置
+        assert_eq!(counts(src), [("HttpClient".into(), 6, 4), ("Observable".into(), 2, 2)]);
+    }
+
+    #[test]
+    fn usage_locations_are_one_based() {
+        let src = "import { map } from 'rxjs';\n\nconst f = map;\nfunction g() { return  map(1); }\n";
+        let analysis = analyze(src);
+        let locations: Vec<_> = analysis.entry.imports[0].locations.iter().map(|loc| (loc.line, loc.column)).collect();
+        assert_eq!(locations, [(3, 11), (4, 24)]);
+        let src = "\tconst x = 1;\nimport { unused } from 'lib';\n";
+        let unused = &analyze(src).unused[0];
+        assert_eq!((unused.line, unused.column), (2, 10));
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use angular_dependency_analyzer::{
+    self as analyzer,
+    cache::Cache,
+    encoding,
+    AnalyzerConfig,
+    ProjectAnalysis,
+    report::Severity,
+    resolve::{self, PathAliases},
+};
+use crate::{
+    daemon::{self, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR},
+    watch::Project,
+    Args,
+};
+
+// 診断の発生元としてエディタに表示する名前
+const SOURCE: &str = env!("CARGO_PKG_NAME");
+
+// LSP の TextDocumentSyncKind.Full (変更のたびにドキュメント全体が送られる)
+const SYNC_FULL: u64 = 1;
+
+struct Server {
+    // target は initialize で受け取ったワークスペースのルートに置き換える
+    args: Args,
+    root: PathBuf,
+    cache: Option<Cache>,
+    project: Project,
+    // 最後に集計した解析結果 (initialized 前は None)
+    analysis: Option<ProjectAnalysis>,
+    // 相対でないインポートを解析対象のファイルに解決する tsconfig の paths 等 (参照を探すのに使う)
+    aliases: PathAliases,
+    // 開いているドキュメントの編集中の内容
+    documents: HashMap<PathBuf, String>,
+    // 前回診断を送ったファイル。指摘が無くなったら空の診断を送って消す
+    published: HashSet<String>,
+}
+
+// lsp コマンド: 標準入出力で Language Server Protocol を話し、指摘を診断として、
+// インポート名の使用箇所を参照 (Find All References) として返す
+pub fn run(args: &Args) -> Result<()> {
+    let mut input = io::stdin().lock();
+    let mut out = io::stdout().lock();
+    let mut server = Server {
+        args: args.clone(),
+        root: fs::canonicalize(args.base_dir())?,
+        cache: None,
+        project: Project::default(),
+        analysis: None,
+        aliases: PathAliases::default(),
+        documents: HashMap::new(),
+        published: HashSet::new(),
+    };
+
+    while let Some(body) = read_message(&mut input)? {
+        let message: Value = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(err) => {
+                write_message(&mut out, &daemon::error(Value::Null, PARSE_ERROR, &err.to_string()))?;
+                continue;
+            }
+        };
+        // クライアントからの応答 (method なし) は使わない
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            continue;
+        };
+        let params = message.get("params").unwrap_or(&Value::Null);
+        match message.get("id") {
+            Some(id) => {
+                let response = match server.request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => daemon::error(id.clone(), code, &message),
+                };
+                write_message(&mut out, &response)?;
+            }
+            None if method == "exit" => return Ok(()),
+            None => server.notify(method, params, &mut out)?,
+        }
+    }
+    Ok(())
+}
+
+impl Server {
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => {
+                let root = params
+                    .get("rootUri")
+                    .and_then(Value::as_str)
+                    .and_then(uri_to_path)
+                    .or_else(|| params.get("rootPath").and_then(Value::as_str).map(PathBuf::from));
+                if let Some(root) = root {
+                    self.root = root;
+                }
+                self.args.targets = vec![self.root.clone()];
+                self.cache = crate::open_cache(&self.args);
+                Ok(json!({
+                    "capabilities": {
+                        "textDocumentSync": { "openClose": true, "change": SYNC_FULL, "save": true },
+                        "referencesProvider": true,
+                    },
+                    "serverInfo": { "name": SOURCE, "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "textDocument/references" => self.references(params),
+            "shutdown" => Ok(Value::Null),
+            other => Err((METHOD_NOT_FOUND, format!("未対応のメソッドです: {other}"))),
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: &Value, out: &mut impl Write) -> Result<()> {
+        let path = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(uri_to_path);
+        let config = self.args.config()?;
+        match (method, path) {
+            // 最初にワークスペース全体を解析する
+            ("initialized", _) => {
+                self.project.refresh(&self.args.targets, &config, &HashSet::new(), self.cache.as_ref())?;
+            }
+            ("textDocument/didOpen", Some(path)) => {
+                let Some(text) = params.pointer("/textDocument/text").and_then(Value::as_str) else {
+                    return Ok(());
+                };
+                self.edit(&config, path, text.to_string());
+            }
+            ("textDocument/didChange", Some(path)) => {
+                let Some(text) = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str)
+                else {
+                    return Ok(());
+                };
+                self.edit(&config, path, text.to_string());
+            }
+            // 保存・破棄されたらディスクの内容で解析し直す (ファイルの追加・削除もここで拾う)
+            ("textDocument/didSave" | "textDocument/didClose", Some(path)) => {
+                if method == "textDocument/didClose" {
+                    self.documents.remove(&path);
+                }
+                self.project.refresh(&self.args.targets, &config, &HashSet::from([path]), self.cache.as_ref())?;
+            }
+            _ => return Ok(()),
+        }
+        self.aliases = config.path_aliases;
+        self.publish(out)
+    }
+
+    // 編集中の内容を解析し、そのファイルの結果だけを差し替える
+    fn edit(&mut self, config: &AnalyzerConfig, path: PathBuf, text: String) {
+        if config.is_source_path(&path)
+            && !config.is_excluded(&self.root, &path)
+            && config.is_included(&self.root, &path)
+        {
+            self.project.update(path.clone(), analyzer::analyze_source(&path, text.clone()));
+        }
+        self.documents.insert(path, text);
+    }
+
+    // 集計し直し、ファイルごとの診断を送る
+    fn publish(&mut self, out: &mut impl Write) -> Result<()> {
+        let analysis = self.project.aggregate(&self.args, &mut io::sink())?.finish();
+        let mut diagnostics: BTreeMap<String, Vec<Value>> =
+            self.published.iter().map(|path| (path.clone(), Vec::new())).collect();
+        // 指摘の位置にある識別子 (無ければ行末まで) に下線を引くため、ファイルの内容を読む
+        let mut texts: HashMap<String, String> = HashMap::new();
+        for finding in analysis.report.findings() {
+            let text = texts.entry(finding.path.clone()).or_insert_with(|| self.text(Path::new(&finding.path)));
+            let len = token_len(text, finding.line, finding.column);
+            diagnostics.entry(finding.path.clone()).or_default().push(json!({
+                "range": range(finding.line, finding.column, len),
+                "severity": match finding.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                },
+                "code": finding.rule.id,
+                "source": SOURCE,
+                "message": finding.message,
+            }));
+        }
+        self.published = diagnostics
+            .iter()
+            .filter(|(_, list)| !list.is_empty())
+            .map(|(path, _)| path.clone())
+            .collect();
+        for (path, list) in diagnostics {
+            write_message(
+                out,
+                &json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": { "uri": path_to_uri(Path::new(&path)), "diagnostics": list },
+                }),
+            )?;
+        }
+        self.analysis = Some(analysis);
+        Ok(())
+    }
+
+    // 編集中の内容 (開いていなければディスクの内容)
+    fn text(&self, path: &Path) -> String {
+        match self.documents.get(path) {
+            Some(text) => text.clone(),
+            None => fs::read(path).map(|bytes| encoding::decode_lossy(&bytes)).unwrap_or_default(),
+        }
+    }
+
+    // カーソル位置の識別子がインポート名なら、同じインポートの使用箇所をすべて返す
+    fn references(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let invalid = || (INVALID_PARAMS, "textDocument.uri と position が必要です".to_string());
+        let path = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(uri_to_path)
+            .ok_or_else(invalid)?;
+        let line = params.pointer("/position/line").and_then(Value::as_u64).ok_or_else(invalid)?;
+        let character = params
+            .pointer("/position/character")
+            .and_then(Value::as_u64)
+            .ok_or_else(invalid)?;
+        let Some(ProjectAnalysis { report, graph }) = &self.analysis else {
+            return Ok(Value::Null);
+        };
+
+        let text = self.text(&path);
+        let Some(word) = word_at(&text, line as usize, character as usize) else {
+            return Ok(json!([]));
+        };
+        let key = path.display().to_string();
+        let Some(import) = report
+            .files
+            .iter()
+            .find(|file| file.path == key)
+            .and_then(|file| file.imports.iter().find(|i| i.alias.as_deref().unwrap_or(&i.name) == word))
+        else {
+            return Ok(json!([]));
+        };
+
+        // 相対インポート・paths 等で解決するインポートは同じ指定子でもファイルによって指す先が違い、違う指定子でも
+        // 同じファイルを指すので、解決した先のファイルで比べる (解決できなければそのファイル内だけを探す)
+        let root = Path::new(&report.target);
+        let files: HashSet<PathBuf> = graph.nodes.iter().map(|node| resolve::normalize(&root.join(&node.id))).collect();
+        let target = |from: &str, module: &str| resolve::resolve(Path::new(from), module, &files, &self.aliases);
+        let local = resolve::is_relative(&import.module) || self.aliases.is_local(&import.module);
+        let wanted = local.then(|| target(&key, &import.module)).flatten();
+        let same_module = |path: &str, module: &str| match (local, &wanted) {
+            (false, _) => module == import.module,
+            (true, Some(wanted)) => target(path, module).as_ref() == Some(wanted),
+            (true, None) => path == key && module == import.module,
+        };
+        let locations: Vec<Value> = report
+            .files
+            .iter()
+            .flat_map(|file| file.imports.iter().map(move |other| (file, other)))
+            .filter(|(file, other)| other.name == import.name && same_module(&file.path, &other.module))
+            .flat_map(|(file, other)| {
+                let len = other.alias.as_deref().unwrap_or(&other.name).chars().count();
+                other.locations.iter().map(move |location| {
+                    json!({
+                        "uri": path_to_uri(Path::new(&file.path)),
+                        "range": range(location.line, location.column, len),
+                    })
+                })
+            })
+            .collect();
+        Ok(json!(locations))
+    }
+}
+
+// 1 始まりの行・列から LSP の 0 始まりの範囲を作る。
+// 列は文字単位なので、UTF-16 で 2 単位になる文字を含む行では少しずれる
+fn range(line: usize, column: usize, len: usize) -> Value {
+    let line = line.saturating_sub(1);
+    let column = column.saturating_sub(1);
+    json!({
+        "start": { "line": line, "character": column },
+        "end": { "line": line, "character": column + len },
+    })
+}
+
+// 1 始まりの line 行目 column 文字目から始まる識別子の文字数。識別子でなければ行末までの文字数
+fn token_len(text: &str, line: usize, column: usize) -> usize {
+    let Some(line) = text.lines().nth(line.saturating_sub(1)) else {
+        return 0;
+    };
+    let rest = line.chars().skip(column.saturating_sub(1));
+    match rest.clone().take_while(|&c| c.is_alphanumeric() || c == '_' || c == '$').count() {
+        0 => rest.collect::<String>().trim_end().chars().count(),
+        len => len,
+    }
+}
+
+// line 行目 character 単位目 (UTF-16) にある識別子
+fn word_at(text: &str, line: usize, character: usize) -> Option<&str> {
+    let line = text.lines().nth(line)?;
+    let mut units = 0;
+    let index = line
+        .char_indices()
+        .find(|(_, c)| {
+            let found = units >= character;
+            units += c.len_utf16();
+            found
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(line.len());
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let start = line[..index]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_ident(*c))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let end = line[index..].find(|c| !is_ident(c)).map_or(line.len(), |i| index + i);
+    (start < end).then(|| &line[start..end])
+}
+
+// file:///home/me/my%20app -> /home/me/my app
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let decoded = (encoded[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(encoded[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+// Content-Length ヘッダー付きのメッセージを 1 つ読む。入力が閉じられたら None
+fn read_message(input: &mut impl BufRead) -> Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+    let Some(length) = length else {
+        bail!("Content-Length ヘッダーがありません");
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_message(out: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(out, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_len_covers_identifier_or_rest_of_line() {
+        let text = "import { map } from 'rxjs';\n  foo();  \n";
+        assert_eq!(token_len(text, 1, 10), 3);
+        assert_eq!(token_len(text, 1, 1), 6);
+        assert_eq!(token_len(text, 1, 16), 4);
+        assert_eq!(token_len(text, 2, 6), 3);
+        assert_eq!(token_len(text, 9, 1), 0);
+    }
+
+    #[test]
+    fn word_at_finds_identifier_around_cursor() {
+        let text = "const s = new Svc();";
+        assert_eq!(word_at(text, 0, 15), Some("Svc"));
+        assert_eq!(word_at(text, 0, 14), Some("Svc"));
+        assert_eq!(word_at(text, 0, 9), None);
+    }
+
+    #[test]
+    fn uri_round_trip() {
+        let path = Path::new("/home/me/my app/a.ts");
+        assert_eq!(path_to_uri(path), "file:///home/me/my%20app/a.ts");
+        assert_eq!(uri_to_path(&path_to_uri(path)).as_deref(), Some(path));
+    }
+}
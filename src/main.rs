@@ -1,121 +1,1040 @@
-use std::{collections::HashMap, fs, env};
-use anyhow::Result;
-use walkdir::WalkDir;
-use swc_common::{sync::Lrc, SourceMap, FileName};
-use swc_ecma_parser::{Parser, StringInput, Syntax, TsSyntax};
-use swc_ecma_visit::{Visit, VisitWith};
-use swc_ecma_ast::{ImportDecl, Ident};
-
-struct Analyzer {
-    imports: Vec<String>,
-    usage: HashMap<String, usize>,
+mod angular;
+mod cli;
+mod config;
+mod daemon;
+mod diff;
+mod git;
+mod history;
+mod hook;
+mod impact;
+mod lsp;
+mod nx;
+mod projects;
+mod watch;
+mod workspaces;
+
+use std::{collections::{BTreeMap, HashMap}, io::{self, Read, Write}, fs, path::{Path, PathBuf}, time::Instant};
+use anyhow::{bail, Context, Result};
+use angular_dependency_analyzer::{
+    baseline::{Baseline, CyclesBaseline, BASELINE_VERSION},
+    cache::{self, Cache},
+    encoding,
+    export::{self, Export},
+    glob::Glob,
+    graph::{self, DirectoryGrouping},
+    health::HealthWeights,
+    manifest::{self, Manifest},
+    output::{self, GraphFormat, OutputFormat},
+    policy::Policy,
+    report::{CacheStats, Report, RuleLevel},
+    resolve::{self, PathAliases},
+    tsconfig::{self, ProjectFiles, TsConfig},
+    Aggregate, AnalyzerConfig, FileOutcome, ProjectAnalysis,
+};
+use cli::Options;
+use config::{ConfigFile, Thresholds};
+use projects::CycleScope;
+
+// サブコマンド
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Command {
+    // インポート名ごとの使用回数レポート (既定)
+    #[default]
+    Analyze,
+    // ファイル単位の依存グラフ
+    Graph,
+    // 未使用のインポート一覧
+    Unused,
+    // 指定したインポート名の使用箇所
+    Usages,
+    // 指定したファイル・パッケージを直接・間接にインポートしているファイル
+    Rdeps,
+    // 変更したファイルを直接・間接にインポートしているファイル
+    Impact,
+    // 2 つのファイル (またはファイルとパッケージ) をつなぐ最短のインポートの連鎖
+    Path,
+    // 依存グラフをまとめたファイルの組 (フォルダの分け方の候補)
+    Clusters,
+    // 中心性の高い (多くのファイルをつないでいる) ファイル
+    Hubs,
+    // エントリポイントからのインポートの連鎖の深さ
+    Depth,
+    // 各ファイルが直接・間接に読み込むファイル・パッケージの数
+    Deps,
+    // 未使用のインポート・循環依存などから求めた依存関係の健全性スコア
+    Health,
+    // 解析結果を保持したまま JSON-RPC で問い合わせに答える常駐プロセス
+    Daemon,
+    // 標準入出力で話す Language Server
+    Lsp,
+    // git の履歴を辿ったパッケージごとの使用回数の推移
+    History,
+    // 2 つのフォルダ・ブランチの比較
+    Diff,
+    // tsconfig の references で繋がったプロジェクトごとの集計とプロジェクト間の依存
+    Projects,
+    // npm / yarn / pnpm のワークスペースのパッケージごとの集計とパッケージ間の依存
+    Workspaces,
+    // Nx のワークスペースのプロジェクト (project.json) ごとの集計とプロジェクト間の依存
+    Nx,
+    // angular.json のプロジェクトごとの集計とプロジェクト間の依存
+    Angular,
 }
 
-impl Analyzer {
-    fn new() -> Self {
-        Self {
-            imports: Vec::new(),
-            usage: HashMap::new(),
+impl Command {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Analyze => "analyze",
+            Self::Graph => "graph",
+            Self::Unused => "unused",
+            Self::Usages => "usages",
+            Self::Rdeps => "rdeps",
+            Self::Impact => "impact",
+            Self::Path => "path",
+            Self::Clusters => "clusters",
+            Self::Hubs => "hubs",
+            Self::Health => "health",
+            Self::Depth => "depth",
+            Self::Deps => "deps",
+            Self::Daemon => "daemon",
+            Self::Lsp => "lsp",
+            Self::History => "history",
+            Self::Diff => "diff",
+            Self::Projects => "projects",
+            Self::Workspaces => "workspaces",
+            Self::Nx => "nx",
+            Self::Angular => "angular",
         }
     }
 }
 
-impl Visit for Analyzer {
-    fn visit_import_decl(&mut self, n: &ImportDecl) {
-        for spec in &n.specifiers {
-            let name = match spec {
-                swc_ecma_ast::ImportSpecifier::Named(named) => named.local.sym.to_string(),
-                swc_ecma_ast::ImportSpecifier::Default(def) => def.local.sym.to_string(),
-                swc_ecma_ast::ImportSpecifier::Namespace(ns) => ns.local.sym.to_string(),
-            };
-            self.imports.push(name);
+// --stdin-filepath を省略したときのパス
+const STDIN_FILEPATH: &str = "stdin.ts";
+
+// コマンドライン引数
+#[derive(Clone)]
+struct Args {
+    command: Command,
+    // 解析対象のフォルダ・ファイル (複数指定可。未指定ならカレントディレクトリ)
+    targets: Vec<PathBuf>,
+    // 形式名はコマンドごとに解釈が異なるため文字列のまま保持する
+    format: Option<String>,
+    output: Option<PathBuf>,
+    // --export sqlite:deps.db (複数指定可)
+    exports: Vec<Export>,
+    // --schema: JSON 出力のスキーマを表示して終了する
+    schema: bool,
+    // --no-cache: 解析結果のキャッシュを読み書きしない
+    no_cache: bool,
+    // --cache-dir: キャッシュの保存先 (既定は解析対象ディレクトリ直下の .angular-dep-cache)
+    cache_dir: Option<PathBuf>,
+    // --watch: 変更を監視して、変わったファイルだけ解析し直して出力し直す
+    watch: bool,
+    // --stdin: 標準入力のソースをこのパス (--stdin-filepath、既定は stdin.ts) のファイルとして解析する
+    stdin: Option<PathBuf>,
+    // --changed[=<ブランチ>]: git でこの基準 (既定は HEAD) との分岐点から変更されたファイルだけを解析する
+    changed: Option<String>,
+    // --with-dependents: --changed のファイルを (間接的にでも) インポートしているファイルも解析する
+    with_dependents: bool,
+    // --hook: ステージされた内容を HEAD と比べ、新しく増える指摘があれば失敗する (pre-commit 用)
+    hook: bool,
+    // --write-baseline: 解析結果のスナップショットを保存する
+    write_baseline: Option<PathBuf>,
+    // --baseline: 保存したスナップショットと比べ、変わったものだけを報告する (設定ファイルの baseline でも指定できる)
+    baseline: Option<PathBuf>,
+    // --write-cycles-baseline: 今ある循環依存を保存する
+    write_cycles_baseline: Option<PathBuf>,
+    // --cycles-baseline: 保存した循環依存に無いものだけを報告して失敗にする (設定ファイルの cycles-baseline でも指定できる)
+    cycles_baseline: Option<PathBuf>,
+    // --socket: daemon コマンドが待ち受けるソケット (既定は解析対象ディレクトリ直下の .angular-dep.sock)
+    socket: Option<PathBuf>,
+    // --filter: 出力するインポートをモジュール・インポート名のグロブで絞り込む (複数指定したらどれかに一致するもの)
+    filters: Vec<Glob>,
+    // --include / --exclude: 走査中に判定するグロブ (設定ファイルの include / exclude も加える)
+    include: Vec<String>,
+    exclude: Vec<String>,
+    // --ignore-file: .gitignore 形式の除外パターンを書いたファイル (設定ファイルの ignore-files も加える)
+    ignore_files: Vec<PathBuf>,
+    // --no-ignore: .gitignore / .ignore を読まない
+    no_ignore: bool,
+    // --max-depth: 走査する深さの上限 (設定ファイルの max-depth でも指定できる)
+    max_depth: Option<usize>,
+    // --follow-symlinks: シンボリックリンクを辿る (設定ファイルの follow-symlinks でも指定できる)
+    follow_symlinks: bool,
+    // --max-file-size: これより大きいファイルは解析しない (設定ファイルの max-file-size でも指定できる)
+    max_file_size: Option<u64>,
+    // --include-generated: 生成されたファイルも解析する (設定ファイルの include-generated でも指定できる)
+    include_generated: bool,
+    // usages コマンドで探すインポート名 / rdeps コマンドで調べるファイルかパッケージ / path コマンドの起点のファイル
+    symbol: Option<String>,
+    // path コマンドで連鎖の終点にするファイルかパッケージ
+    path_to: Option<String>,
+    // impact コマンドの --file で指定した変更したファイル
+    impact_files: Vec<PathBuf>,
+    // usages コマンドで表示する前後の行数
+    context: usize,
+    // --top: hubs / deps コマンドで表示するファイル数 / depth コマンドで表示する連鎖の数
+    // (analyze コマンドでは output_options.top に入れる)
+    top: Option<usize>,
+    // --group-by: graph コマンドでファイルをフォルダにまとめる
+    group_by: Option<DirectoryGrouping>,
+    // --circular: graph コマンドの madge 形式で隣接リストの代わりに循環依存の一覧を出す (madge --circular --json)
+    circular: bool,
+    output_options: output::Options,
+    history_options: history::Options,
+    // 以下は設定ファイル (.angular-analyzer.toml) から読む
+    extensions: Option<Vec<String>>,
+    policy: Option<Policy>,
+    // 読み込んだ tsconfig (paths / baseUrl と、--tsconfig-files のときのコンパイル対象はここから作る)
+    tsconfig: Option<TsConfig>,
+    path_aliases: PathAliases,
+    // --alias と設定ファイルの aliases (名前, 絶対パス)。path_aliases には加えてある
+    aliases: Vec<(String, PathBuf)>,
+    project_files: Option<ProjectFiles>,
+    // --check-deps のとき照合する package.json
+    manifest: Option<Manifest>,
+    // 孤立ファイル・未使用のエクスポートの判定で起点にするファイルのグロブ
+    entry_points: Vec<String>,
+    // --orphans
+    check_orphans: bool,
+    // --unused-exports
+    check_unused_exports: bool,
+    // 循環依存を調べる (--cycles か設定ファイルの check-cycles。analyze コマンドだけ)
+    check_cycles: bool,
+    // --cycles[=<範囲>]。analyze コマンドでは範囲は all だけ
+    cycles: Option<CycleScope>,
+    // --coupling か設定ファイルの check-coupling
+    check_coupling: bool,
+    // nx コマンドで比べる nx graph --file の出力
+    nx_graph: Option<PathBuf>,
+    // nx コマンドで使う depConstraints のファイル (既定はワークスペースのルートの .eslintrc.json)
+    nx_constraints: Option<PathBuf>,
+    thresholds: Thresholds,
+    rules: BTreeMap<String, RuleLevel>,
+    // 設定ファイルの [health] (健全性スコアの指標ごとの重み)
+    health: HealthWeights,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        // コマンドごとに使えるオプションと位置引数は clap が検証している
+        let cli = Options::from_env()?;
+        let command = cli.command;
+        let watch = cli.watch;
+        let mut targets = cli.targets;
+        // --html out.html は --format html --output out.html の省略形
+        let (mut format, mut output) = match cli.html {
+            Some(html) => (Some("html".to_string()), Some(html)),
+            None => (cli.format, cli.output),
+        };
+        let mut output_options = output::Options {
+            per_file: cli.per_file,
+            by_package: cli.by_package,
+            sort: cli.sort.unwrap_or_default(),
+            reverse: cli.reverse,
+            min_count: cli.min_count.unwrap_or_default(),
+            ..output::Options::default()
+        };
+        let mut history_options = history::Options {
+            range: cli.range,
+            tags: cli.tags,
+            packages: cli.package,
+            ..history::Options::default()
+        };
+        if let Some(limit) = cli.limit {
+            history_options.limit = limit;
+        }
+        let mut thresholds = Thresholds {
+            max_unused_imports: cli.max_unused_imports,
+            max_duplicate_imports: cli.max_duplicate_imports,
+            max_parse_failures: cli.max_parse_failures,
+            max_uses_of: cli.max_uses_of.into_iter().collect(),
+            fail_on: Vec::new(),
+        };
+        for fail_on in cli.fail_on {
+            if !thresholds.fail_on.contains(&fail_on) {
+                thresholds.fail_on.push(fail_on);
+            }
+        }
+        let Options {
+            symbol,
+            path_to,
+            export: exports,
+            context,
+            top,
+            group_by,
+            circular,
+            config: config_path,
+            no_config,
+            no_cache,
+            cache_dir,
+            filter,
+            mut include,
+            mut exclude,
+            ignore_file: mut ignore_files,
+            no_ignore,
+            max_depth,
+            mut follow_symlinks,
+            max_file_size,
+            mut include_generated,
+            strict,
+            file: impact_files,
+            socket,
+            stdin,
+            stdin_filepath,
+            changed,
+            with_dependents,
+            write_baseline,
+            write_cycles_baseline,
+            cycles_baseline,
+            baseline,
+            policy,
+            tsconfig,
+            tsconfig_files,
+            alias: aliases,
+            orphans,
+            cycles,
+            coupling,
+            unused_exports,
+            entry: entry_points,
+            check_deps,
+            package_json,
+            nx_graph,
+            nx_constraints,
+            hook,
+            schema,
+            sort,
+            ..
+        } = cli;
+        let filters: Vec<Glob> = filter.iter().map(|pattern| Glob::new(pattern)).collect();
+        let context = context.unwrap_or(2);
+
+        if stdin_filepath.is_some() && !stdin {
+            bail!("--stdin-filepath は --stdin と一緒に指定してください");
+        }
+        if with_dependents && changed.is_none() {
+            bail!("--with-dependents は --changed と一緒に指定してください");
+        }
+        if stdin && changed.is_some() {
+            bail!("--stdin と --changed は同時に指定できません");
+        }
+        if hook && (stdin || changed.is_some()) {
+            bail!("--hook と --stdin / --changed は同時に指定できません");
+        }
+        if (check_deps || package_json.is_some()) && (stdin || changed.is_some() || hook) {
+            bail!("--check-deps は --stdin / --changed / --hook と同時に指定できません (一部のファイルだけでは使われていない依存を判定できません)");
+        }
+        if (orphans || !entry_points.is_empty()) && (stdin || changed.is_some() || hook) {
+            bail!("--orphans は --stdin / --changed / --hook と同時に指定できません (一部のファイルだけでは孤立したファイルを判定できません)");
+        }
+        let any_cycles = cycles.is_some() || cycles_baseline.is_some() || write_cycles_baseline.is_some();
+        if any_cycles && (stdin || changed.is_some() || hook) {
+            bail!("--cycles / --cycles-baseline / --write-cycles-baseline は --stdin / --changed / --hook と同時に指定できません (一部のファイルだけでは循環依存を判定できません)");
+        }
+        if command == Command::Analyze && cycles.is_some_and(|scope| scope != CycleScope::All) {
+            bail!("--cycles=within / across は projects / workspaces / nx / angular コマンドで指定してください");
+        }
+        if unused_exports && (stdin || changed.is_some() || hook) {
+            bail!("--unused-exports は --stdin / --changed / --hook と同時に指定できません (一部のファイルだけでは使われていないエクスポートを判定できません)");
+        }
+        if coupling && (stdin || changed.is_some() || hook) {
+            bail!("--coupling は --stdin / --changed / --hook と同時に指定できません (一部のファイルだけでは fan-in を数えられません)");
+        }
+        if stdin && !targets.is_empty() {
+            bail!("--stdin と解析対象のフォルダ・ファイルは同時に指定できません");
+        }
+        let stdin = stdin.then(|| stdin_filepath.unwrap_or_else(|| PathBuf::from(STDIN_FILEPATH)));
+        // 未指定ならカレントディレクトリ (--stdin ではそのファイルのあるディレクトリ)
+        if targets.is_empty() {
+            let dir = stdin.as_deref().and_then(Path::parent).filter(|dir| !dir.as_os_str().is_empty());
+            targets.push(dir.unwrap_or(Path::new(".")).to_path_buf());
+        }
+
+        // 設定ファイルの値は、コマンドラインで指定されなかったものにだけ使う
+        let config_path = match config_path {
+            Some(path) => Some(path),
+            None if no_config => None,
+            None => config::find(&targets[0]),
+        };
+        let file = match &config_path {
+            Some(path) => config::load(path)?,
+            None => ConfigFile::default(),
+        };
+        if command == Command::Analyze {
+            format = format.or(file.format);
+            output = output.or(file.output);
+        }
+        if let Some(file_sort) = file.sort
+            && sort.is_none()
+        {
+            output_options.sort = file_sort.parse()?;
+        }
+        output_options.per_file |= file.per_file;
+        output_options.by_package |= file.by_package;
+        output_options.reverse |= file.reverse;
+        if command == Command::Analyze {
+            output_options.top = top;
+        }
+        follow_symlinks |= file.follow_symlinks;
+        let max_depth = max_depth.or(file.max_depth);
+        let baseline = baseline.or(file.baseline);
+        // 指定が無ければ解析対象のフォルダとその親の dependency-policy.yaml を使う
+        let policy = match policy.or(file.policy).or_else(|| config::find_policy(&targets[0])) {
+            Some(path) => Some(config::load_policy(&path)?),
+            None => None,
+        };
+        // 指定が無ければ解析対象のフォルダとその親の tsconfig.json を使う (読めなければ警告して paths を使わない)
+        let tsconfig = match tsconfig.or(file.tsconfig) {
+            Some(path) => Some(tsconfig::load(&path)?),
+            None => match tsconfig::find(&targets[0]).map(|path| tsconfig::load(&path)) {
+                Some(Ok(found)) => Some(found),
+                Some(Err(err)) if tsconfig_files || file.tsconfig_files => return Err(err),
+                Some(Err(err)) => {
+                    eprintln!("⚠️ {err:#}");
+                    None
+                }
+                None => None,
+            },
+        };
+        let cwd = cwd_for(&targets[0])?;
+        let mut path_aliases = tsconfig.as_ref().map(|tsconfig| tsconfig.path_aliases(cwd.clone())).unwrap_or_default();
+        path_aliases.cwd = cwd.clone();
+        // 設定ファイルのエイリアスを先に、コマンドラインのものを後から加えて上書きする
+        let aliases: Vec<(String, PathBuf)> = match file.aliases.is_empty() && aliases.is_empty() {
+            true => Vec::new(),
+            false => {
+                let current = fs::canonicalize(".")?;
+                file.aliases
+                    .into_iter()
+                    .chain(aliases)
+                    .map(|(name, target)| (name, resolve::normalize(&current.join(target))))
+                    .collect()
+            }
+        };
+        for (name, target) in &aliases {
+            path_aliases.add_alias(name, target);
+        }
+        let project_files = match (&tsconfig, tsconfig_files || file.tsconfig_files) {
+            (_, false) => None,
+            (Some(tsconfig), true) => Some(tsconfig.project_files(cwd.clone())),
+            (None, true) => bail!("--tsconfig-files を指定しましたが tsconfig.json が見つかりません (--tsconfig で指定できます)"),
+        };
+        // 全ファイルを解析する analyze コマンドでだけ照合する (設定ファイルの指定は他のコマンドでは無視する)
+        let manifest = match check_deps || package_json.is_some() || file.check_dependencies {
+            true if command == Command::Analyze && stdin.is_none() && changed.is_none() && !hook => {
+                let Some(path) = package_json.or(file.package_json).or_else(|| config::find_manifest(&targets[0])) else {
+                    bail!("--check-deps を指定しましたが package.json が見つかりません (--package-json で指定できます)");
+                };
+                let display = match &cwd {
+                    Some(cwd) => resolve::relative_display(cwd, &fs::canonicalize(&path).unwrap_or(path.clone())),
+                    None => path.display().to_string(),
+                };
+                Some(config::load_manifest(&path, &display, &file.ignore_dependencies, &file.test_files)?)
+            }
+            _ => None,
+        };
+        // 孤立ファイル・未使用のエクスポートも全ファイルを解析する analyze コマンドでだけ調べる
+        let whole = command == Command::Analyze && stdin.is_none() && changed.is_none() && !hook;
+        let check_orphans = whole && (orphans || !entry_points.is_empty() || file.check_orphans);
+        let check_unused_exports = whole && (unused_exports || file.check_unused_exports);
+        let cycles_baseline = cycles_baseline.or(file.cycles_baseline).filter(|_| whole);
+        let check_cycles = whole && (cycles.is_some() || file.check_cycles || cycles_baseline.is_some());
+        let check_coupling = whole && (coupling || file.check_coupling);
+        let entry_points = graph::DEFAULT_ENTRY_POINTS
+            .iter()
+            .chain(manifest::TEST_FILES)
+            .map(|pattern| pattern.to_string())
+            .chain(file.entry_points)
+            .chain(entry_points)
+            .collect();
+        let max_file_size = match (max_file_size, &file.max_file_size) {
+            (None, Some(size)) => Some(size.bytes()?),
+            (size, _) => size,
+        };
+        include_generated |= file.include_generated;
+        let mut thresholds = thresholds.or(file.thresholds);
+        // パースに失敗したファイルは集計から外れるので、厳密に数えたいときは失敗にする
+        if strict || file.strict {
+            thresholds.max_parse_failures = Some(0);
+        }
+        include.extend(file.include);
+        exclude.extend(file.exclude);
+        ignore_files.extend(file.ignore_files);
+
+        let args = Self {
+            command,
+            targets,
+            format,
+            output,
+            exports,
+            schema,
+            no_cache,
+            cache_dir,
+            watch,
+            stdin,
+            changed,
+            with_dependents,
+            hook,
+            write_baseline,
+            baseline,
+            write_cycles_baseline,
+            cycles_baseline,
+            socket,
+            filters,
+            include,
+            exclude,
+            ignore_files,
+            no_ignore,
+            max_depth,
+            follow_symlinks,
+            max_file_size,
+            include_generated,
+            symbol,
+            path_to,
+            impact_files,
+            context,
+            top,
+            group_by,
+            circular,
+            output_options,
+            history_options,
+            extensions: file.extensions,
+            policy,
+            tsconfig,
+            path_aliases,
+            aliases,
+            project_files,
+            manifest,
+            entry_points,
+            check_orphans,
+            check_unused_exports,
+            cycles,
+            check_cycles,
+            check_coupling,
+            nx_graph,
+            nx_constraints,
+            thresholds,
+            rules: file.rules,
+            health: file.health,
+        };
+        if args.schema {
+            return Ok(args);
+        }
+        if args.stdin.is_some() && args.watch {
+            bail!("--stdin と --watch は同時に指定できません");
+        }
+        if (args.changed.is_some() || args.hook) && args.watch {
+            bail!("--changed / --hook と --watch は同時に指定できません");
+        }
+        if (args.baseline.is_some() || args.write_baseline.is_some()) && args.watch {
+            bail!("--baseline / --write-baseline と --watch は同時に指定できません");
+        }
+        if (args.cycles_baseline.is_some() || args.write_cycles_baseline.is_some()) && args.watch {
+            bail!("--cycles-baseline / --write-cycles-baseline と --watch は同時に指定できません");
+        }
+        // 走査を始める前に形式名の誤りを検出する
+        match args.command {
+            Command::Analyze => {
+                // 件数を絞るのはテキストと Markdown の表示だけ (JSON・CSV 等には全件を出す)
+                let format = args.report_format()?;
+                let limit = match (args.top, args.output_options.min_count) {
+                    (Some(_), _) => Some("--top"),
+                    (None, 0) => None,
+                    (None, _) => Some("--min-count"),
+                };
+                if !matches!(format, OutputFormat::Text | OutputFormat::Markdown)
+                    && let Some(option) = limit
+                {
+                    bail!("{option} は --format text / markdown でだけ指定できます");
+                }
+            }
+            Command::Graph => {
+                let format = args.graph_format()?;
+                if format == GraphFormat::Depcruise && args.group_by.is_some() {
+                    bail!("--group-by は dependency-cruiser 形式では使えません");
+                }
+                if args.circular && format != GraphFormat::Madge {
+                    bail!("--circular は --format madge と一緒に指定してください");
+                }
+            }
+            Command::Unused => {
+                args.unused_format()?;
+            }
+            Command::Impact => {
+                if args.changed.is_some() && !args.impact_files.is_empty() {
+                    bail!("--file と --changed は同時に指定できません");
+                }
+                args.unused_format()?;
+            }
+            Command::Usages
+            | Command::Rdeps
+            | Command::Path
+            | Command::Clusters
+            | Command::Hubs
+            | Command::Depth
+            | Command::Deps
+            | Command::Health => {
+                args.unused_format()?;
+            }
+            Command::Diff => {
+                args.diff_format()?;
+            }
+            Command::History => {
+                if args.history_options.range.is_some() && args.history_options.tags {
+                    bail!("--range と --tags は同時に指定できません");
+                }
+                args.history_format()?;
+            }
+            Command::Projects | Command::Workspaces | Command::Nx | Command::Angular => {
+                args.projects_format()?;
+            }
+            Command::Daemon | Command::Lsp => {}
         }
-        n.visit_children_with(self);
+        Ok(args)
     }
 
-    fn visit_ident(&mut self, ident: &Ident) {
-        let key = ident.sym.to_string();
-        if self.imports.contains(&key) {
-            *self.usage.entry(key).or_insert(0) += 1;
+    // --format 未指定なら --output の拡張子から推定し、それも無ければテキスト
+    fn report_format(&self) -> Result<OutputFormat> {
+        match &self.format {
+            Some(format) => format.parse(),
+            None => Ok(self
+                .output
+                .as_deref()
+                .and_then(OutputFormat::from_extension)
+                .unwrap_or(OutputFormat::Text)),
         }
     }
-}
 
-fn main() -> Result<()> {
-    // 解析対象ディレクトリをコマンドライン引数から取得。未指定ならカレントディレクトリ
-    let target = env::args().nth(1).unwrap_or_else(|| ".".into());
-
-    // グローバル集計マップと SourceMap 準備
-    let mut global_counts: HashMap<String, usize> = HashMap::new();
-    let cm: Lrc<SourceMap> = Default::default();
-
-    // 再帰的に .ts/.tsx ファイルだけを走査 (.d.ts は除外)
-    for entry in WalkDir::new(&target)
-        .into_iter()
-        .filter_entry(|e| {
-            let p = e.path().to_string_lossy();
-            !p.contains("node_modules")
-                && !p.contains(".vscode")
-                && !p.contains(".angular")
-                && !p.contains(".git")
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| {
-            let p = e.path().to_string_lossy();
-            if p.ends_with(".d.ts") {
-                return false;
-            }
-            matches!(
-                e.path()
-                    .extension()
-                    .and_then(|s| s.to_str()),
-                Some("ts") | Some("tsx")
-            )
+    // unused / usages / rdeps / impact / path / clusters / hubs / depth / deps / health コマンドはテキストと JSON のみ
+    fn unused_format(&self) -> Result<OutputFormat> {
+        match self.report_format()? {
+            format @ (OutputFormat::Text | OutputFormat::Json) => Ok(format),
+            _ => bail!("{} コマンドで使える出力形式は text / json のみです", self.command.name()),
+        }
+    }
+
+    // diff コマンドはテキストと JSON のみ
+    fn diff_format(&self) -> Result<OutputFormat> {
+        match self.report_format()? {
+            format @ (OutputFormat::Text | OutputFormat::Json) => Ok(format),
+            _ => bail!("diff コマンドで使える出力形式は text / json のみです"),
+        }
+    }
+
+    // projects / workspaces コマンドはテキストと JSON のみ
+    fn projects_format(&self) -> Result<OutputFormat> {
+        match self.report_format()? {
+            format @ (OutputFormat::Text | OutputFormat::Json) => Ok(format),
+            _ => bail!("{} コマンドで使える出力形式は text / json のみです", self.command.name()),
+        }
+    }
+
+    // history コマンドはテキスト・JSON・CSV
+    fn history_format(&self) -> Result<OutputFormat> {
+        match self.report_format()? {
+            format @ (OutputFormat::Text | OutputFormat::Json | OutputFormat::Csv) => Ok(format),
+            _ => bail!("history コマンドで使える出力形式は text / json / csv のみです"),
+        }
+    }
+
+    // graph コマンドの既定は DOT
+    fn graph_format(&self) -> Result<GraphFormat> {
+        match &self.format {
+            Some(format) => format.parse(),
+            None => Ok(self
+                .output
+                .as_deref()
+                .and_then(GraphFormat::from_extension)
+                .unwrap_or(GraphFormat::Dot)),
+        }
+    }
+
+    // NDJSON はファイルごとの結果を解析し終えた順に書き出す
+    fn streaming(&self) -> Result<bool> {
+        Ok(self.command == Command::Analyze && self.report_format()? == OutputFormat::Ndjson)
+    }
+
+    // キャッシュ・ソケット等の既定の置き場所 (最初の解析対象。ファイルならそのディレクトリ)
+    fn base_dir(&self) -> &Path {
+        let target = &self.targets[0];
+        match target.is_file() {
+            true => target.parent().unwrap_or(Path::new(".")),
+            false => target,
+        }
+    }
+
+    // --filter に一致しないインポートを出力から除く
+    fn retain_filtered(&self, report: &mut Report) {
+        if !self.filters.is_empty() {
+            report.retain_matching(&self.filters);
+        }
+    }
+
+    // キャッシュは open_cache で開いて解析に渡すので cache_dir は使わない
+    fn config(&self) -> Result<AnalyzerConfig> {
+        let default = AnalyzerConfig::default();
+        Ok(AnalyzerConfig {
+            sort: self.output_options.sort,
+            reverse: self.output_options.reverse,
+            // エクスポートではファイル単位の内訳も使うため、その場合は保持する
+            keep_files: !self.streaming()? || !self.exports.is_empty(),
+            cache_dir: None,
+            exclude: self.exclude.iter().map(|pattern| Glob::new(pattern)).collect(),
+            include: self.include.iter().map(|pattern| Glob::new(pattern)).collect(),
+            extensions: self.extensions.clone().unwrap_or(default.extensions),
+            max_depth: self.max_depth,
+            follow_symlinks: self.follow_symlinks,
+            use_ignore_files: !self.no_ignore,
+            ignore_files: self.ignore_files.clone(),
+            max_file_size: self.max_file_size,
+            policy: self.policy.clone(),
+            path_aliases: self.path_aliases.clone(),
+            project_files: self.project_files.clone(),
+            manifest: self.manifest.clone(),
+            entry_points: self.entry_points.iter().map(|pattern| Glob::new(pattern)).collect(),
+            check_orphans: self.check_orphans,
+            check_unused_exports: self.check_unused_exports,
+            check_cycles: self.check_cycles,
+            check_coupling: self.check_coupling,
+            skip_generated: !self.include_generated,
+            rules: self.rules.clone(),
         })
-    {
-        let path = entry.path();
-
-        // ソース読み込み＆SourceFile化
-        let src = fs::read_to_string(path)?;
-        let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), src.clone());
-
-        // 拡張子ごとに TSX モード切替 (tsx のときだけ true)
-        let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-        let syntax = Syntax::Typescript(TsSyntax {
-            tsx: ext == "tsx",
-            decorators: true, // Angular の @Component 等を許可
-            ..Default::default()
-        });
-
-        let mut parser = Parser::new(syntax, StringInput::from(&*fm), None);
-
-        // パース失敗したらスキップして次へ
-        let module = match parser.parse_module() {
-            Ok(m) => m,
-            Err(err) => {
-                eprintln!("⚠️ 解析スキップ: {}: {:?}", path.display(), err);
-                continue;
+    }
+}
+
+// 解析対象が相対パスなら、tsconfig で解決したパスを走査したファイルと同じ形にするためのカレントディレクトリ
+fn cwd_for(target: &Path) -> Result<Option<PathBuf>> {
+    Ok(match target.is_relative() {
+        true => Some(fs::canonicalize(".")?),
+        false => None,
+    })
+}
+
+// 1 ファイル分の結果を集計に加える。NDJSON は溜め込まずにその場で書き出す
+// (集計は最後に --filter で絞り込むので、書き出すファイルのレコードもここで同じように絞り込む)
+fn merge(
+    aggregate: &mut Aggregate,
+    outcome: FileOutcome,
+    streaming: bool,
+    filters: &[Glob],
+    out: &mut impl Write,
+) -> Result<()> {
+    match &outcome {
+        FileOutcome::Parsed(analysis) => {
+            if streaming && filters.is_empty() {
+                output::ndjson::write_file(&analysis.entry, out)?;
+            } else if streaming {
+                let mut entry = analysis.entry.clone();
+                entry.retain_matching(filters);
+                if !entry.imports.is_empty() {
+                    output::ndjson::write_file(&entry, out)?;
+                }
+            }
+        }
+        FileOutcome::Failed { failure, .. } => {
+            if streaming {
+                output::ndjson::write_parse_failure(failure, out)?;
             }
+        }
+        FileOutcome::Skipped { skipped, .. } => {
+            if streaming {
+                output::ndjson::write_skipped(skipped, out)?;
+            }
+        }
+    }
+    aggregate.add(outcome);
+    Ok(())
+}
+
+// コマンドに応じたレポートを書き出す
+// (sources は --stdin で受け取ったソースなど、ディスクの代わりに使うファイルの内容)
+fn write_report(
+    analysis: &ProjectAnalysis,
+    args: &Args,
+    sources: &HashMap<PathBuf, String>,
+    out: &mut impl Write,
+) -> Result<()> {
+    let ProjectAnalysis { report, graph } = analysis;
+    if args.command == Command::Graph {
+        let grouped;
+        let graph = match args.group_by {
+            Some(grouping) => {
+                grouped = graph.group_by_directory(grouping);
+                &grouped
+            }
+            None => graph,
         };
+        match args.graph_format()? {
+            GraphFormat::Madge if args.circular => output::madge::write_circular(graph, out)?,
+            format => output::write_graph(format, report, graph, out)?,
+        }
+        out.flush()?;
+        return Ok(());
+    }
 
-        // AST をトラバースして imports と usage を収集
-        let mut analyzer = Analyzer::new();
-        module.visit_with(&mut analyzer);
+    if args.command == Command::Unused {
+        output::unused::write(args.unused_format()?, report, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if let (Command::Usages, Some(symbol)) = (args.command, &args.symbol) {
+        output::usages::write(args.unused_format()?, report, symbol, args.context, sources, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if let (Command::Path, Some(from), Some(to)) = (args.command, &args.symbol, &args.path_to) {
+        output::path::write(args.unused_format()?, report, graph, from, to, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if args.command == Command::Clusters {
+        output::clusters::write(args.unused_format()?, report, graph, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if args.command == Command::Hubs {
+        let top = args.top.unwrap_or(output::hubs::DEFAULT_TOP);
+        output::hubs::write(args.unused_format()?, report, graph, top, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if args.command == Command::Deps {
+        let top = args.top.unwrap_or(output::deps::DEFAULT_TOP);
+        output::deps::write(args.unused_format()?, report, graph, top, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if args.command == Command::Health {
+        output::health::write(args.unused_format()?, report, graph, &args.health, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    if args.command == Command::Depth {
+        let entry_points: Vec<Glob> = args.entry_points.iter().map(|pattern| Glob::new(pattern)).collect();
+        let top = args.top.unwrap_or(output::depth::DEFAULT_TOP);
+        output::depth::write(args.unused_format()?, report, graph, &entry_points, top, out)?;
+        out.flush()?;
+        return Ok(());
+    }
 
-        // ファイルごとの結果をグローバル集計へマージ
-        for (k, v) in analyzer.usage {
-            *global_counts.entry(k).or_insert(0) += v;
+    if let (Command::Rdeps, Some(query)) = (args.command, &args.symbol) {
+        output::rdeps::write(args.unused_format()?, report, graph, query, out)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    for export in &args.exports {
+        export::write(export, report, graph)?;
+    }
+
+    output::write(args.report_format()?, report, args.output_options, out)?;
+    out.flush()?;
+    Ok(())
+}
+
+// --output 指定時はファイルへ、それ以外は標準出力へ書き出す
+fn open_output(args: &Args) -> Result<Box<dyn Write>> {
+    Ok(match &args.output {
+        Some(path) => Box::new(io::BufWriter::new(fs::File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    })
+}
+
+// 前回から変わっていないファイルはキャッシュの結果を使う (--no-cache で無効)
+fn open_cache(args: &Args) -> Option<Cache> {
+    if args.no_cache {
+        return None;
+    }
+    let dir = args
+        .cache_dir
+        .clone()
+        .unwrap_or_else(|| args.base_dir().join(cache::DEFAULT_DIR));
+    match Cache::open(&dir) {
+        Ok(cache) => Some(cache),
+        Err(err) => {
+            eprintln!("⚠️ キャッシュを使わずに解析します: {}: {err}", dir.display());
+            None
         }
     }
+}
 
-    // 最終結果を降順ソートして出力
-    let mut sorted: Vec<_> = global_counts.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+fn main() -> Result<()> {
+    // 解析対象ディレクトリと出力形式をコマンドライン引数から取得
+    let args = Args::parse()?;
 
-    println!("\n===== インポート名／使用回数（多い順） =====");
-    for (name, count) in sorted {
-        println!("{:<30} {}", name, count);
+    if args.schema {
+        print!("{}", output::json::SCHEMA);
+        return Ok(());
+    }
+
+    if args.command == Command::Daemon {
+        return daemon::run(&args, open_cache(&args));
+    }
+    if args.command == Command::Lsp {
+        return lsp::run(&args);
+    }
+    if args.watch {
+        return watch::run(&args, open_cache(&args).as_ref());
+    }
+    if args.hook {
+        return hook::run(&args);
+    }
+    if args.command == Command::History {
+        return history::run(&args);
+    }
+    if args.command == Command::Diff {
+        return diff::run(&args);
+    }
+    if args.command == Command::Projects {
+        return projects::run(&args);
+    }
+    if args.command == Command::Workspaces {
+        return workspaces::run(&args);
+    }
+    if args.command == Command::Nx {
+        return nx::run(&args);
+    }
+    if args.command == Command::Angular {
+        return angular::run(&args);
+    }
+    if args.command == Command::Impact {
+        return impact::run(&args);
     }
 
+    let mut out = open_output(&args)?;
+    let streaming = args.streaming()?;
+    let config = args.config()?;
+    let mut sources = HashMap::new();
+    let started = Instant::now();
+    let mut cache = None;
+    let mut analysis = match &args.stdin {
+        // 標準入力のソースだけを解析する (ディスクもキャッシュも使わない)
+        Some(path) => {
+            let mut bytes = Vec::new();
+            io::stdin().read_to_end(&mut bytes)?;
+            let Some(source) = encoding::decode(&bytes) else {
+                bail!("標準入力の文字コードを判別できません (UTF-8 / UTF-16 / Shift_JIS のどれでもありません)");
+            };
+            let mut aggregate = Aggregate::with_targets(&args.targets, &config);
+            let outcome = angular_dependency_analyzer::analyze_source(path, source.clone());
+            merge(&mut aggregate, outcome, streaming, &args.filters, &mut out)?;
+            sources.insert(path.clone(), source);
+            aggregate.finish()
+        }
+        None => {
+            cache = open_cache(&args);
+            let mut aggregate = Aggregate::with_targets(&args.targets, &config);
+            let merge_one = |outcome| merge(&mut aggregate, outcome, streaming, &args.filters, &mut out);
+            match &args.changed {
+                Some(base) => {
+                    let changed = git::changed_files(args.base_dir(), base)?;
+                    git::for_each_changed_file(
+                        &args.targets,
+                        &changed,
+                        args.with_dependents,
+                        &config,
+                        cache.as_ref(),
+                        merge_one,
+                    )?;
+                }
+                None => angular_dependency_analyzer::for_each_file(&args.targets, &config, cache.as_ref(), merge_one)?,
+            }
+            aggregate.finish()
+        }
+    };
+    analysis.report.summary.elapsed_ms = Some(started.elapsed().as_millis() as u64);
+    analysis.report.summary.cache = cache.map(|cache| {
+        let (hits, misses) = cache.stats();
+        CacheStats::new(hits, misses)
+    });
+    if let Some(path) = &args.write_baseline {
+        let baseline = Baseline::from_analysis(&analysis);
+        fs::write(path, serde_json::to_string_pretty(&baseline)? + "\n")
+            .with_context(|| format!("ベースラインを書き込めません: {}", path.display()))?;
+    }
+    if let Some(path) = &args.write_cycles_baseline {
+        let known = CyclesBaseline::from_graph(&analysis.graph);
+        fs::write(path, serde_json::to_string_pretty(&known)? + "\n")
+            .with_context(|| format!("循環依存のベースラインを書き込めません: {}", path.display()))?;
+    }
+    // 既知の循環依存は報告せず、閾値の判定では新しいものだけを失敗にする
+    let cycles_baseline = args.cycles_baseline.as_deref().map(load_cycles_baseline).transpose()?;
+    if let Some(known) = &cycles_baseline {
+        known.retain_new(&mut analysis.report);
+    }
+    // ベースラインにもあった指摘は閾値の判定や指摘の出力に含めない
+    let baseline = args.baseline.as_deref().map(load_baseline).transpose()?;
+    if let Some(baseline) = &baseline {
+        let format = args.report_format()?;
+        if args.command == Command::Analyze && matches!(format, OutputFormat::Text | OutputFormat::Json) {
+            output::baseline::write(format, &baseline.diff(&analysis.report), &mut out)?;
+            out.flush()?;
+            baseline.retain_new(&mut analysis.report);
+            check_thresholds(&args, &analysis, Some(baseline), cycles_baseline.as_ref());
+            return Ok(());
+        }
+        baseline.retain_new(&mut analysis.report);
+    }
+    args.retain_filtered(&mut analysis.report);
+    write_report(&analysis, &args, &sources, &mut out)?;
+    out.flush()?;
+    check_thresholds(&args, &analysis, baseline.as_ref(), cycles_baseline.as_ref());
     Ok(())
 }
+
+// 閾値を超えていたら、超えた項目ごとの終了コードで終了する
+fn check_thresholds(
+    args: &Args,
+    analysis: &ProjectAnalysis,
+    baseline: Option<&Baseline>,
+    cycles_baseline: Option<&CyclesBaseline>,
+) {
+    // 個々のエラーはレポートのパースエラーの節に出すので、ここでは件数だけを知らせる
+    let failures = analysis.report.parse_failures.len();
+    if failures > 0 {
+        eprintln!("⚠️ {failures} ファイルはパースに失敗したため集計に含まれていません");
+    }
+    let partial = analysis.report.partial_files.len();
+    if partial > 0 {
+        eprintln!("⚠️ {partial} ファイルはパースエラーの箇所を除いて解析しました");
+    }
+    if let Some(exceeded) = args.thresholds.check(analysis, baseline, cycles_baseline) {
+        eprintln!("Error: {}", exceeded.message);
+        std::process::exit(exceeded.code);
+    }
+}
+
+fn load_baseline(path: &Path) -> Result<Baseline> {
+    let src = fs::read_to_string(path).with_context(|| format!("ベースラインを読み込めません: {}", path.display()))?;
+    let baseline: Baseline =
+        serde_json::from_str(&src).with_context(|| format!("ベースラインの形式が正しくありません: {}", path.display()))?;
+    if baseline.baseline_version != BASELINE_VERSION {
+        bail!(
+            "ベースラインの形式のバージョンが違います ({} / 対応しているのは {BASELINE_VERSION}): {}。--write-baseline で作り直してください",
+            baseline.baseline_version,
+            path.display()
+        );
+    }
+    Ok(baseline)
+}
+
+fn load_cycles_baseline(path: &Path) -> Result<CyclesBaseline> {
+    let src = fs::read_to_string(path).with_context(|| format!("循環依存のベースラインを読み込めません: {}", path.display()))?;
+    let known: CyclesBaseline = serde_json::from_str(&src)
+        .with_context(|| format!("循環依存のベースラインの形式が正しくありません: {}", path.display()))?;
+    if known.baseline_version != BASELINE_VERSION {
+        bail!(
+            "循環依存のベースラインの形式のバージョンが違います ({} / 対応しているのは {BASELINE_VERSION}): {}。--write-cycles-baseline で作り直してください",
+            known.baseline_version,
+            path.display()
+        );
+    }
+    Ok(known)
+}
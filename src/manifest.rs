@@ -0,0 +1,546 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use anyhow::{bail, Result};
+use serde_json::Value;
+use crate::{
+    glob::Glob,
+    graph::ModuleRef,
+    report::{DependencyIssue, DependencyRule, PackageUsage},
+    resolve, semver,
+};
+
+// 解析対象ディレクトリから親へ辿って探す npm のパッケージ定義
+pub const FILE_NAME: &str = "package.json";
+
+// インポートされなくても使われる依存 (コンパイラが出力するヘルパーと、angular.json の polyfills で読み込むもの)
+const IMPLICIT_DEPENDENCIES: [&str; 2] = ["tslib", "zone.js"];
+
+// package.json と同じディレクトリで探すロックファイル (先にあるものを使う)
+pub const LOCKFILES: [&str; 3] = ["package-lock.json", "yarn.lock", "pnpm-lock.yaml"];
+
+// @angular/* のうち、フレームワーク本体とバージョンを揃えずにリリースされるもの
+const INDEPENDENT_ANGULAR_PACKAGES: [&str; 2] = ["@angular/fire", "@angular/flex-layout"];
+
+// テストのファイルとみなすパターン (Glob::matches_path の形式)。devDependencies をインポートしてよい
+pub const TEST_FILES: &[&str] = &[
+    "*.spec.*", "*.test.*", "*.stories.*", "*.cy.*", "test.ts", "setup-jest.ts", "test", "tests", "__tests__",
+    "__mocks__", "testing", "e2e", "cypress",
+];
+
+// package.json に書かずにインポートできる Node.js の組み込みモジュール ('fs/promises' は fs として数える)
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "async_hooks", "buffer", "child_process", "cluster", "console", "constants", "crypto", "dgram",
+    "diagnostics_channel", "dns", "domain", "events", "fs", "http", "http2", "https", "inspector", "module", "net",
+    "os", "path", "perf_hooks", "process", "punycode", "querystring", "readline", "repl", "stream",
+    "string_decoder", "timers", "tls", "trace_events", "tty", "url", "util", "v8", "vm", "wasi", "worker_threads",
+    "zlib",
+];
+
+// Node.js の組み込みモジュールか ('node:' 等のスキームが付いたものを含む)
+pub fn is_node_builtin(package: &str) -> bool {
+    package.contains(':') || NODE_BUILTINS.contains(&package)
+}
+
+// node_modules/<名前>/package.json から読んだ、インストールされているパッケージ
+#[derive(Debug, Clone, Default)]
+pub struct InstalledPackage {
+    pub version: String,
+    // ピア依存の名前と範囲 (peerDependenciesMeta で optional にしたものはインストールされていなくてもよい)
+    pub peer_dependencies: BTreeMap<String, String>,
+    pub optional_peers: BTreeSet<String>,
+}
+
+impl InstalledPackage {
+    pub fn parse(src: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(src)?;
+        Ok(Self {
+            version: value.get("version").and_then(Value::as_str).unwrap_or_default().to_string(),
+            peer_dependencies: value
+                .get("peerDependencies")
+                .and_then(Value::as_object)
+                .map(|peers| {
+                    peers
+                        .iter()
+                        .map(|(name, range)| (name.clone(), range.as_str().unwrap_or_default().to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            optional_peers: value
+                .get("peerDependenciesMeta")
+                .and_then(Value::as_object)
+                .map(|meta| {
+                    meta.iter()
+                        .filter(|(_, meta)| meta.get("optional").and_then(Value::as_bool) == Some(true))
+                        .map(|(name, _)| name.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+// package.json の依存の宣言
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    // 指摘に表示する package.json のパス
+    pub path: String,
+    // パッケージ自身の名前 (自分自身を名前でインポートするのは宣言が無くてもよい)
+    pub name: Option<String>,
+    // workspaces のパッケージのディレクトリのグロブ (yarn の { "packages": [...] } の形も読む)
+    pub workspaces: Vec<String>,
+    // exports で公開しているサブパス ("." / "./testing" 等)。exports が無ければ None
+    pub exports: Option<Vec<String>>,
+    // source / types / typings / module / main のうち最初にあるもの (パッケージの入口のファイル)
+    pub entry: Option<String>,
+    pub dependencies: BTreeMap<String, String>,
+    pub dev_dependencies: BTreeMap<String, String>,
+    pub peer_dependencies: BTreeMap<String, String>,
+    pub optional_dependencies: BTreeMap<String, String>,
+    // package.json との照合で指摘しないパッケージ (グロブ)
+    pub ignore: Vec<Glob>,
+    // devDependencies をインポートしてよいテストのファイル (解析対象からの相対パスと比べる)
+    pub test_files: Vec<Glob>,
+    // ロックファイルにあるインストールされるバージョン (直接の依存のみ)
+    pub locked: BTreeMap<String, String>,
+    // locked を読んだロックファイルの名前
+    pub lockfile: Option<String>,
+    // node_modules にインストールされているパッケージ (宣言した依存と、それらのピア依存)
+    pub installed: BTreeMap<String, InstalledPackage>,
+    // 行番号を求めるための package.json の内容
+    source: String,
+}
+
+impl Manifest {
+    pub fn parse(path: &str, src: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(src)?;
+        if !value.is_object() {
+            bail!("package.json の内容がオブジェクトではありません");
+        }
+        let section = |key: &str| -> BTreeMap<String, String> {
+            value
+                .get(key)
+                .and_then(Value::as_object)
+                .map(|deps| {
+                    deps.iter()
+                        .map(|(name, version)| (name.clone(), version.as_str().unwrap_or_default().to_string()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Ok(Self {
+            path: path.to_string(),
+            name: value.get("name").and_then(Value::as_str).map(str::to_string),
+            workspaces: value
+                .get("workspaces")
+                .and_then(|workspaces| workspaces.get("packages").or(Some(workspaces)))
+                .and_then(Value::as_array)
+                .map(|patterns| patterns.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                .unwrap_or_default(),
+            exports: value.get("exports").map(|exports| match exports.as_object() {
+                Some(map) if map.keys().any(|key| key.starts_with('.')) => {
+                    map.keys().filter(|key| key.starts_with('.')).cloned().collect()
+                }
+                // 文字列や条件 (import / require 等) だけなら "." のみ
+                _ => vec![".".to_string()],
+            }),
+            entry: ["source", "types", "typings", "module", "main"]
+                .into_iter()
+                .find_map(|key| value.get(key).and_then(Value::as_str))
+                .map(str::to_string),
+            dependencies: section("dependencies"),
+            dev_dependencies: section("devDependencies"),
+            peer_dependencies: section("peerDependencies"),
+            optional_dependencies: section("optionalDependencies"),
+            ignore: Vec::new(),
+            test_files: TEST_FILES.iter().map(|pattern| Glob::new(pattern)).collect(),
+            locked: BTreeMap::new(),
+            lockfile: None,
+            installed: BTreeMap::new(),
+            source: src.to_string(),
+        })
+    }
+
+    // サブパス ("." / "./testing") を exports で公開しているか ("./features/*" のようなパターンも見る)。
+    // exports が無ければ入口 (".") だけを公開しているとみなす
+    pub fn exports_subpath(&self, subpath: &str) -> bool {
+        let Some(exports) = &self.exports else {
+            return subpath == ".";
+        };
+        exports.iter().any(|export| match export.split_once('*') {
+            Some((prefix, suffix)) => subpath.len() >= prefix.len() + suffix.len()
+                && subpath.starts_with(prefix)
+                && subpath.ends_with(suffix),
+            None => export == subpath,
+        })
+    }
+
+    // ロックファイル (LOCKFILES のいずれか) から、package.json で宣言したパッケージのインストールされるバージョンを読む
+    pub fn read_lockfile(&mut self, name: &str, src: &str) -> Result<()> {
+        let locked = match name {
+            "package-lock.json" => lock_npm(src)?,
+            "yarn.lock" => lock_yarn(src, |package| self.declared_range(package)),
+            _ => lock_pnpm(src),
+        };
+        self.locked = locked.into_iter().filter(|(package, _)| self.declares(package)).collect();
+        self.lockfile = Some(name.to_string());
+        Ok(())
+    }
+
+    // @angular/* のメジャー・マイナーバージョンが @angular/core (無ければ最も多いもの) と違うパッケージ。
+    // バージョンはロックファイルにあればそれを、無ければ package.json の範囲の下限を使う (^ の範囲はマイナーを比べない)
+    pub fn angular_version_skew(&self, packages: &[PackageUsage]) -> Vec<DependencyIssue> {
+        let names: BTreeSet<&String> = self.dependencies.keys().chain(self.dev_dependencies.keys()).collect();
+        let versions: Vec<PackageVersion> = names
+            .into_iter()
+            .filter(|name| name.starts_with("@angular/") && !INDEPENDENT_ANGULAR_PACKAGES.contains(&name.as_str()))
+            .filter_map(|name| {
+                let (version, source) = match (self.locked.get(name), &self.lockfile) {
+                    (Some(version), Some(lockfile)) => (version.as_str(), lockfile.as_str()),
+                    _ => (self.declared_range(name)?, FILE_NAME),
+                };
+                let (major, minor) = major_minor(version)?;
+                let minor = minor.filter(|_| !version.starts_with('^'));
+                Some(PackageVersion { name, version, source, major, minor })
+            })
+            .collect();
+        let reference = match versions.iter().find(|v| v.name == "@angular/core") {
+            Some(core) => core,
+            None => {
+                let mut counts: BTreeMap<u64, usize> = BTreeMap::new();
+                for v in &versions {
+                    *counts.entry(v.major).or_default() += 1;
+                }
+                let Some((&major, _)) = counts.iter().max_by_key(|(_, count)| **count) else {
+                    return Vec::new();
+                };
+                let Some(common) = versions.iter().find(|v| v.major == major) else {
+                    return Vec::new();
+                };
+                common
+            }
+        };
+        versions
+            .iter()
+            .filter(|v| {
+                v.name != reference.name
+                    && (v.major != reference.major || matches!((v.minor, reference.minor), (Some(a), Some(b)) if a != b))
+            })
+            .map(|v| {
+                let usage = match packages.iter().find(|p| p.package == *v.name) {
+                    Some(usage) => format!("使用回数 {} / {} ファイル", usage.count, usage.files),
+                    None => "インポートなし".to_string(),
+                };
+                let section = if self.dependencies.contains_key(v.name) { "dependencies" } else { "devDependencies" };
+                let (line, column) = self.position(section, v.name);
+                DependencyIssue {
+                    rule: DependencyRule::AngularVersionSkew,
+                    package: v.name.clone(),
+                    path: self.path.clone(),
+                    line,
+                    column,
+                    message: format!(
+                        "'{}' {} ({}) は {} {} ({}) とメジャー・マイナーバージョンが違います ({usage})",
+                        v.name, v.version, v.source, reference.name, reference.version, reference.source
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    // インポートしているパッケージのピア依存のうち、インストールされていないか範囲を満たさないもの。
+    // パッケージの使用回数の多い順 (packages の順) に並べる
+    pub fn unmet_peers(&self, packages: &[PackageUsage], imported: &HashSet<String>) -> Vec<DependencyIssue> {
+        let mut ranked: Vec<(&str, Option<&PackageUsage>)> = packages
+            .iter()
+            .filter(|usage| imported.contains(&usage.package))
+            .map(|usage| (usage.package.as_str(), Some(usage)))
+            .collect();
+        // 副作用のみのインポート等で使用回数の無いパッケージは最後に名前順で
+        let mut rest: Vec<&str> = imported
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !packages.iter().any(|usage| usage.package == *name))
+            .collect();
+        rest.sort_unstable();
+        ranked.extend(rest.into_iter().map(|name| (name, None)));
+
+        let mut issues = Vec::new();
+        for (name, usage) in ranked {
+            let Some(package) = self.installed.get(name) else {
+                continue;
+            };
+            let usage = match usage {
+                Some(usage) => format!("使用回数 {} / {} ファイル", usage.count, usage.files),
+                None => "使用回数 0".to_string(),
+            };
+            for (peer, range) in &package.peer_dependencies {
+                let problem = match self.installed.get(peer) {
+                    None if package.optional_peers.contains(peer) => continue,
+                    None => format!("がインストールされていません ({usage})"),
+                    Some(installed) if !semver::satisfies(&installed.version, range) => {
+                        format!("を満たしていません (インストールされているのは {}。{usage})", installed.version)
+                    }
+                    Some(_) => continue,
+                };
+                let section = if self.dependencies.contains_key(name) { "dependencies" } else { "devDependencies" };
+                let (line, column) = self.position(section, name);
+                issues.push(DependencyIssue {
+                    rule: DependencyRule::UnmetPeerDependency,
+                    package: name.to_string(),
+                    path: self.path.clone(),
+                    line,
+                    column,
+                    message: format!("'{name}' {} のピア依存 '{peer}' ({range}) {problem}", package.version),
+                });
+            }
+        }
+        issues
+    }
+
+    fn declared_range(&self, package: &str) -> Option<&str> {
+        [&self.dependencies, &self.dev_dependencies, &self.peer_dependencies, &self.optional_dependencies]
+            .into_iter()
+            .find_map(|deps| deps.get(package))
+            .map(String::as_str)
+    }
+
+    // dependencies にあるが、どのファイルからもインポートされていないパッケージ
+    pub fn unused_dependencies(&self, imported: &HashSet<String>) -> Vec<DependencyIssue> {
+        self.dependencies
+            .iter()
+            .filter(|(name, _)| !imported.contains(*name) && !self.is_implicit(name))
+            .map(|(name, version)| {
+                let (line, column) = self.position("dependencies", name);
+                DependencyIssue {
+                    rule: DependencyRule::UnusedDependency,
+                    package: name.clone(),
+                    path: self.path.clone(),
+                    line,
+                    column,
+                    message: format!("'{name}' ({version}) は dependencies にありますが、どこからもインポートされていません"),
+                }
+            })
+            .collect()
+    }
+
+    // 1 ファイルのインポートを package.json と照合する。relative はテストのファイルかを判定する解析対象からの相対パス
+    pub fn check_imports(&self, path: &str, relative: &str, sources: &[ModuleRef]) -> Vec<DependencyIssue> {
+        let is_test = self.test_files.iter().any(|glob| glob.matches_path(relative));
+        sources
+            .iter()
+            .filter_map(|source| {
+                let package = resolve::package_name(&source.specifier)?;
+                if self.is_builtin(package) {
+                    return None;
+                }
+                let issue = |rule, message| DependencyIssue {
+                    rule,
+                    package: package.to_string(),
+                    path: path.to_string(),
+                    line: source.line,
+                    column: source.column,
+                    message,
+                };
+                // 他のパッケージの依存として node_modules の直下に置かれているから動いているだけで、
+                // pnpm の strict モード等では解決できない
+                if !self.declares(package) {
+                    let message = format!("'{package}' は package.json の dependencies / devDependencies にありません");
+                    return Some(issue(DependencyRule::UndeclaredDependency, message));
+                }
+                // 本番用のインストール (npm ci --omit=dev 等) では入らない。型だけの参照はコンパイル後に消えるのでよい
+                if !is_test && !source.type_only && self.is_dev_only(package) {
+                    let message = format!("'{package}' は devDependencies にしかありませんが、本番のコードからインポートされています");
+                    return Some(issue(DependencyRule::DevDependencyInProduction, message));
+                }
+                None
+            })
+            .collect()
+    }
+
+    // dependencies / devDependencies (と peerDependencies / optionalDependencies) にある
+    fn declares(&self, package: &str) -> bool {
+        self.dependencies.contains_key(package)
+            || self.dev_dependencies.contains_key(package)
+            || self.peer_dependencies.contains_key(package)
+            || self.optional_dependencies.contains_key(package)
+    }
+
+    fn is_dev_only(&self, package: &str) -> bool {
+        self.dev_dependencies.contains_key(package)
+            && !self.dependencies.contains_key(package)
+            && !self.peer_dependencies.contains_key(package)
+            && !self.optional_dependencies.contains_key(package)
+    }
+
+    // Node.js の組み込みモジュール ('node:' 等のスキームが付いたものを含む)・自分自身・指摘しないパッケージ
+    fn is_builtin(&self, package: &str) -> bool {
+        is_node_builtin(package)
+            || self.name.as_deref() == Some(package)
+            || self.ignore.iter().any(|glob| glob.matches(package))
+    }
+
+    // 型定義 (@types/*) と、インポートせずに使われるパッケージ
+    fn is_implicit(&self, name: &str) -> bool {
+        name.starts_with("@types/")
+            || IMPLICIT_DEPENDENCIES.contains(&name)
+            || self.ignore.iter().any(|glob| glob.matches(name))
+    }
+
+    // section の中で name を宣言している位置 (1 始まり)。見つからなければ section の位置か 1 行目
+    fn position(&self, section: &str, name: &str) -> (usize, usize) {
+        let quoted = |key: &str| format!("\"{key}\"");
+        let Some(start) = self.source.find(&format!("{}:", quoted(section))).or_else(|| self.source.find(&quoted(section)))
+        else {
+            return (1, 1);
+        };
+        let offset = self.source[start..].find(&quoted(name)).map_or(start, |i| start + i);
+        let before = &self.source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |s| s.chars().count()) + 1;
+        (line, column)
+    }
+}
+
+// (メジャー, マイナー)。"^17" / "17.x" のようにマイナーの無い範囲は None
+type MajorMinor = (u64, Option<u64>);
+
+// バージョンを比べる 1 パッケージ (source はバージョンを読んだファイル)
+struct PackageVersion<'a> {
+    name: &'a String,
+    version: &'a str,
+    source: &'a str,
+    major: u64,
+    minor: Option<u64>,
+}
+
+// pnpm-workspace.yaml の packages に並べたグロブ ('!' で始まるものは除外)
+pub fn parse_pnpm_workspace(src: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+    for line in src.lines() {
+        let trimmed = line.split(" #").next().unwrap_or(line).trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '-']) {
+            in_packages = trimmed == "packages:";
+            continue;
+        }
+        if in_packages && let Some(item) = trimmed.strip_prefix('-') {
+            patterns.push(item.trim().trim_matches(|c| c == '\'' || c == '"').to_string());
+        }
+    }
+    patterns
+}
+
+// "^17.1.0" / "~17.1" / ">=17.0.0 <18" / "17.1.2" の先頭のバージョンの (メジャー, マイナー)。
+// "latest" や "file:..." のように数字で始まらないものは None
+fn major_minor(version: &str) -> Option<MajorMinor> {
+    let version = version.trim_start_matches(['^', '~', '>', '<', '=', 'v', ' ']);
+    let mut parts = version.split_whitespace().next()?.split('.');
+    let major = parts.next()?.parse().ok()?;
+    Some((major, parts.next().and_then(|minor| minor.parse().ok())))
+}
+
+// package-lock.json: lockfileVersion 2 以降は packages の "node_modules/<名前>"、1 は dependencies の <名前>
+fn lock_npm(src: &str) -> Result<BTreeMap<String, String>> {
+    let value: Value = serde_json::from_str(src)?;
+    let version = |entry: &Value| entry.get("version").and_then(Value::as_str).map(str::to_string);
+    let mut locked = BTreeMap::new();
+    if let Some(packages) = value.get("packages").and_then(Value::as_object) {
+        for (key, entry) in packages {
+            // 入れ子の node_modules は他のパッケージの依存
+            if let Some(name) = key.strip_prefix("node_modules/").filter(|name| !name.contains("/node_modules/"))
+                && let Some(version) = version(entry)
+            {
+                locked.insert(name.to_string(), version);
+            }
+        }
+    } else if let Some(dependencies) = value.get("dependencies").and_then(Value::as_object) {
+        for (name, entry) in dependencies {
+            if let Some(version) = version(entry) {
+                locked.insert(name.clone(), version);
+            }
+        }
+    }
+    Ok(locked)
+}
+
+// yarn.lock: 字下げの無い '"<名前>@<範囲>", "<名前>@<範囲>":' の見出しと、その下の 'version "x.y.z"'
+// (Yarn 2 以降は 'version: x.y.z'、範囲は 'npm:<範囲>')。同じ名前が複数あれば package.json の範囲のものを使う
+fn lock_yarn<'a>(src: &str, declared: impl Fn(&str) -> Option<&'a str>) -> BTreeMap<String, String> {
+    let mut locked = BTreeMap::new();
+    let mut current: Vec<(String, String)> = Vec::new();
+    for line in src.lines() {
+        if !line.starts_with([' ', '#']) && line.ends_with(':') {
+            current = line
+                .trim_end_matches(':')
+                .split(", ")
+                .filter_map(|spec| {
+                    let spec = spec.trim().trim_matches('"');
+                    let at = spec[1..].find('@')? + 1;
+                    let range = spec[at + 1..].trim_start_matches("npm:");
+                    Some((spec[..at].to_string(), range.to_string()))
+                })
+                .collect();
+        } else if let Some(version) = line.trim_start().strip_prefix("version") {
+            let version = version.trim_start_matches(':').trim().trim_matches('"').to_string();
+            for (name, range) in current.drain(..) {
+                let exact = declared(&name) == Some(range.as_str());
+                if exact || !locked.contains_key(&name) {
+                    locked.insert(name, version.clone());
+                }
+            }
+        }
+    }
+    locked
+}
+
+// pnpm-lock.yaml: dependencies / devDependencies の "'<名前>': x.y.z" (lockfileVersion 5)、
+// またはその下の "version: x.y.z" (6 以降)。"(rxjs@7.8.1)" / "_rxjs@7.8.1" のようなピアの情報は除く
+fn lock_pnpm(src: &str) -> BTreeMap<String, String> {
+    let mut locked = BTreeMap::new();
+    let mut in_dependencies = false;
+    let mut current: Option<(String, usize)> = None;
+    for line in src.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if matches!(trimmed, "dependencies:" | "devDependencies:" | "optionalDependencies:") {
+            in_dependencies = true;
+            current = None;
+            continue;
+        }
+        if let Some((name, depth)) = &current
+            && indent > *depth
+        {
+            if let Some(version) = trimmed.strip_prefix("version:") {
+                locked.entry(name.clone()).or_insert_with(|| pnpm_version(version));
+            }
+            continue;
+        }
+        current = None;
+        let Some((key, value)) = trimmed.split_once(':').filter(|_| in_dependencies) else {
+            continue;
+        };
+        // importers 以外 (packages 等) の見出しに戻った
+        if indent == 0 {
+            in_dependencies = false;
+            continue;
+        }
+        let name = key.trim_matches(|c| c == '\'' || c == '"');
+        match value.trim() {
+            "" => current = Some((name.to_string(), indent)),
+            version => {
+                locked.entry(name.to_string()).or_insert_with(|| pnpm_version(version));
+            }
+        }
+    }
+    locked
+}
+
+fn pnpm_version(version: &str) -> String {
+    let version = version.trim().trim_matches(|c| c == '\'' || c == '"');
+    version.split(['(', '_']).next().unwrap_or(version).to_string()
+}
@@ -0,0 +1,345 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use angular_dependency_analyzer::{
+    glob::Glob,
+    graph::ModuleRef,
+    output,
+    resolve::PathAliases,
+    tsconfig,
+};
+use crate::{
+    cwd_for, open_output,
+    projects::{self, Members, Project},
+    Args,
+};
+
+// Nx のワークスペースのルートにあるファイル
+const NX_JSON: &str = "nx.json";
+// Nx のプロジェクトの定義
+const PROJECT_JSON: &str = "project.json";
+// Nx のワークスペースの paths を書く tsconfig
+const TSCONFIG_BASE: &str = "tsconfig.base.json";
+// @nx/enforce-module-boundaries の depConstraints を書く ESLint の設定
+const ESLINTRC: [&str; 2] = [".eslintrc.json", ".eslintrc"];
+
+// project.json を探す深さの上限 (ワークスペースのルートから)
+const MAX_PROJECT_DEPTH: usize = 8;
+
+// Nx のプロジェクト 1 つ (project.json)
+struct NxProject {
+    name: String,
+    // project.json のあるディレクトリ (正規化した絶対パス)
+    dir: PathBuf,
+    tags: Vec<String>,
+    // projectType (application / library)
+    kind: Option<String>,
+    // targets.build.executor
+    build: Option<String>,
+}
+
+// @nx/enforce-module-boundaries の depConstraints 1 件
+struct DepConstraint {
+    source_tag: Option<String>,
+    // すべてのタグを持つプロジェクトに当てはまる (sourceTag の代わりに使う)
+    all_source_tags: Vec<String>,
+    only_depend_on: Vec<String>,
+    not_depend_on: Vec<String>,
+}
+
+impl DepConstraint {
+    fn parse(value: &Value) -> Self {
+        let strings = |key: &str| -> Vec<String> {
+            value
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        };
+        Self {
+            source_tag: value.get("sourceTag").and_then(Value::as_str).map(str::to_string),
+            all_source_tags: strings("allSourceTags"),
+            only_depend_on: strings("onlyDependOnLibsWithTags"),
+            not_depend_on: strings("notDependOnLibsWithTags"),
+        }
+    }
+
+    // tags のプロジェクトに当てはまるか ("*" はタグの無いプロジェクトにも当てはまる)
+    fn applies(&self, tags: &[String]) -> bool {
+        match &self.source_tag {
+            Some(source) => source == "*" || tags.iter().any(|tag| tag_matches(source, tag)),
+            None => {
+                !self.all_source_tags.is_empty()
+                    && self.all_source_tags.iter().all(|source| tags.iter().any(|tag| tag_matches(source, tag)))
+            }
+        }
+    }
+
+    // 当てはまるときの表示 (sourceTag か allSourceTags)
+    fn source(&self) -> String {
+        match &self.source_tag {
+            Some(source) => source.clone(),
+            None => self.all_source_tags.join(" + "),
+        }
+    }
+
+    // to_tags のプロジェクトへの依存が許されなければその理由
+    fn check(&self, to: &str, to_tags: &[String]) -> Option<String> {
+        let has = |pattern: &String| to_tags.iter().any(|tag| tag_matches(pattern, tag));
+        if !self.only_depend_on.is_empty() && !self.only_depend_on.iter().any(has) {
+            return Some(format!(
+                "{} のプロジェクトは {} のタグのプロジェクトにしか依存できませんが、'{to}' のタグは {} です",
+                self.source(),
+                self.only_depend_on.join(", "),
+                display_tags(to_tags)
+            ));
+        }
+        let banned = self.not_depend_on.iter().find(|pattern| has(pattern))?;
+        Some(format!(
+            "{} のプロジェクトは {banned} のタグのプロジェクトに依存できませんが、'{to}' のタグは {} です",
+            self.source(),
+            display_tags(to_tags)
+        ))
+    }
+}
+
+// タグのパターン ("*"、scope:* のようなグロブ) が tag に一致するか
+fn tag_matches(pattern: &str, tag: &str) -> bool {
+    pattern == "*" || Glob::new(pattern).matches(tag)
+}
+
+fn display_tags(tags: &[String]) -> String {
+    match tags.is_empty() {
+        true => "(なし)".to_string(),
+        false => tags.join(", "),
+    }
+}
+
+// nx コマンド: nx.json のあるワークスペースの project.json ごとに解析し、プロジェクトをまたぐファイル間の依存を出力する。
+// --nx-graph で nx graph --file の出力を渡すと、Nx が認識している依存と実際のインポートを比べる
+pub fn run(args: &Args) -> Result<()> {
+    let Some(root) = find_root(&args.targets[0])? else {
+        bail!("Nx のワークスペースが見つかりません (nx.json が必要です)");
+    };
+    let nx_projects = find_projects(&root)?;
+    if nx_projects.is_empty() {
+        bail!("Nx のプロジェクト (project.json) が見つかりません: {}", root.display());
+    }
+    let graph = match &args.nx_graph {
+        Some(path) => Some(load_graph(path)?),
+        None => None,
+    };
+
+    let aliases = workspace_aliases(args, &root)?;
+    let configs: BTreeMap<&str, PathBuf> =
+        nx_projects.iter().map(|project| (project.name.as_str(), project.dir.join(PROJECT_JSON))).collect();
+    let projects: Vec<Project> = nx_projects
+        .iter()
+        .map(|project| Project {
+            name: project.name.clone(),
+            config: project.dir.join(PROJECT_JSON),
+            members: Members::Dir(project.dir.clone()),
+            references: graph
+                .as_ref()
+                .and_then(|graph| graph.get(&project.name))
+                .into_iter()
+                .flatten()
+                .filter_map(|target| configs.get(target.as_str()).cloned())
+                .collect(),
+            aliases: aliases.clone(),
+            kind: project.kind.clone(),
+            build: project.build.clone(),
+        })
+        .collect();
+    let constraints = load_constraints(args, &root)?;
+    let tags: BTreeMap<&str, &[String]> =
+        nx_projects.iter().map(|project| (project.name.as_str(), project.tags.as_slice())).collect();
+    let check = |from: &Project, to: &Project, _: &ModuleRef| {
+        check_constraints(&constraints, &from.name, tags[from.name.as_str()], &to.name, tags[to.name.as_str()])
+    };
+    let undeclared = graph.as_ref().map(|_| "nx graph にありません");
+    let report = projects::analyze(args, projects, aliases, "Nx", undeclared, &check)?;
+    let mut out = open_output(args)?;
+    output::projects::write(args.projects_format()?, &report, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+// start とその親ディレクトリから nx.json のあるディレクトリを探す
+fn find_root(start: &Path) -> Result<Option<PathBuf>> {
+    let start = fs::canonicalize(start)?;
+    Ok(start.ancestors().find(|dir| dir.join(NX_JSON).is_file()).map(Path::to_path_buf))
+}
+
+// root の下の project.json (node_modules と . で始まるディレクトリは除く)
+fn find_projects(root: &Path) -> Result<Vec<NxProject>> {
+    let mut projects = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        let path = dir.join(PROJECT_JSON);
+        if path.is_file() {
+            let src = fs::read_to_string(&path).with_context(|| format!("project.json を読み込めません: {}", path.display()))?;
+            let json: Value = serde_json::from_str(&src)
+                .with_context(|| format!("project.json の形式が正しくありません: {}", path.display()))?;
+            // name が無ければ Nx と同じくディレクトリ名を使う
+            let name = match json.get("name").and_then(Value::as_str) {
+                Some(name) => name.to_string(),
+                None => dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| ".".to_string()),
+            };
+            let tags = json
+                .get("tags")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect();
+            let kind = json.get("projectType").and_then(Value::as_str).map(str::to_string);
+            let build = json.pointer("/targets/build/executor").and_then(Value::as_str).map(str::to_string);
+            projects.push(NxProject {
+                name,
+                dir: dir.clone(),
+                tags,
+                kind,
+                build,
+            });
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if depth + 1 < MAX_PROJECT_DEPTH && entry.path().is_dir() && name != "node_modules" && !name.starts_with('.') {
+                pending.push((entry.path(), depth + 1));
+            }
+        }
+    }
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+    if let Some(pair) = projects.windows(2).find(|pair| pair[0].name == pair[1].name) {
+        bail!(
+            "Nx のプロジェクト名 '{}' が重複しています: {} と {}",
+            pair[0].name,
+            pair[0].dir.display(),
+            pair[1].dir.display()
+        );
+    }
+    Ok(projects)
+}
+
+// from のプロジェクトから to のプロジェクトへの依存が depConstraints で許されなければその理由
+fn check_constraints(
+    constraints: &[DepConstraint],
+    from: &str,
+    from_tags: &[String],
+    to: &str,
+    to_tags: &[String],
+) -> Option<String> {
+    if constraints.is_empty() {
+        return None;
+    }
+    let applicable: Vec<&DepConstraint> = constraints.iter().filter(|constraint| constraint.applies(from_tags)).collect();
+    // Nx と同じく、どの決まりにも当てはまらないプロジェクトはタグのあるプロジェクトに依存できない
+    if applicable.is_empty() {
+        return (!to_tags.is_empty()).then(|| {
+            format!(
+                "'{from}' のタグ ({}) に当てはまる depConstraints が無いため、タグのある '{to}' には依存できません",
+                display_tags(from_tags)
+            )
+        });
+    }
+    let reason = applicable.iter().find_map(|constraint| constraint.check(to, to_tags))?;
+    Some(format!("'{from}' → '{to}': {reason}"))
+}
+
+// --nx-constraints のファイルか、ワークスペースのルートの .eslintrc.json の depConstraints
+fn load_constraints(args: &Args, root: &Path) -> Result<Vec<DepConstraint>> {
+    let path = match &args.nx_constraints {
+        Some(path) => path.clone(),
+        None => match ESLINTRC.iter().map(|name| root.join(name)).find(|path| path.is_file()) {
+            Some(path) => path,
+            None => return Ok(Vec::new()),
+        },
+    };
+    let src = fs::read_to_string(&path).with_context(|| format!("depConstraints を読み込めません: {}", path.display()))?;
+    let json: Value = serde_json::from_str(&tsconfig::strip_jsonc(&src))
+        .with_context(|| format!("depConstraints のファイルの形式が正しくありません: {}", path.display()))?;
+    let constraints = match &json {
+        Value::Array(_) => Some(&json),
+        _ => find_key(&json, "depConstraints"),
+    };
+    let constraints: Vec<DepConstraint> =
+        constraints.and_then(Value::as_array).into_iter().flatten().map(DepConstraint::parse).collect();
+    let mut patterns = constraints.iter().flat_map(|constraint| {
+        constraint
+            .source_tag
+            .iter()
+            .chain(&constraint.all_source_tags)
+            .chain(&constraint.only_depend_on)
+            .chain(&constraint.not_depend_on)
+    });
+    if let Some(pattern) = patterns.find(|pattern| pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/')) {
+        eprintln!("⚠️ 正規表現のタグ ({pattern}) には対応していません (文字列として比べます)");
+    }
+    if args.nx_constraints.is_some() && constraints.is_empty() {
+        bail!("depConstraints が見つかりません: {}", path.display());
+    }
+    Ok(constraints)
+}
+
+// value の中を辿って最初に見つかった key の値 (.eslintrc.json の overrides[].rules[...][1] 等)
+fn find_key<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map.get(key).or_else(|| map.values().find_map(|value| find_key(value, key))),
+        Value::Array(values) => values.iter().find_map(|value| find_key(value, key)),
+        _ => None,
+    }
+}
+
+// --tsconfig を指定していなければ、ワークスペースのルートの tsconfig.base.json の paths でインポートを解決する
+fn workspace_aliases(args: &Args, root: &Path) -> Result<PathAliases> {
+    let base = root.join(TSCONFIG_BASE);
+    let given = args
+        .tsconfig
+        .as_ref()
+        .is_some_and(|tsconfig| tsconfig::find(&args.targets[0]).is_none_or(|found| found != tsconfig.path));
+    if given || !base.is_file() {
+        return Ok(args.path_aliases.clone());
+    }
+    let mut aliases = tsconfig::load(&base)?.path_aliases(cwd_for(&args.targets[0])?);
+    for (name, target) in &args.aliases {
+        aliases.add_alias(name, target);
+    }
+    Ok(aliases)
+}
+
+// nx graph --file=graph.json の出力から、プロジェクトごとに Nx が認識している依存先 (npm のパッケージと暗黙の依存は除く)
+fn load_graph(path: &Path) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let src = fs::read_to_string(path).with_context(|| format!("nx graph の出力を読み込めません: {}", path.display()))?;
+    let json: Value =
+        serde_json::from_str(&src).with_context(|| format!("nx graph の出力の形式が正しくありません: {}", path.display()))?;
+    let graph = json.get("graph").unwrap_or(&json);
+    let Some(dependencies) = graph.get("dependencies").and_then(Value::as_object) else {
+        bail!("nx graph の出力に dependencies がありません: {}", path.display());
+    };
+    let mut claimed = BTreeMap::new();
+    for (name, targets) in dependencies {
+        let targets: BTreeSet<String> = targets
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|dependency| dependency.get("type").and_then(Value::as_str) != Some("implicit"))
+            .filter_map(|dependency| dependency.get("target").and_then(Value::as_str))
+            .filter(|target| !target.starts_with("npm:"))
+            .map(str::to_string)
+            .collect();
+        claimed.insert(name.clone(), targets);
+    }
+    Ok(claimed)
+}
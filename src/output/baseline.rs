@@ -0,0 +1,117 @@
+use std::io::Write;
+use anyhow::Result;
+use super::OutputFormat;
+use crate::{
+    baseline::{BaselineDiff, TreeDiff, UsageDelta},
+    graph::Edge,
+};
+
+// --baseline 指定時の text / json 出力。ベースラインから変わったものだけを表示する
+pub fn write(format: OutputFormat, diff: &BaselineDiff, out: &mut impl Write) -> Result<()> {
+    if format == OutputFormat::Json {
+        serde_json::to_writer_pretty(&mut *out, diff)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+    write_text(diff, out)
+}
+
+// diff コマンドの text / json 出力。インポートの差分に加えて、ファイルとファイル間の依存の増減を表示する
+pub fn write_tree_diff(format: OutputFormat, diff: &TreeDiff, out: &mut impl Write) -> Result<()> {
+    if format == OutputFormat::Json {
+        serde_json::to_writer_pretty(&mut *out, diff)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== {} → {} =====", diff.before, diff.after)?;
+    for (title, files) in [("追加されたファイル", &diff.added_files), ("削除されたファイル", &diff.removed_files)] {
+        if !files.is_empty() {
+            writeln!(out, "\n===== {title} =====")?;
+            for file in files {
+                writeln!(out, "{file}")?;
+            }
+        }
+    }
+    write_edges(out, "新しいファイル間の依存", &diff.added_edges)?;
+    write_edges(out, "なくなったファイル間の依存", &diff.removed_edges)?;
+    write_text(&diff.usages, out)?;
+    writeln!(
+        out,
+        "ファイル: 追加 {} / 削除 {}、ファイル間の依存: 追加 {} / 削除 {}",
+        diff.added_files.len(),
+        diff.removed_files.len(),
+        diff.added_edges.len(),
+        diff.removed_edges.len()
+    )?;
+    Ok(())
+}
+
+fn write_text(diff: &BaselineDiff, out: &mut impl Write) -> Result<()> {
+    write_usages(out, "新しくインポートされたもの", &diff.added, |d| {
+        format!("{} 回 / {} ファイル", d.after, d.files_after)
+    })?;
+    write_usages(out, "インポートされなくなったもの", &diff.removed, |d| {
+        format!("{} 回 / {} ファイル", d.before, d.files_before)
+    })?;
+    write_usages(out, "使用回数の変化", &diff.changed, |d| {
+        format!(
+            "{} → {} ({:+}) / {} → {} ファイル",
+            d.before, d.after, d.delta, d.files_before, d.files_after
+        )
+    })?;
+
+    if !diff.new_unused_imports.is_empty() {
+        writeln!(out, "\n===== 新しい未使用のインポート =====")?;
+        for unused in &diff.new_unused_imports {
+            writeln!(out, "{}:{}:{}  {} ({})", unused.path, unused.line, unused.column, unused.name, unused.module)?;
+        }
+    }
+    if !diff.new_duplicate_imports.is_empty() {
+        writeln!(out, "\n===== 新しい重複した import 文 =====")?;
+        for duplicate in &diff.new_duplicate_imports {
+            let lines: Vec<_> = duplicate.lines.iter().map(|l| l.to_string()).collect();
+            writeln!(out, "{}  {}  ({} 行目)", duplicate.path, duplicate.module, lines.join(", "))?;
+        }
+    }
+
+    writeln!(
+        out,
+        "\n合計: 新しいインポート {} / なくなったインポート {} / 回数の変化 {} / 新しい未使用 {} / 解消した未使用 {} / 新しい重複 {}",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.new_unused_imports.len(),
+        diff.fixed_unused_imports.len(),
+        diff.new_duplicate_imports.len()
+    )?;
+    Ok(())
+}
+
+fn write_edges(out: &mut impl Write, title: &str, edges: &[Edge]) -> Result<()> {
+    if edges.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== {title} =====")?;
+    for edge in edges {
+        let dynamic = if edge.dynamic { " (dynamic)" } else { "" };
+        writeln!(out, "{} → {}{dynamic}", edge.from, edge.to)?;
+    }
+    Ok(())
+}
+
+fn write_usages(
+    out: &mut impl Write,
+    title: &str,
+    deltas: &[UsageDelta],
+    detail: impl Fn(&UsageDelta) -> String,
+) -> Result<()> {
+    if deltas.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== {title} =====")?;
+    for delta in deltas {
+        writeln!(out, "{:<50} {}", delta.qualified_name(), detail(delta))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::{collections::BTreeMap, io::Write};
+use anyhow::Result;
+use super::xml_escape;
+use crate::report::{Finding, Report};
+
+// Checkstyle 互換 XML。指摘をファイルごとにまとめる
+pub fn write(report: &Report, out: &mut impl Write) -> Result<()> {
+    let findings = report.findings();
+    let mut by_file: BTreeMap<&str, Vec<&Finding>> = BTreeMap::new();
+    for finding in &findings {
+        by_file.entry(finding.path.as_str()).or_default().push(finding);
+    }
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<checkstyle version="4.3">"#)?;
+    for (path, findings) in by_file {
+        writeln!(out, r#"  <file name="{}">"#, xml_escape(path))?;
+        for finding in findings {
+            writeln!(
+                out,
+                r#"    <error line="{}" column="{}" severity="{}" message="{}" source="{}.{}"/>"#,
+                finding.line,
+                finding.column,
+                finding.severity.as_str(),
+                xml_escape(&finding.message),
+                env!("CARGO_PKG_NAME"),
+                finding.rule.id
+            )?;
+        }
+        writeln!(out, "  </file>")?;
+    }
+    writeln!(out, "</checkstyle>")?;
+    Ok(())
+}
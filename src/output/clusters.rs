@@ -0,0 +1,160 @@
+use std::{collections::BTreeMap, io::Write, path::Path};
+use anyhow::Result;
+use serde::Serialize;
+use super::OutputFormat;
+use crate::{
+    graph::{self, DependencyGraph},
+    report::Report,
+};
+
+#[derive(Serialize)]
+struct ClustersReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    // 分け方のモジュラリティ (大きいほど組の中のインポートが多く組の間が少ない)
+    modularity: f64,
+    clusters: Vec<Cluster>,
+}
+
+// インポートの多いファイルの組 1 つ (2 ファイル以上のものだけ)
+#[derive(Debug, Serialize)]
+pub struct Cluster {
+    // 組のファイルが最も多いフォルダ (同じ数ならパスの順で先のもの)
+    pub folder: String,
+    pub files: Vec<ClusterFile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClusterFile {
+    pub path: String,
+    pub folder: String,
+    // 組の主なフォルダと違うフォルダにある (移す・分ける候補)
+    pub misplaced: bool,
+}
+
+// clusters コマンドの出力: 依存グラフを Louvain 法でまとめたファイルの組を、今のフォルダと合っていないファイルに印をつけて表示する
+pub fn write(format: OutputFormat, report: &Report, graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    let root = Path::new(&report.target);
+    let display = |path: &str| match path {
+        "." => report.target.clone(),
+        path => root.join(path).display().to_string(),
+    };
+    let (communities, modularity) = graph.communities();
+    let clusters: Vec<Cluster> = communities
+        .into_iter()
+        .filter(|ids| ids.len() > 1)
+        .map(|ids| {
+            let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+            for id in &ids {
+                *counts.entry(graph::directory_of(id)).or_insert(0) += 1;
+            }
+            // 数が同じならパスの順で先のもの
+            let folder = counts.iter().rev().max_by_key(|(_, count)| **count).map(|(folder, _)| folder.clone());
+            let folder = folder.unwrap_or_default();
+            Cluster {
+                files: ids
+                    .iter()
+                    .map(|id| {
+                        let directory = graph::directory_of(id);
+                        ClusterFile {
+                            path: display(id),
+                            misplaced: directory != folder,
+                            folder: display(&directory),
+                        }
+                    })
+                    .collect(),
+                folder: display(&folder),
+            }
+        })
+        .collect();
+
+    if format == OutputFormat::Json {
+        let report = ClustersReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            modularity,
+            clusters,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== インポートの多いファイルの組（{} 組。モジュラリティ {modularity:.2}） =====", clusters.len())?;
+    for (i, cluster) in clusters.iter().enumerate() {
+        writeln!(out, "\n組 {}: {}  ({} ファイル)", i + 1, cluster.folder, cluster.files.len())?;
+        for file in &cluster.files {
+            match file.misplaced {
+                true => writeln!(out, "  {}  ← フォルダが違う", file.path)?,
+                false => writeln!(out, "  {}", file.path)?,
+            }
+        }
+    }
+    let misplaced = clusters.iter().flat_map(|cluster| &cluster.files).filter(|file| file.misplaced).count();
+    writeln!(out, "\n組の主なフォルダと違うフォルダにあるファイル: {misplaced} 件")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        graph::{Edge, Node},
+        Aggregate, AnalyzerConfig,
+    };
+
+    fn graph(nodes: &[&str], edges: &[(&str, &str)]) -> DependencyGraph {
+        DependencyGraph {
+            nodes: nodes.iter().map(|id| Node { id: id.to_string(), size: 0 }).collect(),
+            edges: edges
+                .iter()
+                .map(|(from, to)| Edge { from: from.to_string(), to: to.to_string(), weight: 1, dynamic: false })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn marks_files_outside_main_folder() {
+        let report = Aggregate::new(Path::new("src"), &AnalyzerConfig::default()).finish().report;
+        let nodes = ["core/a.ts", "core/b.ts", "feature/c.ts", "feature/d.ts", "feature/e.ts", "feature/f.ts", "main.ts"];
+        let edges = [
+            ("core/a.ts", "core/b.ts"),
+            ("core/b.ts", "feature/c.ts"),
+            ("feature/c.ts", "core/a.ts"),
+            ("feature/d.ts", "feature/e.ts"),
+            ("feature/e.ts", "feature/f.ts"),
+            ("feature/f.ts", "feature/d.ts"),
+            ("feature/c.ts", "feature/d.ts"),
+        ];
+        let mut out = Vec::new();
+        write(OutputFormat::Json, &report, &graph(&nodes, &edges), &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        // どこからもインポートされていない main.ts は 1 ファイルの組なので載せない
+        let clusters = json["clusters"].as_array().unwrap();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0]["folder"], "src/core");
+        let files: Vec<_> = clusters[0]["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| (file["path"].as_str().unwrap(), file["misplaced"].as_bool().unwrap()))
+            .collect();
+        assert_eq!(files, [("src/core/a.ts", false), ("src/core/b.ts", false), ("src/feature/c.ts", true)]);
+        assert_eq!(clusters[1]["folder"], "src/feature");
+        assert!(json["modularity"].as_f64().unwrap() > 0.3);
+    }
+
+    #[test]
+    fn text_output_counts_misplaced_files() {
+        let report = Aggregate::new(Path::new("src"), &AnalyzerConfig::default()).finish().report;
+        let graph = graph(&["a/x.ts", "b/y.ts"], &[("a/x.ts", "b/y.ts")]);
+        let mut out = Vec::new();
+        write(OutputFormat::Text, &report, &graph, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        // 同じ数のフォルダはパスの順で先のもの
+        assert!(text.contains("組 1: src/a  (2 ファイル)"), "{text}");
+        assert!(text.contains("src/b/y.ts  ← フォルダが違う"), "{text}");
+        assert!(text.contains("フォルダと違うフォルダにあるファイル: 1 件"), "{text}");
+    }
+}
@@ -0,0 +1,87 @@
+use std::{collections::HashMap, io::Write};
+use anyhow::Result;
+use super::Options;
+use crate::{graph::DependencyGraph, report::Report};
+
+pub fn write(report: &Report, options: Options, out: &mut impl Write) -> Result<()> {
+    // CSV は 1 つの表しか持てないので、--per-file のときはファイル単位の行に切り替える
+    if options.per_file {
+        writeln!(out, "path,symbol,alias,module,count,type_count,type_only")?;
+        for file in &report.files {
+            for import in &file.imports {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    csv_field(&file.path),
+                    csv_field(&import.name),
+                    csv_field(import.alias.as_deref().unwrap_or("")),
+                    csv_field(&import.module),
+                    import.count,
+                    import.type_count,
+                    import.type_only
+                )?;
+            }
+        }
+        return Ok(());
+    }
+
+    if options.by_package {
+        writeln!(out, "package,count,files,symbols")?;
+        for package in &report.packages {
+            writeln!(
+                out,
+                "{},{},{},{}",
+                csv_field(&package.package),
+                package.count,
+                package.files,
+                csv_field(&package.symbols.join(";"))
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(out, "symbol,module,count,type_count,files,type_only_files")?;
+    for entry in &report.usages {
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            csv_field(&entry.name),
+            csv_field(&entry.module),
+            entry.count,
+            entry.type_count,
+            entry.files,
+            entry.type_only_files
+        )?;
+    }
+    Ok(())
+}
+
+// 依存グラフの隣接行列。1 行目と 1 列目がノードで、セルは行のノードが列のノードをインポートしている
+// import / export 宣言の数 (無ければ 0)
+pub fn write_matrix(graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    let weights: HashMap<(&str, &str), usize> =
+        graph.edges.iter().map(|edge| ((edge.from.as_str(), edge.to.as_str()), edge.weight)).collect();
+    write!(out, "from\\to")?;
+    for node in &graph.nodes {
+        write!(out, ",{}", csv_field(&node.id))?;
+    }
+    writeln!(out)?;
+    for from in &graph.nodes {
+        write!(out, "{}", csv_field(&from.id))?;
+        for to in &graph.nodes {
+            let weight = weights.get(&(from.id.as_str(), to.id.as_str())).copied().unwrap_or(0);
+            write!(out, ",{weight}")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+// カンマ・ダブルクォート・改行を含むフィールドはクォートする
+pub(super) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,342 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io::Write,
+    path::Path,
+};
+use anyhow::Result;
+use serde::Serialize;
+use crate::{
+    graph::DependencyGraph,
+    manifest,
+    report::{Report, Rule, RuleLevel, Severity},
+    resolve,
+};
+
+// dependency-cruiser の JSON 出力 (depcruise --output-type json) と同じ形
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CruiseResult {
+    modules: Vec<Module>,
+    summary: Summary,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Module {
+    source: String,
+    followable: bool,
+    core_module: bool,
+    could_not_resolve: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    dependency_types: Vec<&'static str>,
+    matches_do_not_follow: bool,
+    dependencies: Vec<Dependency>,
+    dependents: Vec<String>,
+    orphan: bool,
+    valid: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Dependency {
+    module: String,
+    resolved: String,
+    module_system: &'static str,
+    core_module: bool,
+    followable: bool,
+    could_not_resolve: bool,
+    dependency_types: Vec<&'static str>,
+    dynamic: bool,
+    exotically_required: bool,
+    matches_do_not_follow: bool,
+    circular: bool,
+    valid: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    violations: Vec<Violation>,
+    error: usize,
+    warn: usize,
+    info: usize,
+    ignore: usize,
+    total_cruised: usize,
+    total_dependencies_cruised: usize,
+    options_used: OptionsUsed,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Violation {
+    from: String,
+    to: String,
+    rule: ViolationRule,
+}
+
+#[derive(Serialize)]
+struct ViolationRule {
+    severity: &'static str,
+    name: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OptionsUsed {
+    output_type: &'static str,
+    ts_pre_compilation_deps: bool,
+    args: String,
+}
+
+// ファイルがインポートしている npm パッケージ・組み込みモジュール 1 つ (同じ指定子のインポートをまとめたもの)
+#[derive(Clone, Copy)]
+struct External {
+    // すべて import() か
+    dynamic: bool,
+    // すべて import type か
+    type_only: bool,
+}
+
+// graph コマンドの dependency-cruiser 形式。解析対象のファイルは target からのパス、npm パッケージは
+// node_modules/<パッケージ名> (depcruise の --collapse "node_modules/[^/]+" と同じ形)、組み込みモジュールは名前を
+// source / resolved にする。summary.violations には依存ポリシーの違反を入れる
+pub fn write(report: &Report, graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    let root = Path::new(&report.target);
+    let display = |id: &str| root.join(id).display().to_string();
+    let id = |path: &str| resolve::relative_display(root, Path::new(path));
+
+    // ファイル (ノード ID) ごとの npm パッケージ・組み込みモジュールのインポート。tsconfig の paths 等で解析対象内に
+    // 解決される指定子を除くため、Report::packages にあるものだけを見る
+    let known: HashSet<&str> = report.packages.iter().map(|package| package.package.as_str()).collect();
+    let is_external = |module: &str| {
+        resolve::package_name(module).is_some_and(|name| known.contains(name) || manifest::is_node_builtin(name))
+    };
+    let static_imports = report.files.iter().flat_map(|file| {
+        let external = |type_only| External { dynamic: false, type_only };
+        file.imports.iter().map(move |import| (&file.path, &import.module, external(import.type_only)))
+    });
+    let side_effects = report.side_effect_imports.iter().map(|import| {
+        (&import.path, &import.module, External { dynamic: false, type_only: false })
+    });
+    let dynamic = report.dynamic_imports.iter().map(|import| {
+        (&import.path, &import.module, External { dynamic: true, type_only: false })
+    });
+    let mut externals: HashMap<String, BTreeMap<&str, External>> = HashMap::new();
+    for (path, module, external) in static_imports.chain(side_effects).chain(dynamic) {
+        if !is_external(module) {
+            continue;
+        }
+        let merged = externals.entry(id(path)).or_default().entry(module).or_insert(external);
+        merged.dynamic &= external.dynamic;
+        merged.type_only &= external.type_only;
+    }
+
+    // 同じ循環 (強連結成分) に入っているファイル同士の依存は circular
+    let component: HashMap<String, usize> = graph
+        .cycles()
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, cycle)| cycle.into_iter().map(move |id| (id, i)))
+        .collect();
+    let circular = |from: &str, to: &str| component.get(from).is_some_and(|c| component.get(to) == Some(c));
+
+    let level = |rule: &'static Rule| match report.rule_levels.get(rule.id) {
+        Some(RuleLevel::Off) => None,
+        Some(RuleLevel::Warning) => Some(Severity::Warning),
+        Some(RuleLevel::Error) => Some(Severity::Error),
+        None => Some(rule.severity),
+    };
+    let violations: Vec<Violation> = report
+        .policy_violations
+        .iter()
+        .filter_map(|violation| {
+            let rule = violation.rule.rule();
+            Some(Violation {
+                from: violation.path.clone(),
+                to: violation.package.clone(),
+                rule: ViolationRule {
+                    severity: match level(rule)? {
+                        Severity::Error => "error",
+                        Severity::Warning => "warn",
+                    },
+                    name: rule.id,
+                },
+            })
+        })
+        .collect();
+    let invalid: HashSet<&str> = violations.iter().map(|violation| violation.from.as_str()).collect();
+
+    let mut dependents: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut modules: Vec<Module> = Vec::new();
+    for node in &graph.nodes {
+        let source = display(&node.id);
+        let mut dependencies: Vec<Dependency> = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.from == node.id)
+            .map(|edge| Dependency {
+                module: relative_specifier(&edge.from, &edge.to),
+                resolved: display(&edge.to),
+                module_system: "es6",
+                core_module: false,
+                followable: true,
+                could_not_resolve: false,
+                dependency_types: vec!["local", if edge.dynamic { "dynamic-import" } else { "import" }],
+                dynamic: edge.dynamic,
+                exotically_required: false,
+                matches_do_not_follow: false,
+                circular: circular(&edge.from, &edge.to),
+                valid: true,
+            })
+            .collect();
+        for (&module, external) in externals.get(&node.id).into_iter().flatten() {
+            let package = resolve::package_name(module).unwrap_or(module);
+            let core = manifest::is_node_builtin(package);
+            let mut types = vec![if core { "core" } else { "npm" }];
+            types.extend(external.type_only.then_some("type-only"));
+            types.extend(external.dynamic.then_some("dynamic-import"));
+            let resolved = match core {
+                true => package.to_string(),
+                false => format!("node_modules/{package}"),
+            };
+            dependencies.push(Dependency {
+                module: module.to_string(),
+                resolved,
+                module_system: "es6",
+                core_module: core,
+                followable: false,
+                could_not_resolve: false,
+                dependency_types: types,
+                dynamic: external.dynamic,
+                exotically_required: false,
+                matches_do_not_follow: !core,
+                circular: false,
+                valid: !report.policy_violations.iter().any(|v| v.path == source && v.package == package),
+            });
+        }
+        for dependency in &dependencies {
+            dependents.entry(dependency.resolved.clone()).or_default().insert(source.clone());
+        }
+        modules.push(Module {
+            followable: true,
+            core_module: false,
+            could_not_resolve: false,
+            dependency_types: Vec::new(),
+            matches_do_not_follow: false,
+            orphan: false,
+            valid: !invalid.contains(source.as_str()),
+            source,
+            dependencies,
+            dependents: Vec::new(),
+        });
+    }
+    let total_dependencies = modules.iter().map(|module| module.dependencies.len()).sum();
+    for module in &mut modules {
+        module.dependents = dependents.remove(&module.source).unwrap_or_default().into_iter().collect();
+        module.orphan = module.dependencies.is_empty() && module.dependents.is_empty();
+    }
+    // 残りはインポートされているパッケージ・組み込みモジュール
+    for (source, importers) in dependents {
+        let core = !source.starts_with("node_modules/");
+        modules.push(Module {
+            source,
+            followable: false,
+            core_module: core,
+            could_not_resolve: false,
+            dependency_types: vec![if core { "core" } else { "npm" }],
+            matches_do_not_follow: !core,
+            dependencies: Vec::new(),
+            dependents: importers.into_iter().collect(),
+            orphan: false,
+            valid: true,
+        });
+    }
+    modules.sort_by(|a, b| a.source.cmp(&b.source));
+
+    let count = |severity| violations.iter().filter(|violation| violation.rule.severity == severity).count();
+    let result = CruiseResult {
+        summary: Summary {
+            error: count("error"),
+            warn: count("warn"),
+            info: 0,
+            ignore: 0,
+            total_cruised: modules.len(),
+            total_dependencies_cruised: total_dependencies,
+            options_used: OptionsUsed {
+                output_type: "json",
+                ts_pre_compilation_deps: true,
+                args: report.target.clone(),
+            },
+            violations,
+        },
+        modules,
+    };
+    serde_json::to_writer_pretty(&mut *out, &result)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+// from から to をインポートするときの相対指定子 (拡張子は付けない)。グラフの辺は解決後のファイルしか持たないので、
+// 元の指定子の代わりにする
+fn relative_specifier(from: &str, to: &str) -> String {
+    let from: Vec<&str> = from.split('/').collect();
+    let to: Vec<&str> = to.split('/').collect();
+    let directory = &from[..from.len() - 1];
+    let common = directory.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<&str> = vec![".."; directory.len() - common];
+    if parts.is_empty() {
+        parts.push(".");
+    }
+    parts.extend(&to[common..]);
+    let specifier = parts.join("/");
+    match specifier.rsplit_once('.') {
+        Some((stem, "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs")) => stem.to_string(),
+        _ => specifier,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use crate::{analyze_sources, AnalyzerConfig};
+
+    #[test]
+    fn writes_files_packages_and_circular_dependencies() {
+        let files = [
+            ("src/app.ts", "import { map } from 'rxjs/operators';\nimport { b } from './lib/b';\nimport fs from 'fs';\nexport const app = [map, b, fs];\n"),
+            ("src/lib/b.ts", "import { app } from '../app';\nexport const b = app;\n"),
+            ("src/lib/c.ts", "export const c = 1;\n"),
+        ];
+        let files = files.map(|(path, src)| (PathBuf::from(path), src.to_string()));
+        let analysis = analyze_sources(Path::new("src"), files, &AnalyzerConfig::default());
+        let mut out = Vec::new();
+        write(&analysis.report, &analysis.graph, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        let modules = json["modules"].as_array().unwrap();
+        let sources: Vec<_> = modules.iter().map(|module| module["source"].as_str().unwrap()).collect();
+        assert_eq!(sources, ["fs", "node_modules/rxjs", "src/app.ts", "src/lib/b.ts", "src/lib/c.ts"]);
+        let dependencies: Vec<_> = modules[2]["dependencies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|dependency| {
+                let module = dependency["module"].as_str().unwrap();
+                (module, dependency["resolved"].as_str().unwrap(), dependency["circular"].as_bool().unwrap())
+            })
+            .collect();
+        assert_eq!(
+            dependencies,
+            [("./lib/b", "src/lib/b.ts", true), ("fs", "fs", false), ("rxjs/operators", "node_modules/rxjs", false)]
+        );
+        assert_eq!(modules[0]["coreModule"], true);
+        assert_eq!(modules[1]["dependencyTypes"], serde_json::json!(["npm"]));
+        assert_eq!(modules[1]["dependents"], serde_json::json!(["src/app.ts"]));
+        assert_eq!(modules[3]["dependencies"][0]["module"], "../app");
+        assert_eq!(modules[4]["orphan"], true);
+        assert_eq!(json["summary"]["totalCruised"], 5);
+        assert_eq!(json["summary"]["totalDependenciesCruised"], 4);
+    }
+}
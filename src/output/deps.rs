@@ -0,0 +1,118 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io::Write,
+    path::Path,
+};
+use anyhow::Result;
+use serde::Serialize;
+use super::{human_size, OutputFormat};
+use crate::{graph::DependencyGraph, report::Report, resolve};
+
+// deps コマンドで既定で表示するファイル数
+pub const DEFAULT_TOP: usize = 20;
+
+#[derive(Serialize)]
+struct DepsReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    // 解析したファイル数 (heaviest はそのうち上位のものだけ)
+    files: usize,
+    heaviest: Vec<FileDependencies>,
+}
+
+// 1 ファイルが直接・間接に読み込むもの
+#[derive(Debug, Serialize)]
+pub struct FileDependencies {
+    pub path: String,
+    // インポートを辿って読み込まれる解析対象内のファイル数 (自分は含まない)
+    pub files: usize,
+    // 自分と読み込まれるファイルがインポートしている npm パッケージ数
+    pub packages: usize,
+    // 自分と読み込まれるファイルのサイズの合計 (バイト)
+    pub size: u64,
+}
+
+// deps コマンドの出力: 各ファイルからインポートを辿って読み込まれるファイル・パッケージを数え、ファイル数の多い順
+// (同じならサイズの大きい順) に上位 top 件を表示する。import() だけで繋がっているファイルは別のチャンクになるので辿らない
+pub fn write(
+    format: OutputFormat,
+    report: &Report,
+    graph: &DependencyGraph,
+    top: usize,
+    out: &mut impl Write,
+) -> Result<()> {
+    let root = Path::new(&report.target);
+    let mut imports: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in graph.edges.iter().filter(|edge| !edge.dynamic) {
+        imports.entry(&edge.from).or_default().push(&edge.to);
+    }
+    // ファイル (ノード ID) ごとにインポートしている npm パッケージ。tsconfig の paths 等で解析対象内に解決される
+    // 指定子を除くため、Report::packages にあるものだけを数える
+    let known: HashSet<&str> = report.packages.iter().map(|package| package.package.as_str()).collect();
+    let mut packages: HashMap<String, BTreeSet<&str>> = HashMap::new();
+    let id = |path: &str| resolve::relative_display(root, Path::new(path));
+    for file in &report.files {
+        let names = file.imports.iter().filter_map(|import| resolve::package_name(&import.module));
+        packages.entry(id(&file.path)).or_default().extend(names.filter(|name| known.contains(name)));
+    }
+    for import in &report.side_effect_imports {
+        let names = resolve::package_name(&import.module);
+        packages.entry(id(&import.path)).or_default().extend(names);
+    }
+    let sizes: HashMap<&str, u64> = graph.nodes.iter().map(|node| (node.id.as_str(), node.size)).collect();
+
+    let mut heaviest: Vec<FileDependencies> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let mut seen: HashSet<&str> = HashSet::from([node.id.as_str()]);
+            let mut pending = vec![node.id.as_str()];
+            while let Some(id) = pending.pop() {
+                for &to in imports.get(id).into_iter().flatten() {
+                    if seen.insert(to) {
+                        pending.push(to);
+                    }
+                }
+            }
+            let used: BTreeSet<&str> = seen.iter().filter_map(|id| packages.get(*id)).flatten().copied().collect();
+            FileDependencies {
+                path: root.join(&node.id).display().to_string(),
+                files: seen.len() - 1,
+                packages: used.len(),
+                size: seen.iter().map(|id| sizes[id]).sum(),
+            }
+        })
+        .collect();
+    heaviest.sort_by(|a, b| (b.files, b.size, &a.path).cmp(&(a.files, a.size, &b.path)));
+    heaviest.truncate(top);
+
+    if format == OutputFormat::Json {
+        let report = DepsReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            files: graph.nodes.len(),
+            heaviest,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "\n===== 読み込むファイルの多いファイル（{} ファイル中の上位 {} 件。import() は辿らない） =====",
+        graph.nodes.len(),
+        heaviest.len()
+    )?;
+    for file in &heaviest {
+        writeln!(
+            out,
+            "{:<60} {:>5} ファイル  {:>4} パッケージ  {:>9}",
+            file.path,
+            file.files,
+            file.packages,
+            human_size(file.size)
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::{io::Write, path::Path};
+use anyhow::Result;
+use serde::Serialize;
+use super::OutputFormat;
+use crate::{glob::Glob, graph::DependencyGraph, report::Report};
+
+// depth コマンドで既定で表示する連鎖の数
+pub const DEFAULT_TOP: usize = 10;
+
+#[derive(Serialize)]
+struct DepthReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    // 解析対象にあったエントリポイントの数
+    entry_points: usize,
+    // エントリポイントからの最も長い連鎖の段数の最大と平均
+    max_depth: usize,
+    average_depth: f64,
+    // 深い順に上位のもの
+    chains: Vec<Chain>,
+}
+
+// エントリポイント 1 つからの最も長いインポートの連鎖
+#[derive(Debug, Serialize)]
+pub struct Chain {
+    pub entry: String,
+    // インポートの段数 (files の数 - 1)
+    pub depth: usize,
+    // 循環している組は 1 段と数え、組に入ったファイルだけを載せる
+    pub files: Vec<String>,
+}
+
+// depth コマンドの出力: エントリポイント (entry_points に一致するファイル) ごとに最も長いインポートの連鎖を求め、
+// 段数の最大・平均と、深いものから上位 top 件の連鎖を表示する
+pub fn write(
+    format: OutputFormat,
+    report: &Report,
+    graph: &DependencyGraph,
+    entry_points: &[Glob],
+    top: usize,
+    out: &mut impl Write,
+) -> Result<()> {
+    let root = Path::new(&report.target);
+    let display = |id: &String| root.join(id).display().to_string();
+    let entries: Vec<String> = graph
+        .nodes
+        .iter()
+        .filter(|node| entry_points.iter().any(|glob| glob.matches_path(&node.id)))
+        .map(|node| node.id.clone())
+        .collect();
+    let mut chains: Vec<Chain> = graph
+        .longest_chains(&entries)
+        .into_iter()
+        .map(|files| Chain {
+            entry: files.first().map(display).unwrap_or_default(),
+            depth: files.len() - 1,
+            files: files.iter().map(display).collect(),
+        })
+        .collect();
+    chains.sort_by(|a, b| b.depth.cmp(&a.depth).then_with(|| a.entry.cmp(&b.entry)));
+    let max_depth = chains.first().map_or(0, |chain| chain.depth);
+    let average_depth = match chains.len() {
+        0 => 0.0,
+        n => chains.iter().map(|chain| chain.depth).sum::<usize>() as f64 / n as f64,
+    };
+    let entry_count = chains.len();
+    chains.truncate(top);
+
+    if format == OutputFormat::Json {
+        let report = DepthReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            entry_points: entry_count,
+            max_depth,
+            average_depth,
+            chains,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== エントリポイントからのインポートの連鎖の深さ =====")?;
+    writeln!(out, "エントリポイント {entry_count} 件 / 最大 {max_depth} 段 / 平均 {average_depth:.1} 段")?;
+    if chains.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== 深い連鎖（上位 {} 件） =====", chains.len())?;
+    for chain in &chains {
+        writeln!(out, "\n{}  ({} 段)", chain.entry, chain.depth)?;
+        for file in chain.files.iter().skip(1) {
+            writeln!(out, "  → {file}")?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use std::io::Write;
+use anyhow::Result;
+use crate::graph::DependencyGraph;
+
+// Graphviz の DOT 形式
+pub fn write(graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "digraph dependencies {{")?;
+    writeln!(out, "  rankdir=LR;")?;
+    writeln!(out, "  node [shape=box, fontname=\"sans-serif\"];")?;
+    for node in &graph.nodes {
+        writeln!(out, "  {};", quote(&node.id))?;
+    }
+    for edge in &graph.edges {
+        // import() だけの辺は破線にする
+        let style = if edge.dynamic { " [style=dashed]" } else { "" };
+        writeln!(out, "  {} -> {}{style};", quote(&edge.from), quote(&edge.to))?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
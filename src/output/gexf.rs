@@ -0,0 +1,46 @@
+use std::io::Write;
+use anyhow::Result;
+use super::xml_escape;
+use crate::graph::DependencyGraph;
+
+// GEXF 1.3 形式 (Gephi のネイティブ形式)
+pub fn write(graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    let fan_in = graph.fan_in();
+    let fan_out = graph.fan_out();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+    writeln!(out, r#"  <meta><creator>{}</creator></meta>"#, env!("CARGO_PKG_NAME"))?;
+    writeln!(out, r#"  <graph defaultedgetype="directed">"#)?;
+    writeln!(out, r#"    <attributes class="node">"#)?;
+    writeln!(out, r#"      <attribute id="0" title="size" type="long"/>"#)?;
+    writeln!(out, r#"      <attribute id="1" title="fan_in" type="integer"/>"#)?;
+    writeln!(out, r#"      <attribute id="2" title="fan_out" type="integer"/>"#)?;
+    writeln!(out, "    </attributes>")?;
+    writeln!(out, "    <nodes>")?;
+    for node in &graph.nodes {
+        writeln!(
+            out,
+            r#"      <node id="{id}" label="{id}"><attvalues><attvalue for="0" value="{}"/><attvalue for="1" value="{}"/><attvalue for="2" value="{}"/></attvalues></node>"#,
+            node.size,
+            fan_in[node.id.as_str()],
+            fan_out[node.id.as_str()],
+            id = xml_escape(&node.id),
+        )?;
+    }
+    writeln!(out, "    </nodes>")?;
+    writeln!(out, "    <edges>")?;
+    for (i, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            r#"      <edge id="{i}" source="{}" target="{}" weight="{}"/>"#,
+            xml_escape(&edge.from),
+            xml_escape(&edge.to),
+            edge.weight
+        )?;
+    }
+    writeln!(out, "    </edges>")?;
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</gexf>")?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use std::{io::Write, path::Path};
+use anyhow::Result;
+use crate::{report::Report, resolve};
+
+// GitHub Actions のワークフローコマンド (::warning file=...,line=...::message)
+pub fn write(report: &Report, out: &mut impl Write) -> Result<()> {
+    for finding in report.findings() {
+        writeln!(
+            out,
+            "::{} file={},line={},col={},title={}::{}",
+            finding.severity.as_str(),
+            // ./src/... のような先頭の ./ はアノテーションの対応付けを妨げるので取り除く
+            escape_property(&resolve::normalize(Path::new(&finding.path)).to_string_lossy()),
+            finding.line,
+            finding.column,
+            escape_property(finding.rule.id),
+            escape_data(&finding.message)
+        )?;
+    }
+    Ok(())
+}
+
+// メッセージ部分のエスケープ
+fn escape_data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+// file= など属性値のエスケープ (区切り文字の : と , も対象)
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
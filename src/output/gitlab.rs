@@ -0,0 +1,55 @@
+use std::{io::Write, path::Path};
+use anyhow::Result;
+use serde_json::{json, Value};
+use crate::{
+    report::{Finding, Report, Severity},
+    resolve,
+};
+
+// GitLab Code Quality レポート (MR ウィジェットに表示される JSON 配列)
+pub fn write(report: &Report, out: &mut impl Write) -> Result<()> {
+    let issues: Vec<Value> = report
+        .findings()
+        .iter()
+        .map(|finding| {
+            let path = resolve::normalize(Path::new(&finding.path)).to_string_lossy().into_owned();
+            json!({
+                "type": "issue",
+                "check_name": finding.rule.id,
+                "description": finding.message,
+                "categories": ["Style"],
+                "severity": severity(finding.severity),
+                "fingerprint": fingerprint(finding, &path),
+                "location": {
+                    "path": path,
+                    "lines": { "begin": finding.line },
+                },
+            })
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(&mut *out, &issues)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "minor",
+        Severity::Error => "major",
+    }
+}
+
+// ブランチ間で同じ指摘を同一視するための識別子。
+// 行番号は含めないので、前後の行が変わっても新しい指摘扱いにならない
+fn fingerprint(finding: &Finding, path: &str) -> String {
+    // FNV-1a (64bit)。実行環境や Rust のバージョンに依らず同じ値になる
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in [finding.rule.id, path, finding.message.as_str()] {
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{hash:016x}")
+}
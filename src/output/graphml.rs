@@ -0,0 +1,38 @@
+use std::io::Write;
+use anyhow::Result;
+use super::xml_escape;
+use crate::graph::DependencyGraph;
+
+// GraphML 形式 (Gephi / yEd で読み込める)
+pub fn write(graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    let fan_in = graph.fan_in();
+    let fan_out = graph.fan_out();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(out, r#"  <key id="size" for="node" attr.name="size" attr.type="long"/>"#)?;
+    writeln!(out, r#"  <key id="fan_in" for="node" attr.name="fan_in" attr.type="int"/>"#)?;
+    writeln!(out, r#"  <key id="fan_out" for="node" attr.name="fan_out" attr.type="int"/>"#)?;
+    writeln!(out, r#"  <key id="weight" for="edge" attr.name="weight" attr.type="double"/>"#)?;
+    writeln!(out, r#"  <graph id="dependencies" edgedefault="directed">"#)?;
+    for node in &graph.nodes {
+        writeln!(out, r#"    <node id="{}">"#, xml_escape(&node.id))?;
+        writeln!(out, r#"      <data key="size">{}</data>"#, node.size)?;
+        writeln!(out, r#"      <data key="fan_in">{}</data>"#, fan_in[node.id.as_str()])?;
+        writeln!(out, r#"      <data key="fan_out">{}</data>"#, fan_out[node.id.as_str()])?;
+        writeln!(out, "    </node>")?;
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            r#"    <edge id="e{i}" source="{}" target="{}">"#,
+            xml_escape(&edge.from),
+            xml_escape(&edge.to)
+        )?;
+        writeln!(out, r#"      <data key="weight">{}</data>"#, edge.weight)?;
+        writeln!(out, "    </edge>")?;
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::io::Write;
+use anyhow::Result;
+use serde::Serialize;
+use super::OutputFormat;
+use crate::{
+    graph::DependencyGraph,
+    health::{self, Health, HealthWeights},
+    report::Report,
+};
+
+#[derive(Serialize)]
+struct HealthReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    #[serde(flatten)]
+    health: Health,
+}
+
+// health コマンドの出力: 健全性スコアと、指標ごとの指摘の数・ファイルの割合・重み
+pub fn write(
+    format: OutputFormat,
+    report: &Report,
+    graph: &DependencyGraph,
+    weights: &HealthWeights,
+    out: &mut impl Write,
+) -> Result<()> {
+    let health = health::score(report, graph, &[], weights);
+
+    if format == OutputFormat::Json {
+        let report = HealthReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            health,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== 依存関係の健全性 =====")?;
+    writeln!(out, "スコア {} / 100 ({})  {} ファイル", health.score, health.grade, health.files)?;
+    write_signals(&health, out)
+}
+
+// 指標ごとの内訳 (projects などのコマンドと共通)
+pub fn write_signals(health: &Health, out: &mut impl Write) -> Result<()> {
+    for signal in &health.signals {
+        writeln!(
+            out,
+            "  {:<20} {:>5} 件  {:>5} ファイル ({:>5.1}%)  重み {}",
+            signal.signal.label(),
+            signal.count,
+            signal.files,
+            signal.ratio * 100.0,
+            signal.weight
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::io::Write;
+use anyhow::Result;
+use serde::Serialize;
+use super::{csv::csv_field, OutputFormat};
+use crate::report::PackageUsage;
+
+// テキストの表に並べるパッケージ数 (--package の指定が無いとき、最新の時点で使用回数の多い順)
+const TEXT_COLUMNS: usize = 8;
+
+// history コマンドの 1 時点分の集計
+#[derive(Debug, Serialize)]
+pub struct HistoryPoint {
+    pub revision: String,
+    pub date: String,
+    // タグ名かコミットの件名
+    pub label: String,
+    pub files_analyzed: usize,
+    pub packages: Vec<PackageUsage>,
+}
+
+// パッケージごとの使用回数の推移 (points は古い順)。
+// CSV は時点 × パッケージごとに 1 行、テキストは時点ごとに 1 行でパッケージを列に並べる
+pub fn write(format: OutputFormat, points: &[HistoryPoint], out: &mut impl Write) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, points)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "revision,date,label,files_analyzed,package,count,files")?;
+            for point in points {
+                for package in &point.packages {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{}",
+                        point.revision,
+                        point.date,
+                        csv_field(&point.label),
+                        point.files_analyzed,
+                        csv_field(&package.package),
+                        package.count,
+                        package.files
+                    )?;
+                }
+            }
+        }
+        _ => write_text(points, out)?,
+    }
+    Ok(())
+}
+
+fn write_text(points: &[HistoryPoint], out: &mut impl Write) -> Result<()> {
+    let columns: Vec<&str> = points
+        .last()
+        .map(|point| point.packages.iter().take(TEXT_COLUMNS).map(|p| p.package.as_str()).collect())
+        .unwrap_or_default();
+    let widths: Vec<usize> = columns.iter().map(|name| name.chars().count().max(6)).collect();
+
+    writeln!(out, "\n===== パッケージごとの使用回数の推移（古い順） =====")?;
+    // 見出しの全角文字は 2 桁分で表示されるので、データの行と揃うよう空白で合わせる
+    write!(out, "日付        コミット  ファイル数")?;
+    for (name, width) in columns.iter().zip(&widths) {
+        write!(out, "  {name:>width$}")?;
+    }
+    writeln!(out)?;
+    for point in points {
+        let revision: String = point.revision.chars().take(8).collect();
+        write!(out, "{:<10}  {:<8}  {:>10}", point.date, revision, point.files_analyzed)?;
+        for (name, width) in columns.iter().zip(&widths) {
+            let count = point.packages.iter().find(|p| p.package == *name).map_or(0, |p| p.count);
+            write!(out, "  {count:>width$}")?;
+        }
+        writeln!(out, "  {}", point.label)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use std::io::Write;
+use anyhow::Result;
+use crate::report::Report;
+
+// HTML テンプレート。レポートの JSON を埋め込み、表示はブラウザ側の JS で行う
+const HTML_TEMPLATE: &str = include_str!("report.html");
+
+pub fn write(report: &Report, out: &mut impl Write) -> Result<()> {
+    // </script> で埋め込みが途切れないよう "</" をエスケープする
+    let data = serde_json::to_string(report)?.replace("</", "<\\/");
+    let html = HTML_TEMPLATE
+        .replace("__TITLE__", &html_escape(&report.target))
+        .replace("__REPORT_JSON__", &data);
+    out.write_all(html.as_bytes())?;
+    Ok(())
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,131 @@
+use std::{io::Write, path::Path};
+use anyhow::Result;
+use serde::Serialize;
+use super::OutputFormat;
+use crate::{graph::DependencyGraph, report::Report};
+
+// hubs コマンドで既定で表示するファイル数
+pub const DEFAULT_TOP: usize = 20;
+
+#[derive(Serialize)]
+struct HubsReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    // 解析したファイル数 (hubs はそのうち上位のものだけ)
+    files: usize,
+    hubs: Vec<Hub>,
+}
+
+// 中心性の高いファイル 1 件
+#[derive(Debug, Serialize)]
+pub struct Hub {
+    pub path: String,
+    // 媒介中心性 (0〜1。ほかのファイル同士をつなぐ最短のインポートの連鎖がこのファイルを通る割合)
+    pub betweenness: f64,
+    // 次数中心性 (fan_in + fan_out)
+    pub fan_in: usize,
+    pub fan_out: usize,
+    // このファイルを直接・間接にインポートしているファイル数 (変更が波及する範囲)
+    pub dependents: usize,
+}
+
+// hubs コマンドの出力: 媒介中心性の高い順 (同じなら影響するファイル数の多い順) に上位 top 件
+pub fn write(
+    format: OutputFormat,
+    report: &Report,
+    graph: &DependencyGraph,
+    top: usize,
+    out: &mut impl Write,
+) -> Result<()> {
+    let root = Path::new(&report.target);
+    let (fan_in, fan_out, betweenness) = (graph.fan_in(), graph.fan_out(), graph.betweenness());
+    let mut hubs: Vec<Hub> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            let id = node.id.as_str();
+            Hub {
+                path: root.join(id).display().to_string(),
+                betweenness: betweenness[id],
+                fan_in: fan_in[id],
+                fan_out: fan_out[id],
+                dependents: graph.transitive_dependents(std::slice::from_ref(&node.id)).len(),
+            }
+        })
+        .collect();
+    hubs.sort_by(|a, b| {
+        b.betweenness
+            .total_cmp(&a.betweenness)
+            .then(b.dependents.cmp(&a.dependents))
+            .then(b.fan_in.cmp(&a.fan_in))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    hubs.truncate(top);
+
+    if format == OutputFormat::Json {
+        let report = HubsReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            files: graph.nodes.len(),
+            hubs,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "\n===== 中心性の高いファイル（{} ファイル中の上位 {} 件。媒介中心性の高い順） =====",
+        graph.nodes.len(),
+        hubs.len()
+    )?;
+    for hub in &hubs {
+        writeln!(
+            out,
+            "{:<60} 媒介 {:.3}  fan-in {:<4} fan-out {:<4} 影響 {} ファイル",
+            hub.path, hub.betweenness, hub.fan_in, hub.fan_out, hub.dependents
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        graph::{Edge, Node},
+        Aggregate, AnalyzerConfig,
+    };
+
+    fn graph(nodes: &[&str], edges: &[(&str, &str)]) -> DependencyGraph {
+        DependencyGraph {
+            nodes: nodes.iter().map(|id| Node { id: id.to_string(), size: 0 }).collect(),
+            edges: edges
+                .iter()
+                .map(|(from, to)| Edge { from: from.to_string(), to: to.to_string(), weight: 1, dynamic: false })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn orders_by_betweenness_then_dependents() {
+        let report = Aggregate::new(Path::new("src"), &AnalyzerConfig::default()).finish().report;
+        let edges = [("a.ts", "hub.ts"), ("b.ts", "hub.ts"), ("hub.ts", "x.ts"), ("hub.ts", "y.ts"), ("y.ts", "z.ts")];
+        let graph = graph(&["a.ts", "b.ts", "hub.ts", "x.ts", "y.ts", "z.ts"], &edges);
+        let mut out = Vec::new();
+        write(OutputFormat::Json, &report, &graph, 3, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(json["files"], 6);
+        let hubs = json["hubs"].as_array().unwrap();
+        let paths: Vec<_> = hubs.iter().map(|hub| hub["path"].as_str().unwrap()).collect();
+        // 媒介中心性が 0 の z.ts と x.ts では、影響するファイルの多い z.ts が先
+        assert_eq!(paths, ["src/hub.ts", "src/y.ts", "src/z.ts"]);
+        assert_eq!(hubs[0]["fan_in"], 2);
+        assert_eq!(hubs[0]["fan_out"], 2);
+        assert_eq!(hubs[0]["dependents"], 2);
+        // a / b から x / y / z への 6 本が hub.ts を通る。(n-1)(n-2) = 20 で割る
+        assert!((hubs[0]["betweenness"].as_f64().unwrap() - 0.3).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::Write;
+use anyhow::Result;
+use serde::Serialize;
+use super::OutputFormat;
+
+// impact コマンドの結果
+#[derive(Debug, Serialize)]
+pub struct ImpactReport {
+    pub schema_version: u32,
+    pub target: String,
+    // 変更したファイルのうち解析対象のもの (テンプレート・スタイルは同じ名前の .ts に置き換える)
+    pub changed: Vec<String>,
+    // 変更したファイルを直接・間接にインポートしているファイル (深さの順)
+    pub affected: Vec<AffectedFile>,
+    // 解析対象に無い、変更したファイル (指定されたとおりに表示する)
+    pub ignored: Vec<String>,
+}
+
+// 変更の影響を受けるファイル 1 件
+#[derive(Debug, Serialize)]
+pub struct AffectedFile {
+    pub path: String,
+    // 変更したファイルを直接インポートしていれば 1
+    pub depth: usize,
+    // テストのファイル (*.spec.* や e2e フォルダ等)
+    pub test: bool,
+}
+
+// impact コマンドの出力
+pub fn write(format: OutputFormat, report: &ImpactReport, out: &mut impl Write) -> Result<()> {
+    if format == OutputFormat::Json {
+        serde_json::to_writer_pretty(&mut *out, report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== 変更したファイル（{} 件） =====", report.changed.len())?;
+    for path in &report.changed {
+        writeln!(out, "{path}")?;
+    }
+    let tests = report.affected.iter().filter(|file| file.test).count();
+    writeln!(out, "\n===== 影響を受けるファイル（{} 件。うちテスト {tests} 件） =====", report.affected.len())?;
+    for file in &report.affected {
+        let test = if file.test { "  [テスト]" } else { "" };
+        writeln!(out, "{:<60} 深さ {}{test}", file.path, file.depth)?;
+    }
+    if !report.ignored.is_empty() {
+        writeln!(out, "\n===== 解析対象に含まれないファイル（{} 件） =====", report.ignored.len())?;
+        for path in &report.ignored {
+            writeln!(out, "{path}")?;
+        }
+    }
+    Ok(())
+}
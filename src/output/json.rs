@@ -0,0 +1,12 @@
+use std::io::Write;
+use anyhow::Result;
+use crate::report::Report;
+
+// --format json の出力に対応する JSON Schema (--schema で表示する)
+pub const SCHEMA: &str = include_str!("report.schema.json");
+
+pub fn write(report: &Report, out: &mut impl Write) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *out, report)?;
+    writeln!(out)?;
+    Ok(())
+}
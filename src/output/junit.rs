@@ -0,0 +1,70 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+};
+use anyhow::Result;
+use super::xml_escape;
+use crate::report::{Finding, Report, RULES};
+
+// JUnit XML。ルール × ファイルを 1 テストケースとし、指摘があれば failure にする
+pub fn write(report: &Report, out: &mut impl Write) -> Result<()> {
+    let findings = report.findings();
+    let mut by_case: BTreeMap<(&str, &str), Vec<&Finding>> = BTreeMap::new();
+    for finding in &findings {
+        by_case
+            .entry((finding.rule.id, finding.path.as_str()))
+            .or_default()
+            .push(finding);
+    }
+
+    // 指摘の無いファイルも成功したテストケースとして出す
+    let paths: BTreeSet<&str> = report
+        .files
+        .iter()
+        .map(|f| f.path.as_str())
+        .chain(report.parse_failures.iter().map(|f| f.path.as_str()))
+        .collect();
+
+    let tests = RULES.len() * paths.len();
+    let failures = by_case.len();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<testsuites name="{}" tests="{tests}" failures="{failures}">"#,
+        env!("CARGO_PKG_NAME")
+    )?;
+    for rule in RULES {
+        let rule_failures = by_case.keys().filter(|(id, _)| *id == rule.id).count();
+        writeln!(
+            out,
+            r#"  <testsuite name="{}" tests="{}" failures="{rule_failures}">"#,
+            rule.id,
+            paths.len()
+        )?;
+        for path in &paths {
+            let name = xml_escape(path);
+            match by_case.get(&(rule.id, path)) {
+                None => writeln!(out, r#"    <testcase classname="{}" name="{name}"/>"#, rule.id)?,
+                Some(case_findings) => {
+                    writeln!(out, r#"    <testcase classname="{}" name="{name}">"#, rule.id)?;
+                    let details: Vec<String> = case_findings
+                        .iter()
+                        .map(|f| format!("{}:{}:{}: {}", f.path, f.line, f.column, f.message))
+                        .collect();
+                    writeln!(
+                        out,
+                        r#"      <failure message="{}" type="{}">{}</failure>"#,
+                        xml_escape(&format!("{} 件: {}", case_findings.len(), rule.description)),
+                        rule.id,
+                        xml_escape(&details.join("\n"))
+                    )?;
+                    writeln!(out, "    </testcase>")?;
+                }
+            }
+        }
+        writeln!(out, "  </testsuite>")?;
+    }
+    writeln!(out, "</testsuites>")?;
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{collections::BTreeMap, io::Write};
+use anyhow::Result;
+use crate::graph::DependencyGraph;
+
+// madge --json と同じ形: 解析対象からの相対パスをキーに、インポートしているファイルをパスの順に並べる
+// (インポートの無いファイルは空の配列。import() だけのものも含める)
+pub fn write(graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    let mut adjacency: BTreeMap<&str, Vec<&str>> = graph.nodes.iter().map(|node| (node.id.as_str(), Vec::new())).collect();
+    for edge in &graph.edges {
+        adjacency.entry(&edge.from).or_default().push(&edge.to);
+    }
+    for imports in adjacency.values_mut() {
+        imports.sort();
+    }
+    serde_json::to_writer_pretty(&mut *out, &adjacency)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+// madge --circular --json と同じ形: 循環しているファイルの組ごとに、先頭のファイルからインポートを辿って戻ってくる
+// までのファイルの配列 (先頭のファイルは末尾に繰り返さない)
+pub fn write_circular(graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    let circular: Vec<Vec<String>> = graph
+        .cycles()
+        .iter()
+        .map(|files| {
+            let mut chain = graph.cycle_chain(files);
+            chain.pop();
+            chain
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *out, &circular)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, Node};
+
+    fn graph(nodes: &[&str], edges: &[(&str, &str)]) -> DependencyGraph {
+        DependencyGraph {
+            nodes: nodes.iter().map(|id| Node { id: id.to_string(), size: 0 }).collect(),
+            edges: edges
+                .iter()
+                .map(|(from, to)| Edge { from: from.to_string(), to: to.to_string(), weight: 1, dynamic: false })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn writes_adjacency_and_cycles() {
+        let edges = [("main.ts", "b.ts"), ("main.ts", "a.ts"), ("a.ts", "b.ts"), ("b.ts", "a.ts")];
+        let graph = graph(&["main.ts", "a.ts", "b.ts", "util.ts"], &edges);
+
+        let mut out = Vec::new();
+        write(&graph, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"a.ts": ["b.ts"], "b.ts": ["a.ts"], "main.ts": ["a.ts", "b.ts"], "util.ts": []})
+        );
+
+        let mut out = Vec::new();
+        write_circular(&graph, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json, serde_json::json!([["a.ts", "b.ts"]]));
+    }
+}
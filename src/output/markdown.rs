@@ -0,0 +1,327 @@
+use std::io::Write;
+use anyhow::Result;
+use super::{human_duration, human_size, Options};
+use crate::report::Report;
+
+pub fn write(report: &Report, options: Options, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "# 依存関係レポート")?;
+    writeln!(out)?;
+    writeln!(out, "- 解析対象: `{}`", report.target)?;
+    writeln!(out, "- 解析ファイル数: {}", report.files_analyzed)?;
+    writeln!(out, "- パース失敗: {}", report.parse_failures.len())?;
+    writeln!(out, "- インポート宣言: {}", report.summary.import_declarations)?;
+    writeln!(out, "- パッケージ: {}", report.summary.distinct_packages)?;
+    if !report.partial_files.is_empty() {
+        writeln!(out, "- パースエラーの箇所を除いて解析: {}", report.partial_files.len())?;
+    }
+    if !report.skipped_files.is_empty() {
+        writeln!(out, "- 解析しなかったファイル: {}", report.skipped_files.len())?;
+    }
+    if !report.policy_violations.is_empty() {
+        writeln!(out, "- 依存ポリシーの違反: {}", report.policy_violations.len())?;
+    }
+    if !report.dependency_issues.is_empty() {
+        writeln!(out, "- package.json の依存との食い違い: {}", report.dependency_issues.len())?;
+    }
+    if !report.orphan_files.is_empty() {
+        writeln!(out, "- どこからもインポートされていないファイル: {}", report.orphan_files.len())?;
+    }
+    if !report.unused_exports.is_empty() {
+        writeln!(out, "- どこからもインポートされていないエクスポート: {}", report.unused_exports.len())?;
+    }
+    if !report.cycles.is_empty() {
+        writeln!(out, "- 循環依存: {}", report.cycles.len())?;
+    }
+    if let Some(elapsed) = report.summary.elapsed_ms {
+        writeln!(out, "- 所要時間: {}", human_duration(elapsed))?;
+    }
+    if let Some(cache) = report.summary.cache {
+        let lookups = cache.hits + cache.misses;
+        writeln!(out, "- キャッシュのヒット率: {:.0}% ({} / {lookups})", cache.hit_rate * 100.0, cache.hits)?;
+    }
+
+    writeln!(out)?;
+    if options.by_package {
+        writeln!(out, "## よく使われているパッケージ")?;
+        writeln!(out)?;
+        if report.packages.is_empty() {
+            writeln!(out, "なし")?;
+        } else {
+            writeln!(out, "| パッケージ | 使用回数 | ファイル数 | インポート名 |")?;
+            writeln!(out, "| --- | ---: | ---: | --- |")?;
+            let (packages, rest) = options.limit(report.packages.iter().collect(), |package| package.count);
+            for package in packages {
+                writeln!(
+                    out,
+                    "| {} | {} | {} | {} |",
+                    md_cell(&package.package),
+                    package.count,
+                    package.files,
+                    md_cell(&package.symbols.join(", "))
+                )?;
+            }
+            if rest > 0 {
+                writeln!(out)?;
+                writeln!(out, "その他（{rest} パッケージ）")?;
+            }
+        }
+    } else {
+        writeln!(out, "## よく使われているインポート")?;
+        writeln!(out)?;
+        if report.usages.is_empty() {
+            writeln!(out, "なし")?;
+        } else {
+            writeln!(out, "| インポート名 | モジュール | 使用回数 | 型の位置 | ファイル数 | 型のみ |")?;
+            writeln!(out, "| --- | --- | ---: | ---: | ---: | ---: |")?;
+            let (usages, rest) = options.limit(report.usages.iter().collect(), |entry| entry.count);
+            for entry in usages {
+                writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} | {} | {} |",
+                    md_cell(&entry.name),
+                    md_cell(&entry.module),
+                    entry.count,
+                    entry.type_count,
+                    entry.files,
+                    entry.type_only_files
+                )?;
+            }
+            if rest > 0 {
+                writeln!(out)?;
+                writeln!(out, "その他（{rest} 件のインポート名）")?;
+            }
+        }
+    }
+
+    if options.per_file {
+        writeln!(out)?;
+        writeln!(out, "## ファイル別")?;
+        for file in &report.files {
+            writeln!(out)?;
+            writeln!(out, "### {}", file.path)?;
+            writeln!(out)?;
+            if file.imports.is_empty() {
+                writeln!(out, "インポートなし")?;
+                continue;
+            }
+            writeln!(out, "| インポート名 | モジュール | 使用回数 | 型のみ |")?;
+            writeln!(out, "| --- | --- | ---: | --- |")?;
+            for import in &file.imports {
+                writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    md_cell(&import.display_name()),
+                    md_cell(&import.module),
+                    import.count,
+                    if import.type_only { "✓" } else { "" }
+                )?;
+            }
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## 未使用のインポート")?;
+    writeln!(out)?;
+    if report.unused_imports.is_empty() {
+        writeln!(out, "なし")?;
+    } else {
+        writeln!(out, "| ファイル | インポート名 | モジュール |")?;
+        writeln!(out, "| --- | --- | --- |")?;
+        for unused in &report.unused_imports {
+            writeln!(
+                out,
+                "| {} | `{}` | {} |",
+                md_cell(&unused.path),
+                md_cell(&unused.name),
+                md_cell(&unused.module)
+            )?;
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## 動的インポート（import()）")?;
+    writeln!(out)?;
+    let dynamic = report.dynamic_modules();
+    if dynamic.is_empty() {
+        writeln!(out, "なし")?;
+    } else {
+        writeln!(out, "| モジュール | ファイル数 |")?;
+        writeln!(out, "| --- | ---: |")?;
+        for (module, files) in dynamic {
+            writeln!(out, "| {} | {files} |", md_cell(module))?;
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## 副作用のみのインポート")?;
+    writeln!(out)?;
+    let side_effects = report.side_effect_modules();
+    if side_effects.is_empty() {
+        writeln!(out, "なし")?;
+    } else {
+        writeln!(out, "| モジュール | ファイル |")?;
+        writeln!(out, "| --- | --- |")?;
+        for (module, paths) in side_effects {
+            let paths: Vec<_> = paths.into_iter().map(md_cell).collect();
+            writeln!(out, "| {} | {} |", md_cell(module), paths.join("<br>"))?;
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## 重複した import 文")?;
+    writeln!(out)?;
+    if report.duplicate_imports.is_empty() {
+        writeln!(out, "なし")?;
+    } else {
+        writeln!(out, "| ファイル | モジュール | 行 |")?;
+        writeln!(out, "| --- | --- | --- |")?;
+        for duplicate in &report.duplicate_imports {
+            let lines: Vec<_> = duplicate.lines.iter().map(|l| l.to_string()).collect();
+            writeln!(
+                out,
+                "| {} | {} | {} |",
+                md_cell(&duplicate.path),
+                md_cell(&duplicate.module),
+                lines.join(", ")
+            )?;
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## パースエラー")?;
+    writeln!(out)?;
+    if report.parse_failures.is_empty() {
+        writeln!(out, "なし")?;
+    } else {
+        writeln!(out, "| ファイル | 行:列 | 種類 | エラー | ソース |")?;
+        writeln!(out, "| --- | --- | --- | --- | --- |")?;
+        for failure in &report.parse_failures {
+            let snippet = failure.snippet.trim();
+            writeln!(
+                out,
+                "| {} | {}:{} | {} | {} | {} |",
+                md_cell(&failure.path),
+                failure.line,
+                failure.column,
+                md_cell(&failure.kind),
+                md_cell(&failure.message),
+                if snippet.is_empty() { String::new() } else { format!("`{}`", md_cell(&snippet.replace('`', "'"))) }
+            )?;
+        }
+    }
+
+    if !report.policy_violations.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "## 依存ポリシーの違反")?;
+        writeln!(out)?;
+        writeln!(out, "| ファイル | 行 | インポート | 違反 |")?;
+        writeln!(out, "| --- | ---: | --- | --- |")?;
+        for violation in &report.policy_violations {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                md_cell(&violation.path),
+                violation.line,
+                md_cell(&violation.package),
+                md_cell(&violation.message)
+            )?;
+        }
+    }
+
+    if !report.dependency_issues.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "## package.json の依存との食い違い")?;
+        writeln!(out)?;
+        writeln!(out, "| ファイル | 行 | パッケージ | 内容 |")?;
+        writeln!(out, "| --- | ---: | --- | --- |")?;
+        for issue in &report.dependency_issues {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                md_cell(&issue.path),
+                issue.line,
+                md_cell(&issue.package),
+                md_cell(&issue.message)
+            )?;
+        }
+    }
+
+    if !report.orphan_files.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "## どこからもインポートされていないファイル")?;
+        writeln!(out)?;
+        writeln!(out, "| ファイル | サイズ |")?;
+        writeln!(out, "| --- | ---: |")?;
+        for orphan in &report.orphan_files {
+            writeln!(out, "| {} | {} |", md_cell(&orphan.path), orphan.size)?;
+        }
+    }
+
+    if !report.unused_exports.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "## どこからもインポートされていないエクスポート")?;
+        writeln!(out)?;
+        writeln!(out, "| ファイル | 行 | エクスポート |")?;
+        writeln!(out, "| --- | ---: | --- |")?;
+        for export in &report.unused_exports {
+            writeln!(out, "| {} | {} | {} |", md_cell(&export.path), export.line, md_cell(&export.name))?;
+        }
+    }
+
+    if !report.cycles.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "## 循環依存")?;
+        writeln!(out)?;
+        writeln!(out, "| ファイル数 | インポートの連鎖 |")?;
+        writeln!(out, "| ---: | --- |")?;
+        for cycle in &report.cycles {
+            writeln!(out, "| {} | {} |", cycle.files.len(), md_cell(&cycle.chain.join(" → ")))?;
+        }
+    }
+
+    if !report.file_coupling.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "## ファイルの fan-in / fan-out")?;
+        writeln!(out)?;
+        writeln!(out, "| ファイル | fan-in | fan-out |")?;
+        writeln!(out, "| --- | ---: | ---: |")?;
+        for file in &report.file_coupling {
+            writeln!(out, "| {} | {} | {} |", md_cell(&file.path), file.fan_in, file.fan_out)?;
+        }
+        writeln!(out)?;
+        writeln!(out, "## フォルダの fan-in / fan-out")?;
+        writeln!(out)?;
+        writeln!(out, "| フォルダ | ファイル数 | fan-in | fan-out | 不安定度 | 抽象度 | 主系列からの距離 |")?;
+        writeln!(out, "| --- | ---: | ---: | ---: | ---: | ---: | ---: |")?;
+        for directory in &report.directory_coupling {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {:.2} | {:.2} | {:.2} |",
+                md_cell(&directory.path),
+                directory.files,
+                directory.fan_in,
+                directory.fan_out,
+                directory.instability,
+                directory.abstractness,
+                directory.distance
+            )?;
+        }
+    }
+
+    if !report.skipped_files.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "## 解析しなかったファイル")?;
+        writeln!(out)?;
+        writeln!(out, "| ファイル | サイズ | 理由 |")?;
+        writeln!(out, "| --- | ---: | --- |")?;
+        for file in &report.skipped_files {
+            writeln!(out, "| {} | {} | {} |", md_cell(&file.path), human_size(file.size), file.reason.label())?;
+        }
+    }
+    Ok(())
+}
+
+// 表のセル内で区切り文字と解釈されないよう | をエスケープし、改行は空白にする
+fn md_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
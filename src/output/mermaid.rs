@@ -0,0 +1,30 @@
+use std::{collections::HashMap, io::Write};
+use anyhow::Result;
+use crate::graph::DependencyGraph;
+
+// Mermaid の flowchart (graph TD)。Markdown にそのまま埋め込める
+pub fn write(graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    // パスは ID に使えない文字を含むため連番 ID を振ってラベルに表示する
+    let ids: HashMap<&str, String> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), format!("n{i}")))
+        .collect();
+
+    writeln!(out, "graph TD")?;
+    for node in &graph.nodes {
+        writeln!(out, "  {}[\"{}\"]", ids[node.id.as_str()], label(&node.id))?;
+    }
+    for edge in &graph.edges {
+        // import() だけの辺は点線にする
+        let arrow = if edge.dynamic { "-.->" } else { "-->" };
+        writeln!(out, "  {} {arrow} {}", ids[edge.from.as_str()], ids[edge.to.as_str()])?;
+    }
+    Ok(())
+}
+
+// ラベル内の " は Mermaid のエンティティで表す
+fn label(node: &str) -> String {
+    node.replace('"', "#quot;")
+}
@@ -0,0 +1,276 @@
+pub mod baseline;
+mod checkstyle;
+pub mod clusters;
+mod csv;
+mod depcruise;
+pub mod deps;
+pub mod depth;
+mod dot;
+mod gexf;
+mod github;
+mod gitlab;
+mod graphml;
+pub mod health;
+pub mod history;
+pub mod hubs;
+pub mod impact;
+mod html;
+pub mod json;
+mod junit;
+pub mod madge;
+mod markdown;
+mod mermaid;
+pub mod ndjson;
+pub mod path;
+pub mod projects;
+pub mod rdeps;
+mod sarif;
+mod text;
+pub mod unused;
+pub mod usages;
+
+use std::{io::Write, path::Path, str::FromStr};
+use anyhow::{bail, Result};
+use crate::{graph::DependencyGraph, report::Report};
+
+// 解析レポートの出力形式 (--format)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    Markdown,
+    Html,
+    Sarif,
+    Ndjson,
+    Checkstyle,
+    Junit,
+    Github,
+    Gitlab,
+}
+
+impl OutputFormat {
+    // --output の拡張子から出力形式を推定する
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("json") => Some(Self::Json),
+            Some("csv") => Some(Self::Csv),
+            Some("md") => Some(Self::Markdown),
+            Some("html") | Some("htm") => Some(Self::Html),
+            Some("sarif") => Some(Self::Sarif),
+            Some("ndjson") | Some("jsonl") => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "sarif" => Ok(Self::Sarif),
+            "ndjson" => Ok(Self::Ndjson),
+            "checkstyle" => Ok(Self::Checkstyle),
+            "junit" => Ok(Self::Junit),
+            "github" => Ok(Self::Github),
+            "gitlab" => Ok(Self::Gitlab),
+            other => bail!(
+                "未対応の出力形式です: {other} \
+                 (text / json / csv / markdown / html / sarif / ndjson / checkstyle / junit / github / gitlab)"
+            ),
+        }
+    }
+}
+
+// 形式をまたいで共通の表示オプション
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    // --per-file: ファイルごとの内訳も表示する
+    pub per_file: bool,
+    // --by-package: インポート名ではなくパッケージ単位の集計を表示する
+    pub by_package: bool,
+    // --sort: 並び順
+    pub sort: SortKey,
+    // --reverse: 並び順を逆にする
+    pub reverse: bool,
+    // --top: テキスト・Markdown で表示するインポート名・パッケージの数
+    pub top: Option<usize>,
+    // --min-count: テキスト・Markdown で使用回数がこれより少ないものを表示しない (0 なら絞り込まない)
+    pub min_count: usize,
+}
+
+impl Options {
+    // 使用回数が --min-count 未満のものを除き、先頭から --top 件までに絞る。2 つ目は表示しなかった件数
+    // (「その他」の 1 行にまとめる)
+    fn limit<'a, T>(&self, entries: Vec<&'a T>, count: impl Fn(&T) -> usize) -> (Vec<&'a T>, usize) {
+        let total = entries.len();
+        let mut shown: Vec<&T> = entries.into_iter().filter(|entry| count(entry) >= self.min_count).collect();
+        if let Some(top) = self.top {
+            shown.truncate(top);
+        }
+        let rest = total - shown.len();
+        (shown, rest)
+    }
+}
+
+// 集計結果の並び順 (数は多い順、名前は辞書順)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    // 参照回数の合計
+    #[default]
+    Count,
+    // インポートしているファイル数
+    Files,
+    // インポート名 (パッケージ単位の集計ではパッケージ名)
+    Name,
+    // インポート元モジュール (実行ごとの出力の差分を読みやすくする)
+    Module,
+}
+
+impl SortKey {
+    // 見出しに使う並び順の説明
+    pub fn label(self, reverse: bool) -> &'static str {
+        match (self, reverse) {
+            (Self::Count, false) => "使用回数の多い順",
+            (Self::Count, true) => "使用回数の少ない順",
+            (Self::Files, false) => "ファイル数の多い順",
+            (Self::Files, true) => "ファイル数の少ない順",
+            (Self::Name, false) => "名前順",
+            (Self::Name, true) => "名前の逆順",
+            (Self::Module, false) => "モジュール順",
+            (Self::Module, true) => "モジュールの逆順",
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "count" => Ok(Self::Count),
+            "files" => Ok(Self::Files),
+            "name" => Ok(Self::Name),
+            "module" => Ok(Self::Module),
+            other => bail!("未対応の並び順です: {other} (count / files / name / module)"),
+        }
+    }
+}
+
+// 指定された形式でレポートを書き出す
+pub fn write(format: OutputFormat, report: &Report, options: Options, out: &mut impl Write) -> Result<()> {
+    match format {
+        OutputFormat::Text => text::write(report, options, out),
+        OutputFormat::Json => json::write(report, out),
+        OutputFormat::Csv => csv::write(report, options, out),
+        OutputFormat::Markdown => markdown::write(report, options, out),
+        OutputFormat::Html => html::write(report, out),
+        OutputFormat::Sarif => sarif::write(report, out),
+        // ファイルごとの行は解析中に書き出し済みなので、最後に集計行だけを書く
+        OutputFormat::Ndjson => ndjson::write_summary(report, out),
+        OutputFormat::Checkstyle => checkstyle::write(report, out),
+        OutputFormat::Junit => junit::write(report, out),
+        OutputFormat::Github => github::write(report, out),
+        OutputFormat::Gitlab => gitlab::write(report, out),
+    }
+}
+
+// graph コマンドの出力形式 (--format)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Graphml,
+    Gexf,
+    // 行がインポートする側、列がインポートされる側の隣接行列 (--group-by でフォルダ間の行列にする)
+    Csv,
+    // dependency-cruiser の JSON 出力と同じ形 (npm パッケージと依存ポリシーの違反も含む)
+    Depcruise,
+    // madge --json と同じ、ファイルごとにインポートしているファイルを並べた JSON
+    Madge,
+}
+
+impl GraphFormat {
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("dot") | Some("gv") => Some(Self::Dot),
+            Some("mmd") | Some("mermaid") => Some(Self::Mermaid),
+            Some("graphml") => Some(Self::Graphml),
+            Some("gexf") => Some(Self::Gexf),
+            Some("csv") => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "mermaid" => Ok(Self::Mermaid),
+            "graphml" => Ok(Self::Graphml),
+            "gexf" => Ok(Self::Gexf),
+            "csv" => Ok(Self::Csv),
+            "dependency-cruiser" | "depcruise" => Ok(Self::Depcruise),
+            "madge" => Ok(Self::Madge),
+            other => bail!(
+                "graph コマンドで未対応の出力形式です: {other} \
+                 (dot / mermaid / graphml / gexf / csv / dependency-cruiser / madge)"
+            ),
+        }
+    }
+}
+
+// 指定された形式で依存グラフを書き出す (report は npm パッケージなどグラフに無いものも出す形式で使う)
+pub fn write_graph(format: GraphFormat, report: &Report, graph: &DependencyGraph, out: &mut impl Write) -> Result<()> {
+    match format {
+        GraphFormat::Dot => dot::write(graph, out),
+        GraphFormat::Mermaid => mermaid::write(graph, out),
+        GraphFormat::Graphml => graphml::write(graph, out),
+        GraphFormat::Gexf => gexf::write(graph, out),
+        GraphFormat::Csv => csv::write_matrix(graph, out),
+        GraphFormat::Depcruise => depcruise::write(report, graph, out),
+        GraphFormat::Madge => madge::write(graph, out),
+    }
+}
+
+// バイト数を 1.5 MB のように表示する
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+// ミリ秒を 850 ms / 1.2 秒 のように表示する
+fn human_duration(ms: u64) -> String {
+    match ms {
+        0..1000 => format!("{ms} ms"),
+        _ => format!("{:.1} 秒", ms as f64 / 1000.0),
+    }
+}
+
+// XML の属性値・テキストとして安全な形にエスケープする
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
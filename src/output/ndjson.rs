@@ -0,0 +1,93 @@
+use std::io::Write;
+use anyhow::Result;
+use serde::Serialize;
+use crate::report::{
+    Cycle, DependencyIssue, DirectoryCoupling, FileCoupling, FileEntry, OrphanFile, ParseFailure, PolicyViolation, Report,
+    RunSummary, SkippedFile, UnusedExport, UsageEntry,
+};
+
+// 1 行 1 レコードの NDJSON。type フィールドでレコードの種類を区別する
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    File(&'a FileEntry),
+    ParseFailure(&'a ParseFailure),
+    Skipped(&'a SkippedFile),
+    PolicyViolation(&'a PolicyViolation),
+    DependencyIssue(&'a DependencyIssue),
+    OrphanFile(&'a OrphanFile),
+    UnusedExport(&'a UnusedExport),
+    Cycle(&'a Cycle),
+    FileCoupling(&'a FileCoupling),
+    DirectoryCoupling(&'a DirectoryCoupling),
+    Summary {
+        schema_version: u32,
+        target: &'a str,
+        // JSON 出力の summary と同じ概要 (飛ばしたファイル・かかった時間・キャッシュのヒット率)
+        summary: &'a RunSummary,
+        files_analyzed: usize,
+        parse_failures: usize,
+        // パースエラーの箇所を除いて解析したファイル (file レコードの partial が true のもの)
+        partial_files: usize,
+        skipped_files: usize,
+        usages: &'a [UsageEntry],
+    },
+}
+
+fn write_record(record: &Record, out: &mut impl Write) -> Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    writeln!(out)?;
+    // 下流のコンシューマがすぐ読めるよう 1 行ごとに flush する
+    out.flush()?;
+    Ok(())
+}
+
+pub fn write_file(file: &FileEntry, out: &mut impl Write) -> Result<()> {
+    write_record(&Record::File(file), out)
+}
+
+pub fn write_parse_failure(failure: &ParseFailure, out: &mut impl Write) -> Result<()> {
+    write_record(&Record::ParseFailure(failure), out)
+}
+
+pub fn write_skipped(skipped: &SkippedFile, out: &mut impl Write) -> Result<()> {
+    write_record(&Record::Skipped(skipped), out)
+}
+
+// 予算の判定は全ファイルを解析し終えてからなので、ポリシーの違反は最後にまとめて書く (package.json との照合・孤立ファイル・未使用のエクスポート・循環依存・結合度も同じ)
+pub fn write_summary(report: &Report, out: &mut impl Write) -> Result<()> {
+    for violation in &report.policy_violations {
+        write_record(&Record::PolicyViolation(violation), out)?;
+    }
+    for issue in &report.dependency_issues {
+        write_record(&Record::DependencyIssue(issue), out)?;
+    }
+    for orphan in &report.orphan_files {
+        write_record(&Record::OrphanFile(orphan), out)?;
+    }
+    for export in &report.unused_exports {
+        write_record(&Record::UnusedExport(export), out)?;
+    }
+    for cycle in &report.cycles {
+        write_record(&Record::Cycle(cycle), out)?;
+    }
+    for file in &report.file_coupling {
+        write_record(&Record::FileCoupling(file), out)?;
+    }
+    for directory in &report.directory_coupling {
+        write_record(&Record::DirectoryCoupling(directory), out)?;
+    }
+    write_record(
+        &Record::Summary {
+            schema_version: report.schema_version,
+            target: &report.target,
+            summary: &report.summary,
+            files_analyzed: report.files_analyzed,
+            parse_failures: report.parse_failures.len(),
+            partial_files: report.partial_files.len(),
+            skipped_files: report.skipped_files.len(),
+            usages: &report.usages,
+        },
+        out,
+    )
+}
@@ -0,0 +1,81 @@
+use std::{io::Write, path::Path};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use super::{rdeps::{self, QueryKind}, OutputFormat};
+use crate::{graph::DependencyGraph, report::Report, resolve};
+
+#[derive(Serialize)]
+struct PathReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    from: &'a str,
+    to: &'a str,
+    kind: QueryKind,
+    // from から to までのファイルの連鎖 (両端を含む。パッケージなら最後はそれをインポートしているファイル)。
+    // 辿り着けなければ空
+    chain: Vec<String>,
+    // パッケージなら、連鎖の最後のファイルでの指定子 (rxjs/operators 等)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module: Option<String>,
+}
+
+// path コマンドの出力: from のファイルからインポートを辿って to のファイルかパッケージに着くまでの最短の連鎖
+pub fn write(
+    format: OutputFormat,
+    report: &Report,
+    graph: &DependencyGraph,
+    from: &str,
+    to: &str,
+    out: &mut impl Write,
+) -> Result<()> {
+    let root = Path::new(&report.target);
+    let id = |path: &str| resolve::relative_display(root, Path::new(path));
+    let is_node = |id: &str| graph.nodes.iter().any(|node| node.id == id);
+    let from_id = id(from);
+    if !is_node(&from_id) {
+        bail!("解析対象のファイルではありません: {from}");
+    }
+    let to_id = id(to);
+    let (kind, chain, module) = if is_node(&to_id) {
+        (QueryKind::File, graph.shortest_path(&from_id, |id| id == to_id), None)
+    } else if to.starts_with('.') || to.starts_with('/') || Path::new(to).is_file() {
+        bail!("解析対象のファイルではありません: {to}");
+    } else {
+        let importers = rdeps::package_importers(report, to);
+        let chain = graph.shortest_path(&from_id, |id| importers.contains_key(id));
+        let module = chain.as_ref().and_then(|chain| chain.last()).and_then(|last| importers.get(last)).cloned();
+        (QueryKind::Package, chain, module)
+    };
+    let chain: Vec<String> = chain.unwrap_or_default().iter().map(|id| root.join(id).display().to_string()).collect();
+
+    if format == OutputFormat::Json {
+        let report = PathReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            from,
+            to,
+            kind,
+            chain,
+            module,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== {from} から {to} へのインポートの連鎖 =====")?;
+    let Some((first, rest)) = chain.split_first() else {
+        writeln!(out, "インポートを辿っても {to} に着きません")?;
+        return Ok(());
+    };
+    writeln!(out, "{first}")?;
+    for path in rest {
+        writeln!(out, "  → {path}")?;
+    }
+    if let Some(module) = &module {
+        writeln!(out, "  → {module}")?;
+    }
+    let steps = rest.len() + usize::from(module.is_some());
+    writeln!(out, "\n{steps} 段のインポート")?;
+    Ok(())
+}
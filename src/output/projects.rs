@@ -0,0 +1,266 @@
+use std::io::Write;
+use anyhow::Result;
+use serde::Serialize;
+use super::{health::write_signals, OutputFormat};
+use crate::{graph::Edge, health::Health};
+
+// projects / workspaces / nx / angular コマンドの結果
+#[derive(Debug, Serialize)]
+pub struct ProjectsReport {
+    // テキストの見出し (tsconfig の references / ワークスペース / Nx / angular.json)
+    #[serde(skip)]
+    pub title: &'static str,
+    // 宣言されていない依存に付ける印の説明 (依存を宣言する仕組みが無ければ None)
+    #[serde(skip)]
+    pub undeclared: Option<&'static str>,
+    // すべてのプロジェクトを合わせた健全性スコア
+    pub health: Health,
+    pub projects: Vec<ProjectSummary>,
+    pub dependencies: Vec<ProjectDependency>,
+    // matrix[from][to] は projects の順で from のプロジェクトから to のプロジェクトへの import / export 宣言の数
+    pub matrix: Vec<Vec<usize>>,
+    // 宣言しているのに、どのファイルからもインポートしていない依存
+    pub unused_references: Vec<ProjectReference>,
+    // プロジェクトの境界の決まりを破っているインポート (公開していない入口を迂回したもの等)
+    pub boundary_violations: Vec<BoundaryViolation>,
+    // npm パッケージごとの、使っているアプリとライブラリ (種類の分かるプロジェクトがあるときだけ)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub package_split: Vec<PackageSplit>,
+    // ファイル間の循環依存 (--cycles を指定したときだけ)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cycles: Vec<ProjectCycle>,
+}
+
+// 1 プロジェクト分の集計 (そのプロジェクトの設定だけで解析した結果)
+#[derive(Debug, Serialize)]
+pub struct ProjectSummary {
+    // tsconfig のあるディレクトリ (解析対象からの相対パス)・パッケージ名・Nx / angular.json のプロジェクト名
+    pub name: String,
+    // プロジェクトを定義しているファイル (tsconfig.json / package.json / project.json / angular.json)
+    pub config: String,
+    // 依存を宣言しているプロジェクトの name (tsconfig の references / package.json の依存)
+    pub references: Vec<String>,
+    pub files_analyzed: usize,
+    pub usages: usize,
+    pub unused_imports: usize,
+    pub parse_failures: usize,
+    // application / library
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    // build ターゲットのビルダー
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+    // このプロジェクトの健全性スコア (境界の違反はこのプロジェクトのファイルからのインポート)
+    pub health: Health,
+}
+
+// from のプロジェクトのファイルが to のプロジェクトのファイルをインポートしている
+#[derive(Debug, Serialize)]
+pub struct ProjectDependency {
+    pub from: String,
+    pub to: String,
+    // import / export 宣言の数の合計
+    pub weight: usize,
+    // from が to への依存を宣言しているか (tsconfig の references に無ければ tsc -b のビルド順が保証されない)。
+    // 依存を宣言する仕組みが無ければ None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared: Option<bool>,
+    // ファイル単位の依存
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectReference {
+    pub from: String,
+    pub to: String,
+}
+
+// npm パッケージを使っているアプリとライブラリ
+#[derive(Debug, Serialize)]
+pub struct PackageSplit {
+    pub package: String,
+    pub used_by: PackageScope,
+    pub applications: Vec<String>,
+    pub libraries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageScope {
+    Applications,
+    Libraries,
+    Both,
+}
+
+impl PackageScope {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Applications => "アプリだけ",
+            Self::Libraries => "ライブラリだけ",
+            Self::Both => "アプリとライブラリの両方",
+        }
+    }
+}
+
+// ファイル間の循環依存 1 件
+#[derive(Debug, Serialize)]
+pub struct ProjectCycle {
+    // 先頭のファイルからインポートを辿って先頭に戻ってくる最短の連鎖 (先頭と末尾は同じファイル)
+    pub chain: Vec<String>,
+    // 連鎖のファイルが属するプロジェクト (連鎖の順)
+    pub projects: Vec<String>,
+    // 2 つ以上のプロジェクトにまたがっている
+    pub crosses: bool,
+}
+
+// プロジェクトの境界の決まりを破っているインポート 1 件
+#[derive(Debug, Serialize)]
+pub struct BoundaryViolation {
+    pub from: String,
+    pub to: String,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub specifier: String,
+    pub message: String,
+}
+
+// テキストで依存ごとに並べるファイル単位の依存の数
+const TEXT_EDGES: usize = 3;
+
+pub fn write(format: OutputFormat, report: &ProjectsReport, out: &mut impl Write) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, report)?;
+            writeln!(out)?;
+        }
+        _ => write_text(report, out)?,
+    }
+    Ok(())
+}
+
+fn write_text(report: &ProjectsReport, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "\n===== プロジェクト（{}） =====", report.title)?;
+    for project in &report.projects {
+        writeln!(
+            out,
+            "{:<30} {:>5} ファイル  使用回数 {:>5}  未使用 {:>3}  パースエラー {:>3}  ({})",
+            project.name,
+            project.files_analyzed,
+            project.usages,
+            project.unused_imports,
+            project.parse_failures,
+            project.config
+        )?;
+        writeln!(out, "  健全性: {} ({})", project.health.score, project.health.grade)?;
+        match (&project.kind, &project.build) {
+            (Some(kind), Some(build)) => writeln!(out, "  種類: {kind}  ビルド: {build}")?,
+            (Some(kind), None) => writeln!(out, "  種類: {kind}")?,
+            (None, Some(build)) => writeln!(out, "  ビルド: {build}")?,
+            (None, None) => {}
+        }
+        if !project.references.is_empty() {
+            writeln!(out, "  参照: {}", project.references.join(", "))?;
+        }
+    }
+
+    writeln!(out, "\n===== プロジェクト間の依存 =====")?;
+    if report.dependencies.is_empty() {
+        writeln!(out, "プロジェクトをまたぐインポートはありません")?;
+    }
+    for dependency in &report.dependencies {
+        let warning = match (dependency.declared, report.undeclared) {
+            (Some(false), Some(undeclared)) => format!("  ⚠️ {undeclared}"),
+            _ => String::new(),
+        };
+        writeln!(
+            out,
+            "{} → {}  {} 件 ({} ファイル間){warning}",
+            dependency.from,
+            dependency.to,
+            dependency.weight,
+            dependency.edges.len()
+        )?;
+        for edge in dependency.edges.iter().take(TEXT_EDGES) {
+            writeln!(out, "  {} → {}", edge.from, edge.to)?;
+        }
+        if dependency.edges.len() > TEXT_EDGES {
+            writeln!(out, "  ほか {} 件", dependency.edges.len() - TEXT_EDGES)?;
+        }
+    }
+
+    if !report.dependencies.is_empty() {
+        write_matrix(report, out)?;
+    }
+
+    if !report.unused_references.is_empty() {
+        writeln!(out, "\n===== 宣言しているがインポートの無い依存（{} 件） =====", report.unused_references.len())?;
+        for reference in &report.unused_references {
+            writeln!(out, "{} → {}", reference.from, reference.to)?;
+        }
+    }
+
+    if !report.package_split.is_empty() {
+        writeln!(out, "\n===== アプリとライブラリで使っているパッケージ（{} 件） =====", report.package_split.len())?;
+        for scope in [PackageScope::Libraries, PackageScope::Applications, PackageScope::Both] {
+            let packages: Vec<&PackageSplit> = report.package_split.iter().filter(|split| split.used_by == scope).collect();
+            if packages.is_empty() {
+                continue;
+            }
+            writeln!(out, "{}:", scope.label())?;
+            for split in packages {
+                let users = match scope {
+                    PackageScope::Libraries => split.libraries.join(", "),
+                    PackageScope::Applications => split.applications.join(", "),
+                    PackageScope::Both => {
+                        format!("アプリ: {} / ライブラリ: {}", split.applications.join(", "), split.libraries.join(", "))
+                    }
+                };
+                writeln!(out, "  {:<30} ({users})", split.package)?;
+            }
+        }
+    }
+
+    if !report.cycles.is_empty() {
+        writeln!(out, "\n===== 循環依存（{} 件） =====", report.cycles.len())?;
+        for cycle in &report.cycles {
+            let scope = if cycle.crosses { "プロジェクトをまたぐ" } else { "プロジェクト内" };
+            writeln!(out, "{scope}: {}", cycle.projects.join(", "))?;
+            writeln!(out, "  {}", cycle.chain.join(" → "))?;
+        }
+    }
+
+    if !report.boundary_violations.is_empty() {
+        writeln!(out, "\n===== プロジェクトの境界を破っているインポート（{} 件） =====", report.boundary_violations.len())?;
+        for violation in &report.boundary_violations {
+            writeln!(out, "{}:{}:{}  {}", violation.path, violation.line, violation.column, violation.message)?;
+        }
+    }
+
+    writeln!(out, "\n===== 全体の健全性 =====")?;
+    writeln!(out, "スコア {} / 100 ({})  {} ファイル", report.health.score, report.health.grade, report.health.files)?;
+    write_signals(&report.health, out)?;
+    Ok(())
+}
+
+// 行のプロジェクトから列のプロジェクトへのインポートの数 (列はプロジェクトの番号で表す)
+fn write_matrix(report: &ProjectsReport, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "\n===== プロジェクト間の依存の行列（行 → 列） =====")?;
+    let width = report.projects.iter().map(|project| project.name.chars().count()).max().unwrap_or(0);
+    let mut header = format!("{:>3} {:<width$}", "", "");
+    for i in 1..=report.projects.len() {
+        header.push_str(&format!(" {i:>5}"));
+    }
+    writeln!(out, "{}", header.trim_end())?;
+    for (i, (project, row)) in report.projects.iter().zip(&report.matrix).enumerate() {
+        let mut line = format!("{:>3} {:<width$}", i + 1, project.name);
+        for weight in row {
+            match weight {
+                0 => line.push_str(&format!(" {:>5}", ".")),
+                weight => line.push_str(&format!(" {weight:>5}")),
+            }
+        }
+        writeln!(out, "{line}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    path::Path,
+};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use super::OutputFormat;
+use crate::{graph::DependencyGraph, report::Report, resolve};
+
+#[derive(Serialize)]
+struct RdepsReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    query: &'a str,
+    kind: QueryKind,
+    dependents: Vec<Dependent>,
+}
+
+// 調べる対象が解析対象内のファイルか npm パッケージか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryKind {
+    File,
+    Package,
+}
+
+// 対象を直接・間接にインポートしているファイル 1 件
+#[derive(Debug, Serialize)]
+pub struct Dependent {
+    pub path: String,
+    // 直接インポートしていれば 1
+    pub depth: usize,
+    // このファイルがインポートしている、1 つ浅いファイル (深さ 1 なら対象のファイル。パッケージなら空)
+    pub via: Vec<String>,
+}
+
+// package (サブパスも含む) を直接インポートしているファイル (グラフのノード ID) と、そのファイルでの最初の指定子
+pub fn package_importers(report: &Report, package: &str) -> BTreeMap<String, String> {
+    let root = Path::new(&report.target);
+    let imports = |module: &str| module == package || module.strip_prefix(package).is_some_and(|rest| rest.starts_with('/'));
+    let mut importers = BTreeMap::new();
+    let modules = report
+        .files
+        .iter()
+        .flat_map(|file| file.imports.iter().map(move |import| (&file.path, &import.module)))
+        .chain(report.side_effect_imports.iter().map(|s| (&s.path, &s.module)));
+    for (path, module) in modules.filter(|(_, module)| imports(module)) {
+        importers.entry(resolve::relative_display(root, Path::new(path))).or_insert_with(|| module.clone());
+    }
+    importers
+}
+
+// query が解析対象内のファイルならそのファイルを、それ以外は npm パッケージ (サブパスも含む) をインポートしている
+// ファイルから被依存関係を辿る。パスは解析対象の表示と同じ形にする
+pub fn find(report: &Report, graph: &DependencyGraph, query: &str) -> Result<(QueryKind, Vec<Dependent>)> {
+    let root = Path::new(&report.target);
+    let id = |path: &str| resolve::relative_display(root, Path::new(path));
+    let display = |id: &str| root.join(id).display().to_string();
+    let query_id = id(query);
+    // パッケージは直接インポートしているファイルを深さ 1 とし、そこから辿る
+    let (kind, starts, offset) = if graph.nodes.iter().any(|node| node.id == query_id) {
+        (QueryKind::File, vec![query_id], 0)
+    } else if query.starts_with('.') || query.starts_with('/') || Path::new(query).is_file() {
+        bail!("解析対象のファイルではありません: {query}");
+    } else {
+        (QueryKind::Package, package_importers(report, query).into_keys().collect(), 1)
+    };
+    let mut found: Vec<(String, usize)> = match kind {
+        QueryKind::File => Vec::new(),
+        QueryKind::Package => starts.iter().map(|id| (id.clone(), 1)).collect(),
+    };
+    found.extend(graph.transitive_dependents(&starts).into_iter().map(|(id, depth)| (id, depth + offset)));
+
+    // 対象のファイルは深さ 0 (パッケージは直接インポートしているファイルが深さ 1)
+    let depths: HashMap<&str, usize> = starts
+        .iter()
+        .map(|id| (id.as_str(), offset))
+        .chain(found.iter().map(|(id, depth)| (id.as_str(), *depth)))
+        .collect();
+    let dependents = found
+        .iter()
+        .map(|(id, depth)| Dependent {
+            path: display(id),
+            depth: *depth,
+            via: graph
+                .edges
+                .iter()
+                .filter(|edge| edge.from == *id)
+                .filter(|edge| depths.get(edge.to.as_str()).is_some_and(|d| d + 1 == *depth))
+                .map(|edge| display(&edge.to))
+                .collect(),
+        })
+        .collect();
+    Ok((kind, dependents))
+}
+
+// rdeps コマンドの出力。テキストは深さごとにまとめ、間接的なものには経由している 1 つ浅いファイルを添える
+pub fn write(
+    format: OutputFormat,
+    report: &Report,
+    graph: &DependencyGraph,
+    query: &str,
+    out: &mut impl Write,
+) -> Result<()> {
+    let (kind, dependents) = find(report, graph, query)?;
+    if format == OutputFormat::Json {
+        let report = RdepsReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            query,
+            kind,
+            dependents,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== {query} をインポートしているファイル =====")?;
+    let mut current = 0;
+    for dependent in &dependents {
+        if dependent.depth != current {
+            current = dependent.depth;
+            match current {
+                1 => writeln!(out, "\n深さ 1 (直接インポート)")?,
+                depth => writeln!(out, "\n深さ {depth}")?,
+            }
+        }
+        match current {
+            1 => writeln!(out, "  {}", dependent.path)?,
+            _ => writeln!(out, "  {}  ← {}", dependent.path, dependent.via.join(", "))?,
+        }
+    }
+    let direct = dependents.iter().filter(|d| d.depth == 1).count();
+    writeln!(
+        out,
+        "\n合計 {} ファイル (直接 {direct} / 間接 {})",
+        dependents.len(),
+        dependents.len() - direct
+    )?;
+    Ok(())
+}
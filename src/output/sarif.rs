@@ -0,0 +1,65 @@
+use std::{io::Write, path::Path};
+use anyhow::Result;
+use serde_json::{json, Value};
+use crate::{
+    report::{Report, RULES},
+    resolve::relative_display,
+};
+
+// SARIF 2.1.0 形式。GitHub code scanning へのアップロードを想定
+pub fn write(report: &Report, out: &mut impl Write) -> Result<()> {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+                "defaultConfiguration": { "level": rule.severity.as_str() },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = report
+        .findings()
+        .iter()
+        .map(|finding| {
+            json!({
+                "ruleId": finding.rule.id,
+                "ruleIndex": RULES.iter().position(|r| r.id == finding.rule.id),
+                "level": finding.severity.as_str(),
+                "message": { "text": finding.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": relative_display(Path::new(&report.target), Path::new(&finding.path)),
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                        "region": {
+                            "startLine": finding.line,
+                            "startColumn": finding.column,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+
+    serde_json::to_writer_pretty(&mut *out, &sarif)?;
+    writeln!(out)?;
+    Ok(())
+}
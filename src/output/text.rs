@@ -0,0 +1,340 @@
+use std::io::Write;
+use anyhow::Result;
+use super::{human_duration, human_size, Options};
+use crate::report::{ParseFailure, Report};
+
+// 走査したファイル数・飛ばした理由・インポート宣言の数・パッケージ数・かかった時間・キャッシュの利用率
+fn write_summary(report: &Report, out: &mut impl Write) -> Result<()> {
+    let summary = &report.summary;
+    writeln!(out, "\n===== 解析の概要 =====")?;
+    writeln!(
+        out,
+        "ファイル {} 件（解析 {} / パース失敗 {} / エラー箇所を除いて解析 {}）",
+        summary.files_scanned,
+        report.files_analyzed,
+        report.parse_failures.len(),
+        report.partial_files.len()
+    )?;
+    for skipped in &summary.files_skipped {
+        writeln!(out, "  解析しなかったファイル {} 件: {}", skipped.files, skipped.reason.label())?;
+    }
+    writeln!(out, "インポート宣言 {} 件 / パッケージ {} 種類", summary.import_declarations, summary.distinct_packages)?;
+    if let Some(elapsed) = summary.elapsed_ms {
+        writeln!(out, "所要時間 {}", human_duration(elapsed))?;
+    }
+    if let Some(cache) = summary.cache {
+        writeln!(
+            out,
+            "キャッシュ {:.0}%（{} / {} ファイル）",
+            cache.hit_rate * 100.0,
+            cache.hits,
+            cache.hits + cache.misses
+        )?;
+    }
+    Ok(())
+}
+
+pub fn write(report: &Report, options: Options, out: &mut impl Write) -> Result<()> {
+    write_summary(report, out)?;
+    if options.per_file {
+        writeln!(out, "\n===== ファイル別 インポート名／使用回数 =====")?;
+        for file in &report.files {
+            writeln!(out, "\n{}", file.path)?;
+            if file.imports.is_empty() {
+                writeln!(out, "  (インポートなし)")?;
+            }
+            for import in &file.imports {
+                let kind = if import.type_only { " (型のみ)" } else { "" };
+                writeln!(out, "  {:<28} {:<6} {}{kind}", import.display_name(), import.count, import.module)?;
+            }
+        }
+    }
+
+    if options.by_package {
+        writeln!(out, "\n===== パッケージ／使用回数（{}） =====", options.sort.label(options.reverse))?;
+        let (packages, rest) = options.limit(report.packages.iter().collect(), |package| package.count);
+        for package in packages {
+            writeln!(out, "{:<30} {:<6} ({} ファイル)", package.package, package.count, package.files)?;
+        }
+        if rest > 0 {
+            writeln!(out, "その他（{rest} パッケージ）")?;
+        }
+        write_dynamic(report, out)?;
+        write_side_effects(report, out)?;
+        write_duplicates(report, out)?;
+        write_policy(report, out)?;
+        write_dependency_issues(report, out)?;
+        write_orphan_files(report, out)?;
+        write_unused_exports(report, out)?;
+        write_cycles(report, out)?;
+        write_coupling(report, out)?;
+        write_parse_failures(report, out)?;
+        return write_skipped(report, out);
+    }
+
+    writeln!(
+        out,
+        "\n===== インポート名／使用回数／ファイル数（{}） =====",
+        options.sort.label(options.reverse)
+    )?;
+    let (usages, rest) = options.limit(report.usages.iter().filter(|e| !e.is_type_only()).collect(), |e| e.count);
+    for entry in usages {
+        let type_count = if entry.type_count > 0 {
+            format!(" (型 {})", entry.type_count)
+        } else {
+            String::new()
+        };
+        writeln!(
+            out,
+            "{:<50} {:<6} {} ファイル{type_count}",
+            entry.qualified_name(),
+            entry.count,
+            entry.files
+        )?;
+    }
+    write_rest(rest, out)?;
+
+    // 型のみのインポートはバンドルに影響しないので分けて表示する
+    let type_only: Vec<_> = report.usages.iter().filter(|e| e.type_only_files > 0).collect();
+    if !type_only.is_empty() {
+        writeln!(out, "\n===== 型のみのインポート（import type） =====")?;
+        let (type_only, rest) = options.limit(type_only, |e| e.count);
+        for entry in type_only {
+            writeln!(
+                out,
+                "{:<50} {:<6} (型のみ {} / {} ファイル)",
+                entry.qualified_name(),
+                entry.count,
+                entry.type_only_files,
+                entry.files
+            )?;
+        }
+        write_rest(rest, out)?;
+    }
+
+    // 実行時には使われていないので import type にできる
+    let type_position: Vec<_> = report.usages.iter().filter(|e| e.is_type_position_only()).collect();
+    if !type_position.is_empty() {
+        writeln!(out, "\n===== 型の位置でしか使われていないインポート（import type に変更可能） =====")?;
+        let (type_position, rest) = options.limit(type_position, |e| e.count);
+        for entry in type_position {
+            writeln!(out, "{:<50} {:<6} ({} ファイル)", entry.qualified_name(), entry.count, entry.files)?;
+        }
+        write_rest(rest, out)?;
+    }
+
+    write_dynamic(report, out)?;
+    write_side_effects(report, out)?;
+    write_duplicates(report, out)?;
+    write_policy(report, out)?;
+    write_dependency_issues(report, out)?;
+    write_orphan_files(report, out)?;
+    write_unused_exports(report, out)?;
+    write_cycles(report, out)?;
+    write_coupling(report, out)?;
+    write_parse_failures(report, out)?;
+    write_skipped(report, out)
+}
+
+// --top / --min-count で表示しなかったインポート名の数
+fn write_rest(rest: usize, out: &mut impl Write) -> Result<()> {
+    if rest > 0 {
+        writeln!(out, "その他（{rest} 件のインポート名）")?;
+    }
+    Ok(())
+}
+
+fn write_dynamic(report: &Report, out: &mut impl Write) -> Result<()> {
+    let modules = report.dynamic_modules();
+    if modules.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== 動的インポート（import()） =====")?;
+    for (module, files) in modules {
+        writeln!(out, "{:<50} dynamic ({files} ファイル)", module)?;
+    }
+    Ok(())
+}
+
+fn write_side_effects(report: &Report, out: &mut impl Write) -> Result<()> {
+    let modules = report.side_effect_modules();
+    if modules.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== 副作用のみのインポート（import 'x'） =====")?;
+    for (module, paths) in modules {
+        writeln!(out, "{:<50} ({} ファイル)", module, paths.len())?;
+        for path in paths {
+            writeln!(out, "  {path}")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_duplicates(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.duplicate_imports.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== 重複した import 文（1 つにまとめられます） =====")?;
+    for duplicate in &report.duplicate_imports {
+        let lines: Vec<_> = duplicate.lines.iter().map(|l| l.to_string()).collect();
+        writeln!(
+            out,
+            "{}  {}  ({} 行目)",
+            duplicate.path,
+            duplicate.module,
+            lines.join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+fn write_policy(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.policy_violations.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== 依存ポリシーの違反（{} 件） =====", report.policy_violations.len())?;
+    for violation in &report.policy_violations {
+        writeln!(out, "{}:{}:{}  {}", violation.path, violation.line, violation.column, violation.message)?;
+    }
+    Ok(())
+}
+
+fn write_dependency_issues(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.dependency_issues.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== package.json の依存との食い違い（{} 件） =====", report.dependency_issues.len())?;
+    for issue in &report.dependency_issues {
+        writeln!(out, "{}:{}:{}  {}", issue.path, issue.line, issue.column, issue.message)?;
+    }
+    Ok(())
+}
+
+fn write_orphan_files(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.orphan_files.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== どこからもインポートされていないファイル（{} 件） =====", report.orphan_files.len())?;
+    for orphan in &report.orphan_files {
+        writeln!(out, "{}  ({} バイト)", orphan.path, orphan.size)?;
+    }
+    Ok(())
+}
+
+fn write_unused_exports(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.unused_exports.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== どこからもインポートされていないエクスポート（{} 件） =====", report.unused_exports.len())?;
+    for export in &report.unused_exports {
+        writeln!(out, "{}:{}:{}  {}", export.path, export.line, export.column, export.name)?;
+    }
+    Ok(())
+}
+
+fn write_cycles(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.cycles.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== 循環依存（{} 件） =====", report.cycles.len())?;
+    for cycle in &report.cycles {
+        let first = cycle.chain.first().map(String::as_str).unwrap_or_default();
+        writeln!(out, "{first}:{}:{}  ({} ファイル)", cycle.line, cycle.column, cycle.files.len())?;
+        writeln!(out, "  {}", cycle.chain.join(" → "))?;
+    }
+    Ok(())
+}
+
+fn write_coupling(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.file_coupling.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n===== ファイルの fan-in / fan-out（fan-in の多い順） =====")?;
+    for file in &report.file_coupling {
+        writeln!(out, "{:<60} fan-in {:<4} fan-out {}", file.path, file.fan_in, file.fan_out)?;
+    }
+    writeln!(out, "\n===== フォルダの fan-in / fan-out（フォルダの外とのインポート。fan-in の多い順） =====")?;
+    writeln!(out, "(I: 不安定度 / A: 抽象度 / D: 主系列からの距離)")?;
+    for directory in &report.directory_coupling {
+        writeln!(
+            out,
+            "{:<60} fan-in {:<4} fan-out {:<4} I {:.2}  A {:.2}  D {:.2}  ({} ファイル)",
+            directory.path,
+            directory.fan_in,
+            directory.fan_out,
+            directory.instability,
+            directory.abstractness,
+            directory.distance,
+            directory.files
+        )?;
+    }
+    let distances: Vec<f64> = report.directory_coupling.iter().map(|directory| directory.distance).collect();
+    if !distances.is_empty() {
+        let average = distances.iter().sum::<f64>() / distances.len() as f64;
+        writeln!(out, "\n主系列からの距離の平均 {average:.2} (0 に近いほど依存の向きと抽象度が釣り合っている)")?;
+    }
+    Ok(())
+}
+
+fn write_parse_failures(report: &Report, out: &mut impl Write) -> Result<()> {
+    if !report.partial_files.is_empty() {
+        writeln!(
+            out,
+            "\n===== パースエラーの箇所を除いて解析したファイル（{} ファイル。未使用のインポートは判定していません） =====",
+            report.partial_files.len()
+        )?;
+        for partial in &report.partial_files {
+            let scope = if partial.imports_only { " (import 宣言のみ)" } else { "" };
+            write_failure(&partial.failure, scope, out)?;
+        }
+    }
+    if !report.parse_failures.is_empty() {
+        writeln!(
+            out,
+            "\n===== パースに失敗したファイル（{} ファイル。集計に含まれていません） =====",
+            report.parse_failures.len()
+        )?;
+        for failure in &report.parse_failures {
+            write_failure(failure, "", out)?;
+        }
+    }
+    Ok(())
+}
+
+// エラー位置の行に ^ で列を示す
+fn write_failure(failure: &ParseFailure, note: &str, out: &mut impl Write) -> Result<()> {
+    writeln!(
+        out,
+        "{}:{}:{}  [{}] {}{note}",
+        failure.path, failure.line, failure.column, failure.kind, failure.message
+    )?;
+    if !failure.snippet.trim().is_empty() {
+        let indent: String = failure
+            .snippet
+            .chars()
+            .take(failure.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        writeln!(out, "    {}", failure.snippet)?;
+        writeln!(out, "    {indent}^")?;
+    }
+    Ok(())
+}
+
+fn write_skipped(report: &Report, out: &mut impl Write) -> Result<()> {
+    if report.skipped_files.is_empty() {
+        return Ok(());
+    }
+    let total: u64 = report.skipped_files.iter().map(|file| file.size).sum();
+    writeln!(
+        out,
+        "\n===== 解析しなかったファイル（{} ファイル / {}） =====",
+        report.skipped_files.len(),
+        human_size(total)
+    )?;
+    for file in &report.skipped_files {
+        writeln!(out, "{:<50} {:>10}  {}", file.path, human_size(file.size), file.reason.label())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::io::Write;
+use anyhow::Result;
+use serde::Serialize;
+use super::OutputFormat;
+use crate::report::{Report, UnusedImport};
+
+#[derive(Serialize)]
+struct UnusedReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    files_analyzed: usize,
+    unused_imports: &'a [UnusedImport],
+}
+
+// unused コマンドの出力。テキストはファイルごとにまとめて行番号順に並べる
+pub fn write(format: OutputFormat, report: &Report, out: &mut impl Write) -> Result<()> {
+    if format == OutputFormat::Json {
+        let unused = UnusedReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            files_analyzed: report.files_analyzed,
+            unused_imports: &report.unused_imports,
+        };
+        serde_json::to_writer_pretty(&mut *out, &unused)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== 未使用のインポート =====")?;
+    let mut current = None;
+    for unused in &report.unused_imports {
+        if current != Some(unused.path.as_str()) {
+            writeln!(out, "\n{}", unused.path)?;
+            current = Some(unused.path.as_str());
+        }
+        writeln!(
+            out,
+            "  {:>4}:{:<4} {:<30} ({})",
+            unused.line, unused.column, unused.name, unused.module
+        )?;
+    }
+    writeln!(
+        out,
+        "\n合計 {} 件 ({} ファイル中)",
+        report.unused_imports.len(),
+        report.files_analyzed
+    )?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use anyhow::Result;
+use serde::Serialize;
+use super::OutputFormat;
+use crate::{
+    encoding,
+    report::{FileImport, Report},
+};
+
+#[derive(Serialize)]
+struct UsagesReport<'a> {
+    schema_version: u32,
+    target: &'a str,
+    symbol: &'a str,
+    usages: Vec<SymbolUsage<'a>>,
+}
+
+// 使用箇所 1 件分
+#[derive(Serialize)]
+pub struct SymbolUsage<'a> {
+    pub path: &'a str,
+    pub name: &'a str,
+    pub alias: Option<&'a str>,
+    pub module: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+// 'HttpClient' はエクスポート名・別名のどちらにも一致し、'@angular/common/http::HttpClient' はモジュールも絞り込む
+fn matches(import: &FileImport, symbol: &str) -> bool {
+    match symbol.rsplit_once("::") {
+        Some((module, name)) => import.module == module && import.name == name,
+        None => import.name == symbol || import.alias.as_deref() == Some(symbol),
+    }
+}
+
+// symbol の使用箇所をファイル順・出現順に並べる
+pub fn find<'a>(report: &'a Report, symbol: &str) -> Vec<SymbolUsage<'a>> {
+    report
+        .files
+        .iter()
+        .flat_map(|file| file.imports.iter().map(move |import| (file, import)))
+        .filter(|(_, import)| matches(import, symbol))
+        .flat_map(|(file, import)| {
+            import.locations.iter().map(move |loc| SymbolUsage {
+                path: &file.path,
+                name: &import.name,
+                alias: import.alias.as_deref(),
+                module: &import.module,
+                line: loc.line,
+                column: loc.column,
+            })
+        })
+        .collect()
+}
+
+// usages コマンドの出力。テキストは使用箇所ごとに前後 context 行のソースを添える
+// (sources にあるファイルはディスクではなくその内容を使う。--stdin で渡された保存前の内容など)
+pub fn write(
+    format: OutputFormat,
+    report: &Report,
+    symbol: &str,
+    context: usize,
+    sources: &HashMap<PathBuf, String>,
+    out: &mut impl Write,
+) -> Result<()> {
+    let usages = find(report, symbol);
+    if format == OutputFormat::Json {
+        let report = UsagesReport {
+            schema_version: report.schema_version,
+            target: &report.target,
+            symbol,
+            usages,
+        };
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "\n===== {symbol} の使用箇所 =====")?;
+    let mut current_path = None;
+    let mut lines: Vec<String> = Vec::new();
+    for usage in &usages {
+        // ソースはファイルが変わったときだけ読み直す
+        if current_path != Some(usage.path) {
+            let source = match sources.get(Path::new(usage.path)) {
+                Some(source) => source.clone(),
+                None => fs::read(usage.path).map(|bytes| encoding::decode_lossy(&bytes)).unwrap_or_default(),
+            };
+            lines = source.lines().map(str::to_string).collect();
+            current_path = Some(usage.path);
+        }
+        let name = match usage.alias {
+            Some(alias) => format!("{} as {alias}", usage.name),
+            None => usage.name.to_string(),
+        };
+        writeln!(out, "\n{}:{}:{}  {} ({})", usage.path, usage.line, usage.column, name, usage.module)?;
+        let first = usage.line.saturating_sub(context).max(1);
+        let last = (usage.line + context).min(lines.len());
+        for n in first..=last {
+            let marker = if n == usage.line { ">" } else { " " };
+            writeln!(out, "{marker} {n:>5} | {}", lines[n - 1])?;
+        }
+    }
+    let files = usages.iter().map(|u| u.path).collect::<BTreeSet<_>>().len();
+    writeln!(out, "\n合計 {} 件 ({files} ファイル)", usages.len())?;
+    Ok(())
+}
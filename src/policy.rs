@@ -0,0 +1,516 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use anyhow::{bail, Result};
+use crate::{
+    glob::Glob,
+    graph::ModuleRef,
+    manifest,
+    report::{PackageUsage, PolicyRule, PolicyViolation},
+    resolve::{self, PathAliases},
+};
+
+// 解析対象ディレクトリから親へ辿って探す依存ポリシーファイル
+pub const FILE_NAME: &str = "dependency-policy.yaml";
+
+// 使ってよい・使ってはいけない npm パッケージと、パッケージごとの使用回数の予算、フォルダの層の順序。
+//
+//   allowed:            # 空でなければ、これ以外のパッケージは違反 (グロブ。Node.js の組み込みモジュールは除く)
+//     - "@angular/*"
+//     - rxjs
+//   banned:             # 使ってはいけないパッケージ (: の後ろは理由)
+//     - moment: date-fns を使う
+//     - jquery
+//   budgets:            # 使用回数の上限
+//     lodash: 50
+//   layers:             # 下の層から順に (層名: フォルダのグロブ)。下の層が上の層をインポートすると違反
+//     - core: "**/core/**"
+//     - shared: "**/shared/**"
+//     - features: ["**/features/**", "**/pages/**"]
+//   forbidden:          # "ファイルのグロブ -> インポートのグロブ" (: の後ろは理由。! で始まるとそのグロブの外のファイル)
+//     - "**/domain/** -> @angular/*": ドメイン層はフレームワークに依存しない
+//     - "!**/data-access/** -> axios"
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    pub allowed: Vec<Glob>,
+    pub banned: Vec<BannedPackage>,
+    pub budgets: BTreeMap<String, usize>,
+    pub layers: Vec<Layer>,
+    pub forbidden: Vec<ForbiddenImport>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BannedPackage {
+    pub pattern: String,
+    glob: Glob,
+    pub reason: Option<String>,
+}
+
+// forbidden の規則 1 つ。from (! で始まれば from の外) のファイルは import をインポートしてはいけない
+#[derive(Debug, Clone)]
+pub struct ForbiddenImport {
+    pub from_pattern: String,
+    from: Glob,
+    outside: bool,
+    pub import_pattern: String,
+    import: Glob,
+    pub reason: Option<String>,
+}
+
+// インポートしているもの (解析対象のファイルの相対パスか npm パッケージ名)
+enum Imported<'a> {
+    File(String),
+    Package(&'a str),
+}
+
+impl ForbiddenImport {
+    fn applies_to(&self, relative: &str) -> bool {
+        self.from.matches_path(relative) != self.outside
+    }
+
+    fn forbids(&self, imported: &Imported, specifier: &str) -> bool {
+        match imported {
+            Imported::File(relative) => self.import.matches_path(relative),
+            Imported::Package(package) => self.import.matches(package) || self.import.matches(specifier),
+        }
+    }
+}
+
+// 層 1 つ。ファイルは解析対象からの相対パスが globs のどれかに一致する最初の層に属する
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    globs: Vec<Glob>,
+}
+
+// 予算を超えたときに指摘する位置 (パッケージを最初にインポートしている箇所)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ImportSite {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Policy {
+    // YAML のうち、トップレベルのキーとその下のリスト・マップ・フローリスト ([a, b]) だけを読む
+    pub fn parse(src: &str) -> Result<Self> {
+        let mut policy = Self::default();
+        let mut section: Option<&str> = None;
+        for (i, raw) in src.lines().enumerate() {
+            let line_no = i + 1;
+            let line = strip_comment(raw).trim_end();
+            if line.trim().is_empty() || line.trim() == "---" {
+                continue;
+            }
+            let indented = line.starts_with([' ', '\t']);
+            let line = line.trim();
+            if !indented {
+                let Some((key, value)) = split_key(line) else {
+                    bail!("{line_no} 行目: キー: の形式で書いてください: {line}");
+                };
+                let key = match key.as_str() {
+                    "allowed" => "allowed",
+                    "banned" => "banned",
+                    "budgets" => "budgets",
+                    "layers" => "layers",
+                    "forbidden" => "forbidden",
+                    other => {
+                        bail!("{line_no} 行目: 不明なキーです: {other} (allowed / banned / budgets / layers / forbidden)")
+                    }
+                };
+                section = Some(key);
+                match value {
+                    None => {}
+                    Some(value) if value.starts_with('[') && value.ends_with(']') => {
+                        for item in value[1..value.len() - 1].split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                            policy.add_item(key, item, line_no)?;
+                        }
+                    }
+                    Some(value) => bail!("{line_no} 行目: {key} にはリストかマップを書いてください: {value}"),
+                }
+                continue;
+            }
+            let Some(section) = section else {
+                bail!("{line_no} 行目: allowed / banned / budgets / layers / forbidden の下に書いてください: {line}");
+            };
+            let item = line.strip_prefix('-').map(str::trim).unwrap_or(line);
+            policy.add_item(section, item, line_no)?;
+        }
+        Ok(policy)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.banned.is_empty() && self.budgets.is_empty() && self.layers.is_empty()
+            && self.forbidden.is_empty()
+    }
+
+    // リストの要素 (パッケージ名) かマップの要素 (パッケージ名: 値、層名: グロブ、規則: 理由)
+    fn add_item(&mut self, section: &str, item: &str, line_no: usize) -> Result<()> {
+        let (name, value) = match split_key(item) {
+            Some((name, value)) => (name, value),
+            None => (unquote(item).to_string(), None),
+        };
+        if name.is_empty() {
+            bail!("{line_no} 行目: パッケージ名がありません");
+        }
+        match section {
+            "allowed" => {
+                if value.is_some() {
+                    bail!("{line_no} 行目: allowed にはパッケージ名だけを書いてください: {item}");
+                }
+                self.allowed.push(Glob::new(&name));
+            }
+            "banned" => self.banned.push(BannedPackage {
+                glob: Glob::new(&name),
+                pattern: name,
+                reason: value.map(|v| unquote(&v).to_string()).filter(|v| !v.is_empty()),
+            }),
+            "layers" => {
+                let globs: Vec<Glob> = match value.as_deref() {
+                    Some(value) if value.starts_with('[') && value.ends_with(']') => value[1..value.len() - 1]
+                        .split(',')
+                        .map(unquote)
+                        .filter(|glob| !glob.is_empty())
+                        .map(Glob::new)
+                        .collect(),
+                    Some(value) => vec![Glob::new(unquote(value))],
+                    None => Vec::new(),
+                };
+                if globs.is_empty() {
+                    bail!("{line_no} 行目: layers には 層名: フォルダのグロブ を書いてください: {item}");
+                }
+                if self.layers.iter().any(|layer| layer.name == name) {
+                    bail!("{line_no} 行目: 層 {name} が重複しています");
+                }
+                self.layers.push(Layer { name, globs });
+            }
+            "forbidden" => {
+                let Some((from, import)) = name.split_once("->").map(|(a, b)| (a.trim(), b.trim())) else {
+                    bail!("{line_no} 行目: forbidden には \"ファイルのグロブ -> インポートのグロブ\" を書いてください: {item}");
+                };
+                let (from, outside) = match from.strip_prefix('!') {
+                    Some(from) => (from.trim(), true),
+                    None => (from, false),
+                };
+                if from.is_empty() || import.is_empty() {
+                    bail!("{line_no} 行目: forbidden のグロブが空です: {item}");
+                }
+                self.forbidden.push(ForbiddenImport {
+                    from_pattern: from.to_string(),
+                    from: Glob::new(from),
+                    outside,
+                    import_pattern: import.to_string(),
+                    import: Glob::new(import),
+                    reason: value.map(|v| unquote(&v).to_string()).filter(|v| !v.is_empty()),
+                });
+            }
+            _ => {
+                let budget = value.as_deref().map(unquote).and_then(|v| v.parse().ok());
+                let Some(budget) = budget else {
+                    bail!("{line_no} 行目: budgets には パッケージ: 上限 (0 以上の整数) を書いてください: {item}");
+                };
+                self.budgets.insert(name, budget);
+            }
+        }
+        Ok(())
+    }
+
+    // 1 ファイルのインポートのうち、禁止・許可されていないパッケージのもの
+    pub fn check_file(&self, path: &str, sources: &[ModuleRef]) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        for source in sources {
+            let Some(package) = resolve::package_name(&source.specifier) else {
+                continue;
+            };
+            let violation = |rule, message| PolicyViolation {
+                rule,
+                package: package.to_string(),
+                path: path.to_string(),
+                line: source.line,
+                column: source.column,
+                message,
+            };
+            if let Some(banned) = self.banned.iter().find(|b| b.glob.matches(package)) {
+                let message = match &banned.reason {
+                    Some(reason) => format!("'{package}' は使用が禁止されています: {reason}"),
+                    None => format!("'{package}' は使用が禁止されています"),
+                };
+                violations.push(violation(PolicyRule::BannedPackage, message));
+            } else if !self.allowed.is_empty()
+                && !manifest::is_node_builtin(package)
+                && !self.allowed.iter().any(|glob| glob.matches(package))
+            {
+                let message = format!("'{package}' は許可されたパッケージ (allowed) にありません");
+                violations.push(violation(PolicyRule::DisallowedPackage, message));
+            }
+        }
+        violations
+    }
+
+    // 解析対象からの相対パスが属する層 (layers の添字。下の層ほど小さい)
+    fn layer_of(&self, relative: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.globs.iter().any(|glob| glob.matches_path(relative)))
+    }
+
+    // 解析対象内に解決したインポートを見る違反 (下の層から上の層へのインポートと、forbidden のインポート)
+    pub fn check_resolved(
+        &self,
+        root: &Path,
+        source_files: &HashSet<PathBuf>,
+        file_sources: &[(PathBuf, Vec<ModuleRef>)],
+        aliases: &PathAliases,
+    ) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        if self.layers.is_empty() && self.forbidden.is_empty() {
+            return violations;
+        }
+        for (path, sources) in file_sources {
+            let from = resolve::normalize(path);
+            let relative = resolve::relative_display(root, &from);
+            let lower = self.layer_of(&relative);
+            let rules: Vec<&ForbiddenImport> = self.forbidden.iter().filter(|rule| rule.applies_to(&relative)).collect();
+            if lower.is_none() && rules.is_empty() {
+                continue;
+            }
+            for source in sources {
+                let violation = |rule, message| PolicyViolation {
+                    rule,
+                    package: source.specifier.clone(),
+                    path: path.display().to_string(),
+                    line: source.line,
+                    column: source.column,
+                    message,
+                };
+                let target = resolve::resolve(&from, &source.specifier, source_files, aliases);
+                // ファイルは解析対象からの相対パス、パッケージはパッケージ名か指定子全体で比べる
+                let imported = match &target {
+                    Some(target) => Some(Imported::File(resolve::relative_display(root, target))),
+                    None if aliases.is_local(&source.specifier) => None,
+                    None => resolve::package_name(&source.specifier).map(Imported::Package),
+                };
+                let Some(imported) = imported else {
+                    continue;
+                };
+                if let Some(rule) = rules.iter().find(|rule| rule.forbids(&imported, &source.specifier)) {
+                    let subject = match rule.outside {
+                        true => format!("'{}' の外のファイル", rule.from_pattern),
+                        false => format!("'{}' に一致するファイル", rule.from_pattern),
+                    };
+                    let message = match &rule.reason {
+                        Some(reason) => format!("{subject}は '{}' をインポートできません: {reason}", source.specifier),
+                        None => format!("{subject}は '{}' をインポートできません", source.specifier),
+                    };
+                    violations.push(violation(PolicyRule::ForbiddenImport, message));
+                }
+                if let (Some(lower), Imported::File(relative)) = (lower, &imported)
+                    && let Some(upper) = self.layer_of(relative)
+                    && upper > lower
+                {
+                    let message = format!(
+                        "'{}' 層から上の '{}' 層の {} をインポートしています",
+                        self.layers[lower].name,
+                        self.layers[upper].name,
+                        root.join(relative).display()
+                    );
+                    violations.push(violation(PolicyRule::LayerViolation, message));
+                }
+            }
+        }
+        violations
+    }
+
+    // 予算を持つパッケージを最初にインポートしている箇所を sites に記録する
+    pub fn record_sites(&self, path: &str, sources: &[ModuleRef], sites: &mut HashMap<String, ImportSite>) {
+        if self.budgets.is_empty() {
+            return;
+        }
+        for source in sources {
+            let Some(package) = resolve::package_name(&source.specifier) else {
+                continue;
+            };
+            if !self.budgets.contains_key(package) {
+                continue;
+            }
+            let site = ImportSite {
+                path: path.to_string(),
+                line: source.line,
+                column: source.column,
+            };
+            match sites.get_mut(package) {
+                Some(first) if site < *first => *first = site,
+                Some(_) => {}
+                None => {
+                    sites.insert(package.to_string(), site);
+                }
+            }
+        }
+    }
+
+    // 使用回数が予算を超えたパッケージ
+    pub fn check_budgets(&self, packages: &[PackageUsage], sites: &HashMap<String, ImportSite>) -> Vec<PolicyViolation> {
+        self.budgets
+            .iter()
+            .filter_map(|(package, &budget)| {
+                let usage = packages.iter().find(|p| p.package == *package)?;
+                let site = sites.get(package)?;
+                (usage.count > budget).then(|| PolicyViolation {
+                    rule: PolicyRule::PackageBudget,
+                    package: package.clone(),
+                    path: site.path.clone(),
+                    line: site.line,
+                    column: site.column,
+                    message: format!(
+                        "'{package}' の使用回数 {} 回が予算 {budget} 回を超えています ({} ファイル)",
+                        usage.count, usage.files
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+// # 以降のコメントを除く (引用符の中と、空白の後ろでない # は除かない)
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if prev.is_whitespace() => return &line[..i],
+            _ => {}
+        }
+        prev = c;
+    }
+    line
+}
+
+// key: value を分ける (引用符で囲んだキーと、node:fs のように空白の続かない : はキーの一部)
+fn split_key(line: &str) -> Option<(String, Option<String>)> {
+    let (key, rest) = match line.chars().next() {
+        Some(q @ ('"' | '\'')) => {
+            let end = line[1..].find(q)? + 1;
+            (&line[1..end], line[end + 1..].trim_start())
+        }
+        _ => {
+            let end = line
+                .match_indices(':')
+                .map(|(i, _)| i)
+                .find(|&i| line[i + 1..].is_empty() || line[i + 1..].starts_with([' ', '\t']))?;
+            (line[..end].trim_end(), &line[end..])
+        }
+    };
+    let rest = rest.strip_prefix(':')?.trim();
+    Some((key.to_string(), (!rest.is_empty()).then(|| rest.to_string())))
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(specifier: &str) -> ModuleRef {
+        ModuleRef {
+            specifier: specifier.to_string(),
+            dynamic: false,
+            type_only: false,
+            names: Vec::new(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    #[test]
+    fn parse_reads_every_section() {
+        let src = "\
+---
+# 依存ポリシー
+allowed: [\"@angular/*\", rxjs]
+banned:
+  - moment: date-fns を使う   # 理由
+  - 'jquery'
+budgets:
+  lodash: 50
+layers:
+  - core: \"**/core/**\"
+  - features: [\"**/features/**\", '**/pages/**']
+forbidden:
+  - \"!**/data-access/** -> axios\": HTTP は data-access だけ
+  - \"**/domain/** -> @angular/*\"
+";
+        let policy = Policy::parse(src).unwrap();
+        assert_eq!(policy.allowed, vec![Glob::new("@angular/*"), Glob::new("rxjs")]);
+        assert_eq!(policy.banned.len(), 2);
+        assert_eq!(policy.banned[0].pattern, "moment");
+        assert_eq!(policy.banned[0].reason.as_deref(), Some("date-fns を使う"));
+        assert_eq!(policy.banned[1].pattern, "jquery");
+        assert_eq!(policy.banned[1].reason, None);
+        assert_eq!(policy.budgets, BTreeMap::from([("lodash".to_string(), 50)]));
+        let layers: Vec<_> = policy.layers.iter().map(|layer| (layer.name.as_str(), layer.globs.len())).collect();
+        assert_eq!(layers, [("core", 1), ("features", 2)]);
+        assert_eq!(policy.forbidden.len(), 2);
+        assert!(policy.forbidden[0].outside);
+        assert_eq!(policy.forbidden[0].from_pattern, "**/data-access/**");
+        assert_eq!(policy.forbidden[0].import_pattern, "axios");
+        assert_eq!(policy.forbidden[0].reason.as_deref(), Some("HTTP は data-access だけ"));
+        assert!(!policy.forbidden[1].outside);
+        assert_eq!(policy.forbidden[1].reason, None);
+    }
+
+    #[test]
+    fn parse_keeps_colons_and_hashes_inside_names() {
+        let policy = Policy::parse("banned:\n  - node:child_process\n  - \"a#b\": x\n").unwrap();
+        assert_eq!(policy.banned[0].pattern, "node:child_process");
+        assert_eq!(policy.banned[1].pattern, "a#b");
+        assert_eq!(policy.banned[1].reason.as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_entries() {
+        let error = |src| Policy::parse(src).unwrap_err().to_string();
+        assert!(error("unknown:\n").starts_with("1 行目: 不明なキーです"));
+        assert!(error("  - rxjs\n").starts_with("1 行目: "));
+        assert!(error("allowed: rxjs\n").starts_with("1 行目: "));
+        assert!(error("allowed:\n  - rxjs: x\n").starts_with("2 行目: "));
+        assert!(error("budgets:\n  lodash: many\n").starts_with("2 行目: "));
+        assert!(error("layers:\n  - core\n").starts_with("2 行目: "));
+        assert!(error("layers:\n  - core: a\n  - core: b\n").starts_with("3 行目: 層 core が重複しています"));
+        assert!(error("forbidden:\n  - \"a => b\"\n").starts_with("2 行目: "));
+        assert!(error("forbidden:\n  - \"! -> b\"\n").starts_with("2 行目: forbidden のグロブが空です"));
+    }
+
+    #[test]
+    fn check_file_reports_banned_and_disallowed_packages() {
+        let policy = Policy::parse("allowed: [\"@angular/*\", rxjs]\nbanned: [moment, node:child_process]\n").unwrap();
+        let sources = [
+            import("@angular/core"),
+            import("fs"),
+            import("node:path"),
+            import("node:child_process"),
+            import("rxjs/operators"),
+            import("moment"),
+            import("lodash/fp"),
+            import("./local"),
+        ];
+        let violations = policy.check_file("src/a.ts", &sources);
+        let found: Vec<_> = violations.iter().map(|v| (v.rule, v.package.as_str())).collect();
+        assert_eq!(
+            found,
+            [
+                (PolicyRule::BannedPackage, "node:child_process"),
+                (PolicyRule::BannedPackage, "moment"),
+                (PolicyRule::DisallowedPackage, "lodash"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use anyhow::{bail, Result};
+use angular_dependency_analyzer::{
+    self as analyzer,
+    graph::{Edge, ModuleRef},
+    health,
+    output::{
+        self,
+        projects::{
+            BoundaryViolation, PackageScope, PackageSplit, ProjectCycle, ProjectDependency, ProjectReference, ProjectSummary,
+            ProjectsReport,
+        },
+    },
+    resolve::{self, PathAliases},
+    tsconfig::{self, ProjectFiles, TsConfig},
+    Aggregate, FileOutcome, ProjectAnalysis,
+};
+use crate::{cwd_for, open_cache, open_output, Args};
+
+// angular.json / project.json の projectType
+pub const APPLICATION: &str = "application";
+pub const LIBRARY: &str = "library";
+
+// ファイルをまとめる単位 (tsconfig の references のプロジェクト / ワークスペースのパッケージ)
+pub struct Project {
+    pub name: String,
+    // プロジェクトを定義しているファイル (正規化した絶対パス)
+    pub config: PathBuf,
+    pub members: Members,
+    // 依存を宣言しているプロジェクトの config
+    pub references: Vec<PathBuf>,
+    // このプロジェクトだけで解析するときのエイリアス
+    pub aliases: PathAliases,
+    // application / library (angular.json / project.json の projectType)
+    pub kind: Option<String>,
+    // build ターゲットのビルダー (angular.json の builder / project.json の executor)
+    pub build: Option<String>,
+}
+
+// プロジェクトに含めるファイル。複数のプロジェクトに含まれるファイルは最も深い階層にあるもの
+// (Files は config、Dir はそのディレクトリの階層) に数える
+pub enum Members {
+    // tsconfig の files / include / exclude
+    Files(ProjectFiles),
+    // ディレクトリ (正規化した絶対パス) の下のすべて
+    Dir(PathBuf),
+}
+
+impl Members {
+    fn contains(&self, path: &Path, cwd: Option<&Path>) -> bool {
+        match self {
+            Self::Files(files) => files.contains(path),
+            Self::Dir(dir) => {
+                let path = match cwd {
+                    Some(cwd) => resolve::normalize(&cwd.join(path)),
+                    None => resolve::normalize(path),
+                };
+                path.starts_with(dir)
+            }
+        }
+    }
+}
+
+// --cycles で報告する循環依存
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleScope {
+    All,
+    // 1 つのプロジェクトの中で閉じているもの
+    Within,
+    // 2 つ以上のプロジェクトにまたがるもの
+    Across,
+}
+
+impl std::str::FromStr for CycleScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "all" => Ok(Self::All),
+            "within" => Ok(Self::Within),
+            "across" => Ok(Self::Across),
+            other => bail!("--cycles に指定できるのは all / within / across です: {other}"),
+        }
+    }
+}
+
+// 異なるプロジェクトのファイルをつなぐインポートを調べる (境界の決まりを破っていればその説明を返す)
+pub type BoundaryCheck<'a> = dyn Fn(&Project, &Project, &ModuleRef) -> Option<String> + 'a;
+
+// projects コマンド: tsconfig の references を辿って見つけたプロジェクトごとに、そのプロジェクトの tsconfig
+// (files / include / exclude と paths) で解析し、プロジェクトをまたぐファイル間の依存をまとめて出力する
+pub fn run(args: &Args) -> Result<()> {
+    let Some(root) = &args.tsconfig else {
+        bail!("tsconfig.json が見つかりません (--tsconfig で指定できます)");
+    };
+    let configs = tsconfig::load_projects(&root.path)?;
+    let base = fs::canonicalize(args.base_dir())?;
+    let cwd = cwd_for(&args.targets[0])?;
+    let projects: Vec<Project> = configs
+        .iter()
+        .map(|project| Project {
+            name: match resolve::relative_display(&base, project.path.parent().unwrap_or(Path::new(""))) {
+                name if name.is_empty() => ".".to_string(),
+                name => name,
+            },
+            config: project.path.clone(),
+            members: Members::Files(project.project_files(cwd.clone())),
+            references: project.references.clone(),
+            aliases: project.path_aliases(cwd.clone()),
+            kind: None,
+            build: None,
+        })
+        .collect();
+    // プロジェクトをまたぐインポートは、すべてのプロジェクトの paths を合わせて解決する
+    let aliases = merged_aliases(&configs, cwd);
+    let report = analyze(args, projects, aliases, "tsconfig の references", Some("references にありません"), &|_, _, _| None)?;
+    let mut out = open_output(args)?;
+    output::projects::write(args.projects_format()?, &report, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+// プロジェクトごとに解析し、プロジェクトをまたぐファイル間の依存と境界の決まりを破っているインポートをまとめる。
+// aliases はプロジェクトをまたぐインポートの解決に使う。undeclared は依存を宣言する仕組みが無ければ None
+pub fn analyze(
+    args: &Args,
+    projects: Vec<Project>,
+    mut aliases: PathAliases,
+    title: &'static str,
+    undeclared: Option<&'static str>,
+    check: &BoundaryCheck,
+) -> Result<ProjectsReport> {
+    let base = fs::canonicalize(args.base_dir())?;
+    let cwd = cwd_for(&args.targets[0])?;
+    let mut config = args.config()?;
+    config.project_files = None;
+    let depth = |project: &Project| match &project.members {
+        Members::Files(_) => project.config.components().count(),
+        Members::Dir(dir) => dir.components().count(),
+    };
+    let mut owners = BTreeMap::new();
+    let mut paths = Vec::new();
+    for path in analyzer::collect_source_files_in(&args.targets, &config)? {
+        let owner = (0..projects.len())
+            .filter(|&i| projects[i].members.contains(&path, cwd.as_deref()))
+            .max_by_key(|&i| depth(&projects[i]));
+        if let Some(owner) = owner {
+            owners.insert(resolve::normalize(&path), owner);
+            paths.push(path);
+        }
+    }
+
+    for (name, target) in &args.aliases {
+        aliases.add_alias(name, target);
+    }
+    config.path_aliases = aliases.clone();
+    let mut all = Aggregate::with_targets(&args.targets, &config);
+    let mut each: Vec<_> = projects
+        .iter()
+        .map(|project| {
+            let mut config = config.clone();
+            config.path_aliases = project.aliases.clone();
+            for (name, target) in &args.aliases {
+                config.path_aliases.add_alias(name, target);
+            }
+            Aggregate::with_targets(&args.targets, &config)
+        })
+        .collect();
+    // 境界の決まりはインポート文ごとに調べる
+    let mut sources: Vec<(PathBuf, Vec<ModuleRef>)> = Vec::new();
+    let cache = open_cache(args);
+    analyzer::for_each_path(&paths, &config, cache.as_ref(), |outcome| {
+        if let Some(&owner) = owners.get(&resolve::normalize(outcome.path())) {
+            each[owner].add(outcome.clone());
+        }
+        if let FileOutcome::Parsed(analysis) = &outcome {
+            sources.push((analysis.path.clone(), analysis.sources.clone()));
+        }
+        all.add(outcome);
+        Ok(())
+    })?;
+    let all = all.finish();
+
+    let names: Vec<String> = projects.iter().map(|project| project.name.clone()).collect();
+    let files: HashSet<PathBuf> = owners.keys().cloned().collect();
+    let mut boundary_violations = Vec::new();
+    for (path, refs) in &sources {
+        let Some(&from) = owners.get(&resolve::normalize(path)) else {
+            continue;
+        };
+        for source in refs {
+            let Some(to) = resolve::resolve(path, &source.specifier, &files, &aliases).and_then(|target| owners.get(&target))
+            else {
+                continue;
+            };
+            if from == *to {
+                continue;
+            }
+            if let Some(message) = check(&projects[from], &projects[*to], source) {
+                boundary_violations.push(BoundaryViolation {
+                    from: names[from].clone(),
+                    to: names[*to].clone(),
+                    path: path.display().to_string(),
+                    line: source.line,
+                    column: source.column,
+                    specifier: source.specifier.clone(),
+                    message,
+                });
+            }
+        }
+    }
+    boundary_violations.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+
+    // パッケージごとに使っているアプリとライブラリ
+    let mut split: BTreeMap<String, (BTreeSet<String>, BTreeSet<String>)> = BTreeMap::new();
+    let summaries = projects
+        .iter()
+        .zip(each)
+        .enumerate()
+        .map(|(i, (project, aggregate))| {
+            let ProjectAnalysis { report, graph } = aggregate.finish();
+            // プロジェクトの境界の違反は、そのプロジェクトのファイルからのインポートに数える
+            let boundary: Vec<&str> = boundary_violations
+                .iter()
+                .filter(|violation| violation.from == names[i])
+                .map(|violation| violation.path.as_str())
+                .collect();
+            for package in &report.packages {
+                let (applications, libraries) = split.entry(package.package.clone()).or_default();
+                match project.kind.as_deref() {
+                    Some(APPLICATION) => applications.insert(project.name.clone()),
+                    Some(LIBRARY) => libraries.insert(project.name.clone()),
+                    _ => false,
+                };
+            }
+            ProjectSummary {
+                name: project.name.clone(),
+                config: resolve::relative_display(&base, &project.config),
+                references: project
+                    .references
+                    .iter()
+                    .filter_map(|reference| projects.iter().position(|p| p.config == *reference))
+                    .map(|i| names[i].clone())
+                    .collect(),
+                files_analyzed: report.files_analyzed,
+                usages: report.usages.iter().map(|usage| usage.count).sum(),
+                unused_imports: report.unused_imports.len(),
+                parse_failures: report.parse_failures.len(),
+                kind: project.kind.clone(),
+                build: project.build.clone(),
+                health: health::score(&report, &graph, &boundary, &args.health),
+            }
+        })
+        .collect();
+
+    // グラフのノード ID は解析対象の共通の親からの相対パス
+    let root_dir = Path::new(&all.report.target);
+    let owner_of = |id: &str| owners.get(&resolve::normalize(&root_dir.join(id))).copied();
+    let mut dependencies: BTreeMap<(usize, usize), Vec<Edge>> = BTreeMap::new();
+    for edge in &all.graph.edges {
+        if let (Some(from), Some(to)) = (owner_of(&edge.from), owner_of(&edge.to))
+            && from != to
+        {
+            dependencies.entry((from, to)).or_default().push(edge.clone());
+        }
+    }
+    let mut matrix = vec![vec![0; projects.len()]; projects.len()];
+    for (&(from, to), edges) in &dependencies {
+        matrix[from][to] = edges.iter().map(|edge| edge.weight).sum();
+    }
+    // 宣言しているのに、どのファイルからもインポートしていない依存
+    let mut unused_references = BTreeSet::new();
+    if undeclared.is_some() {
+        for (from, project) in projects.iter().enumerate() {
+            for reference in &project.references {
+                if let Some(to) = projects.iter().position(|p| p.config == *reference)
+                    && from != to
+                    && !dependencies.contains_key(&(from, to))
+                {
+                    unused_references.insert((from, to));
+                }
+            }
+        }
+    }
+    let unused_references = unused_references
+        .into_iter()
+        .map(|(from, to)| ProjectReference {
+            from: names[from].clone(),
+            to: names[to].clone(),
+        })
+        .collect();
+    let dependencies = dependencies
+        .into_iter()
+        .map(|((from, to), edges)| ProjectDependency {
+            from: names[from].clone(),
+            to: names[to].clone(),
+            weight: edges.iter().map(|edge| edge.weight).sum(),
+            declared: undeclared.map(|_| projects[from].references.contains(&projects[to].config)),
+            edges,
+        })
+        .collect();
+
+    let package_split = split
+        .into_iter()
+        .filter_map(|(package, (applications, libraries))| {
+            let used_by = match (applications.is_empty(), libraries.is_empty()) {
+                (false, true) => PackageScope::Applications,
+                (true, false) => PackageScope::Libraries,
+                (false, false) => PackageScope::Both,
+                (true, true) => return None,
+            };
+            Some(PackageSplit {
+                package,
+                used_by,
+                applications: applications.into_iter().collect(),
+                libraries: libraries.into_iter().collect(),
+            })
+        })
+        .collect();
+
+    let mut cycles = Vec::new();
+    if let Some(scope) = args.cycles {
+        for files in all.graph.cycles() {
+            let chain = all.graph.cycle_chain(&files);
+            let mut members: Vec<usize> = Vec::new();
+            for owner in chain.iter().filter_map(|id| owner_of(id)) {
+                if !members.contains(&owner) {
+                    members.push(owner);
+                }
+            }
+            let crosses = members.len() > 1;
+            if scope == CycleScope::Within && crosses || scope == CycleScope::Across && !crosses {
+                continue;
+            }
+            cycles.push(ProjectCycle {
+                chain: chain.iter().map(|id| root_dir.join(id).display().to_string()).collect(),
+                projects: members.into_iter().map(|i| names[i].clone()).collect(),
+                crosses,
+            });
+        }
+    }
+
+    let boundary: Vec<&str> = boundary_violations.iter().map(|violation| violation.path.as_str()).collect();
+    let health = health::score(&all.report, &all.graph, &boundary, &args.health);
+
+    Ok(ProjectsReport {
+        title,
+        health,
+        undeclared,
+        projects: summaries,
+        dependencies,
+        matrix,
+        unused_references,
+        boundary_violations,
+        package_split,
+        cycles,
+    })
+}
+
+// 最初のプロジェクト (ルートの tsconfig) の paths / baseUrl に、他のプロジェクトにしか無いパターンを加える
+fn merged_aliases(projects: &[TsConfig], cwd: Option<PathBuf>) -> PathAliases {
+    let mut merged = PathAliases::default();
+    for project in projects {
+        let aliases = project.path_aliases(cwd.clone());
+        if merged.base_url.is_none() {
+            merged.base_url = aliases.base_url;
+            merged.local_roots = aliases.local_roots;
+        }
+        for (pattern, targets) in aliases.paths {
+            if !merged.paths.iter().any(|(existing, _)| *existing == pattern) {
+                merged.paths.push((pattern, targets));
+            }
+        }
+    }
+    merged.cwd = cwd;
+    merged
+}
@@ -0,0 +1,742 @@
+use std::collections::{BTreeMap, BTreeSet};
+use serde::{Deserialize, Serialize};
+use crate::{glob::Glob, resolve};
+
+// JSON 出力のスキーマバージョン。フィールドの削除・意味の変更など互換性の無い変更で上げる
+pub const SCHEMA_VERSION: u32 = 3;
+
+// 解析結果全体。テキスト表示・JSON 出力の両方がこの構造体を元にする
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub target: String,
+    // 走査したファイル数・インポート宣言の数・かかった時間などの概要
+    pub summary: RunSummary,
+    pub files_analyzed: usize,
+    pub parse_failures: Vec<ParseFailure>,
+    // パースエラーの箇所を除いて解析したもの (未使用のインポートは判定しない)
+    pub partial_files: Vec<PartialFile>,
+    // サイズの上限を超えた・生成された・文字コードを判別できないファイルとして解析しなかったもの
+    pub skipped_files: Vec<SkippedFile>,
+    pub usages: Vec<UsageEntry>,
+    pub packages: Vec<PackageUsage>,
+    pub unused_imports: Vec<UnusedImport>,
+    pub duplicate_imports: Vec<DuplicateImport>,
+    pub dynamic_imports: Vec<DynamicImport>,
+    pub side_effect_imports: Vec<SideEffectImport>,
+    // 依存ポリシー (dependency-policy.yaml) の違反
+    pub policy_violations: Vec<PolicyViolation>,
+    // package.json の依存の宣言とインポートの食い違い (--check-deps を指定したときだけ調べる)
+    pub dependency_issues: Vec<DependencyIssue>,
+    // どのエントリポイントからも辿れないファイル (--orphans を指定したときだけ調べる)
+    pub orphan_files: Vec<OrphanFile>,
+    // どのファイルからもインポートされていないエクスポート (--unused-exports を指定したときだけ調べる)
+    pub unused_exports: Vec<UnusedExport>,
+    // ファイル間の循環依存 (--cycles を指定したときだけ調べる)
+    pub cycles: Vec<Cycle>,
+    // ファイルごとの fan-in / fan-out (--coupling を指定したときだけ調べる。fan-in の多い順)
+    pub file_coupling: Vec<FileCoupling>,
+    // フォルダごとの fan-in / fan-out (--coupling を指定したときだけ調べる。fan-in の多い順)
+    pub directory_coupling: Vec<DirectoryCoupling>,
+    pub files: Vec<FileEntry>,
+    // 解析対象ごとの小計 (解析対象を 1 つだけ指定したときも 1 件入る)
+    pub targets: Vec<TargetSummary>,
+    // 設定ファイルの [rules] で変更したルールの重要度 (findings に反映する)
+    #[serde(skip)]
+    pub rule_levels: BTreeMap<String, RuleLevel>,
+}
+
+// 解析の概要。elapsed_ms と cache は CLI で解析したときだけ入る
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunSummary {
+    // 見つけたソースファイルの数 (解析した・パースに失敗した・飛ばしたものの合計)
+    pub files_scanned: usize,
+    // 解析せずに飛ばしたファイルの理由ごとの数
+    pub files_skipped: Vec<SkipCount>,
+    // import / export ... from / import() の数 (パースに失敗したファイルの分は数えない)
+    pub import_declarations: usize,
+    // インポートされている npm パッケージの数
+    pub distinct_packages: usize,
+    // ファイルの走査から集計までにかかった時間 (ミリ秒)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+    // --no-cache のとき・標準入力を解析したときは None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkipCount {
+    pub reason: SkipReason,
+    pub files: usize,
+}
+
+// キャッシュの結果を使ったファイル数と、内容が変わっていた・エントリが無かったので解析し直したファイル数
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    // hits / (hits + misses)。キャッシュを引いていなければ 0
+    pub hit_rate: f64,
+}
+
+impl CacheStats {
+    pub fn new(hits: usize, misses: usize) -> Self {
+        let hit_rate = match hits + misses {
+            0 => 0.0,
+            lookups => hits as f64 / lookups as f64,
+        };
+        Self { hits, misses, hit_rate }
+    }
+}
+
+// 解析対象 (コマンドラインで指定したフォルダ・ファイル) ごとの小計
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TargetSummary {
+    pub target: String,
+    pub files_analyzed: usize,
+    pub parse_failures: usize,
+    // パースエラーの箇所を除いて解析したファイル (files_analyzed にも含まれる)
+    pub partial_files: usize,
+    pub skipped_files: usize,
+    // インポート名の使用回数の合計
+    pub usages: usize,
+    pub unused_imports: usize,
+    pub duplicate_imports: usize,
+}
+
+// パースに失敗して集計から外したファイル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseFailure {
+    pub path: String,
+    pub message: String,
+    // swc のエラーの種類 (Unexpected / Expected / UnterminatedStrLit 等)
+    pub kind: String,
+    pub line: usize,
+    pub column: usize,
+    // エラー位置の行のソース
+    pub snippet: String,
+}
+
+// パースエラーがあり、エラーの箇所を除いて解析したファイル (集計には含める)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialFile {
+    #[serde(flatten)]
+    pub failure: ParseFailure,
+    // import 宣言だけを解析できた (インポート名の使用回数は 0 になる)
+    pub imports_only: bool,
+}
+
+// 解析せずに飛ばしたファイル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub size: u64,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    // --max-file-size を超えている
+    TooLarge,
+    // 先頭のコメントに @generated 等の目印がある
+    Generated,
+    // UTF-8 / UTF-16 / Shift_JIS のどれとしても読めない
+    Undecodable,
+}
+
+impl SkipReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TooLarge => "サイズの上限を超えている",
+            Self::Generated => "生成されたファイル",
+            Self::Undecodable => "文字コードを判別できない",
+        }
+    }
+}
+
+// (インポート元モジュール, インポート名) ごとの使用回数
+#[derive(Debug, Serialize)]
+pub struct UsageEntry {
+    pub name: String,
+    pub module: String,
+    pub count: usize,
+    // そのうち型注釈など型の位置での使用回数 (残りが実行時の使用)
+    pub type_count: usize,
+    // そのインポート名を使っているファイル数
+    pub files: usize,
+    // そのうち import type (型のみ) でインポートしているファイル数
+    pub type_only_files: usize,
+}
+
+impl UsageEntry {
+    // 表示用の module::symbol 形式
+    pub fn qualified_name(&self) -> String {
+        format!("{}::{}", self.module, self.name)
+    }
+
+    // すべてのファイルで型のみのインポート (実行時の依存にならない)
+    pub fn is_type_only(&self) -> bool {
+        self.files > 0 && self.type_only_files == self.files
+    }
+
+    // 通常の import だが型の位置でしか使われていない (import type に変更できる)
+    pub fn is_type_position_only(&self) -> bool {
+        self.count > 0 && self.type_count == self.count && !self.is_type_only()
+    }
+}
+
+// npm パッケージ単位の集計 ('rxjs/operators' は 'rxjs' にまとめる。相対インポートは含めない)
+#[derive(Debug, Serialize)]
+pub struct PackageUsage {
+    pub package: String,
+    pub count: usize,
+    pub files: usize,
+    // 使われているインポート名
+    pub symbols: Vec<String>,
+}
+
+// インポートしているがファイル内で一度も使われていないもの
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusedImport {
+    pub path: String,
+    pub name: String,
+    pub module: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// 同じモジュールを複数の import 文に分けてインポートしているもの
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateImport {
+    pub path: String,
+    pub module: String,
+    // 各 import 文の行番号 (昇順)
+    pub lines: Vec<usize>,
+}
+
+// import('...') による動的インポート (遅延読み込み)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicImport {
+    pub path: String,
+    pub module: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// import 'zone.js' のようにインポート名を持たない副作用のみのインポート
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SideEffectImport {
+    pub path: String,
+    pub module: String,
+    pub line: usize,
+}
+
+// 依存ポリシーの違反 1 件 (予算超過はそのパッケージを最初にインポートしている位置。
+// 層の違反・forbidden の違反の package はインポートの指定子)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    pub rule: PolicyRule,
+    pub package: String,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PolicyRule {
+    // banned のパッケージをインポートしている
+    BannedPackage,
+    // allowed に無いパッケージをインポートしている
+    DisallowedPackage,
+    // budgets の使用回数を超えている
+    PackageBudget,
+    // layers で下の層のファイルが上の層のファイルをインポートしている
+    LayerViolation,
+    // forbidden の規則に反するインポート
+    ForbiddenImport,
+}
+
+impl PolicyRule {
+    pub fn rule(self) -> &'static Rule {
+        match self {
+            Self::BannedPackage => &BANNED_PACKAGE,
+            Self::DisallowedPackage => &DISALLOWED_PACKAGE,
+            Self::PackageBudget => &PACKAGE_BUDGET,
+            Self::LayerViolation => &LAYER_VIOLATION,
+            Self::ForbiddenImport => &FORBIDDEN_IMPORT,
+        }
+    }
+}
+
+// エントリポイント (main.ts・ルート定義・テスト等) からインポートを辿っても届かないファイル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanFile {
+    pub path: String,
+    // ファイルサイズ (バイト)
+    pub size: u64,
+}
+
+// ファイル間の循環依存 1 件 (互いに辿り着けるファイルの組)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cycle {
+    // 組のファイル (パスの順)
+    pub files: Vec<String>,
+    // 先頭のファイルからインポートを辿って先頭に戻ってくる最短の連鎖 (先頭と末尾は同じファイル)
+    pub chain: Vec<String>,
+    // 先頭のファイルで chain の次のファイルをインポートしている位置
+    pub line: usize,
+    pub column: usize,
+}
+
+// ファイルの結合度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileCoupling {
+    pub path: String,
+    // このファイルをインポートしている解析対象内のファイル数 (afferent coupling)
+    pub fan_in: usize,
+    // このファイルがインポートしている解析対象内のファイル数 (efferent coupling)
+    pub fan_out: usize,
+}
+
+// フォルダ (ファイルの直接の親) の結合度。フォルダの中同士のインポートは数えない
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryCoupling {
+    pub path: String,
+    // フォルダの直下にある解析したファイル数
+    pub files: usize,
+    // フォルダの中のファイルをインポートしている、フォルダの外のファイル数
+    pub fan_in: usize,
+    // フォルダの中のファイルがインポートしている、フォルダの外のファイル数
+    pub fan_out: usize,
+    // フォルダの中のファイルで定義しているエクスポートの数と、そのうち interface・abstract class の数
+    pub exports: usize,
+    pub abstract_exports: usize,
+    // 不安定度 fan_out / (fan_in + fan_out)。どちらも 0 なら 0
+    pub instability: f64,
+    // 抽象度 abstract_exports / exports。エクスポートが無ければ 0
+    pub abstractness: f64,
+    // 主系列 (abstractness + instability = 1) からの距離 |abstractness + instability - 1|
+    pub distance: f64,
+}
+
+// そのファイルで定義しているが、どのファイルからもインポートされていないエクスポート
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusedExport {
+    pub path: String,
+    // エクスポート名 (export default は "default")
+    pub name: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// package.json の依存の宣言とインポートの食い違い 1 件 (未使用の依存は package.json 内の宣言の位置)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyIssue {
+    pub rule: DependencyRule,
+    pub package: String,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependencyRule {
+    // dependencies にあるがどこからもインポートされていない
+    UnusedDependency,
+    // package.json に無いパッケージをインポートしている
+    UndeclaredDependency,
+    // テスト以外のファイルが devDependencies にしか無いパッケージをインポートしている
+    DevDependencyInProduction,
+    // @angular/* のメジャー・マイナーバージョンが揃っていない
+    AngularVersionSkew,
+    // インポートしているパッケージのピア依存がインストールされていないか範囲を満たさない
+    UnmetPeerDependency,
+}
+
+impl DependencyRule {
+    pub fn rule(self) -> &'static Rule {
+        match self {
+            Self::UnusedDependency => &UNUSED_DEPENDENCY,
+            Self::UndeclaredDependency => &UNDECLARED_DEPENDENCY,
+            Self::DevDependencyInProduction => &DEV_DEPENDENCY_IN_PRODUCTION,
+            Self::AngularVersionSkew => &ANGULAR_VERSION_SKEW,
+            Self::UnmetPeerDependency => &UNMET_PEER_DEPENDENCY,
+        }
+    }
+}
+
+impl Report {
+    // 動的インポートをモジュールごとにまとめる: (モジュール, インポートしているファイル数)
+    pub fn dynamic_modules(&self) -> Vec<(&str, usize)> {
+        let mut files: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for d in &self.dynamic_imports {
+            files.entry(d.module.as_str()).or_default().insert(d.path.as_str());
+        }
+        files.into_iter().map(|(module, paths)| (module, paths.len())).collect()
+    }
+
+    // 副作用のみのインポートをモジュールごとにまとめる: (モジュール, インポートしているファイル)
+    pub fn side_effect_modules(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut files: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for s in &self.side_effect_imports {
+            files.entry(s.module.as_str()).or_default().insert(s.path.as_str());
+        }
+        files
+            .into_iter()
+            .map(|(module, paths)| (module, paths.into_iter().collect()))
+            .collect()
+    }
+
+    // --filter: インポート元モジュール (またはそのパッケージ名) かインポート名がパターンのどれかに一致するインポートだけを
+    // 残す。パッケージ単位の集計はパッケージ名か、一致するインポート名を含むかで判定する
+    pub fn retain_matching(&mut self, filters: &[Glob]) {
+        let hit = |text: &str| filter_hit(filters, text);
+        let module_hit = |module: &str| filter_module_hit(filters, module);
+        self.usages.retain(|usage| module_hit(&usage.module) || hit(&usage.name));
+        self.packages.retain(|package| hit(&package.package) || package.symbols.iter().any(|symbol| hit(symbol)));
+        for file in &mut self.files {
+            file.retain_matching(filters);
+        }
+        self.files.retain(|file| !file.imports.is_empty());
+        self.unused_imports.retain(|unused| module_hit(&unused.module) || hit(&unused.name));
+        self.duplicate_imports.retain(|duplicate| module_hit(&duplicate.module));
+        self.dynamic_imports.retain(|dynamic| module_hit(&dynamic.module));
+        self.side_effect_imports.retain(|import| module_hit(&import.module));
+    }
+}
+
+// --filter のどれかに一致する
+fn filter_hit(filters: &[Glob], text: &str) -> bool {
+    filters.iter().any(|filter| filter.matches(text))
+}
+
+// モジュールは指定子全体かパッケージ名で比べる (rxjs/operators は rxjs にも一致する)
+fn filter_module_hit(filters: &[Glob], module: &str) -> bool {
+    filter_hit(filters, module) || resolve::package_name(module).is_some_and(|package| filter_hit(filters, package))
+}
+
+// ファイルごとのインポートと使用回数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub path: String,
+    pub imports: Vec<FileImport>,
+    // パースエラーの箇所を除いて解析した
+    #[serde(default)]
+    pub partial: bool,
+}
+
+impl FileEntry {
+    // モジュールかインポート名が --filter に一致するインポートだけを残す
+    pub fn retain_matching(&mut self, filters: &[Glob]) {
+        self.imports
+            .retain(|import| filter_module_hit(filters, &import.module) || filter_hit(filters, &import.name));
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileImport {
+    // インポート元でのエクスポート名
+    pub name: String,
+    // import { A as B } の B (エクスポート名と同じなら null)
+    pub alias: Option<String>,
+    pub module: String,
+    pub count: usize,
+    // そのうち型の位置での使用回数
+    pub type_count: usize,
+    pub type_only: bool,
+    // 使用箇所 (import 文は含まない)
+    pub locations: Vec<Location>,
+}
+
+// ファイル内の位置 (1 始まり)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl FileImport {
+    // 表示用: エイリアスがあれば "A as B"
+    pub fn display_name(&self) -> String {
+        match &self.alias {
+            Some(alias) => format!("{} as {alias}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+// 指摘の重要度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+// 設定ファイルで指定するルールの重要度 (off ならそのルールでは指摘しない)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+// 検出ルール
+#[derive(Debug)]
+pub struct Rule {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub severity: Severity,
+}
+
+pub const UNUSED_IMPORT: Rule = Rule {
+    id: "unused-import",
+    description: "インポートしているがファイル内で使われていない",
+    severity: Severity::Warning,
+};
+
+pub const PARSE_ERROR: Rule = Rule {
+    id: "parse-error",
+    description: "パースに失敗したため解析対象から除外された",
+    severity: Severity::Error,
+};
+
+pub const DUPLICATE_IMPORT: Rule = Rule {
+    id: "duplicate-import",
+    description: "同じモジュールを複数の import 文でインポートしている",
+    severity: Severity::Warning,
+};
+
+pub const BANNED_PACKAGE: Rule = Rule {
+    id: "banned-package",
+    description: "依存ポリシーで禁止されたパッケージをインポートしている",
+    severity: Severity::Error,
+};
+
+pub const DISALLOWED_PACKAGE: Rule = Rule {
+    id: "disallowed-package",
+    description: "依存ポリシーで許可されていないパッケージをインポートしている",
+    severity: Severity::Error,
+};
+
+pub const PACKAGE_BUDGET: Rule = Rule {
+    id: "package-budget",
+    description: "パッケージの使用回数が依存ポリシーの予算を超えている",
+    severity: Severity::Error,
+};
+
+pub const LAYER_VIOLATION: Rule = Rule {
+    id: "layer-violation",
+    description: "依存ポリシーの層の順序に反して下の層のファイルが上の層のファイルをインポートしている",
+    severity: Severity::Error,
+};
+
+pub const FORBIDDEN_IMPORT: Rule = Rule {
+    id: "forbidden-import",
+    description: "依存ポリシーの forbidden の規則で禁止されたインポートをしている",
+    severity: Severity::Error,
+};
+
+pub const UNUSED_DEPENDENCY: Rule = Rule {
+    id: "unused-dependency",
+    description: "package.json の dependencies にあるがどこからもインポートされていない",
+    severity: Severity::Warning,
+};
+
+pub const UNDECLARED_DEPENDENCY: Rule = Rule {
+    id: "undeclared-dependency",
+    description: "package.json の dependencies / devDependencies に無いパッケージをインポートしている",
+    severity: Severity::Error,
+};
+
+pub const DEV_DEPENDENCY_IN_PRODUCTION: Rule = Rule {
+    id: "dev-dependency-in-production",
+    description: "テスト以外のファイルが devDependencies にしか無いパッケージをインポートしている",
+    severity: Severity::Error,
+};
+
+pub const ANGULAR_VERSION_SKEW: Rule = Rule {
+    id: "angular-version-skew",
+    description: "@angular/* のパッケージのメジャー・マイナーバージョンが @angular/core と揃っていない",
+    severity: Severity::Warning,
+};
+
+pub const UNMET_PEER_DEPENDENCY: Rule = Rule {
+    id: "unmet-peer-dependency",
+    description: "インポートしているパッケージのピア依存がインストールされていないか範囲を満たさない",
+    severity: Severity::Warning,
+};
+
+pub const ORPHAN_FILE: Rule = Rule {
+    id: "orphan-file",
+    description: "どのエントリポイントからもインポートを辿って届かない (削除できる候補)",
+    severity: Severity::Warning,
+};
+
+pub const UNUSED_EXPORT: Rule = Rule {
+    id: "unused-export",
+    description: "エクスポートしているがどのファイルからもインポートされていない",
+    severity: Severity::Warning,
+};
+
+pub const CIRCULAR_DEPENDENCY: Rule = Rule {
+    id: "circular-dependency",
+    description: "インポートを辿ると自分自身に戻ってくる (読み込み順によって undefined になる)",
+    severity: Severity::Warning,
+};
+
+pub const RULES: &[&Rule] = &[
+    &UNUSED_IMPORT,
+    &DUPLICATE_IMPORT,
+    &PARSE_ERROR,
+    &BANNED_PACKAGE,
+    &DISALLOWED_PACKAGE,
+    &PACKAGE_BUDGET,
+    &LAYER_VIOLATION,
+    &FORBIDDEN_IMPORT,
+    &UNUSED_DEPENDENCY,
+    &UNDECLARED_DEPENDENCY,
+    &DEV_DEPENDENCY_IN_PRODUCTION,
+    &ANGULAR_VERSION_SKEW,
+    &UNMET_PEER_DEPENDENCY,
+    &ORPHAN_FILE,
+    &UNUSED_EXPORT,
+    &CIRCULAR_DEPENDENCY,
+];
+
+// 各種 CI 向け出力で共通に使う指摘 1 件分 (行・列は 1 始まり)
+#[derive(Debug)]
+pub struct Finding {
+    pub rule: &'static Rule,
+    // rule_levels で変更されていなければルールの既定の重要度
+    pub severity: Severity,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Report {
+    // レポート内の検出結果を指摘の一覧に変換する
+    pub fn findings(&self) -> Vec<Finding> {
+        let unused = self.unused_imports.iter().map(|u| Finding {
+            rule: &UNUSED_IMPORT,
+            severity: UNUSED_IMPORT.severity,
+            path: u.path.clone(),
+            line: u.line,
+            column: u.column,
+            message: format!("'{}' ({}) はインポートされていますが使われていません", u.name, u.module),
+        });
+        // 2 つ目以降の import 文を指摘する
+        let duplicates = self.duplicate_imports.iter().flat_map(|d| {
+            let lines = d.lines.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ");
+            d.lines[1..].iter().map(move |&line| Finding {
+                rule: &DUPLICATE_IMPORT,
+                severity: DUPLICATE_IMPORT.severity,
+                path: d.path.clone(),
+                line,
+                column: 1,
+                message: format!(
+                    "'{}' のインポートが複数の import 文に分かれています ({lines} 行目)。1 つにまとめられます",
+                    d.module
+                ),
+            })
+        });
+        let failures = self.parse_failures.iter().map(|f| Finding {
+            rule: &PARSE_ERROR,
+            severity: PARSE_ERROR.severity,
+            path: f.path.clone(),
+            line: f.line,
+            column: f.column,
+            message: format!("パースに失敗しました: {}", f.message),
+        });
+        let partial = self.partial_files.iter().map(|p| Finding {
+            rule: &PARSE_ERROR,
+            severity: Severity::Warning,
+            path: p.failure.path.clone(),
+            line: p.failure.line,
+            column: p.failure.column,
+            message: match p.imports_only {
+                true => format!("パースに失敗したため import 宣言だけを解析しました: {}", p.failure.message),
+                false => format!("パースに失敗したためエラーの行を除いて解析しました: {}", p.failure.message),
+            },
+        });
+        let violations = self.policy_violations.iter().map(|v| Finding {
+            rule: v.rule.rule(),
+            severity: v.rule.rule().severity,
+            path: v.path.clone(),
+            line: v.line,
+            column: v.column,
+            message: v.message.clone(),
+        });
+        let dependencies = self.dependency_issues.iter().map(|d| Finding {
+            rule: d.rule.rule(),
+            severity: d.rule.rule().severity,
+            path: d.path.clone(),
+            line: d.line,
+            column: d.column,
+            message: d.message.clone(),
+        });
+        let orphans = self.orphan_files.iter().map(|o| Finding {
+            rule: &ORPHAN_FILE,
+            severity: ORPHAN_FILE.severity,
+            path: o.path.clone(),
+            line: 1,
+            column: 1,
+            message: "どのエントリポイントからもインポートされていません (削除できる候補です)".to_string(),
+        });
+        let exports = self.unused_exports.iter().map(|e| Finding {
+            rule: &UNUSED_EXPORT,
+            severity: UNUSED_EXPORT.severity,
+            path: e.path.clone(),
+            line: e.line,
+            column: e.column,
+            message: format!("'{}' はエクスポートされていますが、どのファイルからもインポートされていません", e.name),
+        });
+        let cycles = self.cycles.iter().filter_map(|c| {
+            Some(Finding {
+                rule: &CIRCULAR_DEPENDENCY,
+                severity: CIRCULAR_DEPENDENCY.severity,
+                path: c.chain.first()?.clone(),
+                line: c.line,
+                column: c.column,
+                message: format!("循環依存: {}", c.chain.join(" → ")),
+            })
+        });
+        unused
+            .chain(duplicates)
+            .chain(failures)
+            .chain(partial)
+            .chain(violations)
+            .chain(dependencies)
+            .chain(orphans)
+            .chain(exports)
+            .chain(cycles)
+            .filter_map(|mut finding| {
+                match self.rule_levels.get(finding.rule.id) {
+                    Some(RuleLevel::Off) => return None,
+                    Some(RuleLevel::Warning) => finding.severity = Severity::Warning,
+                    Some(RuleLevel::Error) => finding.severity = Severity::Error,
+                    None => {}
+                }
+                Some(finding)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
+};
+
+// 拡張子なしの指定子に補完する拡張子 (優先順)
+const EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
+
+// ESM 形式で書かれた出力側の拡張子と、それに対応するソースの拡張子
+const OUTPUT_EXTENSIONS: &[(&str, &[&str])] = &[
+    (".js", &["ts", "tsx"]),
+    (".jsx", &["tsx"]),
+    (".mjs", &["mts"]),
+    (".cjs", &["cts"]),
+];
+
+// './' や '../' で始まる相対指定子かどうか
+pub fn is_relative(specifier: &str) -> bool {
+    specifier == "." || specifier == ".." || specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+// 'rxjs/operators' -> 'rxjs', '@angular/common/http' -> '@angular/common'。相対指定子は None
+pub fn package_name(specifier: &str) -> Option<&str> {
+    if is_relative(specifier) || specifier.starts_with('/') || specifier.is_empty() {
+        return None;
+    }
+    let segments = if specifier.starts_with('@') { 2 } else { 1 };
+    let end = specifier
+        .match_indices('/')
+        .nth(segments - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(specifier.len());
+    Some(&specifier[..end])
+}
+
+// tsconfig.json の compilerOptions.paths / baseUrl による、'@app/core' のような非相対指定子の解決先
+#[derive(Debug, Clone, Default)]
+pub struct PathAliases {
+    // baseUrl (絶対パス)
+    pub base_url: Option<PathBuf>,
+    // paths のパターン ('@app/*') と置き換え先 (* を含む絶対パス)
+    pub paths: Vec<(String, Vec<String>)>,
+    // baseUrl 直下のディレクトリ名とファイル名 (拡張子を除く)。先頭の要素がこれと同じ指定子は npm パッケージではない
+    pub local_roots: HashSet<String>,
+    // 解析対象を相対パスで指定したときのカレントディレクトリ (絶対パス)。
+    // 解決したパスを走査したファイルと同じく、ここからの相対パスにする
+    pub cwd: Option<PathBuf>,
+}
+
+impl PathAliases {
+    pub fn is_empty(&self) -> bool {
+        self.base_url.is_none() && self.paths.is_empty()
+    }
+
+    // webpack / Vite の resolve.alias と同じく、name そのものと name/ 以下を target (絶対パス) に置き換える。
+    // 同じパターンが tsconfig の paths にあればこちらを使う
+    pub fn add_alias(&mut self, name: &str, target: &Path) {
+        let wildcard = format!("{name}/*");
+        self.paths.retain(|(pattern, _)| *pattern != name && *pattern != wildcard);
+        let target = target.to_string_lossy();
+        self.paths.push((name.to_string(), vec![target.to_string()]));
+        self.paths.push((wildcard, vec![format!("{target}/*")]));
+    }
+
+    // paths か baseUrl で解析対象内のファイルを指す指定子か (npm パッケージとして数えない)
+    pub fn is_local(&self, specifier: &str) -> bool {
+        if is_relative(specifier) || self.matching(specifier).is_some() {
+            return true;
+        }
+        let first = specifier.split('/').next().unwrap_or(specifier);
+        self.base_url.is_some() && self.local_roots.contains(first)
+    }
+
+    // 一致するパターンの置き換え先と * に当たる部分。
+    // TypeScript と同じく * の無いパターンとの完全一致を優先し、次に * より前が最も長いパターンを使う
+    fn matching<'a>(&self, specifier: &'a str) -> Option<(&[String], &'a str)> {
+        if let Some((_, targets)) = self.paths.iter().find(|(pattern, _)| pattern == specifier) {
+            return Some((targets, ""));
+        }
+        self.paths
+            .iter()
+            .filter_map(|(pattern, targets)| {
+                let (prefix, suffix) = pattern.split_once('*')?;
+                let rest = specifier.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                Some((prefix.len(), targets.as_slice(), rest))
+            })
+            .max_by_key(|&(len, _, _)| len)
+            .map(|(_, targets, rest)| (targets, rest))
+    }
+
+    // 指定子が指しうるパス (拡張子補完の前)。paths の置き換え先を順に、最後に baseUrl からのパス
+    fn bases(&self, specifier: &str) -> Vec<PathBuf> {
+        let mut bases: Vec<PathBuf> = match self.matching(specifier) {
+            Some((targets, rest)) => targets.iter().map(|target| PathBuf::from(target.replacen('*', rest, 1))).collect(),
+            None => Vec::new(),
+        };
+        bases.extend(self.base_url.as_ref().map(|base| base.join(specifier)));
+        bases
+            .into_iter()
+            .map(|base| match &self.cwd {
+                Some(cwd) => relative_to(&normalize(&base), cwd),
+                None => normalize(&base),
+            })
+            .collect()
+    }
+}
+
+// 指定子を解析対象ファイルに解決する (非相対指定子は aliases で解決できるものだけ)。npm パッケージ等は None
+pub fn resolve(from: &Path, specifier: &str, files: &HashSet<PathBuf>, aliases: &PathAliases) -> Option<PathBuf> {
+    if !is_relative(specifier) {
+        return aliases
+            .bases(specifier)
+            .iter()
+            .flat_map(|base| candidates(base))
+            .find(|c| files.contains(c));
+    }
+    let base = normalize(&from.parent().unwrap_or(Path::new("")).join(specifier));
+    candidates(&base).into_iter().find(|c| files.contains(c))
+}
+
+// TypeScript のモジュール解決に倣った候補: そのまま / 拡張子補完 / index ファイル
+fn candidates(base: &Path) -> Vec<PathBuf> {
+    let mut list = vec![base.to_path_buf()];
+    // ESM 形式の './foo.js' は './foo.ts' を、'./foo.mjs' は './foo.mts' を指す
+    for (output, sources) in OUTPUT_EXTENSIONS {
+        if let Some(stem) = base.to_str().and_then(|s| s.strip_suffix(output)) {
+            list.extend(sources.iter().map(|ext| PathBuf::from(format!("{stem}.{ext}"))));
+        }
+    }
+    for ext in EXTENSIONS {
+        let mut with_ext = base.as_os_str().to_owned();
+        with_ext.push(format!(".{ext}"));
+        list.push(with_ext.into());
+    }
+    for ext in EXTENSIONS {
+        list.push(base.join(format!("index.{ext}")));
+    }
+    list
+}
+
+// ファイルシステムに問い合わせずに '.' と '..' を取り除く
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+// base (絶対パス) から path (絶対パス) への相対パス。base の外なら .. でさかのぼる
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut out = PathBuf::new();
+    for _ in common..base.len() {
+        out.push("..");
+    }
+    for component in &path[common..] {
+        out.push(component.as_os_str());
+    }
+    out
+}
+
+// root からの相対パスを / 区切りで返す (root 外ならそのまま)
+pub fn relative_display(root: &Path, path: &Path) -> String {
+    let root = normalize(root);
+    let path = normalize(path);
+    let relative = path.strip_prefix(&root).unwrap_or(&path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use swc_ecma_ast::{
+    ArrowExpr, BlockStmt, BlockStmtOrExpr, Class, Constructor, Decl, Function, ObjectPatProp, ParamOrTsParamProp, Pat,
+    Stmt, TsParamPropParam, VarDecl, VarDeclKind,
+};
+use swc_ecma_visit::{Visit, VisitWith};
+
+// 関数・ブロック等で宣言されたローカル名のスタック。
+// ここに積まれている名前への参照は、同名のインポートではなくローカル変数への参照とみなす
+#[derive(Default)]
+pub struct Scopes {
+    stack: Vec<HashSet<String>>,
+}
+
+impl Scopes {
+    pub fn push(&mut self, names: HashSet<String>) {
+        self.stack.push(names);
+    }
+
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    // いずれかの内側のスコープで同名のローカルが宣言されている
+    pub fn is_shadowed(&self, name: &str) -> bool {
+        self.stack.iter().any(|scope| scope.contains(name))
+    }
+
+    // 内側のスコープから name の宣言を取り除く (関数内の const x = require('foo') 等)
+    pub fn unshadow(&mut self, name: &str) {
+        if let Some(scope) = self.stack.iter_mut().rev().find(|scope| scope.contains(name)) {
+            scope.remove(name);
+        }
+    }
+}
+
+// 分割代入を含むパターンで導入される名前
+pub fn pat_names(pat: &Pat, names: &mut Vec<String>) {
+    match pat {
+        Pat::Ident(id) => names.push(id.id.sym.to_string()),
+        Pat::Array(array) => array.elems.iter().flatten().for_each(|p| pat_names(p, names)),
+        Pat::Object(obj) => {
+            for prop in &obj.props {
+                match prop {
+                    ObjectPatProp::KeyValue(kv) => pat_names(&kv.value, names),
+                    ObjectPatProp::Assign(assign) => names.push(assign.key.id.sym.to_string()),
+                    ObjectPatProp::Rest(rest) => pat_names(&rest.arg, names),
+                }
+            }
+        }
+        Pat::Rest(rest) => pat_names(&rest.arg, names),
+        Pat::Assign(assign) => pat_names(&assign.left, names),
+        _ => {}
+    }
+}
+
+// 関数本体の var 宣言 (関数スコープへ巻き上げられる)。内側の関数には入らない
+#[derive(Default)]
+struct VarCollector {
+    names: Vec<String>,
+}
+
+impl Visit for VarCollector {
+    fn visit_var_decl(&mut self, n: &VarDecl) {
+        if n.kind == VarDeclKind::Var {
+            n.decls.iter().for_each(|d| pat_names(&d.name, &mut self.names));
+        }
+    }
+
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+}
+
+// 関数スコープ: 引数と var 宣言
+pub fn function_scope<'a>(params: impl IntoIterator<Item = &'a Pat>, body: Option<&BlockStmt>) -> HashSet<String> {
+    let mut names = Vec::new();
+    for param in params {
+        pat_names(param, &mut names);
+    }
+    if let Some(body) = body {
+        let mut collector = VarCollector::default();
+        body.visit_with(&mut collector);
+        names.extend(collector.names);
+    }
+    names.into_iter().collect()
+}
+
+// アロー関数の式本体には var 宣言が無い
+pub fn arrow_scope(n: &ArrowExpr) -> HashSet<String> {
+    match &*n.body {
+        BlockStmtOrExpr::BlockStmt(body) => function_scope(&n.params, Some(body)),
+        BlockStmtOrExpr::Expr(_) => function_scope(&n.params, None),
+    }
+}
+
+// コンストラクタ: constructor(private http: HttpClient) の引数プロパティも引数として扱う
+pub fn constructor_scope(n: &Constructor) -> HashSet<String> {
+    let mut scope = function_scope(
+        n.params.iter().filter_map(|p| match p {
+            ParamOrTsParamProp::Param(param) => Some(&param.pat),
+            ParamOrTsParamProp::TsParamProp(_) => None,
+        }),
+        n.body.as_ref(),
+    );
+    for param in &n.params {
+        let ParamOrTsParamProp::TsParamProp(prop) = param else {
+            continue;
+        };
+        match &prop.param {
+            TsParamPropParam::Ident(id) => {
+                scope.insert(id.id.sym.to_string());
+            }
+            TsParamPropParam::Assign(assign) => {
+                let mut names = Vec::new();
+                pat_names(&assign.left, &mut names);
+                scope.extend(names);
+            }
+        }
+    }
+    scope
+}
+
+// ブロックスコープ: ブロック直下の let / const / class / 関数宣言
+pub fn block_scope(stmts: &[Stmt]) -> HashSet<String> {
+    let mut names = Vec::new();
+    for stmt in stmts {
+        let Stmt::Decl(decl) = stmt else {
+            continue;
+        };
+        match decl {
+            Decl::Var(var) if var.kind != VarDeclKind::Var => {
+                var.decls.iter().for_each(|d| pat_names(&d.name, &mut names));
+            }
+            Decl::Class(c) => names.push(c.ident.sym.to_string()),
+            Decl::Fn(f) => names.push(f.ident.sym.to_string()),
+            Decl::TsEnum(e) => names.push(e.id.sym.to_string()),
+            _ => {}
+        }
+    }
+    names.into_iter().collect()
+}
+
+// for (let x of ...) / for (const [k, v] of ...) のヘッドで宣言される名前
+pub fn lexical_decl_names(var: &VarDecl) -> HashSet<String> {
+    let mut names = Vec::new();
+    if var.kind != VarDeclKind::Var {
+        var.decls.iter().for_each(|d| pat_names(&d.name, &mut names));
+    }
+    names.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::{analyze_source, FileOutcome};
+
+    // src の中で map (rxjs からのインポート) を参照している回数
+    fn uses_of_map(src: &str) -> usize {
+        let src = format!("import {{ map }} from 'rxjs';\n{src}");
+        let FileOutcome::Parsed(analysis) = analyze_source(Path::new("a.ts"), src) else {
+            panic!("パースに失敗しました");
+        };
+        analysis.counts.iter().filter(|count| count.name == "map").map(|count| count.count).sum()
+    }
+
+    #[test]
+    fn parameters_shadow_imports() {
+        assert_eq!(uses_of_map("function f(map) { return map; }\nmap;"), 1);
+        assert_eq!(uses_of_map("const f = ({ map }) => map;"), 0);
+        assert_eq!(uses_of_map("const f = ([, ...map]) => map;"), 0);
+        assert_eq!(uses_of_map("const f = (x = map) => x;"), 1);
+        assert_eq!(uses_of_map("class A { constructor(private map: number) { map; } }"), 0);
+    }
+
+    #[test]
+    fn var_is_hoisted_to_function_scope() {
+        assert_eq!(uses_of_map("function f() { map; if (x) { var map = 1; } }\nmap;"), 1);
+        // 内側の関数の var は外側の関数に影響しない
+        assert_eq!(uses_of_map("function f() { map; function g() { var map; } }"), 1);
+    }
+
+    #[test]
+    fn catch_bindings_shadow_imports() {
+        assert_eq!(uses_of_map("try {} catch (map) { map; }\nmap;"), 1);
+        assert_eq!(uses_of_map("try {} catch ({ map }) { map; }"), 0);
+        assert_eq!(uses_of_map("try {} catch { map; }"), 1);
+    }
+
+    #[test]
+    fn block_declarations_shadow_only_inside_block() {
+        assert_eq!(uses_of_map("{ let map = 1; map; }\nmap;"), 1);
+        assert_eq!(uses_of_map("if (x) { const { a: map } = y; map; } else { map; }"), 1);
+        assert_eq!(uses_of_map("{ function map() {} map(); }"), 0);
+        assert_eq!(uses_of_map("for (const map of xs) { map; }\nmap;"), 1);
+        assert_eq!(uses_of_map("for (let map = 0; map < 1; map++) {}"), 0);
+    }
+
+    #[test]
+    fn unshadow_removes_innermost_declaration() {
+        let mut scopes = Scopes::default();
+        scopes.push(HashSet::from(["a".to_string()]));
+        scopes.push(HashSet::from(["a".to_string(), "b".to_string()]));
+        scopes.unshadow("a");
+        assert!(scopes.is_shadowed("a"));
+        scopes.pop();
+        assert!(scopes.is_shadowed("a"));
+        assert!(!scopes.is_shadowed("b"));
+        scopes.unshadow("a");
+        assert!(!scopes.is_shadowed("a"));
+    }
+}
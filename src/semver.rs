@@ -0,0 +1,249 @@
+// npm の semver の範囲 (^1.2.3 / ~1.2 / >=1.0.0 <2 / 1.x / 1.0.0 - 2.0.0 / || でつないだもの) をバージョンが満たすか。
+// プレリリース (-rc.1 等) は npm と同じく、同じメジャー・マイナー・パッチのプレリリースを書いた範囲でだけ満たす
+// (17.0.0-rc.0 は ^17.0.0 を満たさず、^17.0.0-rc.0 を満たす)。
+// 読めない範囲 (latest / workspace:* / file:... 等) は満たしているものとして扱う
+
+use std::cmp::Ordering;
+
+// メジャー・マイナー・パッチとプレリリース (無ければ空で、同じ数字のプレリリースより後)
+#[derive(Debug, PartialEq, Eq)]
+struct Version {
+    core: (u64, u64, u64),
+    pre: Vec<Identifier>,
+}
+
+// プレリリースの . で区切った要素。数字だけのものは数値で比べ、英字を含むものより前
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.core.cmp(&other.core).then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.pre.cmp(&other.pre),
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Version {
+    fn release(core: (u64, u64, u64)) -> Self {
+        Self { core, pre: Vec::new() }
+    }
+}
+
+// 範囲に書かれたバージョン。"1.2.x" は [Some(1), Some(2), None]、"*" / "x" は [None, None, None]
+struct Partial {
+    parts: [Option<u64>; 3],
+    pre: Vec<Identifier>,
+}
+
+impl Partial {
+    // 同じメジャー・マイナー・パッチのプレリリースを書いた比較なら、そのバージョンのプレリリースも比べる
+    fn allows_prerelease_of(&self, version: &Version) -> bool {
+        let (major, minor, patch) = version.core;
+        !self.pre.is_empty() && self.parts == [Some(major), Some(minor), Some(patch)]
+    }
+}
+
+pub fn satisfies(version: &str, range: &str) -> bool {
+    let Some(version) = parse_version(version) else {
+        return true;
+    };
+    range.split("||").any(|alternative| match comparators(alternative) {
+        Some(comparators) => {
+            comparators.iter().all(|(op, partial)| matches(op, partial, &version))
+                && (version.pre.is_empty() || comparators.iter().any(|(_, partial)| partial.allows_prerelease_of(&version)))
+        }
+        None => true,
+    })
+}
+
+// "1.2.3" / "v1.2.3-rc.1" / "1.2.3+build"
+fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches(['v', '=']);
+    let (version, pre) = split_prerelease(version);
+    let mut parts = version.split('.').map(|part| part.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some(Version { core: (major, minor, patch), pre })
+}
+
+// "1.2.3-rc.1+build" を "1.2.3" とプレリリースの要素に分ける (ビルドメタデータは比べない)
+fn split_prerelease(version: &str) -> (&str, Vec<Identifier>) {
+    let version = version.split('+').next().unwrap_or_default();
+    let Some((version, pre)) = version.split_once('-') else {
+        return (version, Vec::new());
+    };
+    let pre = pre
+        .split('.')
+        .map(|part| match part.parse() {
+            Ok(n) if part.bytes().all(|b| b.is_ascii_digit()) => Identifier::Numeric(n),
+            _ => Identifier::Alpha(part.to_string()),
+        })
+        .collect();
+    (version, pre)
+}
+
+// 比較演算子とバージョンの組に分ける ("1.0.0 - 2.0.0" は ">=1.0.0" "<=2.0.0" に、">= 1.2" は ">=1.2" にする)
+fn comparators(alternative: &str) -> Option<Vec<(String, Partial)>> {
+    let tokens: Vec<&str> = alternative.split_whitespace().collect();
+    if let [from, "-", to] = tokens[..] {
+        return Some(vec![(">=".to_string(), partial(from)?), ("<=".to_string(), partial(to)?)]);
+    }
+    let mut comparators = Vec::new();
+    let mut pending = String::new();
+    for token in tokens {
+        let split = token.find(|c: char| c.is_ascii_alphanumeric() || c == '*').unwrap_or(token.len());
+        let (op, version) = token.split_at(split);
+        pending.push_str(op);
+        if version.is_empty() {
+            continue;
+        }
+        if !matches!(pending.as_str(), "" | "=" | "^" | "~" | "~>" | ">" | ">=" | "<" | "<=") {
+            return None;
+        }
+        comparators.push((std::mem::take(&mut pending), partial(version)?));
+    }
+    Some(comparators)
+}
+
+// プレリリースは 3 桁とも書いたときだけ読む
+fn partial(version: &str) -> Option<Partial> {
+    let (version, pre) = split_prerelease(version.trim_start_matches('v'));
+    let mut parts = [None; 3];
+    for (slot, part) in parts.iter_mut().zip(version.split('.')) {
+        *slot = match part {
+            "x" | "X" | "*" => None,
+            part => Some(part.parse().ok()?),
+        };
+    }
+    let pre = if parts.iter().all(Option::is_some) { pre } else { Vec::new() };
+    Some(Partial { parts, pre })
+}
+
+fn matches(op: &str, partial: &Partial, version: &Version) -> bool {
+    // 省略・ワイルドカードより後ろはすべてワイルドカード
+    let known = partial.parts.iter().take_while(|part| part.is_some()).count();
+    let value = |i: usize| partial.parts[i].filter(|_| i < known).unwrap_or(0);
+    let lower = Version { core: (value(0), value(1), value(2)), pre: partial.pre.clone() };
+    // known 桁目までが同じ範囲の上限 (その桁を 1 つ上げたもの。未満で比べる)
+    let bump = |digits: usize| match digits {
+        0 => None,
+        1 => Some(Version::release((lower.core.0 + 1, 0, 0))),
+        2 => Some(Version::release((lower.core.0, lower.core.1 + 1, 0))),
+        _ => Some(Version::release((lower.core.0, lower.core.1, lower.core.2 + 1))),
+    };
+    let below = |upper: Option<Version>| upper.is_none_or(|upper| *version < upper);
+    match op {
+        // 3 桁とも書いたものはそのバージョンだけ (プレリリースも含めて比べる)
+        "" | "=" if known == 3 => *version == lower,
+        "" | "=" => *version >= lower && below(bump(known)),
+        // 0 でない最も左の桁までを固定する
+        "^" => {
+            let fixed = match (lower.core, known) {
+                ((0, 0, _), 3) => 3,
+                ((0, _, _), k) if k >= 2 => 2,
+                _ => known.min(1),
+            };
+            *version >= lower && below(bump(fixed))
+        }
+        "~" | "~>" => *version >= lower && below(bump(known.min(2))),
+        ">" if known == 3 => *version > lower,
+        ">" => bump(known).is_some_and(|upper| *version >= upper),
+        ">=" => *version >= lower,
+        "<" => known > 0 && *version < lower,
+        "<=" if known == 3 => *version <= lower,
+        "<=" => below(bump(known)),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check(range: &str, yes: &[&str], no: &[&str]) {
+        for version in yes {
+            assert!(satisfies(version, range), "{version} は {range} を満たすはず");
+        }
+        for version in no {
+            assert!(!satisfies(version, range), "{version} は {range} を満たさないはず");
+        }
+    }
+
+    #[test]
+    fn caret_fixes_leftmost_non_zero_digit() {
+        check("^1.2.3", &["1.2.3", "1.9.0"], &["1.2.2", "2.0.0"]);
+        check("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0", "0.2.2"]);
+        check("^0.0.3", &["0.0.3"], &["0.0.4", "0.0.2", "0.1.0"]);
+        check("^0.0", &["0.0.0", "0.0.9"], &["0.1.0"]);
+        check("^0", &["0.0.0", "0.9.9"], &["1.0.0"]);
+        check("^1.x", &["1.0.0", "1.9.9"], &["2.0.0"]);
+    }
+
+    #[test]
+    fn tilde_allows_patch_or_minor_updates() {
+        check("~1.2.3", &["1.2.3", "1.2.9"], &["1.3.0", "1.2.2"]);
+        check("~1.2", &["1.2.0", "1.2.9"], &["1.3.0"]);
+        check("~1", &["1.0.0", "1.9.9"], &["2.0.0", "0.9.9"]);
+        check("~>1.2", &["1.2.5"], &["1.3.0"]);
+    }
+
+    #[test]
+    fn comparisons_with_partial_versions() {
+        check(">1", &["2.0.0"], &["1.0.0", "1.9.9"]);
+        check(">1.2", &["1.3.0"], &["1.2.9"]);
+        check(">=1.2", &["1.2.0", "3.0.0"], &["1.1.9"]);
+        check("<2", &["1.9.9"], &["2.0.0"]);
+        check("<=1.2", &["1.2.9"], &["1.3.0"]);
+        check(">= 1.0.0 < 2", &["1.5.0"], &["2.0.0", "0.9.0"]);
+        check("1.x", &["1.0.0", "1.9.9"], &["2.0.0"]);
+        check("1.2.3", &["1.2.3", "v1.2.3", "1.2.3+build"], &["1.2.4", "1.2.3-rc.1"]);
+    }
+
+    #[test]
+    fn prereleases_only_match_ranges_with_prerelease_of_same_version() {
+        check("^17.0.0", &["17.0.0", "17.1.0"], &["17.0.0-rc.0", "17.1.0-rc.0", "18.0.0-rc.0"]);
+        check("^17.0.0-rc.0", &["17.0.0-rc.0", "17.0.0-rc.1", "17.0.0", "17.2.0"], &["17.0.0-next.9", "17.1.0-rc.0", "18.0.0"]);
+        check("1.0.0-rc.1", &["1.0.0-rc.1", "v1.0.0-rc.1+build"], &["1.0.0-rc.2", "1.0.0"]);
+        check("*", &["1.0.0"], &["1.0.0-rc.1"]);
+        check(">=1.0.0-rc.1 <=1.0.0-rc.1 || >=2", &["1.0.0-rc.1", "2.0.0"], &["2.0.0-rc.1"]);
+    }
+
+    #[test]
+    fn prerelease_precedence() {
+        // alpha < alpha.1 < alpha.beta < beta < beta.2 < beta.11 < rc.1 < 正式リリース
+        check(">1.0.0-alpha <1.0.0-beta.11", &["1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2"], &["1.0.0-alpha", "1.0.0-beta.11", "1.0.0-rc.1"]);
+        check("<1.0.0", &["0.9.0"], &["1.0.0"]);
+        check("~1.2.3-beta.2", &["1.2.3-beta.4", "1.2.3", "1.2.9"], &["1.2.3-beta.1", "1.2.4-beta.1", "1.3.0"]);
+    }
+
+    #[test]
+    fn hyphen_ranges_include_both_ends() {
+        check("1.2.3 - 2.3.4", &["1.2.3", "2.3.4"], &["1.2.2", "2.3.5"]);
+        // 終わりを省略したらその桁までの範囲
+        check("1.2 - 2.3", &["1.2.0", "2.3.9"], &["1.1.9", "2.4.0"]);
+    }
+
+    #[test]
+    fn alternatives_and_unreadable_ranges() {
+        check("^1.0.0 || ^3.0.0", &["1.5.0", "3.1.0"], &["2.0.0"]);
+        check("*", &["0.0.1", "9.9.9"], &[]);
+        check("latest", &["1.0.0"], &[]);
+        check("workspace:*", &["1.0.0"], &[]);
+        check("^1.0.0", &["not-a-version"], &[]);
+    }
+}
@@ -0,0 +1,374 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+use crate::{
+    glob::{self, Glob},
+    resolve,
+};
+#[cfg(feature = "native")]
+use {
+    anyhow::{bail, Context, Result},
+    serde_json::Value,
+    std::fs,
+    crate::{is_excluded, resolve::PathAliases, DEFAULT_EXTENSIONS},
+};
+
+// 解析対象ディレクトリから親へ辿って探す TypeScript の設定ファイル
+pub const FILE_NAME: &str = "tsconfig.json";
+
+// extends を辿るときの上限 (循環対策)
+#[cfg(feature = "native")]
+const MAX_EXTENDS_DEPTH: usize = 16;
+
+// tsconfig.json のうち解析に使う値 (extends で継承した値を含む)
+#[derive(Debug, Clone, Default)]
+pub struct TsConfig {
+    // 読み込んだファイル (絶対パス)
+    pub path: PathBuf,
+    // compilerOptions.baseUrl (絶対パス)
+    pub base_url: Option<PathBuf>,
+    // compilerOptions.paths のパターンと置き換え先
+    pub paths: Vec<(String, Vec<String>)>,
+    // paths の置き換え先の基準 (paths を書いたファイルのあるディレクトリ。baseUrl があればそちらを使う)
+    #[cfg(feature = "native")]
+    paths_dir: PathBuf,
+    // files / include / exclude (書いたファイルのあるディレクトリを基準にした絶対パス。書かれていなければ None)
+    pub files: Option<Vec<PathBuf>>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    // compilerOptions.outDir (絶対パス)。exclude が無ければ除外する
+    pub out_dir: Option<PathBuf>,
+    // compilerOptions.allowJs (false なら .js / .jsx / .mjs / .cjs はコンパイル対象にならない)
+    pub allow_js: bool,
+    // references で参照しているプロジェクトの tsconfig (絶対パス。extends では継承しない)
+    pub references: Vec<PathBuf>,
+}
+
+// tsconfig の files / include / exclude で決まるコンパイル対象のファイル
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFiles {
+    // files に書かれたファイル (絶対パス)。exclude によらず対象になる
+    files: HashSet<PathBuf>,
+    // include / exclude (絶対パスのグロブ)
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    allow_js: bool,
+    // 解析対象を相対パスで指定したときのカレントディレクトリ (絶対パス)
+    cwd: Option<PathBuf>,
+}
+
+// allowJs が無いとコンパイル対象にならない拡張子
+const JS_EXTENSIONS: [&str; 4] = ["js", "jsx", "mjs", "cjs"];
+
+impl ProjectFiles {
+    // 走査で見つけたファイル (解析対象と同じ形のパス) がコンパイル対象か
+    pub fn contains(&self, path: &Path) -> bool {
+        let path = resolve::normalize(&match &self.cwd {
+            Some(cwd) => cwd.join(path),
+            None => path.to_path_buf(),
+        });
+        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        if !self.allow_js && JS_EXTENSIONS.contains(&ext) {
+            return false;
+        }
+        if self.files.contains(&path) {
+            return true;
+        }
+        let path = glob::slash_path(&path);
+        self.include.iter().any(|glob| glob.matches(&path)) && !self.exclude.iter().any(|glob| glob.matches(&path))
+    }
+}
+
+// start (解析対象) とその親ディレクトリから tsconfig.json を探す
+#[cfg(feature = "native")]
+pub fn find(start: &Path) -> Option<PathBuf> {
+    let start = fs::canonicalize(start).ok()?;
+    start.ancestors().map(|dir| dir.join(FILE_NAME)).find(|path| path.is_file())
+}
+
+#[cfg(feature = "native")]
+pub fn load(path: &Path) -> Result<TsConfig> {
+    let path = fs::canonicalize(path).with_context(|| format!("tsconfig を読み込めません: {}", path.display()))?;
+    let mut config = TsConfig {
+        path: path.clone(),
+        ..Default::default()
+    };
+    merge(&mut config, &path, 0)?;
+    Ok(config)
+}
+
+// path の tsconfig と、references を辿って見つかるすべてのプロジェクトの tsconfig (見つけた順)
+#[cfg(feature = "native")]
+pub fn load_projects(path: &Path) -> Result<Vec<TsConfig>> {
+    let mut projects = vec![load(path)?];
+    let mut next = 0;
+    while next < projects.len() {
+        let references = projects[next].references.clone();
+        for reference in references {
+            if !projects.iter().any(|project| project.path == reference) {
+                projects.push(load(&reference)?);
+            }
+        }
+        next += 1;
+    }
+    Ok(projects)
+}
+
+// extends 先を先に読み、path 自身の値で上書きする
+#[cfg(feature = "native")]
+fn merge(config: &mut TsConfig, path: &Path, depth: usize) -> Result<()> {
+    if depth > MAX_EXTENDS_DEPTH {
+        bail!("tsconfig の extends が深すぎます (循環していませんか): {}", path.display());
+    }
+    let src = fs::read_to_string(path).with_context(|| format!("tsconfig を読み込めません: {}", path.display()))?;
+    let value: Value = serde_json::from_str(&strip_jsonc(&src))
+        .with_context(|| format!("tsconfig の形式が正しくありません: {}", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    // TypeScript 5 からは extends に配列も書ける (後ろのものほど優先)
+    let extends = match value.get("extends") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    for name in extends {
+        let Some(parent) = extends_path(dir, name) else {
+            bail!("{}: extends の {name} が見つかりません", path.display());
+        };
+        merge(config, &parent, depth + 1)?;
+    }
+
+    // files / include / exclude は継承しても、書いたファイルのディレクトリを基準にする
+    let list = |key: &str| {
+        let items = value.get(key)?.as_array()?.iter().filter_map(Value::as_str);
+        Some(items.map(|item| dir.join(item).to_string_lossy().into_owned()).collect::<Vec<_>>())
+    };
+    if let Some(files) = list("files") {
+        config.files = Some(files.into_iter().map(PathBuf::from).collect());
+    }
+    if let Some(include) = list("include") {
+        config.include = Some(include);
+    }
+    if let Some(exclude) = list("exclude") {
+        config.exclude = Some(exclude);
+    }
+    if depth == 0 {
+        let references = value.get("references").and_then(Value::as_array).into_iter().flatten();
+        for reference in references.filter_map(|r| r.get("path")?.as_str()) {
+            // path はディレクトリ (中の tsconfig.json) か tsconfig のファイル
+            let target = dir.join(reference);
+            let target = if target.is_dir() { target.join(FILE_NAME) } else { target };
+            let target = fs::canonicalize(&target)
+                .with_context(|| format!("{}: references の {reference} が見つかりません", path.display()))?;
+            config.references.push(target);
+        }
+    }
+
+    let Some(options) = value.get("compilerOptions") else {
+        return Ok(());
+    };
+    if let Some(base_url) = options.get("baseUrl").and_then(Value::as_str) {
+        config.base_url = Some(dir.join(base_url));
+    }
+    if let Some(paths) = options.get("paths").and_then(Value::as_object) {
+        config.paths = paths
+            .iter()
+            .map(|(pattern, targets)| {
+                let targets = targets.as_array().map(|t| t.iter().filter_map(Value::as_str).map(str::to_string).collect());
+                (pattern.clone(), targets.unwrap_or_default())
+            })
+            .collect();
+        config.paths_dir = dir.to_path_buf();
+    }
+    if let Some(out_dir) = options.get("outDir").and_then(Value::as_str) {
+        config.out_dir = Some(dir.join(out_dir));
+    }
+    if let Some(allow_js) = options.get("allowJs").and_then(Value::as_bool) {
+        config.allow_js = allow_js;
+    }
+    Ok(())
+}
+
+// extends の値を読み込むファイルにする。相対・絶対パスでなければ node_modules のパッケージから探す
+#[cfg(feature = "native")]
+fn extends_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    // そのまま / .json を補う / ディレクトリなら中の tsconfig.json
+    let candidates = |base: PathBuf| [PathBuf::from(format!("{}.json", base.display())), base.join(FILE_NAME), base];
+    if name.starts_with('.') || Path::new(name).is_absolute() {
+        return candidates(dir.join(name)).into_iter().rev().find(|path| path.is_file());
+    }
+    dir.ancestors()
+        .flat_map(|ancestor| candidates(ancestor.join("node_modules").join(name)).into_iter().rev())
+        .find(|path| path.is_file())
+}
+
+impl TsConfig {
+    // 依存グラフの解決に使う形にする。cwd は解析対象を相対パスで指定したときのカレントディレクトリ (絶対パス)
+    #[cfg(feature = "native")]
+    pub fn path_aliases(&self, cwd: Option<PathBuf>) -> PathAliases {
+        let paths_base = self.base_url.as_deref().unwrap_or(&self.paths_dir);
+        PathAliases {
+            base_url: self.base_url.clone(),
+            paths: self
+                .paths
+                .iter()
+                .map(|(pattern, targets)| {
+                    let targets = targets.iter().map(|target| paths_base.join(target).to_string_lossy().into_owned());
+                    (pattern.clone(), targets.collect())
+                })
+                .collect(),
+            local_roots: self.base_url.as_deref().map(local_roots).unwrap_or_default(),
+            cwd,
+        }
+    }
+
+    // 走査したファイルを絞り込む形にする。TypeScript と同じく、files も include も無ければ include は **/* で、
+    // exclude が無ければ outDir を除く (node_modules 等は走査のときに除いている)
+    pub fn project_files(&self, cwd: Option<PathBuf>) -> ProjectFiles {
+        let dir = self.path.parent().unwrap_or(Path::new(""));
+        let include = match (&self.include, &self.files) {
+            (Some(include), _) => include.clone(),
+            (None, Some(_)) => Vec::new(),
+            (None, None) => vec![dir.join("**/*").to_string_lossy().into_owned()],
+        };
+        let exclude = match (&self.exclude, &self.out_dir) {
+            (Some(exclude), _) => exclude.clone(),
+            (None, Some(out_dir)) => vec![out_dir.to_string_lossy().into_owned()],
+            (None, None) => Vec::new(),
+        };
+        ProjectFiles {
+            files: self.files.iter().flatten().map(|file| resolve::normalize(file)).collect(),
+            include: include.iter().map(|pattern| pattern_glob(pattern)).collect(),
+            exclude: exclude.iter().map(|pattern| pattern_glob(pattern)).collect(),
+            allow_js: self.allow_js,
+            cwd,
+        }
+    }
+}
+
+// include / exclude のパターン (絶対パス) をグロブにする。
+// 最後の要素に拡張子もワイルドカードも無ければディレクトリとして、その中のすべてのファイルに一致させる
+fn pattern_glob(pattern: &str) -> Glob {
+    let path = glob::slash_path(&resolve::normalize(Path::new(pattern)));
+    let last = path.rsplit('/').next().unwrap_or(&path);
+    if last.contains(['.', '*', '?']) {
+        Glob::new(&path)
+    } else {
+        Glob::new(&format!("{path}/**/*"))
+    }
+}
+
+// baseUrl 直下のディレクトリ名と、ソースファイル名から拡張子を除いたもの (node_modules 等は除く)
+#[cfg(feature = "native")]
+fn local_roots(base_url: &Path) -> HashSet<String> {
+    let Ok(entries) = fs::read_dir(base_url) else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| !is_excluded(path))
+        .filter_map(|path| {
+            if path.is_dir() {
+                return path.file_name()?.to_str().map(str::to_string);
+            }
+            let ext = path.extension()?.to_str()?;
+            if !DEFAULT_EXTENSIONS.contains(&ext) {
+                return None;
+            }
+            path.file_stem()?.to_str().map(str::to_string)
+        })
+        .collect()
+}
+
+// tsconfig.json に書ける // と /* */ のコメントと、末尾のカンマを取り除いて JSON にする (.eslintrc.json 等も同じ形式)
+#[cfg(feature = "native")]
+pub fn strip_jsonc(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            (']' | '}', _) => {
+                // 直前の空白を挟んだカンマは末尾のカンマ
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_aliases_resolve_paths_and_base_url() {
+        let dir = std::env::temp_dir().join(format!("angular-dep-tsconfig-test-{}", std::process::id()));
+        for sub in ["src/app/core", "src/environments", "src/shared"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        // paths は extends 元に書かれていても、baseUrl があればそこを基準にする
+        fs::write(
+            dir.join("tsconfig.base.json"),
+            r#"{ "compilerOptions": { "paths": { "@app/*": ["src/app/*"], "@env": ["src/environments/environment.ts"] } } }"#,
+        )
+        .unwrap();
+        fs::write(dir.join(FILE_NAME), "{ \"extends\": \"./tsconfig.base.json\", \"compilerOptions\": { \"baseUrl\": \".\", } }").unwrap();
+        let dir = fs::canonicalize(&dir).unwrap();
+        let files: HashSet<PathBuf> = ["src/main.ts", "src/app/core/index.ts", "src/environments/environment.ts", "src/shared/util.ts"]
+            .map(|file| dir.join(file))
+            .into();
+        let aliases = load(&dir.join(FILE_NAME)).unwrap().path_aliases(None);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let from = dir.join("src/main.ts");
+        let resolve = |specifier: &str| resolve::resolve(&from, specifier, &files, &aliases);
+        assert_eq!(resolve("@app/core"), Some(dir.join("src/app/core/index.ts")));
+        assert_eq!(resolve("@env"), Some(dir.join("src/environments/environment.ts")));
+        assert_eq!(resolve("src/shared/util"), Some(dir.join("src/shared/util.ts")));
+        assert_eq!(resolve("@app/missing"), None);
+        assert_eq!(resolve("rxjs"), None);
+        // baseUrl 直下のフォルダから始まる指定子は npm パッケージとして数えない
+        assert!(aliases.is_local("@app/missing"));
+        assert!(aliases.is_local("src/shared/missing"));
+        assert!(!aliases.is_local("rxjs"));
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rayon::prelude::*;
+use angular_dependency_analyzer::{self as analyzer, cache::Cache, ignore::Ignore, Aggregate, AnalyzerConfig, FileOutcome};
+use crate::Args;
+
+// 保存 1 回で複数のイベントが届くので、この間隔で途切れるまでまとめてから解析し直す
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// ファイルごとの解析結果をメモリに保持し、変更されたファイルだけを解析し直す (--watch / daemon)
+#[derive(Default)]
+pub struct Project {
+    // 走査順のソースファイル
+    paths: Vec<PathBuf>,
+    outcomes: HashMap<PathBuf, FileOutcome>,
+}
+
+impl Project {
+    // 走査し直し、changed に含まれるファイルと新しく増えたファイルを解析する。解析したファイル数を返す
+    pub fn refresh(
+        &mut self,
+        targets: &[PathBuf],
+        config: &AnalyzerConfig,
+        changed: &HashSet<PathBuf>,
+        cache: Option<&Cache>,
+    ) -> Result<usize> {
+        self.paths = analyzer::collect_source_files_in(targets, config)?;
+        let stale: Vec<_> = self
+            .paths
+            .iter()
+            .filter(|path| changed.contains(*path) || !self.outcomes.contains_key(*path))
+            .collect();
+        let analyzed: Vec<_> = stale
+            .par_iter()
+            .map(|path| (*path, analyzer::analyze_file(path, config, cache)))
+            .collect();
+        let count = analyzed.len();
+        for (path, outcome) in analyzed {
+            match outcome {
+                Ok(outcome) => {
+                    self.outcomes.insert(path.clone(), outcome);
+                }
+                // 走査から読み込みまでの間に消えたファイル等。次の変更で改めて解析する
+                Err(err) => {
+                    eprintln!("⚠️ 読み込めません: {}: {err}", path.display());
+                    self.outcomes.remove(path);
+                }
+            }
+        }
+        // 削除・移動されたファイルの結果を捨てる
+        let current: HashSet<_> = self.paths.iter().collect();
+        self.outcomes.retain(|path, _| current.contains(path));
+        Ok(count)
+    }
+
+    // エディタで編集中の内容など、ディスクを読まずに得た解析結果で差し替える (走査で見つかったファイルのみ集計に含まれる)
+    pub fn update(&mut self, path: PathBuf, outcome: FileOutcome) {
+        self.outcomes.insert(path, outcome);
+    }
+
+    // 保持している解析結果を、通常の実行と同じ順でマージし直す
+    pub fn aggregate(&self, args: &Args, out: &mut impl Write) -> Result<Aggregate> {
+        let mut aggregate = Aggregate::with_targets(&args.targets, &args.config()?);
+        let streaming = args.streaming()?;
+        for path in &self.paths {
+            if let Some(outcome) = self.outcomes.get(path) {
+                crate::merge(&mut aggregate, outcome.clone(), streaming, &args.filters, out)?;
+            }
+        }
+        Ok(aggregate)
+    }
+}
+
+// 解析対象以下のファイルの変更通知
+pub struct Changes {
+    // 破棄すると監視が止まるので保持しておく
+    _watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<Event>>,
+    // 除外・拡張子の判定に使う
+    config: AnalyzerConfig,
+    targets: Vec<Watched>,
+}
+
+// 監視している解析対象
+struct Watched {
+    target: PathBuf,
+    // 通知されるパスが絶対パスでも、走査で得たパスと突き合わせられるようにする
+    canonical: PathBuf,
+    // .gitignore / .ignore / --ignore-file で無視するパスへの変更は拾わない
+    ignore: Ignore,
+}
+
+impl Changes {
+    pub fn watch(targets: &[PathBuf], config: &AnalyzerConfig) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        let mut watched = Vec::new();
+        for target in targets {
+            watcher.watch(target, RecursiveMode::Recursive)?;
+            watched.push(Watched {
+                target: target.clone(),
+                canonical: fs::canonicalize(target)?,
+                ignore: Ignore::new(target, &config.ignore_files, config.use_ignore_files)?,
+            });
+        }
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            config: config.clone(),
+            targets: watched,
+        })
+    }
+
+    // ソースファイルかディレクトリへの変更が届くまで待ち、変更されたソースファイルを返す。
+    // ディレクトリの追加・削除だけの場合は空集合になる (差分は次の走査で拾う)
+    pub fn wait(&self) -> Result<HashSet<PathBuf>> {
+        loop {
+            let mut changed = HashSet::new();
+            let mut relevant = false;
+            let mut next = Some(self.rx.recv()?);
+            while let Some(event) = next {
+                match event {
+                    // 解析のためにファイルを開いただけのイベントは無視する
+                    Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+                    Ok(event) => {
+                        for path in event.paths {
+                            let Some((watched, path)) = self.locate(&path) else {
+                                continue;
+                            };
+                            let target = &watched.target;
+                            if self.config.is_excluded(target, &path)
+                                || watched.ignore.is_ignored_under(target, &path, path.is_dir())
+                            {
+                                continue;
+                            }
+                            if self.config.is_source_path(&path) && self.config.is_included(target, &path) {
+                                changed.insert(path);
+                                relevant = true;
+                            } else if path.extension().is_none() {
+                                relevant = true;
+                            }
+                        }
+                    }
+                    Err(err) => eprintln!("⚠️ 変更の監視でエラーが発生しました: {err}"),
+                }
+                next = self.rx.recv_timeout(DEBOUNCE).ok();
+            }
+            if relevant {
+                return Ok(changed);
+            }
+        }
+    }
+
+    // 通知されたパスを含む解析対象と、解析対象の指定どおりの形 (走査結果と同じ形) に直したパス
+    fn locate(&self, path: &Path) -> Option<(&Watched, PathBuf)> {
+        self.targets.iter().find_map(|watched| {
+            if path.starts_with(&watched.target) {
+                Some((watched, path.to_path_buf()))
+            } else {
+                let relative = path.strip_prefix(&watched.canonical).ok()?;
+                Some((watched, watched.target.join(relative)))
+            }
+        })
+    }
+}
+
+// --watch: 最初に全体を解析し、以降は変更・追加されたファイルだけを解析し直して出力し直す
+pub fn run(args: &Args, cache: Option<&Cache>) -> Result<()> {
+    let config = args.config()?;
+    let changes = Changes::watch(&args.targets, &config)?;
+    let mut project = Project::default();
+    let mut changed = HashSet::new();
+    loop {
+        let started = Instant::now();
+        let analyzed = project.refresh(&args.targets, &config, &changed, cache)?;
+
+        // 端末に表示している場合は前回の結果を消してから書く
+        if args.output.is_none() && io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+        }
+        let mut out = crate::open_output(args)?;
+        let mut analysis = project.aggregate(args, &mut out)?.finish();
+        args.retain_filtered(&mut analysis.report);
+        crate::write_report(&analysis, args, &HashMap::new(), &mut out)?;
+        drop(out);
+        // 監視は続けるので、閾値を超えても警告にとどめる
+        if let Some(exceeded) = args.thresholds.check(&analysis, None, None) {
+            eprintln!("⚠️ {}", exceeded.message);
+        }
+
+        eprintln!(
+            "\n--- {analyzed} ファイルを解析しました ({} ms)。変更を監視しています (Ctrl+C で終了) ---",
+            started.elapsed().as_millis()
+        );
+        changed = changes.wait()?;
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use anyhow::{bail, Context, Result};
+use angular_dependency_analyzer::{
+    glob::{slash_path, Glob},
+    graph::ModuleRef,
+    manifest::{self, Manifest},
+    output,
+    resolve::{self, PathAliases},
+};
+use crate::{
+    open_output,
+    projects::{self, Members, Project},
+    Args,
+};
+
+// pnpm のワークスペースの定義
+const PNPM_WORKSPACE: &str = "pnpm-workspace.yaml";
+
+// パッケージのディレクトリを探す深さの上限 (ワークスペースのルートから)
+const MAX_PACKAGE_DEPTH: usize = 6;
+
+// workspaces コマンド: package.json の workspaces / pnpm-workspace.yaml のパッケージごとに解析し、
+// パッケージをまたぐファイル間の依存と、公開している入口を迂回したインポートを出力する
+pub fn run(args: &Args) -> Result<()> {
+    let Some((root, patterns)) = find_root(&args.targets[0])? else {
+        bail!("ワークスペースが見つかりません (package.json の workspaces か pnpm-workspace.yaml が必要です)");
+    };
+    let mut packages: Vec<(PathBuf, Manifest)> = Vec::new();
+    for dir in package_dirs(&root, &patterns) {
+        let path = dir.join(manifest::FILE_NAME);
+        let src = fs::read_to_string(&path).with_context(|| format!("package.json を読み込めません: {}", path.display()))?;
+        let manifest = Manifest::parse(&slash_path(&path), &src)
+            .with_context(|| format!("package.json の形式が正しくありません: {}", path.display()))?;
+        packages.push((dir, manifest));
+    }
+    if packages.is_empty() {
+        bail!("ワークスペースのパッケージが見つかりません: {}", root.display());
+    }
+
+    // パッケージ名のインポートは node_modules のリンクではなくパッケージのソースに解決する
+    let mut aliases = args.path_aliases.clone();
+    for (dir, manifest) in &packages {
+        if let Some(name) = &manifest.name {
+            add_package(&mut aliases, name, dir, manifest);
+        }
+    }
+    let names: BTreeMap<&str, PathBuf> = packages
+        .iter()
+        .filter_map(|(dir, manifest)| Some((manifest.name.as_deref()?, dir.join(manifest::FILE_NAME))))
+        .collect();
+    let projects: Vec<Project> = packages
+        .iter()
+        .map(|(dir, manifest)| Project {
+            name: manifest.name.clone().unwrap_or_else(|| resolve::relative_display(&root, dir)),
+            config: dir.join(manifest::FILE_NAME),
+            members: Members::Dir(dir.clone()),
+            references: [
+                &manifest.dependencies,
+                &manifest.dev_dependencies,
+                &manifest.peer_dependencies,
+                &manifest.optional_dependencies,
+            ]
+            .into_iter()
+            .flat_map(|deps| deps.keys())
+            .filter_map(|name| names.get(name.as_str()).cloned())
+            .collect(),
+            aliases: aliases.clone(),
+            kind: None,
+            build: None,
+        })
+        .collect();
+    let manifests: BTreeMap<PathBuf, &Manifest> =
+        packages.iter().map(|(dir, manifest)| (dir.join(manifest::FILE_NAME), manifest)).collect();
+    let check = |_: &Project, to: &Project, source: &ModuleRef| check_entry(manifests.get(&to.config)?, &to.name, source);
+    let report = projects::analyze(args, projects, aliases, "ワークスペースのパッケージ", Some("package.json の依存にありません"), &check)?;
+    let mut out = open_output(args)?;
+    output::projects::write(args.projects_format()?, &report, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+// start とその親ディレクトリから、pnpm-workspace.yaml か workspaces のある package.json を探す
+fn find_root(start: &Path) -> Result<Option<(PathBuf, Vec<String>)>> {
+    let start = fs::canonicalize(start)?;
+    for dir in start.ancestors() {
+        let pnpm = dir.join(PNPM_WORKSPACE);
+        if pnpm.is_file() {
+            let src = fs::read_to_string(&pnpm).with_context(|| format!("{} を読み込めません", pnpm.display()))?;
+            return Ok(Some((dir.to_path_buf(), manifest::parse_pnpm_workspace(&src))));
+        }
+        let package = dir.join(manifest::FILE_NAME);
+        if let Ok(src) = fs::read_to_string(&package)
+            && let Ok(manifest) = Manifest::parse(&slash_path(&package), &src)
+            && !manifest.workspaces.is_empty()
+        {
+            return Ok(Some((dir.to_path_buf(), manifest.workspaces)));
+        }
+    }
+    Ok(None)
+}
+
+// patterns ('packages/*' 等。'!' で始まるものは除外) に一致し、package.json のあるディレクトリ
+fn package_dirs(root: &Path, patterns: &[String]) -> Vec<PathBuf> {
+    let glob = |pattern: &str| Glob::new(pattern.trim_start_matches("./").trim_end_matches('/'));
+    let include: Vec<Glob> = patterns.iter().filter(|p| !p.starts_with('!')).map(|p| glob(p)).collect();
+    let exclude: Vec<Glob> = patterns.iter().filter_map(|p| p.strip_prefix('!')).map(glob).collect();
+    let mut dirs = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !path.is_dir() || name == "node_modules" || name.starts_with('.') {
+                continue;
+            }
+            let relative = resolve::relative_display(root, &path);
+            if path.join(manifest::FILE_NAME).is_file()
+                && include.iter().any(|glob| glob.matches(&relative))
+                && !exclude.iter().any(|glob| glob.matches(&relative))
+            {
+                dirs.push(path.clone());
+            }
+            if depth + 1 < MAX_PACKAGE_DEPTH {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+    dirs.sort();
+    dirs
+}
+
+// name を package.json の入口 (source / types / main 等)、src/index、ディレクトリの順に、name/ 以下をディレクトリに解決する
+fn add_package(aliases: &mut PathAliases, name: &str, dir: &Path, manifest: &Manifest) {
+    let mut entries: Vec<String> = manifest.entry.iter().map(|entry| dir.join(entry).to_string_lossy().to_string()).collect();
+    entries.push(dir.join("src/index").to_string_lossy().to_string());
+    entries.push(dir.to_string_lossy().to_string());
+    let wildcard = format!("{name}/*");
+    aliases.paths.retain(|(pattern, _)| *pattern != name && *pattern != wildcard);
+    aliases.paths.push((name.to_string(), entries));
+    aliases.paths.push((wildcard, vec![format!("{}/*", dir.to_string_lossy())]));
+}
+
+// 他のパッケージのファイルへのインポートは、パッケージ名とその exports で公開しているサブパスだけを使う
+fn check_entry(to: &Manifest, name: &str, source: &ModuleRef) -> Option<String> {
+    let specifier = source.specifier.as_str();
+    if resolve::is_relative(specifier) {
+        return Some(format!(
+            "'{specifier}' は相対パスでパッケージ '{name}' の中を直接参照しています (パッケージ名でインポートしてください)"
+        ));
+    }
+    let Some(rest) = specifier.strip_prefix(name).filter(|rest| rest.is_empty() || rest.starts_with('/')) else {
+        return Some(format!("'{specifier}' はパッケージ名 '{name}' を使わずにパッケージの中を参照しています"));
+    };
+    let subpath = format!(".{rest}");
+    (!to.exports_subpath(&subpath)).then(|| format!("'{specifier}' はパッケージ '{name}' が exports で公開していないパスです"))
+}
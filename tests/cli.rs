@@ -0,0 +1,51 @@
+// ビルドしたコマンドを実行して終了コードを確かめる
+use std::{fs, process::Command};
+
+#[test]
+fn strict_fails_on_partially_parsed_file() {
+    let dir = std::env::temp_dir().join(format!("angular-dep-strict-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    // 2 行目だけがパースエラーで、残りはエラー箇所を除いて解析される
+    fs::write(dir.join("broken.ts"), "import { a } from 'a';\nconst x = ;\na;\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_angular-dependency-analyzer"))
+        .arg(&dir)
+        .args(["--strict", "--no-cache", "--no-config"])
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(4), "{stderr}");
+    assert!(stderr.contains("パースエラー 1 件 (上限 0 件)"), "{stderr}");
+    assert!(stderr.contains("broken.ts"), "{stderr}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("パース失敗 0 / エラー箇所を除いて解析 1"), "{stdout}");
+}
+
+#[test]
+fn rejects_options_of_other_commands() {
+    let output = Command::new(env!("CARGO_BIN_EXE_angular-dependency-analyzer"))
+        .args(["graph", "--html", "out.html"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(stderr.contains("--html はこのコマンドでは使えません"), "{stderr}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_angular-dependency-analyzer")).arg("usages").output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(stderr.contains("<インポート名 | モジュール::名前> を指定してください"), "{stderr}");
+}
+
+#[test]
+fn prints_help_per_command() {
+    let output = Command::new(env!("CARGO_BIN_EXE_angular-dependency-analyzer"))
+        .args(["rdeps", "--help"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("使い方: angular-dependency-analyzer rdeps <ファイル | パッケージ>"), "{stdout}");
+    assert!(!stdout.contains("--html"), "{stdout}");
+}